@@ -0,0 +1,451 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use zuri_net::proto::packet::camera_instruction::{
+    CameraEaseType, CameraInstruction, CameraInstructionFade, CameraInstructionSet,
+};
+use zuri_net::proto::packet::camera_presets::{CameraPresetEntry, CameraPresets};
+use zuri_net::proto::packet::start_game::StartGame;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::player;
+
+/// Lets the server take over the camera with the presets of the CameraPresets packet and the
+/// instructions of the CameraInstruction packet, which are used for cutscenes. The camera moves
+/// between positions with an easing curve, and the screen can be faded to a colour.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ServerCamera::default())
+            .add_startup_system(fade_setup_system)
+            .add_system(camera_instruction_system.in_base_set(NetworkSet::Process))
+            .add_systems(
+                (camera_apply_system, fade_render_system).after(player::camera_sync_system),
+            )
+            .add_system(reset_resource::<ServerCamera>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// How many parents of a preset are followed at most, so that presets that are their own ancestor
+/// do not cause an endless loop.
+const MAX_PRESET_DEPTH: usize = 8;
+
+/// The presets and instructions sent by the server for the camera.
+#[derive(Resource, Default, Debug)]
+pub struct ServerCamera {
+    presets: Vec<CameraPresetEntry>,
+    /// The camera set by the server, or None if the camera follows the player.
+    set: Option<CameraTarget>,
+    /// The current fade, along with the time at which it started.
+    fade: Option<(CameraInstructionFade, f64)>,
+    /// Where the camera was put in the last frame, so that the next camera set by the server eases
+    /// from there instead of from the eyes of the player.
+    applied: Option<Transform>,
+}
+
+impl ServerCamera {
+    /// Returns a preset by its name.
+    pub fn preset(&self, name: &str) -> Option<&CameraPresetEntry> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Resolves the position and rotation of a preset, taking fields that the preset does not set
+    /// from its parents.
+    fn resolve(&self, index: usize) -> (Option<Vec3>, Option<Vec2>) {
+        let mut preset = self.presets.get(index);
+        let (mut x, mut y, mut z) = (None, None, None);
+        let (mut pitch, mut yaw) = (None, None);
+        for _ in 0..MAX_PRESET_DEPTH {
+            let Some(current) = preset else {
+                break;
+            };
+            x = x.or(current.pos_x);
+            y = y.or(current.pos_y);
+            z = z.or(current.pos_z);
+            pitch = pitch.or(current.rot_x);
+            yaw = yaw.or(current.rot_y);
+            preset = self.preset(&current.parent);
+        }
+        let position = match (x, y, z) {
+            (Some(x), Some(y), Some(z)) => Some(Vec3::new(x, y, z)),
+            _ => None,
+        };
+        let rotation = match (pitch, yaw) {
+            (Some(pitch), Some(yaw)) => Some(Vec2::new(pitch, yaw)),
+            _ => None,
+        };
+        (position, rotation)
+    }
+
+    fn handle_set(&mut self, set: &CameraInstructionSet, now: f64) {
+        let (preset_position, preset_rotation) = self.resolve(set.preset as usize);
+        self.set = Some(CameraTarget {
+            position: set.position.or(preset_position),
+            rotation: set.rotation.or(preset_rotation),
+            facing: set.facing,
+            ease: set
+                .ease
+                .as_ref()
+                .filter(|ease| ease.duration > 0.)
+                .map(|ease| (ease.r#type.clone(), ease.duration)),
+            started: now,
+            from: None,
+        });
+    }
+}
+
+/// Where the server put the camera.
+#[derive(Debug)]
+struct CameraTarget {
+    /// The position of the camera, or None to keep the camera at the eyes of the player.
+    position: Option<Vec3>,
+    /// The pitch and yaw of the camera in degrees.
+    rotation: Option<Vec2>,
+    /// A point that the camera looks at, which takes precedence over the rotation.
+    facing: Option<Vec3>,
+    /// The easing curve used to move to the target, along with how long it takes in seconds.
+    ease: Option<(CameraEaseType, f32)>,
+    /// The time at which the camera started moving, in seconds since the start of the app.
+    started: f64,
+    /// Where the camera was when it started moving. Set the first time the target is applied.
+    from: Option<Transform>,
+}
+
+/// Marks the node that covers the screen while the camera fades.
+#[derive(Component)]
+struct Fade;
+
+fn camera_instruction_system(
+    time: Res<Time>,
+    mut camera: ResMut<ServerCamera>,
+    mut start_game: EventReader<StartGame>,
+    mut presets: EventReader<CameraPresets>,
+    mut instructions: EventReader<CameraInstruction>,
+) {
+    if start_game.iter().next().is_some() {
+        *camera = ServerCamera::default();
+    }
+    for pk in presets.iter() {
+        camera.presets = pk.data.clone();
+    }
+
+    let now = time.elapsed_seconds_f64();
+    for pk in instructions.iter() {
+        for instruction in &pk.data {
+            if instruction.clear == Some(true) {
+                camera.set = None;
+                camera.applied = None;
+            }
+            if let Some(set) = &instruction.set {
+                camera.handle_set(set, now);
+            }
+            if let Some(fade) = &instruction.fade {
+                camera.fade = Some((fade.clone(), now));
+            }
+        }
+    }
+}
+
+/// Moves the camera to where the server put it. This runs after the camera was moved to the eyes
+/// of the player, so that cameras without a position stay there.
+fn camera_apply_system(
+    time: Res<Time>,
+    mut camera: ResMut<ServerCamera>,
+    mut cameras: Query<&mut Transform, (With<Camera3d>, Without<player::Local>)>,
+) {
+    let camera = &mut *camera;
+    let Some(target) = &mut camera.set else {
+        return;
+    };
+    let Ok(mut transform) = cameras.get_single_mut() else {
+        return;
+    };
+    let player = *transform;
+    let from = *target.from.get_or_insert(camera.applied.unwrap_or(player));
+
+    let translation = target.position.unwrap_or(player.translation);
+    let rotation = match (target.facing, target.rotation) {
+        (Some(facing), _) if facing != translation => {
+            Transform::from_translation(translation)
+                .looking_at(facing, Vec3::Y)
+                .rotation
+        }
+        (_, Some(rotation)) => rotation_from_degrees(rotation),
+        _ => player.rotation,
+    };
+
+    let progress = match &target.ease {
+        Some((ease_type, duration)) => {
+            let elapsed = (time.elapsed_seconds_f64() - target.started) as f32;
+            ease(ease_type, (elapsed / duration).clamp(0., 1.))
+        }
+        None => 1.,
+    };
+    transform.translation = from.translation.lerp(translation, progress);
+    transform.rotation = from.rotation.slerp(rotation, progress);
+    camera.applied = Some(*transform);
+}
+
+/// Converts a pitch and yaw in degrees, as used by Minecraft, to the rotation of a camera.
+fn rotation_from_degrees(rotation: Vec2) -> Quat {
+    Quat::from_rotation_y(PI - rotation.y.to_radians())
+        * Quat::from_rotation_x(-rotation.x.to_radians())
+}
+
+/// Returns how far along an easing curve is after a fraction of its duration. Most curves end at
+/// one, but some overshoot it on the way.
+pub fn ease(ease_type: &CameraEaseType, t: f32) -> f32 {
+    use CameraEaseType::*;
+
+    const C1: f32 = 1.70158;
+    const C2: f32 = C1 * 1.525;
+    const C3: f32 = C1 + 1.;
+    const C4: f32 = 2. * PI / 3.;
+    const C5: f32 = 2. * PI / 4.5;
+
+    fn out_bounce(t: f32) -> f32 {
+        const N1: f32 = 7.5625;
+        const D1: f32 = 2.75;
+        if t < 1. / D1 {
+            N1 * t * t
+        } else if t < 2. / D1 {
+            let t = t - 1.5 / D1;
+            N1 * t * t + 0.75
+        } else if t < 2.5 / D1 {
+            let t = t - 2.25 / D1;
+            N1 * t * t + 0.9375
+        } else {
+            let t = t - 2.625 / D1;
+            N1 * t * t + 0.984375
+        }
+    }
+    // Eases in with the power of a polynomial, and out by mirroring that curve.
+    let power_in = |power: i32| t.powi(power);
+    let power_out = |power: i32| 1. - (1. - t).powi(power);
+    let power_in_out = |power: i32| {
+        if t < 0.5 {
+            2f32.powi(power - 1) * t.powi(power)
+        } else {
+            1. - (-2. * t + 2.).powi(power) / 2.
+        }
+    };
+
+    if t <= 0. || t >= 1. {
+        return t.clamp(0., 1.);
+    }
+    match ease_type {
+        EasingTypeLinear => t,
+        // A damped spring that overshoots the target a few times before settling.
+        EasingTypeSpring => 1. - (t * PI * 4.5).cos() * (-6. * t).exp(),
+        EasingTypeInQuad => power_in(2),
+        EasingTypeOutQuad => power_out(2),
+        EasingTypeInOutQuad => power_in_out(2),
+        EasingTypeInCubic => power_in(3),
+        EasingTypeOutCubic => power_out(3),
+        EasingTypeInOutCubic => power_in_out(3),
+        EasingTypeInQuart => power_in(4),
+        EasingTypeOutQuart => power_out(4),
+        EasingTypeInOutQuart => power_in_out(4),
+        EasingTypeInQuint => power_in(5),
+        EasingTypeOutQuint => power_out(5),
+        EasingTypeInOutQuint => power_in_out(5),
+        EasingTypeInSine => 1. - (t * PI / 2.).cos(),
+        EasingTypeOutSine => (t * PI / 2.).sin(),
+        EasingTypeInOutSine => -((PI * t).cos() - 1.) / 2.,
+        EasingTypeInExpo => 2f32.powf(10. * t - 10.),
+        EasingTypeOutExpo => 1. - 2f32.powf(-10. * t),
+        EasingTypeInOutExpo => {
+            if t < 0.5 {
+                2f32.powf(20. * t - 10.) / 2.
+            } else {
+                (2. - 2f32.powf(-20. * t + 10.)) / 2.
+            }
+        }
+        EasingTypeInCirc => 1. - (1. - t * t).sqrt(),
+        EasingTypeOutCirc => (1. - (t - 1.).powi(2)).sqrt(),
+        EasingTypeInOutCirc => {
+            if t < 0.5 {
+                (1. - (1. - (2. * t).powi(2)).sqrt()) / 2.
+            } else {
+                ((1. - (-2. * t + 2.).powi(2)).sqrt() + 1.) / 2.
+            }
+        }
+        EasingTypeInBounce => 1. - out_bounce(1. - t),
+        EasingTypeOutBounce => out_bounce(t),
+        EasingTypeInOutBounce => {
+            if t < 0.5 {
+                (1. - out_bounce(1. - 2. * t)) / 2.
+            } else {
+                (1. + out_bounce(2. * t - 1.)) / 2.
+            }
+        }
+        EasingTypeInBack => C3 * t * t * t - C1 * t * t,
+        EasingTypeOutBack => 1. + C3 * (t - 1.).powi(3) + C1 * (t - 1.).powi(2),
+        EasingTypeInOutBack => {
+            if t < 0.5 {
+                (2. * t).powi(2) * ((C2 + 1.) * 2. * t - C2) / 2.
+            } else {
+                ((2. * t - 2.).powi(2) * ((C2 + 1.) * (t * 2. - 2.) + C2) + 2.) / 2.
+            }
+        }
+        EasingTypeInElastic => -(2f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * C4).sin(),
+        EasingTypeOutElastic => 2f32.powf(-10. * t) * ((t * 10. - 0.75) * C4).sin() + 1.,
+        EasingTypeInOutElastic => {
+            if t < 0.5 {
+                -(2f32.powf(20. * t - 10.) * ((20. * t - 11.125) * C5).sin()) / 2.
+            } else {
+                2f32.powf(-20. * t + 10.) * ((20. * t - 11.125) * C5).sin() / 2. + 1.
+            }
+        }
+    }
+}
+
+/// Returns the opacity of a fade that started a number of seconds ago, or None once it is over.
+fn fade_alpha(fade: &CameraInstructionFade, elapsed: f32) -> Option<f32> {
+    let (fade_in, wait, fade_out) = (
+        fade.fade_in_duration.max(0.),
+        fade.wait_duration.max(0.),
+        fade.fade_out_duration.max(0.),
+    );
+    if elapsed < fade_in {
+        Some(elapsed / fade_in)
+    } else if elapsed < fade_in + wait {
+        Some(1.)
+    } else if elapsed < fade_in + wait + fade_out {
+        Some(1. - (elapsed - fade_in - wait) / fade_out)
+    } else {
+        None
+    }
+}
+
+/// Spawns the node that covers the screen during fades, on top of the rest of the user interface.
+fn fade_setup_system(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::all(Val::Percent(100.)),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(1),
+            ..default()
+        },
+        Fade,
+    ));
+}
+
+fn fade_render_system(
+    time: Res<Time>,
+    mut camera: ResMut<ServerCamera>,
+    mut node: Query<(&mut BackgroundColor, &mut Visibility), With<Fade>>,
+) {
+    let Ok((mut background, mut visibility)) = node.get_single_mut() else {
+        return;
+    };
+    let Some((fade, started)) = &camera.fade else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let elapsed = (time.elapsed_seconds_f64() - started) as f32;
+    match fade_alpha(fade, elapsed) {
+        Some(alpha) => {
+            let colour = &fade.colour;
+            *background = Color::rgba(colour.r, colour.g, colour.b, alpha).into();
+            *visibility = Visibility::Inherited;
+        }
+        None => {
+            camera.fade = None;
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easing_curves() {
+        use CameraEaseType::*;
+        let curves = [
+            EasingTypeLinear,
+            EasingTypeSpring,
+            EasingTypeInQuad,
+            EasingTypeOutQuad,
+            EasingTypeInOutQuad,
+            EasingTypeInCubic,
+            EasingTypeOutCubic,
+            EasingTypeInOutCubic,
+            EasingTypeInQuart,
+            EasingTypeOutQuart,
+            EasingTypeInOutQuart,
+            EasingTypeInQuint,
+            EasingTypeOutQuint,
+            EasingTypeInOutQuint,
+            EasingTypeInSine,
+            EasingTypeOutSine,
+            EasingTypeInOutSine,
+            EasingTypeInExpo,
+            EasingTypeOutExpo,
+            EasingTypeInOutExpo,
+            EasingTypeInCirc,
+            EasingTypeOutCirc,
+            EasingTypeInOutCirc,
+            EasingTypeInBounce,
+            EasingTypeOutBounce,
+            EasingTypeInOutBounce,
+            EasingTypeInBack,
+            EasingTypeOutBack,
+            EasingTypeInOutBack,
+            EasingTypeInElastic,
+            EasingTypeOutElastic,
+            EasingTypeInOutElastic,
+        ];
+        for curve in &curves {
+            assert_eq!(ease(curve, 0.), 0., "{curve:?} does not start at zero");
+            assert_eq!(ease(curve, 1.), 1., "{curve:?} does not end at one");
+            // Curves that are symmetric around the middle pass through one half there.
+            if matches!(
+                curve,
+                EasingTypeLinear | EasingTypeInOutQuad | EasingTypeInOutSine
+            ) {
+                assert!((ease(curve, 0.5) - 0.5).abs() < 1e-5);
+            }
+        }
+        assert!(ease(&EasingTypeInQuad, 0.25) < 0.25);
+        assert!(ease(&EasingTypeOutQuad, 0.25) > 0.25);
+    }
+
+    #[test]
+    fn presets() {
+        let preset =
+            |name: &str, parent: &str, pos: Option<f32>, rot: Option<f32>| CameraPresetEntry {
+                name: name.into(),
+                parent: parent.into(),
+                pos_x: pos,
+                pos_y: pos,
+                pos_z: pos,
+                rot_x: rot,
+                rot_y: rot,
+                listener: None,
+                player_effects: None,
+            };
+        let camera = ServerCamera {
+            presets: vec![
+                preset("minecraft:free", "", Some(1.), Some(10.)),
+                preset("custom:cutscene", "minecraft:free", Some(2.), None),
+                preset("custom:loop", "custom:loop", None, None),
+            ],
+            ..default()
+        };
+        assert_eq!(
+            camera.resolve(1),
+            (Some(Vec3::splat(2.)), Some(Vec2::splat(10.)))
+        );
+        assert_eq!(camera.resolve(2), (None, None));
+        assert_eq!(camera.resolve(3), (None, None));
+    }
+}
@@ -1,38 +1,82 @@
 use async_trait::async_trait;
 use bevy::app::AppExit;
 use std::env;
-use std::net::ToSocketAddrs;
 use std::sync::Arc;
 use std::time::Duration;
 
 use bevy::prelude::*;
 use futures_lite::future;
+use oauth2::basic::BasicTokenResponse;
 use oauth2::devicecode::StandardDeviceAuthorizationResponse;
+use tokio::net::lookup_host;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use uuid::Uuid;
 use zuri_net::client::data::{ClientData, IdentityData};
-use zuri_net::client::Handler;
+use zuri_net::client::{DisconnectReason, Handler};
 use zuri_net::connection::ConnError;
 use zuri_net::proto::packet::add_actor::AddActor;
 use zuri_net::proto::packet::add_player::AddPlayer;
+use zuri_net::proto::packet::available_commands::AvailableCommands;
+use zuri_net::proto::packet::boss_event::BossEvent;
+use zuri_net::proto::packet::camera_instruction::CameraInstruction;
+use zuri_net::proto::packet::camera_presets::CameraPresets;
+use zuri_net::proto::packet::change_dimension::ChangeDimension;
+use zuri_net::proto::packet::command_output::CommandOutput;
+use zuri_net::proto::packet::container_close::ContainerClose;
+use zuri_net::proto::packet::container_open::ContainerOpen;
+use zuri_net::proto::packet::container_set_data::ContainerSetData;
+use zuri_net::proto::packet::crafting_data::CraftingData;
+use zuri_net::proto::packet::death_info::DeathInfo;
+use zuri_net::proto::packet::dimension_data::DimensionData;
+use zuri_net::proto::packet::game_rules_changed::GameRulesChanged;
+use zuri_net::proto::packet::inventory_content::InventoryContent;
+use zuri_net::proto::packet::inventory_slot::InventorySlot;
+use zuri_net::proto::packet::item_component::ItemComponent;
+use zuri_net::proto::packet::item_stack_response::ItemStackResponse;
 use zuri_net::proto::packet::level_chunk::LevelChunk;
 use zuri_net::proto::packet::level_event::LevelEvent;
 use zuri_net::proto::packet::level_sound_event::LevelSoundEvent;
+use zuri_net::proto::packet::mob_equipment::MobEquipment;
+use zuri_net::proto::packet::modal_form_request::ModalFormRequest;
 use zuri_net::proto::packet::move_actor_absolute::MoveActorAbsolute;
 use zuri_net::proto::packet::move_actor_delta::MoveActorDelta;
 use zuri_net::proto::packet::move_player::MovePlayer;
 use zuri_net::proto::packet::network_chunk_publisher_update::NetworkChunkPublisherUpdate;
+use zuri_net::proto::packet::play_sound::PlaySound;
+use zuri_net::proto::packet::player_fog::PlayerFog;
+use zuri_net::proto::packet::player_hot_bar::PlayerHotBar;
+use zuri_net::proto::packet::player_list::PlayerList;
+use zuri_net::proto::packet::player_skin::PlayerSkin;
 use zuri_net::proto::packet::remove_actor::RemoveActor;
+use zuri_net::proto::packet::remove_objective::RemoveObjective;
+use zuri_net::proto::packet::respawn::Respawn;
 use zuri_net::proto::packet::set_actor_data::SetActorData;
+use zuri_net::proto::packet::set_actor_motion::SetActorMotion;
+use zuri_net::proto::packet::set_display_objective::SetDisplayObjective;
+use zuri_net::proto::packet::set_health::SetHealth;
+use zuri_net::proto::packet::set_score::SetScore;
+use zuri_net::proto::packet::set_scoreboard_identity::SetScoreboardIdentity;
+use zuri_net::proto::packet::set_time::SetTime;
+use zuri_net::proto::packet::set_title::SetTitle;
+use zuri_net::proto::packet::spawn_particle_effect::SpawnParticleEffect;
 use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::packet::stop_sound::StopSound;
+use zuri_net::proto::packet::text::Text;
+use zuri_net::proto::packet::toast_request::ToastRequest;
+use zuri_net::proto::packet::transfer::Transfer;
+use zuri_net::proto::packet::update_attributes::UpdateAttributes;
 use zuri_net::proto::packet::update_block::UpdateBlock;
+use zuri_net::proto::packet::update_soft_enum::UpdateSoftEnum;
 use zuri_net::proto::packet::Packet;
 use zuri_xbox::live;
 
+use crate::resource_pack::ServerResourcePacks;
+
 /// The ClientPlugin is responsible for handling and managing the connection to the server.
 ///
 /// To write a packet, the `EventWriter<Packet>` should be used. It can be used for packets of any
@@ -40,7 +84,10 @@ use zuri_xbox::live;
 /// For reading incoming packets, `EventReader<T>` should be used, where `T` is the type of packet
 /// that is expected to be read in the system. These receive events will stay available for the
 /// frame on which the packet was read and the next frame after that.
-/// todo: manual disconnect
+///
+/// The state of the connection is kept in the [ConnectionState] resource. A [Connect] event makes
+/// the client connect to a server, and a [LeaveServer] event makes it leave the server again. Once
+/// the connection is lost, a [Reconnect] event makes the client connect to the server again.
 pub struct ClientPlugin;
 
 impl Plugin for ClientPlugin {
@@ -52,17 +99,56 @@ impl Plugin for ClientPlugin {
             // Packet events go here.
             .add_event::<AddActor>()
             .add_event::<AddPlayer>()
+            .add_event::<AvailableCommands>()
+            .add_event::<BossEvent>()
+            .add_event::<CameraInstruction>()
+            .add_event::<CameraPresets>()
+            .add_event::<ChangeDimension>()
+            .add_event::<CommandOutput>()
+            .add_event::<ContainerClose>()
+            .add_event::<ContainerOpen>()
+            .add_event::<ContainerSetData>()
+            .add_event::<CraftingData>()
+            .add_event::<DeathInfo>()
+            .add_event::<DimensionData>()
+            .add_event::<GameRulesChanged>()
+            .add_event::<InventoryContent>()
+            .add_event::<InventorySlot>()
+            .add_event::<ItemComponent>()
+            .add_event::<ItemStackResponse>()
             .add_event::<LevelChunk>()
             .add_event::<LevelEvent>()
             .add_event::<LevelSoundEvent>()
+            .add_event::<MobEquipment>()
+            .add_event::<ModalFormRequest>()
             .add_event::<MoveActorAbsolute>()
             .add_event::<MoveActorDelta>()
             .add_event::<MovePlayer>()
             .add_event::<NetworkChunkPublisherUpdate>()
+            .add_event::<PlaySound>()
+            .add_event::<PlayerFog>()
+            .add_event::<PlayerHotBar>()
+            .add_event::<PlayerList>()
+            .add_event::<PlayerSkin>()
             .add_event::<RemoveActor>()
+            .add_event::<RemoveObjective>()
+            .add_event::<Respawn>()
             .add_event::<SetActorData>()
+            .add_event::<SetActorMotion>()
+            .add_event::<SetDisplayObjective>()
+            .add_event::<SetHealth>()
+            .add_event::<SetScore>()
+            .add_event::<SetScoreboardIdentity>()
+            .add_event::<SetTime>()
+            .add_event::<SetTitle>()
+            .add_event::<SpawnParticleEffect>()
             .add_event::<StartGame>()
+            .add_event::<StopSound>()
+            .add_event::<Text>()
+            .add_event::<ToastRequest>()
+            .add_event::<UpdateAttributes>()
             .add_event::<UpdateBlock>()
+            .add_event::<UpdateSoftEnum>()
             .configure_sets((
                 NetworkSet::Receive
                     .before(CoreSet::Update)
@@ -76,7 +162,17 @@ impl Plugin for ClientPlugin {
                     .after(CoreSet::Update)
                     .after(CoreSet::FixedUpdate),
             ))
+            .add_event::<Disconnected>()
+            .add_event::<Connect>()
+            .add_event::<LeaveServer>()
+            .add_event::<Reconnect>()
             .add_startup_system(init_client)
+            .add_system(
+                connect_request_system
+                    .in_base_set(NetworkSet::Receive)
+                    .before(reconnect_system),
+            )
+            .add_system(reconnect_system.in_base_set(NetworkSet::Receive))
             .add_system(graceful_disconnect.in_base_set(CoreSet::Last))
             .add_system(
                 client_connection_system
@@ -102,6 +198,93 @@ pub enum NetworkSet {
 
 type Client = zuri_net::client::Client<PacketHandler>;
 
+/// The most attempts made in a row to reconnect to a server before giving up.
+const MAX_RECONNECT_ATTEMPTS: u32 = 8;
+/// How long to wait before the first attempt to reconnect. The wait doubles with every attempt
+/// after that, up to [MAX_RECONNECT_DELAY].
+const RECONNECT_DELAY: f64 = 1.;
+const MAX_RECONNECT_DELAY: f64 = 30.;
+
+/// Sent when the client is disconnected from a server, either because the connection was lost or
+/// because the server transferred the client to another server. Everything that was received from
+/// the server should be torn down when this is sent.
+pub struct Disconnected;
+
+/// Sent to connect to the server at an address, as a host name or an IP address followed by a port.
+/// Ignored while the client is already connected or connecting to a server.
+pub struct Connect(pub String);
+
+/// Sent to leave the current server, or to stop connecting to a server.
+pub struct LeaveServer;
+
+/// Sent to connect to the server again after the connection was lost or could not be made.
+pub struct Reconnect;
+
+/// The state of the connection to the server, as shown to the player.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The client is not connected to a server and is not trying to connect to one, such as while
+    /// the server list is shown.
+    Idle,
+    /// The client is connecting to the server at the address.
+    Connecting(String),
+    /// The client has logged in to the server.
+    Connected,
+    /// The client is not connected to a server, with a message explaining why.
+    Disconnected(String),
+}
+
+/// Resets a resource to its default. Meant to be run once the client is [Disconnected], for
+/// resources that hold state received from the server.
+pub fn reset_resource<R: Resource + Default>(mut resource: ResMut<R>) {
+    *resource = R::default();
+}
+
+/// The server the client connects to, along with what is needed to log in to it again.
+#[derive(Resource)]
+struct Connection {
+    /// The address of the server, as a host name or an IP address followed by a port.
+    address: String,
+    identity_data: Option<IdentityData>,
+    live_token: Option<BasicTokenResponse>,
+    /// Whether to reconnect when the connection is lost or cannot be made, as set by the
+    /// `zuri_reconnect` environment variable.
+    reconnect: bool,
+    /// How many attempts to connect have failed in a row.
+    attempts: u32,
+    /// When to make the next attempt to connect, in seconds since startup.
+    retry_at: Option<f64>,
+}
+
+impl Connection {
+    /// Schedules an attempt to reconnect, waiting longer after every failed attempt. Returns false
+    /// if the client should not reconnect.
+    fn schedule_reconnect(&mut self, now: f64) -> bool {
+        if !self.reconnect || self.attempts >= MAX_RECONNECT_ATTEMPTS {
+            return false;
+        }
+        let delay = reconnect_delay(self.attempts);
+        info!("Reconnecting to {} in {delay} seconds", self.address);
+        self.attempts += 1;
+        self.retry_at = Some(now + delay);
+        true
+    }
+}
+
+/// Returns true if the connection was lost without the server or the client ending it, which is
+/// when the client may reconnect. A server that kicks the client should not have it come back.
+fn unexpected_disconnect(reason: &DisconnectReason) -> bool {
+    matches!(
+        reason,
+        DisconnectReason::Timeout | DisconnectReason::DecodeError(_)
+    )
+}
+
+/// Returns how many seconds to wait before reconnecting after a number of failed attempts.
+fn reconnect_delay(attempts: u32) -> f64 {
+    (RECONNECT_DELAY * 2f64.powi(attempts as i32)).min(MAX_RECONNECT_DELAY)
+}
+
 /// When the app shuts down, we want to disconnect the client if it is still connected at this
 /// point.
 fn graceful_disconnect(shutdown: EventReader<AppExit>, client: Option<NonSend<Arc<Client>>>) {
@@ -110,7 +293,9 @@ fn graceful_disconnect(shutdown: EventReader<AppExit>, client: Option<NonSend<Ar
     }
     info!("Received shutdown signal, disconnecting client...");
     let client_clone = client.unwrap().clone();
-    future::block_on(async move { client_clone.disconnect().await });
+    if let Err(err) = future::block_on(async move { client_clone.disconnect().await }) {
+        warn!("Could not disconnect gracefully: {err}");
+    }
 }
 
 /// Used to keep track of the task responsible for connecting to the server. It is removed after the
@@ -122,7 +307,11 @@ struct ClientWaiter {
 /// Temporary system responsible for starting the thread which handles the login sequence.
 #[allow(clippy::unnecessary_to_owned)] // `verification_uri` doesnt actually implement display.
 fn init_client(world: &mut World) {
-    let address = env::var("zuri_ip").unwrap_or("127.0.0.1:19132".into());
+    let address = env::var("zuri_ip").ok();
+    let reconnect = env::var("zuri_reconnect")
+        .unwrap_or("false".into())
+        .to_lowercase()
+        == "true";
 
     let mut identity_data = None;
     let mut live_token = None;
@@ -147,17 +336,159 @@ fn init_client(world: &mut World) {
         });
     }
 
-    let (send, recv) = channel::<Packet>(16);
+    world.insert_resource(Connection {
+        address: address.clone().unwrap_or_default(),
+        identity_data,
+        live_token,
+        reconnect,
+        attempts: 0,
+        retry_at: None,
+    });
+    // Without an address to connect to right away, the player picks a server from the server list.
+    match address {
+        Some(_) => connect(world),
+        None => world.insert_resource(ConnectionState::Idle),
+    }
+}
+
+/// Starts the thread which resolves the address of the server and handles the login sequence.
+fn connect(world: &mut World) {
+    let connection = world.resource::<Connection>();
+    let address = connection.address.clone();
+    let identity_data = connection.identity_data.clone();
+    let live_token = connection.live_token.clone();
+
+    world.insert_resource(ConnectionState::Connecting(address.clone()));
+    let handler = packet_handler(world);
     world.insert_non_send_resource(ClientWaiter {
-        task: tokio::spawn(Client::connect(
-            address.to_socket_addrs().unwrap().next().unwrap(),
-            ClientData::default(),
-            identity_data,
-            live_token,
-            PacketHandler { send_chan: send },
-        )),
+        task: tokio::spawn(async move {
+            let ip = lookup_host(&address)
+                .await
+                .map_err(|err| ConnError::AddressError(format!("{address}: {err}")))?
+                .next()
+                .ok_or_else(|| ConnError::AddressError(address.clone()))?;
+            Client::connect(
+                ip,
+                ClientData::default(),
+                identity_data,
+                live_token,
+                handler,
+            )
+            .await
+        }),
     });
+}
+
+/// Creates the handler for a new connection, and keeps the receiving ends of its channels for the
+/// main game thread.
+fn packet_handler(world: &mut World) -> PacketHandler {
+    let (send, recv) = channel::<Packet>(16);
+    let (disconnect_send, disconnect_recv) = oneshot::channel();
     world.insert_non_send_resource(recv);
+    world.insert_non_send_resource(disconnect_recv);
+    PacketHandler {
+        send_chan: Some(send),
+        disconnect_chan: Some(disconnect_send),
+    }
+}
+
+/// Connects to a server when a [Connect] event is sent, and leaves the server when a [LeaveServer]
+/// event is sent.
+fn connect_request_system(world: &mut World) {
+    let leave = world.resource_mut::<Events<LeaveServer>>().drain().count() > 0;
+    let address = world
+        .resource_mut::<Events<Connect>>()
+        .drain()
+        .last()
+        .map(|Connect(address)| address);
+
+    if leave {
+        if let Some(waiter) = world.remove_non_send_resource::<ClientWaiter>() {
+            waiter.task.abort();
+        }
+        if let Some(client) = end_session(world) {
+            tokio::spawn(async move {
+                if let Err(err) = client.disconnect().await {
+                    warn!("Could not disconnect gracefully: {err}");
+                }
+            });
+        }
+        world.resource_mut::<Connection>().retry_at = None;
+        world.insert_resource(ConnectionState::Idle);
+    }
+    let Some(address) = address else {
+        return;
+    };
+    if matches!(
+        world.resource::<ConnectionState>(),
+        ConnectionState::Connecting(_) | ConnectionState::Connected
+    ) {
+        warn!("Tried to connect to {address} while already connected to a server");
+        return;
+    }
+    let mut connection = world.resource_mut::<Connection>();
+    connection.address = address;
+    connection.attempts = 0;
+    connection.retry_at = None;
+    connect(world);
+}
+
+/// Makes the next attempt to reconnect once it is time to, or right away when a [Reconnect] event
+/// is sent while the client is disconnected.
+fn reconnect_system(world: &mut World) {
+    let requested = world.resource_mut::<Events<Reconnect>>().drain().count() > 0;
+    let disconnected = matches!(
+        world.resource::<ConnectionState>(),
+        ConnectionState::Disconnected(_)
+    );
+    let now = world.resource::<Time>().elapsed_seconds_f64();
+    let mut connection = world.resource_mut::<Connection>();
+    match connection.retry_at {
+        _ if requested && disconnected => connection.attempts = 0,
+        Some(retry_at) if retry_at <= now => {}
+        _ => return,
+    }
+    connection.retry_at = None;
+    connect(world);
+}
+
+/// Disconnects from the server and connects to the server the client is transferred to.
+fn transfer(world: &mut World, pk: Transfer) {
+    let Some(client) = end_session(world) else {
+        return;
+    };
+    // IPv6 addresses need brackets to be told apart from the port, which servers may or may not
+    // have added already.
+    let host = pk
+        .address
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let address = if host.contains(':') {
+        format!("[{}]:{}", host, pk.port)
+    } else {
+        format!("{}:{}", host, pk.port)
+    };
+    info!("Transferring to {address}");
+    world.resource_mut::<Connection>().address = address.clone();
+    world.insert_resource(ConnectionState::Connecting(address));
+
+    let handler = packet_handler(world);
+    world.insert_non_send_resource(ClientWaiter {
+        task: tokio::spawn(async move { client.transfer(&host, pk.port, handler).await }),
+    });
+}
+
+/// Tears down the connection to the current server, and tells the rest of the client to tear down
+/// everything received from it. Returns the client if it was connected.
+fn end_session(world: &mut World) -> Option<Arc<Client>> {
+    world.remove_non_send_resource::<Receiver<Packet>>();
+    world.remove_non_send_resource::<oneshot::Receiver<DisconnectReason>>();
+    world.remove_non_send_resource::<Sender<Vec<Packet>>>();
+    let client = world.remove_non_send_resource::<Arc<Client>>()?;
+    world.resource_mut::<Events<Packet>>().clear();
+    world.send_event(Disconnected);
+    Some(client)
 }
 
 /// Polls the connecter thread until the login has completed. When the login is complete, the
@@ -171,15 +502,28 @@ fn client_connection_system(world: &mut World) {
     if res.is_none() {
         return;
     }
-    match res.unwrap().unwrap() {
+    world.remove_non_send_resource::<ClientWaiter>();
+    let res = match res.unwrap() {
+        Ok(res) => res.map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    match res {
         Err(e) => {
             error!("Could not connect to the server: {e}");
-            world.send_event(AppExit);
+            world.remove_non_send_resource::<Receiver<Packet>>();
+            world.remove_non_send_resource::<oneshot::Receiver<DisconnectReason>>();
+            world.insert_resource(ConnectionState::Disconnected(format!(
+                "Could not connect to the server: {e}"
+            )));
+            let now = world.resource::<Time>().elapsed_seconds_f64();
+            world.resource_mut::<Connection>().schedule_reconnect(now);
         }
-        Ok(client) => {
+        Ok(mut client) => {
+            world.send_event(ServerResourcePacks(client.take_resource_packs()));
             let client = Arc::<Client>::new(client);
-            world.remove_non_send_resource::<ClientWaiter>();
             world.insert_non_send_resource(client.clone());
+            world.resource_mut::<Connection>().attempts = 0;
+            world.insert_resource(ConnectionState::Connected);
             info!("Connection has been completed");
 
             let (send, mut recv) = mpsc::channel::<Vec<Packet>>(1);
@@ -237,25 +581,79 @@ fn receive_packets(world: &mut World) {
                 return match err {
                     TryRecvError::Empty => {}
                     TryRecvError::Disconnected => {
-                        let _ = world.remove_non_send_resource::<Receiver<Vec<Packet>>>();
-                        info!("Stopped receiving packets: connection closed by server");
+                        // The handler sends the reason before it closes the packet channel.
+                        let reason = world
+                            .get_non_send_resource_mut::<oneshot::Receiver<DisconnectReason>>()
+                            .and_then(|mut recv| recv.try_recv().ok())
+                            .unwrap_or(DisconnectReason::Timeout);
+                        info!("Stopped receiving packets: {reason}");
+                        world.insert_resource(ConnectionState::Disconnected(reason.to_string()));
+                        if end_session(world).is_some() && unexpected_disconnect(&reason) {
+                            let now = world.resource::<Time>().elapsed_seconds_f64();
+                            world.resource_mut::<Connection>().schedule_reconnect(now);
+                        }
                     }
-                }
+                };
             }
             Ok(pk) => match pk {
                 Packet::AddActor(pk) => world.send_event(pk),
                 Packet::AddPlayer(pk) => world.send_event(pk),
+                Packet::AvailableCommands(pk) => world.send_event(pk),
+                Packet::BossEvent(pk) => world.send_event(pk),
+                Packet::CameraInstruction(pk) => world.send_event(pk),
+                Packet::CameraPresets(pk) => world.send_event(pk),
+                Packet::ChangeDimension(pk) => world.send_event(pk),
+                Packet::CommandOutput(pk) => world.send_event(pk),
+                Packet::ContainerClose(pk) => world.send_event(pk),
+                Packet::ContainerOpen(pk) => world.send_event(pk),
+                Packet::ContainerSetData(pk) => world.send_event(pk),
+                Packet::CraftingData(pk) => world.send_event(pk),
+                Packet::DeathInfo(pk) => world.send_event(pk),
+                Packet::DimensionData(pk) => world.send_event(pk),
+                Packet::GameRulesChanged(pk) => world.send_event(pk),
+                Packet::InventoryContent(pk) => world.send_event(pk),
+                Packet::InventorySlot(pk) => world.send_event(pk),
+                Packet::ItemComponent(pk) => world.send_event(pk),
+                Packet::ItemStackResponse(pk) => world.send_event(pk),
                 Packet::LevelChunk(pk) => world.send_event(pk),
                 Packet::LevelEvent(pk) => world.send_event(pk),
                 Packet::LevelSoundEvent(pk) => world.send_event(pk),
+                Packet::MobEquipment(pk) => world.send_event(pk),
+                Packet::ModalFormRequest(pk) => world.send_event(pk),
                 Packet::MoveActorAbsolute(pk) => world.send_event(pk),
                 Packet::MoveActorDelta(pk) => world.send_event(pk),
                 Packet::MovePlayer(pk) => world.send_event(pk),
                 Packet::NetworkChunkPublisherUpdate(pk) => world.send_event(pk),
+                Packet::PlaySound(pk) => world.send_event(pk),
+                Packet::PlayerFog(pk) => world.send_event(pk),
+                Packet::PlayerHotBar(pk) => world.send_event(pk),
+                Packet::PlayerList(pk) => world.send_event(pk),
+                Packet::PlayerSkin(pk) => world.send_event(pk),
                 Packet::RemoveActor(pk) => world.send_event(pk),
+                Packet::RemoveObjective(pk) => world.send_event(pk),
+                Packet::Respawn(pk) => world.send_event(pk),
                 Packet::SetActorData(pk) => world.send_event(pk),
+                Packet::SetActorMotion(pk) => world.send_event(pk),
+                Packet::SetDisplayObjective(pk) => world.send_event(pk),
+                Packet::SetHealth(pk) => world.send_event(pk),
+                Packet::SetScore(pk) => world.send_event(pk),
+                Packet::SetScoreboardIdentity(pk) => world.send_event(pk),
+                Packet::SetTime(pk) => world.send_event(pk),
+                Packet::SetTitle(pk) => world.send_event(pk),
+                Packet::SpawnParticleEffect(pk) => world.send_event(pk),
                 Packet::StartGame(pk) => world.send_event(pk),
+                Packet::StopSound(pk) => world.send_event(pk),
+                Packet::Text(pk) => world.send_event(pk),
+                Packet::ToastRequest(pk) => world.send_event(pk),
+                Packet::Transfer(pk) => {
+                    transfer(world, pk);
+                    return;
+                }
+                Packet::UpdateAttributes(pk) => world.send_event(pk),
                 Packet::UpdateBlock(pk) => world.send_event(pk),
+                Packet::UpdateSoftEnum(pk) => world.send_event(pk),
+                // The message of the server is shown through the reason of the disconnection.
+                Packet::Disconnect(_) => {}
                 // Ignore login sequence packets.
                 Packet::BiomeDefinitionList(_) => {}
                 Packet::CompressedBiomeDefinitionList(_) => {}
@@ -277,13 +675,64 @@ fn receive_packets(world: &mut World) {
 /// Handles incoming packets from the server. It is responsible for sending packets to the main
 /// game thread.
 struct PacketHandler {
-    send_chan: Sender<Packet>,
+    /// The channel to the main game thread, which is closed once the connection is lost.
+    send_chan: Option<Sender<Packet>>,
+    /// Tells the main game thread why the connection was lost.
+    disconnect_chan: Option<oneshot::Sender<DisconnectReason>>,
 }
 
 #[async_trait]
 impl Handler for PacketHandler {
     async fn handle_incoming(&mut self, pk: Packet) -> Vec<Packet> {
-        let _ = self.send_chan.send(pk).await;
+        if let Some(send_chan) = &self.send_chan {
+            let _ = send_chan.send(pk).await;
+        }
         vec![]
     }
+
+    async fn handle_disconnect(&mut self, reason: DisconnectReason) {
+        if let Some(disconnect_chan) = self.disconnect_chan.take() {
+            let _ = disconnect_chan.send(reason);
+        }
+        self.send_chan = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_backoff() {
+        assert_eq!(reconnect_delay(0), 1.);
+        assert_eq!(reconnect_delay(3), 8.);
+        assert_eq!(reconnect_delay(20), MAX_RECONNECT_DELAY);
+
+        let mut connection = Connection {
+            address: "127.0.0.1:19132".into(),
+            identity_data: None,
+            live_token: None,
+            reconnect: true,
+            attempts: 0,
+            retry_at: None,
+        };
+        assert!(connection.schedule_reconnect(10.));
+        assert_eq!(connection.retry_at, Some(11.));
+        connection.attempts = MAX_RECONNECT_ATTEMPTS;
+        assert!(!connection.schedule_reconnect(10.));
+
+        connection.attempts = 0;
+        connection.reconnect = false;
+        assert!(!connection.schedule_reconnect(10.));
+
+        assert!(unexpected_disconnect(&DisconnectReason::Timeout));
+        assert!(unexpected_disconnect(&DisconnectReason::DecodeError(
+            "invalid batch".into()
+        )));
+        assert!(!unexpected_disconnect(&DisconnectReason::Kicked(Some(
+            "You are banned".into()
+        ))));
+        assert!(!unexpected_disconnect(&DisconnectReason::Kicked(None)));
+        assert!(!unexpected_disconnect(&DisconnectReason::Local));
+    }
 }
@@ -1,21 +1,39 @@
+pub mod animation;
+mod fire;
+pub mod interpolation;
+mod leash;
 mod manager;
+pub mod metadata;
+pub mod model;
 pub mod nametag;
+pub mod skin;
 
-pub use manager::{EntityManager, RuntimeId, UniqueId};
+pub use manager::{EntityManager, IdentifiableEntity, RuntimeId, UniqueId};
 
-use crate::client::NetworkSet;
-use crate::entity::manager::{EntityManagerPlugin, IdentifiableEntity};
-use crate::entity::nametag::{Nametag, NametagPlugin};
+use crate::client::{Disconnected, NetworkSet};
+use crate::entity::animation::{AnimationPlugin, Pose};
+use crate::entity::fire::FirePlugin;
+use crate::entity::interpolation::{Interpolation, InterpolationPlugin};
+use crate::entity::leash::LeashPlugin;
+use crate::entity::manager::EntityManagerPlugin;
+use crate::entity::metadata::{Metadata, MetadataPlugin};
+use crate::entity::model::{Appearance, EntityModelPlugin};
+use crate::entity::nametag::NametagPlugin;
+use crate::entity::skin::SkinPlugin;
 use crate::player;
 use bevy::prelude::*;
 use zuri_net::proto::packet::add_actor::AddActor;
 use zuri_net::proto::packet::add_player::AddPlayer;
-use zuri_net::proto::packet::move_actor_absolute::MoveActorAbsolute;
-use zuri_net::proto::packet::move_actor_delta::MoveActorDelta;
-use zuri_net::proto::packet::move_player::MovePlayer;
+use zuri_net::proto::packet::move_actor_absolute::{MoveActorAbsolute, MoveFlag};
+use zuri_net::proto::packet::move_actor_delta::{MoveActorDelta, MoveActorDeltaFlag};
+use zuri_net::proto::packet::move_player::{MoveMode, MovePlayer};
 use zuri_net::proto::packet::remove_actor::RemoveActor;
+use zuri_net::proto::packet::set_actor_motion::SetActorMotion;
 use zuri_net::proto::packet::start_game::StartGame;
 
+/// The height of the eyes of a player above their feet, in blocks.
+const PLAYER_EYE_HEIGHT: f32 = 1.62;
+
 /// Manages entities. Entities here refers to minecraft entities, not all ECS entities.
 pub struct EntityPlugin;
 
@@ -23,12 +41,21 @@ impl Plugin for EntityPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(EntityManagerPlugin)
             .add_plugin(NametagPlugin)
+            .add_plugin(EntityModelPlugin)
+            .add_plugin(SkinPlugin)
+            .add_plugin(AnimationPlugin)
+            .add_plugin(InterpolationPlugin)
+            .add_plugin(MetadataPlugin)
+            .add_plugin(FirePlugin)
+            .add_plugin(LeashPlugin)
             .add_systems(
                 (
                     init_player_system,
                     despawn_entity_system.before(spawn_entity_system),
                     spawn_entity_system,
                     handle_move_system,
+                    handle_motion_system,
+                    entity_reset_system.run_if(on_event::<Disconnected>()),
                 )
                     .in_base_set(NetworkSet::Process),
             );
@@ -61,87 +88,88 @@ fn init_player_system(
     }
 }
 
-/// Adds entities spawned by the server. For now, they are shown as a simple capsule mesh/
-fn spawn_entity_system(
+/// Despawns all entities once the client is disconnected. The local player stays, but is no longer
+/// identified by the runtime ID the server gave it.
+fn entity_reset_system(
     mut commands: Commands,
+    query: Query<(Entity, Option<&player::Local>), With<IdentifiableEntity>>,
+) {
+    for (entity, local) in &query {
+        if local.is_some() {
+            commands.entity(entity).remove::<IdentifiableEntity>();
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
 
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut mats: ResMut<Assets<StandardMaterial>>,
+/// Adds entities spawned by the server. Their models are built from their [Appearance].
+fn spawn_entity_system(
+    mut commands: Commands,
+    time: Res<Time>,
 
     mut pks: EventReader<AddPlayer>,
     mut pks2: EventReader<AddActor>,
 ) {
     for pk in pks.iter() {
         commands
-            .spawn(PbrBundle {
-                mesh: meshes.add(
-                    shape::Capsule {
-                        radius: 0.45,
-                        depth: 1.2,
-                        ..Default::default()
-                    }
-                    .into(),
-                ),
-                material: mats
-                    .add(StandardMaterial {
-                        base_color: Color::RED,
-                        metallic: 0.,
-                        reflectance: 0.,
-                        unlit: true,
-                        ..Default::default()
-                    })
-                    .into(),
-                transform: Transform::from_translation(pk.position),
-                ..Default::default()
-            })
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                pk.position,
+            )))
             .insert(IdentifiableEntity::new(
                 pk.entity_runtime_id,
                 pk.ability_data.entity_unique_id,
             ))
-            .insert(Nametag {
-                contents: pk.username.clone(),
-                y_offset: 1.4,
-            });
+            .insert(Appearance::Player(pk.uuid))
+            .insert(Pose::new(pk.pitch, pk.yaw, pk.head_yaw))
+            .insert(Interpolation::new(
+                time.elapsed_seconds_f64(),
+                pk.position,
+                Vec3::new(pk.pitch, pk.yaw, pk.head_yaw),
+            ))
+            .insert(Metadata(pk.entity_metadata.clone()));
     }
     for pk in pks2.iter() {
         commands
-            .spawn(PbrBundle {
-                mesh: meshes.add(
-                    shape::UVSphere {
-                        radius: 0.45,
-                        ..Default::default()
-                    }
-                    .into(),
-                ),
-                material: mats
-                    .add(StandardMaterial {
-                        base_color: Color::BLUE,
-                        metallic: 0.,
-                        reflectance: 0.,
-                        unlit: true,
-                        ..Default::default()
-                    })
-                    .into(),
-                transform: Transform::from_translation(pk.position),
-                ..Default::default()
-            })
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                pk.position,
+            )))
             .insert(IdentifiableEntity::new(
                 pk.entity_runtime_id,
                 pk.entity_unique_id,
-            ));
+            ))
+            .insert(Appearance::Actor(pk.entity_type.as_str().into()))
+            .insert(Pose::new(pk.pitch, pk.body_yaw, pk.head_yaw))
+            .insert(Interpolation::new(
+                time.elapsed_seconds_f64(),
+                pk.position,
+                Vec3::new(pk.pitch, pk.body_yaw, pk.head_yaw),
+            ))
+            .insert(Metadata(pk.entity_metadata.clone()));
     }
 }
 
-/// Updates the position of entities on the server.
+/// Updates the position and rotation of entities on the server. Entities with [Interpolation]
+/// are moved smoothly, while others, such as the local player, are moved immediately.
 fn handle_move_system(
+    time: Res<Time>,
     manager: Res<EntityManager>,
-    mut query: Query<&mut Transform>,
+    mut query: Query<(&mut Transform, Option<&mut Interpolation>)>,
 
     mut pks_abs: EventReader<MoveActorAbsolute>,
     mut pks_detla: EventReader<MoveActorDelta>,
     mut pks_player: EventReader<MovePlayer>,
 ) {
-    let mut move_to = |pk_name: &str, runtime_id: RuntimeId, pos: Vec3| {
+    let now = time.elapsed_seconds_f64();
+    // Rotations are in the order pitch, yaw and head yaw. Values that were not sent are None.
+    // Players are sent at eye height, but models are positioned at the feet. The local player is
+    // positioned at its eyes, so the eye height is only subtracted for interpolated entities.
+    let mut move_to = |pk_name: &str,
+                       runtime_id: RuntimeId,
+                       pos: [Option<f32>; 3],
+                       rot: [Option<f32>; 3],
+                       teleport: bool,
+                       eye_height: f32| {
         let entity = manager.entity_by_rid(runtime_id);
         if entity.is_none() {
             error!(
@@ -151,34 +179,93 @@ fn handle_move_system(
             return;
         }
 
-        if let Ok(mut transform) = query.get_mut(entity.unwrap()) {
-            transform.translation = pos;
+        match query.get_mut(entity.unwrap()) {
+            Ok((_, Some(mut interpolation))) => {
+                let pos = [pos[0], pos[1].map(|y| y - eye_height), pos[2]];
+                if teleport {
+                    interpolation.teleport(now, pos, rot);
+                } else {
+                    interpolation.push(now, pos, rot);
+                }
+            }
+            Ok((mut transform, None)) => {
+                let old = transform.translation;
+                transform.translation = Vec3::new(
+                    pos[0].unwrap_or(old.x),
+                    pos[1].unwrap_or(old.y),
+                    pos[2].unwrap_or(old.z),
+                );
+            }
+            Err(_) => {}
         }
     };
 
     for pk in pks_abs.iter() {
+        // The rotation is sent as pitch, head yaw and yaw.
         move_to(
             std::any::type_name::<MoveActorAbsolute>(),
             pk.entity_runtime_id.into(),
-            pk.position,
+            pk.position.to_array().map(Some),
+            [
+                Some(pk.rotation.x),
+                Some(pk.rotation.z),
+                Some(pk.rotation.y),
+            ],
+            pk.flags & MoveFlag::Teleport.flag() != 0,
+            0.,
         );
     }
     for pk in pks_detla.iter() {
+        let has = |flag: MoveActorDeltaFlag| pk.flags & flag.flag() != 0;
         move_to(
             std::any::type_name::<MoveActorDelta>(),
             pk.entity_runtime_id.into(),
-            pk.position,
+            [
+                has(MoveActorDeltaFlag::HasX).then_some(pk.position.x),
+                has(MoveActorDeltaFlag::HasY).then_some(pk.position.y),
+                has(MoveActorDeltaFlag::HasZ).then_some(pk.position.z),
+            ],
+            [
+                has(MoveActorDeltaFlag::HasRotX).then_some(pk.rotation.x),
+                has(MoveActorDeltaFlag::HasRotZ).then_some(pk.rotation.z),
+                has(MoveActorDeltaFlag::HasRotY).then_some(pk.rotation.y),
+            ],
+            has(MoveActorDeltaFlag::Teleport),
+            0.,
         );
     }
     for pk in pks_player.iter() {
         move_to(
             std::any::type_name::<MovePlayer>(),
             pk.entity_runtime_id.into(),
-            pk.position,
+            pk.position.to_array().map(Some),
+            [Some(pk.pitch), Some(pk.yaw), Some(pk.head_yaw)],
+            matches!(pk.mode, MoveMode::Teleport(_)),
+            PLAYER_EYE_HEIGHT,
         );
     }
 }
 
+/// Updates the velocity of entities, which is used to predict their movement until the server
+/// sends their next position.
+fn handle_motion_system(
+    manager: Res<EntityManager>,
+    mut query: Query<&mut Interpolation>,
+
+    mut pks: EventReader<SetActorMotion>,
+) {
+    for pk in pks.iter() {
+        let Some(mut interpolation) = manager
+            .entity_by_rid(RuntimeId::from(pk.entity_runtime_id))
+            .and_then(|entity| query.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        // The velocity is sent in blocks per tick.
+        interpolation.set_velocity(pk.velocity * 20.);
+    }
+}
+
 /// Despawns entities when requested by the server.
 fn despawn_entity_system(
     mut commands: Commands,
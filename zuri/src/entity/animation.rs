@@ -0,0 +1,142 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::entity::model::EntityModel;
+
+/// Animates the models of entities based on how they move.
+pub(super) struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate_system);
+    }
+}
+
+/// The names of bones that swing forwards when an entity starts walking, and those that swing
+/// backwards. Humanoids swing their arms opposite to their legs, and quadrupeds swing their legs
+/// diagonally.
+const SWING_FORWARDS: &[&str] = &["leftArm", "rightLeg", "leg0", "leg3"];
+const SWING_BACKWARDS: &[&str] = &["rightArm", "leftLeg", "leg1", "leg2"];
+
+/// How far limbs swing back and forth while walking at full speed, in radians.
+const MAX_SWING: f32 = 1.;
+/// The walking speed in blocks per second at which limbs swing the furthest.
+const FULL_SWING_SPEED: f32 = 4.;
+
+/// How far the body leans forwards while sneaking, in radians.
+const SNEAK_LEAN: f32 = 0.5;
+/// How far the model is lowered while sneaking, in blocks.
+const SNEAK_DROP: f32 = 0.2;
+/// The names of the legs of humanoids, which stay upright while the body leans forwards.
+const LEGS: &[&str] = &["rightLeg", "leftLeg"];
+
+/// The pose of an entity, which decides how its [EntityModel] is animated.
+#[derive(Component, Debug)]
+pub struct Pose {
+    /// The pitch of the head in degrees. Positive values look down.
+    pub pitch: f32,
+    /// The yaw of the body in degrees.
+    pub yaw: f32,
+    /// The yaw of the head in degrees. This is independent from the yaw of the body.
+    pub head_yaw: f32,
+    /// The size of the model, where 1 is the default size.
+    pub scale: f32,
+    /// Whether the entity is sneaking, which makes it lean forwards.
+    pub sneaking: bool,
+    /// How far the limbs have progressed through their walking cycle, in radians.
+    walk_phase: f32,
+    /// How far the limbs currently swing, from 0 to 1.
+    walk_amount: f32,
+    /// The position of the entity on the previous frame, used to work out how fast it is walking.
+    last_position: Option<Vec3>,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self {
+            pitch: 0.,
+            yaw: 0.,
+            head_yaw: 0.,
+            scale: 1.,
+            sneaking: false,
+            walk_phase: 0.,
+            walk_amount: 0.,
+            last_position: None,
+        }
+    }
+}
+
+impl Pose {
+    /// Creates a pose with the provided rotation in degrees.
+    pub fn new(pitch: f32, yaw: f32, head_yaw: f32) -> Self {
+        Self {
+            pitch,
+            yaw,
+            head_yaw,
+            ..default()
+        }
+    }
+}
+
+/// Applies the [Pose] of entities to their models. The body is turned towards the yaw, the head
+/// follows the head rotation and the limbs swing depending on how fast the entity is moving.
+/// Sneaking entities lean forwards, with their head and legs kept upright.
+fn animate_system(
+    time: Res<Time>,
+    mut entities: Query<(&GlobalTransform, &mut Pose, &EntityModel)>,
+    mut bones: Query<&mut Transform>,
+) {
+    let delta = time.delta_seconds();
+    if delta <= 0. {
+        return;
+    }
+
+    for (transform, mut pose, model) in &mut entities {
+        let position = transform.translation();
+        let speed = pose
+            .last_position
+            .map(|last| Vec2::new(position.x - last.x, position.z - last.z).length() / delta)
+            .unwrap_or_default();
+        pose.last_position = Some(position);
+
+        // The swing is smoothed, as movement from the server arrives in steps.
+        let target = (speed / FULL_SWING_SPEED).min(1.);
+        pose.walk_amount += (target - pose.walk_amount) * (delta * 10.).min(1.);
+        pose.walk_phase = (pose.walk_phase + speed * delta * 2.) % (2. * PI);
+
+        let lean = if pose.sneaking { SNEAK_LEAN } else { 0. };
+        // Models face north, while a yaw of zero faces south.
+        if let Ok(mut root) = bones.get_mut(model.root) {
+            root.rotation = Quat::from_rotation_y(PI - pose.yaw.to_radians());
+            root.translation.y = if pose.sneaking { -SNEAK_DROP } else { 0. } * pose.scale;
+            root.scale = Vec3::splat(pose.scale / 16.);
+        }
+        if let Some((body, rest)) = model.bones.get("body") {
+            if let Ok(mut body) = bones.get_mut(*body) {
+                body.rotation = *rest * Quat::from_rotation_x(-lean);
+            }
+        }
+        if let Some((head, rest)) = model.bones.get("head") {
+            if let Ok(mut head) = bones.get_mut(*head) {
+                head.rotation = *rest
+                    * Quat::from_rotation_x(lean)
+                    * Quat::from_rotation_y((pose.yaw - pose.head_yaw).to_radians())
+                    * Quat::from_rotation_x(-pose.pitch.to_radians());
+            }
+        }
+
+        let swing = pose.walk_phase.sin() * pose.walk_amount * MAX_SWING;
+        for (names, swing) in [(SWING_FORWARDS, swing), (SWING_BACKWARDS, -swing)] {
+            for name in names {
+                let Some((bone, rest)) = model.bones.get(*name) else {
+                    continue;
+                };
+                let upright = if LEGS.contains(name) { lean } else { 0. };
+                if let Ok(mut bone) = bones.get_mut(*bone) {
+                    bone.rotation = *rest * Quat::from_rotation_x(swing + upright);
+                }
+            }
+        }
+    }
+}
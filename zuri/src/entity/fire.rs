@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use zuri_net::proto::types::entity_data::EntityDataFlag;
+
+use crate::entity::metadata::{self, Metadata};
+use crate::resource_pack::ResourcePacks;
+
+/// Renders flames around entities that are on fire.
+pub(super) struct FirePlugin;
+
+impl Plugin for FirePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FireAssets::default())
+            .add_systems((fire_assets_system, fire_overlay_system).chain());
+    }
+}
+
+/// The texture of the flames. It holds the frames of the fire animation stacked vertically, of
+/// which only the first is shown.
+const FIRE_TEXTURE: &str = "textures/blocks/fire_0";
+/// How much wider the flames are than the bounding box of the entity.
+const FIRE_SPREAD: f32 = 1.2;
+
+/// The mesh and material used to render flames, which are loaded from the resource packs.
+#[derive(Resource, Default)]
+struct FireAssets {
+    overlay: Option<(Handle<Mesh>, Handle<StandardMaterial>)>,
+}
+
+/// Tracks the flames rendered around an entity.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct FireOverlay(Entity);
+
+/// Loads the fire texture from the resource packs whenever they change.
+fn fire_assets_system(
+    packs: Res<ResourcePacks>,
+    mut assets: ResMut<FireAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+
+    assets.overlay = packs.load_texture(FIRE_TEXTURE).map(|image| {
+        let size = image.size();
+        let mesh = overlay_mesh(size.x / size.y);
+        let material = StandardMaterial {
+            base_color_texture: Some(images.add(image)),
+            alpha_mode: AlphaMode::Mask(0.5),
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        };
+        (meshes.add(mesh), materials.add(material))
+    });
+}
+
+/// Creates the four sides of a box of one block wide and high, standing on the ground. The texture
+/// is mapped so that only the top part of the given height is shown on each side.
+fn overlay_mesh(frame_height: f32) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    for normal in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+        let side = normal.cross(Vec3::Y) * 0.5;
+        let centre = normal * 0.5;
+
+        let start = positions.len() as u32;
+        for (corner, uv) in [
+            (centre - side, [0., frame_height]),
+            (centre + side, [1., frame_height]),
+            (centre + side + Vec3::Y, [1., 0.]),
+            (centre - side + Vec3::Y, [0., 0.]),
+        ] {
+            positions.push(corner.to_array());
+            normals.push(normal.to_array());
+            uvs.push(uv);
+        }
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Adds flames to entities that caught fire and removes them once the fire is out. The flames are
+/// sized to the bounding box of the entity.
+fn fire_overlay_system(
+    mut commands: Commands,
+    assets: Res<FireAssets>,
+    query: Query<(Entity, Ref<Metadata>, Option<&FireOverlay>)>,
+    mut overlays: Query<&mut Transform>,
+) {
+    for (entity, metadata, overlay) in &query {
+        if !metadata.is_changed() && !assets.is_changed() {
+            continue;
+        }
+
+        let on_fire = metadata.flags().contains(EntityDataFlag::OnFire);
+        let (width, height) = metadata.bounding_box().unwrap_or((0.6, 1.8));
+        let scale = metadata::scale(&metadata);
+        let transform = Transform::from_scale(
+            Vec3::new(width * FIRE_SPREAD, height, width * FIRE_SPREAD) * scale,
+        );
+
+        match (on_fire, overlay, &assets.overlay) {
+            // The overlay is respawned if the assets changed, so that it uses the new texture.
+            (true, Some(overlay), _) if !assets.is_changed() => {
+                if let Ok(mut overlay) = overlays.get_mut(overlay.0) {
+                    *overlay = transform;
+                }
+            }
+            (true, overlay, Some((mesh, material))) => {
+                if let Some(overlay) = overlay {
+                    commands.entity(overlay.0).despawn_recursive();
+                }
+                let overlay = commands
+                    .spawn(PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform,
+                        ..default()
+                    })
+                    .id();
+                commands
+                    .entity(entity)
+                    .insert(FireOverlay(overlay))
+                    .add_child(overlay);
+            }
+            (_, Some(overlay), _) => {
+                commands.entity(overlay.0).despawn_recursive();
+                commands.entity(entity).remove::<FireOverlay>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::entity::animation::Pose;
+
+/// Smoothly moves entities between the positions and rotations sent by the server.
+pub(super) struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InterpolationSettings::default())
+            .add_system(interpolate_system);
+    }
+}
+
+/// The longest time in seconds that an entity keeps moving along its velocity after the last
+/// position received from the server, before it stops to wait for the next one.
+const MAX_EXTRAPOLATION: f64 = 0.25;
+/// The maximum number of states that are buffered for a single entity.
+const MAX_STATES: usize = 32;
+
+/// Configures how entity movement is interpolated.
+#[derive(Resource, Debug, Clone)]
+pub struct InterpolationSettings {
+    /// How far behind the server entities are rendered, in seconds. Entities are moved between
+    /// the states they had at this point in the past, so a longer delay makes movement smoother
+    /// at the cost of latency. The server sends movement every tick, so the delay should be at
+    /// least one tick.
+    pub delay: f64,
+}
+
+impl Default for InterpolationSettings {
+    fn default() -> Self {
+        Self { delay: 0.1 }
+    }
+}
+
+/// The position and rotation of an entity at a point in time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct State {
+    /// The time in seconds since the start of the app at which the state was received.
+    time: f64,
+    position: Vec3,
+    /// The pitch, yaw and head yaw in degrees.
+    rotation: Vec3,
+}
+
+/// Buffers the movement of an entity received from the server, so that it can be interpolated.
+///
+/// Entities with this component have their [Transform] and [Pose] controlled by it.
+#[derive(Component, Debug)]
+pub struct Interpolation {
+    /// The received states, from oldest to newest. There is always at least one state.
+    states: VecDeque<State>,
+    /// The velocity of the entity in blocks per second, as last set by the server. It is used to
+    /// predict where the entity goes after the newest state.
+    velocity: Vec3,
+}
+
+impl Interpolation {
+    /// Creates an interpolation that starts at a position with a rotation, in the order pitch, yaw
+    /// and head yaw.
+    pub fn new(time: f64, position: Vec3, rotation: Vec3) -> Self {
+        Self {
+            states: VecDeque::from([State {
+                time,
+                position,
+                rotation,
+            }]),
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Adds a new state of the entity. Components that are None keep the value of the newest
+    /// state, as the server leaves out values that did not change. Rotations are in the order
+    /// pitch, yaw and head yaw.
+    pub fn push(&mut self, time: f64, position: [Option<f32>; 3], rotation: [Option<f32>; 3]) {
+        let newest = self.newest();
+        let merge = |new: [Option<f32>; 3], old: Vec3| {
+            Vec3::new(
+                new[0].unwrap_or(old.x),
+                new[1].unwrap_or(old.y),
+                new[2].unwrap_or(old.z),
+            )
+        };
+        self.states.push_back(State {
+            time,
+            position: merge(position, newest.position),
+            rotation: merge(rotation, newest.rotation),
+        });
+        if self.states.len() > MAX_STATES {
+            self.states.pop_front();
+        }
+    }
+
+    /// Moves the entity to a state immediately, without interpolating from the previous states.
+    pub fn teleport(&mut self, time: f64, position: [Option<f32>; 3], rotation: [Option<f32>; 3]) {
+        self.push(time, position, rotation);
+        let newest = self.newest();
+        self.states.clear();
+        self.states.push_back(newest);
+        self.velocity = Vec3::ZERO;
+    }
+
+    /// Sets the velocity of the entity in blocks per second.
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity;
+    }
+
+    /// Returns the position and rotation of the entity at a point in time. Between two states the
+    /// values are interpolated. After the newest state, the position is extrapolated using the
+    /// velocity for a short while.
+    pub fn sample(&self, time: f64) -> (Vec3, Vec3) {
+        let newest = self.newest();
+        if time >= newest.time {
+            let elapsed = (time - newest.time).min(MAX_EXTRAPOLATION) as f32;
+            return (newest.position + self.velocity * elapsed, newest.rotation);
+        }
+
+        // The newest state is after the time, so there always is a state after it.
+        let next = self
+            .states
+            .iter()
+            .position(|state| state.time > time)
+            .unwrap();
+        if next == 0 {
+            let oldest = self.states[0];
+            return (oldest.position, oldest.rotation);
+        }
+        let (from, to) = (self.states[next - 1], self.states[next]);
+        let t = ((time - from.time) / (to.time - from.time)) as f32;
+        (
+            from.position.lerp(to.position, t),
+            Vec3::new(
+                lerp_angle(from.rotation.x, to.rotation.x, t),
+                lerp_angle(from.rotation.y, to.rotation.y, t),
+                lerp_angle(from.rotation.z, to.rotation.z, t),
+            ),
+        )
+    }
+
+    /// Removes the states that are no longer needed to sample at or after a point in time. One
+    /// state before the time is kept to interpolate from.
+    fn prune(&mut self, time: f64) {
+        while self.states.len() > 1 && self.states[1].time <= time {
+            self.states.pop_front();
+        }
+    }
+
+    fn newest(&self) -> State {
+        *self.states.back().unwrap()
+    }
+}
+
+/// Interpolates between two angles in degrees, taking the shortest way around the circle.
+fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let difference = (to - from + 180.).rem_euclid(360.) - 180.;
+    from + difference * t
+}
+
+/// Moves entities to their interpolated position and rotation.
+fn interpolate_system(
+    time: Res<Time>,
+    settings: Res<InterpolationSettings>,
+    mut query: Query<(&mut Interpolation, &mut Transform, Option<&mut Pose>)>,
+) {
+    let render_time = time.elapsed_seconds_f64() - settings.delay;
+    for (mut interpolation, mut transform, pose) in &mut query {
+        interpolation.prune(render_time);
+        let (position, rotation) = interpolation.sample(render_time);
+        transform.translation = position;
+        if let Some(mut pose) = pose {
+            pose.pitch = rotation.x;
+            pose.yaw = rotation.y;
+            pose.head_yaw = rotation.z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate() {
+        let mut interpolation = Interpolation::new(0., Vec3::ZERO, Vec3::ZERO);
+        interpolation.push(1., [Some(2.), None, None], [None, Some(90.), None]);
+
+        let (position, rotation) = interpolation.sample(0.5);
+        assert_eq!(position, Vec3::new(1., 0., 0.));
+        assert_eq!(rotation, Vec3::new(0., 45., 0.));
+    }
+
+    #[test]
+    fn extrapolate() {
+        let mut interpolation = Interpolation::new(0., Vec3::ZERO, Vec3::ZERO);
+        interpolation.set_velocity(Vec3::X);
+        assert_eq!(interpolation.sample(0.1).0, Vec3::new(0.1, 0., 0.));
+        assert_eq!(interpolation.sample(10.).0, Vec3::new(0.25, 0., 0.));
+    }
+
+    #[test]
+    fn teleport() {
+        let mut interpolation = Interpolation::new(0., Vec3::ZERO, Vec3::ZERO);
+        interpolation.teleport(1., [Some(5.), Some(5.), Some(5.)], [None; 3]);
+        assert_eq!(interpolation.sample(0.5).0, Vec3::splat(5.));
+    }
+
+    #[test]
+    fn angle() {
+        assert_eq!(lerp_angle(350., 10., 0.5), 360.);
+        assert_eq!(lerp_angle(10., 350., 0.5), 0.);
+    }
+}
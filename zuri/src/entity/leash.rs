@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::entity::metadata::{self, Metadata};
+use crate::entity::EntityManager;
+
+/// Renders leashes between leashed entities and the entity holding them.
+pub(super) struct LeashPlugin;
+
+impl Plugin for LeashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((leash_update_system, leash_render_system).chain());
+    }
+}
+
+/// How thick the leash is, in blocks.
+const LEASH_THICKNESS: f32 = 0.04;
+/// The colour of the leash.
+const LEASH_COLOUR: Color = Color::rgb(0.45, 0.3, 0.15);
+/// How high up on the bounding box of an entity the leash is attached, from 0 to 1.
+const LEASH_ATTACH_HEIGHT: f32 = 0.6;
+
+/// Tracks the leash entity attached to a leashed entity. The leash is a thin box that is stretched
+/// between the two entities.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Leash(Entity);
+
+/// Adds leashes to entities that were leashed and removes them once they are let go.
+fn leash_update_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+    query: Query<(Entity, &Metadata, Option<&Leash>), Changed<Metadata>>,
+) {
+    for (entity, metadata, leash) in &query {
+        match (metadata.leash_holder(), leash) {
+            (Some(_), None) => {
+                let (mesh, material) = assets.get_or_insert_with(|| {
+                    (
+                        meshes.add(shape::Cube::new(1.).into()),
+                        materials.add(StandardMaterial {
+                            base_color: LEASH_COLOUR,
+                            perceptual_roughness: 1.,
+                            reflectance: 0.,
+                            ..default()
+                        }),
+                    )
+                });
+                let leash = commands
+                    .spawn(PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    })
+                    .id();
+                commands
+                    .entity(entity)
+                    .insert(Leash(leash))
+                    .add_child(leash);
+            }
+            (None, Some(leash)) => {
+                commands.entity(leash.0).despawn_recursive();
+                commands.entity(entity).remove::<Leash>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Stretches leashes between the leashed entity and its holder. Leashes whose holder is not known
+/// to the client are hidden.
+fn leash_render_system(
+    manager: Res<EntityManager>,
+    leashed: Query<(&GlobalTransform, &Metadata, &Leash)>,
+    holders: Query<(&GlobalTransform, Option<&Metadata>)>,
+    mut leashes: Query<(&mut Transform, &mut Visibility)>,
+) {
+    for (transform, metadata, leash) in &leashed {
+        let Ok((mut leash_transform, mut visibility)) = leashes.get_mut(leash.0) else {
+            continue;
+        };
+        let Some((holder, holder_metadata)) = metadata
+            .leash_holder()
+            .and_then(|holder| manager.entity_by_uid(holder))
+            .and_then(|holder| holders.get(holder).ok())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        // The leash is a child of the leashed entity, so it is positioned relative to it. Entities
+        // without metadata, such as the local player, hold the leash at their position.
+        let position = transform.translation();
+        let from = Vec3::Y * attach_height(metadata);
+        let to = holder.translation() - position
+            + Vec3::Y * holder_metadata.map(attach_height).unwrap_or_default();
+
+        let direction = to - from;
+        *leash_transform = Transform::from_translation((from + to) / 2.)
+            .with_rotation(Quat::from_rotation_arc(
+                Vec3::Z,
+                direction.normalize_or_zero(),
+            ))
+            .with_scale(Vec3::new(
+                LEASH_THICKNESS,
+                LEASH_THICKNESS,
+                direction.length(),
+            ));
+    }
+}
+
+/// Returns the height above the feet of an entity at which a leash is attached to it.
+fn attach_height(metadata: &Metadata) -> f32 {
+    let (_, height) = metadata.bounding_box().unwrap_or_default();
+    height * metadata::scale(metadata) * LEASH_ATTACH_HEIGHT
+}
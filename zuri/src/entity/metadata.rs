@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use zuri_net::proto::packet::set_actor_data::SetActorData;
+use zuri_net::proto::types::entity_data::{EntityDataFlag, EntityMetadata};
+
+use crate::client::NetworkSet;
+use crate::entity::animation::Pose;
+use crate::entity::nametag::Nametag;
+use crate::entity::EntityManager;
+
+/// Keeps the [Metadata] of entities up to date and applies it to how they are rendered.
+pub(super) struct MetadataPlugin;
+
+impl Plugin for MetadataPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(metadata_update_system.in_base_set(NetworkSet::Process))
+            .add_systems((visibility_system, nametag_system, pose_system));
+    }
+}
+
+/// The scale of baby entities, if the server does not send a scale itself.
+const BABY_SCALE: f32 = 0.5;
+/// How far above the bounding box of an entity its nametag is rendered, in blocks.
+const NAMETAG_MARGIN: f32 = 0.3;
+
+/// The metadata of an entity, as sent by the server. It holds the flags, name tag, scale and
+/// other properties of the entity.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct Metadata(pub EntityMetadata);
+
+/// Applies metadata updates from the server to entities that have [Metadata].
+fn metadata_update_system(
+    manager: Res<EntityManager>,
+    mut query: Query<&mut Metadata>,
+
+    mut pks: EventReader<SetActorData>,
+) {
+    for pk in pks.iter() {
+        let Some(mut metadata) = manager
+            .entity_by_rid(pk.entity_runtime_id)
+            .and_then(|entity| query.get_mut(entity).ok())
+        else {
+            continue;
+        };
+        metadata.merge(&pk.entity_metadata);
+    }
+}
+
+/// Hides invisible entities.
+fn visibility_system(mut query: Query<(&Metadata, &mut Visibility), Changed<Metadata>>) {
+    for (metadata, mut visibility) in &mut query {
+        *visibility = if metadata.flags().contains(EntityDataFlag::Invisible) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+/// Shows the name tag of entities above them when the server allows it to be shown.
+fn nametag_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Metadata, Option<&mut Nametag>), Changed<Metadata>>,
+) {
+    for (entity, metadata, nametag) in &mut query {
+        let flags = metadata.flags();
+        let shown = (flags.contains(EntityDataFlag::ShowName)
+            || flags.contains(EntityDataFlag::AlwaysShowName))
+            && !flags.contains(EntityDataFlag::Invisible);
+        let contents = metadata.name_tag().filter(|name| shown && !name.is_empty());
+
+        let (_, height) = metadata.bounding_box().unwrap_or((0., 1.8));
+        let y_offset = height * scale(metadata) + NAMETAG_MARGIN;
+        match (contents, nametag) {
+            (Some(contents), Some(mut nametag)) => {
+                if nametag.contents != contents || nametag.y_offset != y_offset {
+                    nametag.contents = contents.to_string();
+                    nametag.y_offset = y_offset;
+                }
+            }
+            (Some(contents), None) => {
+                commands.entity(entity).insert(Nametag {
+                    contents: contents.to_string(),
+                    y_offset,
+                });
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<Nametag>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Updates the [Pose] of entities with their scale and whether they are sneaking.
+fn pose_system(mut query: Query<(&Metadata, &mut Pose), Changed<Metadata>>) {
+    for (metadata, mut pose) in &mut query {
+        pose.scale = scale(metadata);
+        pose.sneaking = metadata.flags().contains(EntityDataFlag::Sneaking);
+    }
+}
+
+/// Returns the scale an entity is rendered at. Babies are rendered smaller if the server does not
+/// send a scale.
+pub(super) fn scale(metadata: &EntityMetadata) -> f32 {
+    metadata.scale().unwrap_or_else(|| {
+        if metadata.flags().contains(EntityDataFlag::Baby) {
+            BABY_SCALE
+        } else {
+            1.
+        }
+    })
+}
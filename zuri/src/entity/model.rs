@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use json::JsonValue;
+use uuid::Uuid;
+
+use crate::entity::skin::PlayerSkins;
+use crate::model::{self, Model};
+use crate::resource_pack::ResourcePacks;
+
+/// The identifier of the entity definition used for players without a valid skin.
+const PLAYER_IDENTIFIER: &str = "minecraft:player";
+
+/// Builds the models of entities from their [Appearance].
+pub(super) struct EntityModelPlugin;
+
+impl Plugin for EntityModelPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EntityDefinitions::default())
+            .add_systems((definitions_build_system, model_build_system).chain());
+    }
+}
+
+/// Decides what an entity looks like. Changing this component rebuilds the model of the entity.
+#[derive(Component, Debug, Clone)]
+pub enum Appearance {
+    /// An entity that is rendered using the client entity definition with this identifier, such as
+    /// `minecraft:pig`.
+    Actor(Box<str>),
+    /// A player that is rendered using the skin of the player with this UUID.
+    Player(Uuid),
+}
+
+/// The model currently spawned for an entity.
+#[derive(Component)]
+pub struct EntityModel {
+    /// The entity all bones are attached to. Its rotation is the yaw of the body.
+    pub root: Entity,
+    /// The entities of all bones by name, along with their rotation in the default pose.
+    pub bones: HashMap<Box<str>, (Entity, Quat)>,
+}
+
+/// The geometry and textures of all entities, as defined by the resource packs.
+#[derive(Resource, Default)]
+pub struct EntityDefinitions {
+    /// Maps entity identifiers to their definition.
+    entities: HashMap<Box<str>, EntityDefinition>,
+    /// Maps model identifiers to their model.
+    models: HashMap<Box<str>, Model>,
+    /// The materials created for each texture path, so that entities of the same type share them.
+    materials: HashMap<Box<str>, Handle<StandardMaterial>>,
+}
+
+/// The geometry and texture of a single entity type, as read from a client entity file.
+#[derive(Debug)]
+struct EntityDefinition {
+    geometry: Box<str>,
+    texture: Box<str>,
+}
+
+impl EntityDefinition {
+    /// Parses the `minecraft:client_entity` section of a client entity file, returning the
+    /// identifier of the entity and its definition. Only the default geometry and texture are
+    /// used. If there is no default, the first one is used instead.
+    fn parse(file: &JsonValue) -> Option<(Box<str>, Self)> {
+        fn default(entries: &JsonValue) -> Option<&str> {
+            entries["default"]
+                .as_str()
+                .or_else(|| entries.entries().find_map(|(_, v)| v.as_str()))
+        }
+
+        let description = &file["minecraft:client_entity"]["description"];
+        Some((
+            description["identifier"].as_str()?.into(),
+            Self {
+                geometry: default(&description["geometry"])?.into(),
+                texture: default(&description["textures"])?.into(),
+            },
+        ))
+    }
+}
+
+impl EntityDefinitions {
+    /// Returns the model and material of an entity type, if both exist.
+    fn get(
+        &mut self,
+        identifier: &str,
+        packs: &ResourcePacks,
+        images: &mut Assets<Image>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Option<(&Model, Handle<StandardMaterial>)> {
+        let definition = self.entities.get(identifier)?;
+        let material = match self.materials.get(&definition.texture) {
+            Some(material) => material.clone(),
+            None => {
+                let image = packs.load_texture(&definition.texture)?;
+                let material = materials.add(entity_material(images.add(image)));
+                self.materials
+                    .insert(definition.texture.clone(), material.clone());
+                material
+            }
+        };
+        Some((self.models.get(&definition.geometry)?, material))
+    }
+}
+
+/// Reads the entity definitions and models from the resource packs whenever they change.
+fn definitions_build_system(
+    packs: Res<ResourcePacks>,
+    mut definitions: ResMut<EntityDefinitions>,
+    mut appearances: Query<&mut Appearance>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+
+    let mut entities = HashMap::new();
+    for pack in packs.iter() {
+        for path in pack.list_dir("entity") {
+            let Some(file) = pack.read_json(&path) else {
+                continue;
+            };
+            if let Some((identifier, definition)) = EntityDefinition::parse(&file) {
+                entities.insert(identifier, definition);
+            }
+        }
+    }
+
+    // Older models are all stored in a single file, rather than in separate files.
+    let mut models = model::read_models(&packs, "models/entity");
+    for file in packs.read_json_all("models/mobs.json") {
+        for model in model::parse_models(&file) {
+            models.entry(model.identifier.clone()).or_insert(model);
+        }
+    }
+    info!(
+        "Loaded {} entity definitions and {} entity models",
+        entities.len(),
+        models.len()
+    );
+
+    *definitions = EntityDefinitions {
+        entities,
+        models,
+        materials: HashMap::new(),
+    };
+    // Rebuild the models of all entities that are already present.
+    for mut appearance in &mut appearances {
+        appearance.set_changed();
+    }
+}
+
+/// Spawns the model of entities whose [Appearance] has changed, replacing their previous model.
+#[allow(clippy::too_many_arguments)]
+fn model_build_system(
+    mut commands: Commands,
+    packs: Res<ResourcePacks>,
+    skins: Res<PlayerSkins>,
+    mut definitions: ResMut<EntityDefinitions>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &Appearance, Option<&EntityModel>), Changed<Appearance>>,
+) {
+    for (entity, appearance, previous) in &query {
+        if let Some(previous) = previous {
+            commands.entity(previous.root).despawn_recursive();
+        }
+
+        let root = commands
+            .spawn(SpatialBundle::from_transform(Transform::from_scale(
+                Vec3::splat(1. / 16.),
+            )))
+            .id();
+        commands.entity(entity).add_child(root);
+
+        let mut bones = HashMap::new();
+        let decoded = match appearance {
+            Appearance::Player(uuid) => skins.get(uuid).and_then(|skin| skins.decode(skin)),
+            Appearance::Actor(_) => None,
+        };
+        if let Some(decoded) = decoded {
+            let material = materials.add(entity_material(images.add(decoded.image)));
+            bones.extend(spawn_model(
+                &mut commands,
+                &mut meshes,
+                &decoded.model,
+                material,
+                root,
+            ));
+            if let Some(cape) = decoded.cape {
+                let material = materials.add(entity_material(images.add(cape)));
+                bones.extend(spawn_model(
+                    &mut commands,
+                    &mut meshes,
+                    skins.cape_model(),
+                    material,
+                    root,
+                ));
+            }
+        } else {
+            let identifier = match appearance {
+                Appearance::Actor(identifier) => identifier.as_ref(),
+                Appearance::Player(_) => PLAYER_IDENTIFIER,
+            };
+            match definitions.get(identifier, &packs, &mut images, &mut materials) {
+                Some((model, material)) => {
+                    bones.extend(spawn_model(
+                        &mut commands,
+                        &mut meshes,
+                        model,
+                        material,
+                        root,
+                    ));
+                }
+                None => debug!("No model found for entity `{identifier}`"),
+            }
+        }
+
+        commands.entity(entity).insert(EntityModel { root, bones });
+    }
+}
+
+/// Spawns an entity for each bone of a model, attached to the entity of its parent bone. Bones
+/// without a parent are attached to the root. Returns the entities of the bones by name, along
+/// with their default rotation.
+fn spawn_model(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    model: &Model,
+    material: Handle<StandardMaterial>,
+    root: Entity,
+) -> HashMap<Box<str>, (Entity, Quat)> {
+    let pivots: HashMap<&str, Vec3> = model
+        .bones
+        .iter()
+        .map(|bone| (bone.name.as_ref(), bone.pivot))
+        .collect();
+
+    let mut bones = HashMap::new();
+    for bone in &model.bones {
+        let parent = bone
+            .parent
+            .as_deref()
+            .filter(|parent| pivots.contains_key(parent));
+        let parent_pivot = parent.map(|parent| pivots[parent]).unwrap_or_default();
+        let transform =
+            Transform::from_translation(bone.pivot - parent_pivot).with_rotation(bone.rotation);
+
+        let mut entity = commands.spawn(SpatialBundle::from_transform(transform));
+        if let Some(mesh) = bone.mesh(model.texture_size) {
+            entity.insert((meshes.add(mesh), material.clone()));
+        }
+        bones.insert(bone.name.clone(), (entity.id(), bone.rotation));
+    }
+
+    for bone in &model.bones {
+        let parent = bone
+            .parent
+            .as_deref()
+            .and_then(|parent| bones.get(parent))
+            .map(|(entity, _)| *entity)
+            .unwrap_or(root);
+        commands.entity(parent).add_child(bones[&bone.name].0);
+    }
+    bones
+}
+
+/// Creates the material used to render an entity with a texture. Entity textures commonly have
+/// transparent parts, such as the outer layer of a skin.
+pub(super) fn entity_material(texture: Handle<Image>) -> StandardMaterial {
+    StandardMaterial {
+        base_color_texture: Some(texture),
+        alpha_mode: AlphaMode::Mask(0.5),
+        perceptual_roughness: 1.,
+        reflectance: 0.,
+        ..default()
+    }
+}
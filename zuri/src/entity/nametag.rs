@@ -24,6 +24,11 @@ pub struct Nametag {
     pub y_offset: f32,
 }
 
+/// A line shown below the nametag of an entity, such as its score in the objective displayed below
+/// names. Nothing is shown if the line is empty.
+#[derive(Component, Clone, Default)]
+pub struct NametagScore(pub String);
+
 /// Component used to track a nametag entity. Contains the id of the entity it is following.
 #[derive(Component, Copy, Clone)]
 struct NametagMarker;
@@ -36,7 +41,7 @@ struct NametagOwner(Entity);
 fn tag_create_system(
     mut commands: Commands,
     assets: Res<AssetServer>,
-    new_tags: Query<(Entity, &Nametag), Added<Nametag>>,
+    new_tags: Query<(Entity, &Nametag, Option<&NametagScore>), Added<Nametag>>,
 ) {
     if new_tags.is_empty() {
         return;
@@ -44,11 +49,11 @@ fn tag_create_system(
 
     let font = assets.load("monocraft.ttf");
 
-    for (entity, new_tag) in &new_tags {
+    for (entity, new_tag, score) in &new_tags {
         let tag_entity = commands
             .spawn(BillboardTextBundle {
                 text: Text::from_section(
-                    new_tag.contents.clone(),
+                    tag_text(new_tag, score),
                     TextStyle {
                         font: font.clone(),
                         font_size: 18.0,
@@ -70,22 +75,34 @@ fn tag_create_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn tag_update_system(
-    update: Query<(&Nametag, &NametagOwner), Changed<Nametag>>,
+    update: Query<
+        (&Nametag, Option<&NametagScore>, &NametagOwner),
+        Or<(Changed<Nametag>, Changed<NametagScore>)>,
+    >,
     mut tags: Query<(&mut Text, &mut Transform), With<NametagMarker>>,
 ) {
-    for (name_tag, owner) in &update {
+    for (name_tag, score, owner) in &update {
         let tag_entity = tags.get_mut(owner.0);
         if tag_entity.is_err() {
             continue;
         }
 
         let (mut text, mut tag_transform) = tag_entity.unwrap();
-        text.sections[0].value = name_tag.contents.clone();
+        text.sections[0].value = tag_text(name_tag, score);
         tag_transform.translation.y = name_tag.y_offset;
     }
 }
 
+/// Returns the text of a nametag, with the score line below the name if there is one.
+fn tag_text(name_tag: &Nametag, score: Option<&NametagScore>) -> String {
+    match score.filter(|score| !score.0.is_empty()) {
+        Some(score) => format!("{}\n{}", name_tag.contents, score.0),
+        None => name_tag.contents.clone(),
+    }
+}
+
 fn tag_remove_system(
     mut commands: Commands,
     mut removed: RemovedComponents<Nametag>,
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use uuid::Uuid;
+use zuri_net::proto::packet::player_list::{PlayerList, PlayerListAction};
+use zuri_net::proto::packet::player_skin::PlayerSkin;
+use zuri_net::proto::types::skin::Skin;
+
+use crate::entity::model::Appearance;
+use crate::model::{self, Model};
+use crate::resource_pack::parse_json;
+
+/// The default player models, which are used by skins that do not have custom geometry.
+const HUMANOID_MODELS: &str = include_str!("../../assets/humanoid.geo.json");
+
+/// The identifiers of the default player models for wide and slim arms.
+const HUMANOID_WIDE: &str = "geometry.humanoid.custom";
+const HUMANOID_SLIM: &str = "geometry.humanoid.customSlim";
+/// The identifier of the model used to render capes.
+const CAPE: &str = "geometry.cape";
+
+/// Keeps track of the skins of all players in the player list.
+pub(super) struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerSkins::default())
+            .add_system(skin_update_system);
+    }
+}
+
+/// The skins of all players in the player list, by UUID. Players are rendered using the skin of
+/// the entry with the same UUID.
+#[derive(Resource)]
+pub struct PlayerSkins {
+    skins: HashMap<Uuid, Skin>,
+    /// The default player and cape models.
+    models: HashMap<Box<str>, Model>,
+}
+
+impl Default for PlayerSkins {
+    fn default() -> Self {
+        let models = parse_json(HUMANOID_MODELS.as_bytes()).expect("Invalid humanoid models");
+        Self {
+            skins: HashMap::new(),
+            models: model::parse_models(&models)
+                .into_iter()
+                .map(|model| (model.identifier.clone(), model))
+                .collect(),
+        }
+    }
+}
+
+/// A skin that has been decoded into a model and textures.
+pub struct DecodedSkin {
+    pub model: Model,
+    pub image: Image,
+    pub cape: Option<Image>,
+}
+
+impl PlayerSkins {
+    /// Returns the skin of the player with a UUID, if it is in the player list.
+    pub fn get(&self, uuid: &Uuid) -> Option<&Skin> {
+        self.skins.get(uuid)
+    }
+
+    /// Returns the model used to render capes, which uses a separate 64x32 texture.
+    pub fn cape_model(&self) -> &Model {
+        &self.models[CAPE]
+    }
+
+    /// Decodes the model and textures of a skin. Returns None if the skin image is invalid.
+    ///
+    /// The model is looked up in the geometry sent along with the skin, using the identifier from
+    /// its resource patch. Persona skins are sent with their pieces already combined into a
+    /// single image and model, so the pieces themselves do not need to be resolved. Skins without
+    /// valid geometry use the default model matching their arm size.
+    pub fn decode(&self, skin: &Skin) -> Option<DecodedSkin> {
+        let Some(image) = raw_image(
+            skin.skin_image_width,
+            skin.skin_image_height,
+            &skin.skin_data,
+        ) else {
+            warn!("Skin `{}` has invalid image data", skin.skin_id);
+            return None;
+        };
+        let cape = raw_image(
+            skin.cape_image_width,
+            skin.cape_image_height,
+            &skin.cape_data,
+        );
+
+        let identifier = parse_json(&skin.skin_resource_patch)
+            .and_then(|patch| patch["geometry"]["default"].as_str().map(String::from));
+        let custom = identifier.and_then(|identifier| {
+            parse_json(&skin.skin_geometry)
+                .map(|geometry| model::parse_models(&geometry))
+                .unwrap_or_default()
+                .into_iter()
+                .find(|model| *model.identifier == identifier)
+                .or_else(|| self.models.get(identifier.as_str()).cloned())
+        });
+        let model = custom.unwrap_or_else(|| {
+            let default = if skin.arm_size == "slim" {
+                HUMANOID_SLIM
+            } else {
+                HUMANOID_WIDE
+            };
+            self.models[default].clone()
+        });
+
+        Some(DecodedSkin { model, image, cape })
+    }
+}
+
+/// Creates an image from raw RGBA data. Returns None if the size does not match the data.
+fn raw_image(width: u32, height: u32, data: &[u8]) -> Option<Image> {
+    if width == 0 || height == 0 || data.len() != width as usize * height as usize * 4 {
+        return None;
+    }
+    Some(Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data.to_vec(),
+        TextureFormat::Rgba8UnormSrgb,
+    ))
+}
+
+/// Updates the skins of players when the player list changes or a player changes their skin. The
+/// models of players whose skin changed are rebuilt.
+fn skin_update_system(
+    mut skins: ResMut<PlayerSkins>,
+    mut list_events: EventReader<PlayerList>,
+    mut skin_events: EventReader<PlayerSkin>,
+    mut appearances: Query<&mut Appearance>,
+) {
+    let mut changed = Vec::new();
+    for event in list_events.iter() {
+        match &event.action_type {
+            PlayerListAction::Add(add) => {
+                for entry in &add.entries {
+                    skins.skins.insert(entry.uuid, entry.skin.clone());
+                    changed.push(entry.uuid);
+                }
+            }
+            PlayerListAction::Remove(remove) => {
+                for uuid in &remove.uuids {
+                    skins.skins.remove(uuid);
+                }
+            }
+        }
+    }
+    for event in skin_events.iter() {
+        skins.skins.insert(event.uuid, event.skin.clone());
+        changed.push(event.uuid);
+    }
+
+    if changed.is_empty() {
+        return;
+    }
+    for mut appearance in &mut appearances {
+        if matches!(*appearance, Appearance::Player(uuid) if changed.contains(&uuid)) {
+            appearance.set_changed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn humanoid_models_are_valid() {
+        let skins = PlayerSkins::default();
+        for identifier in [HUMANOID_WIDE, HUMANOID_SLIM, CAPE] {
+            assert!(!skins.models[identifier].bones.is_empty());
+        }
+    }
+}
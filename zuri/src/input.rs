@@ -7,6 +7,7 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ClientInput::default())
+            .insert_resource(InputFocus::default())
             .add_system(mouse_input_system)
             .add_system(keyboard_input_system);
     }
@@ -21,7 +22,24 @@ pub struct ClientInput {
     pub sneak: bool,
 }
 
-fn keyboard_input_system(keyboard: Res<Input<KeyCode>>, mut input: ResMut<ClientInput>) {
+/// Decides whether keyboard and mouse input controls the player or the user interface, such as
+/// when typing in chat.
+#[derive(Resource, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InputFocus {
+    #[default]
+    Game,
+    Ui,
+}
+
+fn keyboard_input_system(
+    keyboard: Res<Input<KeyCode>>,
+    focus: Res<InputFocus>,
+    mut input: ResMut<ClientInput>,
+) {
+    if *focus == InputFocus::Ui {
+        *input = ClientInput::default();
+        return;
+    }
     input.movement = Vec2::new(
         if keyboard.pressed(KeyCode::D) {
             1.
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use zuri_net::inventory::{Inventory, RequestResult};
+use zuri_net::proto::packet::container_close::ContainerClose;
+use zuri_net::proto::packet::container_open::ContainerOpen;
+use zuri_net::proto::packet::crafting_data::CraftingData;
+use zuri_net::proto::packet::inventory_content::InventoryContent;
+use zuri_net::proto::packet::inventory_slot::InventorySlot;
+use zuri_net::proto::packet::item_stack_request::ItemStackRequest;
+use zuri_net::proto::packet::item_stack_response::ItemStackResponse;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::item_stack::ItemStackRequestEntry;
+use zuri_net::recipe::RecipeBook;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+
+/// Keeps track of the contents of the inventories of the player, including opened containers, and
+/// of the recipes known to the server.
+///
+/// Items are moved by building a request with [Inventory::request] and sending it with
+/// [send_request]. The results of requests are sent as [RequestResult] events.
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerInventory::default())
+            .insert_resource(Recipes::default())
+            .add_event::<RequestResult>()
+            .add_systems(
+                (inventory_update_system, recipes_update_system).in_base_set(NetworkSet::Process),
+            )
+            .add_systems(
+                (reset_resource::<PlayerInventory>, reset_resource::<Recipes>)
+                    .distributive_run_if(on_event::<Disconnected>()),
+            );
+    }
+}
+
+/// The inventory of the local player.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PlayerInventory(pub Inventory);
+
+/// The recipes sent by the server.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct Recipes(pub RecipeBook);
+
+/// Sends a finished item stack request to the server.
+pub fn send_request(packets: &mut EventWriter<Packet>, request: ItemStackRequestEntry) {
+    packets.send(Packet::ItemStackRequest(ItemStackRequest {
+        requests: vec![request],
+    }));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn inventory_update_system(
+    mut inventory: ResMut<PlayerInventory>,
+    mut results: EventWriter<RequestResult>,
+
+    mut start_game: EventReader<StartGame>,
+    mut contents: EventReader<InventoryContent>,
+    mut slots: EventReader<InventorySlot>,
+    mut opens: EventReader<ContainerOpen>,
+    mut closes: EventReader<ContainerClose>,
+    mut responses: EventReader<ItemStackResponse>,
+) {
+    for pk in start_game.iter() {
+        **inventory = Inventory::default();
+        if !pk.server_authoritative_inventory {
+            warn!("Server does not use server authoritative inventories, item stack requests will be rejected");
+        }
+    }
+    for pk in opens.iter() {
+        inventory.handle_open(pk);
+    }
+    for pk in contents.iter() {
+        inventory.handle_content(pk);
+    }
+    for pk in slots.iter() {
+        inventory.handle_slot(pk);
+    }
+    for pk in responses.iter() {
+        results.send_batch(inventory.handle_response(pk));
+    }
+    for pk in closes.iter() {
+        inventory.handle_close(pk);
+    }
+}
+
+/// Resolves the recipes against the items of the StartGame packet once they are received.
+fn recipes_update_system(
+    mut recipes: ResMut<Recipes>,
+    mut start_game: EventReader<StartGame>,
+    mut crafting_data: EventReader<CraftingData>,
+) {
+    for pk in start_game.iter() {
+        **recipes = RecipeBook::new(&pk.items);
+    }
+    for pk in crafting_data.iter() {
+        recipes.handle_crafting_data(pk);
+    }
+}
@@ -12,16 +12,31 @@ use bevy::{
 
 use dotenvy::dotenv;
 
+use crate::camera::CameraPlugin;
 use crate::client::ClientPlugin;
 use crate::entity::{EntityPlugin, Head};
-use crate::input::InputPlugin;
+use crate::input::{InputFocus, InputPlugin};
+use crate::inventory::InventoryPlugin;
+use crate::particle::ParticlePlugin;
 use crate::player::{Local, LocalPlayerPlugin};
+use crate::resource_pack::ResourcePackPlugin;
+use crate::sky::SkyPlugin;
+use crate::sound::SoundPlugin;
+use crate::ui::UiPlugin;
 use crate::world::WorldPlugin;
 
+mod camera;
 pub mod client;
 pub mod entity;
 mod input;
+pub mod inventory;
+pub mod model;
+mod particle;
 pub mod player;
+pub mod resource_pack;
+mod sky;
+mod sound;
+pub mod ui;
 mod world;
 
 #[tokio::main]
@@ -44,10 +59,17 @@ async fn main() {
         )
         .add_plugin(WireframePlugin)
         .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(CameraPlugin)
         .add_plugin(EntityPlugin)
         .add_plugin(ClientPlugin)
         .add_plugin(InputPlugin)
+        .add_plugin(InventoryPlugin)
         .add_plugin(LocalPlayerPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(ResourcePackPlugin)
+        .add_plugin(SkyPlugin)
+        .add_plugin(SoundPlugin)
+        .add_plugin(UiPlugin)
         .add_plugin(WorldPlugin)
         .add_startup_system(setup)
         .add_system(cursor_grab_system)
@@ -57,11 +79,12 @@ async fn main() {
 fn cursor_grab_system(
     btn: Res<Input<MouseButton>>,
     key: Res<Input<KeyCode>>,
+    focus: Res<InputFocus>,
 
     mut windows: Query<&mut Window>,
 ) {
     if let Some(mut window) = windows.iter_mut().next() {
-        if btn.just_pressed(MouseButton::Left) {
+        if btn.just_pressed(MouseButton::Left) && *focus == InputFocus::Game {
             window.cursor.grab_mode = CursorGrabMode::Locked;
             window.cursor.visible = false;
         }
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use json::JsonValue;
+
+use crate::resource_pack::ResourcePacks;
+use crate::world::component::Face;
+
+/// A model read from a Bedrock geometry file (`*.geo.json`), used by both custom blocks and
+/// entities.
+///
+/// Geometry files store positions in pixels, with the X axis negated compared to how the model
+/// appears in game. All positions and rotations in this struct have already been converted to the
+/// in-game coordinate space, but are still measured in pixels.
+#[derive(Debug, Clone)]
+pub struct Model {
+    /// The identifier of the model, such as `geometry.humanoid.custom`.
+    pub identifier: Box<str>,
+    /// The size of the texture in pixels, which is used to normalise the UVs of the cubes.
+    pub texture_size: Vec2,
+    pub bones: Vec<Bone>,
+}
+
+/// A bone of a [Model]. Bones can be rotated around their pivot, which also rotates their
+/// children.
+#[derive(Debug, Clone)]
+pub struct Bone {
+    pub name: Box<str>,
+    /// The name of the parent of this bone, if any.
+    pub parent: Option<Box<str>>,
+    pub pivot: Vec3,
+    pub rotation: Quat,
+    pub cubes: Vec<Cube>,
+}
+
+/// A single cuboid of a [Bone].
+#[derive(Debug, Clone)]
+pub struct Cube {
+    /// The corner of the cube with the lowest coordinates.
+    pub min: Vec3,
+    /// The corner of the cube with the highest coordinates.
+    pub max: Vec3,
+    pub pivot: Vec3,
+    pub rotation: Quat,
+    /// The UV rectangles in pixels for each face, indexed by [Face]. The rectangles go from the
+    /// top left corner of the face to the bottom right corner, so they can be flipped.
+    pub uvs: [Option<[Vec2; 2]>; 6],
+}
+
+/// A textured quad. Corners are in the order top left, top right, bottom right, bottom left.
+#[derive(Debug, Copy, Clone)]
+pub struct Quad {
+    pub corners: [Vec3; 4],
+    pub uvs: [Vec2; 4],
+    pub normal: Vec3,
+}
+
+impl Model {
+    /// Computes the transform of every bone in its default pose, including the transforms of its
+    /// parents. The transforms are returned in the same order as [Self::bones].
+    pub fn bone_transforms(&self) -> Vec<Mat4> {
+        let indices: HashMap<&str, usize> = self
+            .bones
+            .iter()
+            .enumerate()
+            .map(|(i, bone)| (bone.name.as_ref(), i))
+            .collect();
+
+        (0..self.bones.len())
+            .map(|mut index| {
+                let mut transform = self.bones[index].local_transform();
+                // Bones are limited in depth to prevent parent cycles from hanging the client.
+                for _ in 0..self.bones.len() {
+                    let Some(parent) = self.bones[index]
+                        .parent
+                        .as_ref()
+                        .and_then(|parent| indices.get(parent.as_ref()))
+                    else {
+                        break;
+                    };
+                    index = *parent;
+                    transform = self.bones[index].local_transform() * transform;
+                }
+                transform
+            })
+            .collect()
+    }
+
+    /// Returns all quads of the model in its default pose, with UVs normalised to the texture size.
+    pub fn quads(&self) -> Vec<Quad> {
+        self.bone_transforms()
+            .into_iter()
+            .zip(&self.bones)
+            .flat_map(|(transform, bone)| {
+                bone.cubes.iter().flat_map(move |cube| {
+                    cube.quads(self.texture_size)
+                        .map(move |quad| quad.transformed(transform))
+                })
+            })
+            .collect()
+    }
+}
+
+impl Bone {
+    /// Returns the rotation of the bone around its pivot, without the transforms of its parents.
+    pub fn local_transform(&self) -> Mat4 {
+        rotate_around(self.pivot, self.rotation)
+    }
+
+    /// Builds a mesh of the cubes of the bone, positioned relative to its pivot. The rotation of
+    /// the bone itself is not applied, so that the mesh can be rotated separately when animating.
+    /// Returns None if the bone has no cubes.
+    pub fn mesh(&self, texture_size: Vec2) -> Option<Mesh> {
+        let mut mesh = MeshBuilder::default();
+        let offset = Mat4::from_translation(-self.pivot);
+        for cube in &self.cubes {
+            for quad in cube.quads(texture_size) {
+                mesh.quad(quad.transformed(offset));
+            }
+        }
+        (!mesh.is_empty()).then(|| mesh.build())
+    }
+}
+
+impl Cube {
+    /// Returns the quads of all faces of the cube that have a UV, with the rotation of the cube
+    /// already applied.
+    pub fn quads(&self, texture_size: Vec2) -> impl Iterator<Item = Quad> + '_ {
+        let transform = rotate_around(self.pivot, self.rotation);
+        Face::ALL.into_iter().filter_map(move |face| {
+            let [from, to] = self.uvs[face as usize]?;
+            let (from, to) = (from / texture_size, to / texture_size);
+            let uvs = [from, Vec2::new(to.x, from.y), to, Vec2::new(from.x, to.y)];
+            Some(Quad::face(self.min, self.max, face, uvs).transformed(transform))
+        })
+    }
+}
+
+impl Quad {
+    /// Creates a quad for a face of the cuboid between two corners.
+    pub fn face(min: Vec3, max: Vec3, face: Face, uvs: [Vec2; 4]) -> Self {
+        let (x0, y0, z0) = (min.x, min.y, min.z);
+        let (x1, y1, z1) = (max.x, max.y, max.z);
+        let corners = match face {
+            Face::Down => [[x0, y0, z0], [x1, y0, z0], [x1, y0, z1], [x0, y0, z1]],
+            Face::Up => [[x0, y1, z0], [x1, y1, z0], [x1, y1, z1], [x0, y1, z1]],
+            Face::North => [[x1, y1, z0], [x0, y1, z0], [x0, y0, z0], [x1, y0, z0]],
+            Face::South => [[x0, y1, z1], [x1, y1, z1], [x1, y0, z1], [x0, y0, z1]],
+            Face::West => [[x0, y1, z0], [x0, y1, z1], [x0, y0, z1], [x0, y0, z0]],
+            Face::East => [[x1, y1, z1], [x1, y1, z0], [x1, y0, z0], [x1, y0, z1]],
+        };
+        Self {
+            corners: corners.map(Vec3::from),
+            uvs,
+            normal: face.normal(),
+        }
+    }
+
+    /// Returns the quad with a transform applied to its corners and normal.
+    pub fn transformed(self, transform: Mat4) -> Self {
+        Self {
+            corners: self.corners.map(|c| transform.transform_point3(c)),
+            uvs: self.uvs,
+            normal: transform.transform_vector3(self.normal).normalize_or_zero(),
+        }
+    }
+
+    /// Returns the same quad facing the opposite direction.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// Incrementally builds a triangle list [Mesh] out of quads.
+#[derive(Default)]
+pub struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    /// The amount of vertices added so far. Can be passed to [Self::transform] to only transform
+    /// the vertices added after this point.
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns true if nothing has been added to the mesh yet.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Adds a quad to the mesh. The winding order of the triangles is chosen based on the normal of
+    /// the quad, so that the quad is visible from the side the normal points towards.
+    pub fn quad(&mut self, quad: Quad) {
+        let start = self.positions.len() as u32;
+        for (corner, uv) in quad.corners.iter().zip(quad.uvs) {
+            self.positions.push(corner.to_array());
+            self.normals.push(quad.normal.to_array());
+            self.uvs.push(uv.to_array());
+        }
+
+        let [a, b, c, _] = quad.corners;
+        let order = if (b - a).cross(c - a).dot(quad.normal) >= 0. {
+            [0, 1, 2, 0, 2, 3]
+        } else {
+            [0, 2, 1, 0, 3, 2]
+        };
+        self.indices.extend(order.map(|i| start + i));
+    }
+
+    /// Adds a quad with a vertex colour. Either all or none of the vertices of a mesh should have a
+    /// colour.
+    pub fn coloured_quad(&mut self, quad: Quad, color: Vec4) {
+        self.quad(quad);
+        self.colors.extend([color.to_array(); 4]);
+    }
+
+    /// Adds all triangles of an existing mesh at an offset. The colour of each vertex is decided
+    /// by its normal.
+    pub fn append(&mut self, mesh: &Mesh, offset: Vec3, color: impl Fn(Vec3) -> Vec4) {
+        let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x3(normals)),
+            Some(VertexAttributeValues::Float32x2(uvs)),
+            Some(indices),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+            mesh.attribute(Mesh::ATTRIBUTE_UV_0),
+            mesh.indices(),
+        )
+        else {
+            return;
+        };
+
+        let start = self.positions.len() as u32;
+        for ((position, normal), uv) in positions.iter().zip(normals).zip(uvs) {
+            self.positions
+                .push((Vec3::from(*position) + offset).to_array());
+            self.normals.push(*normal);
+            self.uvs.push(*uv);
+            self.colors.push(color(Vec3::from(*normal)).to_array());
+        }
+        self.indices
+            .extend(indices.iter().map(|index| start + index as u32));
+    }
+
+    /// Adds a quad that is visible from both sides.
+    pub fn double_sided(&mut self, quad: Quad) {
+        self.quad(quad);
+        self.quad(quad.flipped());
+    }
+
+    /// Applies a transform to all vertices starting at the provided vertex index.
+    pub fn transform(&mut self, from: usize, transform: Mat4) {
+        for position in &mut self.positions[from..] {
+            *position = transform.transform_point3((*position).into()).to_array();
+        }
+        for normal in &mut self.normals[from..] {
+            *normal = transform
+                .transform_vector3((*normal).into())
+                .normalize_or_zero()
+                .to_array();
+        }
+    }
+
+    pub fn build(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        if !self.colors.is_empty() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        }
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+/// Reads all models from the geometry files in a directory of the resource packs. Models in packs
+/// with a higher priority override models with the same identifier in packs with a lower priority.
+pub fn read_models(packs: &ResourcePacks, dir: &str) -> HashMap<Box<str>, Model> {
+    let mut models = HashMap::new();
+    for pack in packs.iter() {
+        for path in pack.list_dir(dir) {
+            if !path.ends_with(".json") {
+                continue;
+            }
+            let Some(file) = pack.read_json(&path) else {
+                continue;
+            };
+            for model in parse_models(&file) {
+                models.insert(model.identifier.clone(), model);
+            }
+        }
+    }
+    models
+}
+
+/// Parses all models in a geometry file. Both the current format, which lists models under
+/// `minecraft:geometry`, and the legacy format from before 1.12, which uses the model identifiers
+/// as keys, are supported.
+pub fn parse_models(file: &JsonValue) -> Vec<Model> {
+    if file.has_key("minecraft:geometry") {
+        return file["minecraft:geometry"]
+            .members()
+            .filter_map(|geometry| {
+                let description = &geometry["description"];
+                parse_model(
+                    description["identifier"].as_str()?,
+                    Vec2::new(
+                        description["texture_width"].as_f32().unwrap_or(64.),
+                        description["texture_height"].as_f32().unwrap_or(64.),
+                    ),
+                    &geometry["bones"],
+                )
+            })
+            .collect();
+    }
+
+    file.entries()
+        .filter(|(key, _)| key.starts_with("geometry."))
+        .filter_map(|(key, geometry)| {
+            // Legacy models may inherit from another model using `geometry.child:geometry.parent`.
+            // Inheritance is not supported, so only the bones of the model itself are used.
+            parse_model(
+                key.split(':').next().unwrap(),
+                Vec2::new(
+                    geometry["texturewidth"].as_f32().unwrap_or(64.),
+                    geometry["textureheight"].as_f32().unwrap_or(64.),
+                ),
+                &geometry["bones"],
+            )
+        })
+        .collect()
+}
+
+fn parse_model(identifier: &str, texture_size: Vec2, bones: &JsonValue) -> Option<Model> {
+    if texture_size.x <= 0. || texture_size.y <= 0. {
+        return None;
+    }
+    Some(Model {
+        identifier: identifier.into(),
+        texture_size,
+        bones: bones.members().filter_map(parse_bone).collect(),
+    })
+}
+
+fn parse_bone(bone: &JsonValue) -> Option<Bone> {
+    let pivot = position(vec3(&bone["pivot"]).unwrap_or_default());
+    let mirror = bone["mirror"].as_bool().unwrap_or(false);
+    let inflate = bone["inflate"].as_f32().unwrap_or(0.);
+    let cubes = if bone["neverRender"].as_bool().unwrap_or(false) {
+        Vec::new()
+    } else {
+        bone["cubes"]
+            .members()
+            .filter_map(|cube| parse_cube(cube, pivot, mirror, inflate))
+            .collect()
+    };
+
+    Some(Bone {
+        name: bone["name"].as_str()?.into(),
+        parent: bone["parent"].as_str().map(Into::into),
+        pivot,
+        rotation: rotation(vec3(&bone["rotation"]).unwrap_or_default()),
+        cubes,
+    })
+}
+
+fn parse_cube(cube: &JsonValue, pivot: Vec3, mirror: bool, inflate: f32) -> Option<Cube> {
+    let origin = vec3(&cube["origin"])?;
+    let size = vec3(&cube["size"])?;
+    let inflate = cube["inflate"].as_f32().unwrap_or(inflate);
+    let mirror = cube["mirror"].as_bool().unwrap_or(mirror);
+
+    let uv = &cube["uv"];
+    let uvs = if uv.is_array() {
+        box_uvs(vec2(uv)?, size.floor(), mirror)
+    } else {
+        Face::ALL.map(|face| {
+            let face = &uv[face.name()];
+            let from = vec2(&face["uv"])?;
+            Some([from, from + vec2(&face["uv_size"])?])
+        })
+    };
+
+    // The X axis is negated, so the origin is on the other side of the cube.
+    let min = Vec3::new(-(origin.x + size.x), origin.y, origin.z) - inflate;
+    let max = Vec3::new(-origin.x, origin.y + size.y, origin.z + size.z) + inflate;
+    Some(Cube {
+        min,
+        max,
+        pivot: vec3(&cube["pivot"]).map(position).unwrap_or(pivot),
+        rotation: rotation(vec3(&cube["rotation"]).unwrap_or_default()),
+        uvs,
+    })
+}
+
+/// Computes the UV rectangles of a cube that uses box UV mapping, where all faces are laid out
+/// around a single point on the texture.
+fn box_uvs(uv: Vec2, size: Vec3, mirror: bool) -> [Option<[Vec2; 2]>; 6] {
+    let (w, h, d) = (size.x, size.y, size.z);
+    let rect = |x: f32, y: f32, to_x: f32, to_y: f32| {
+        let (x, to_x) = if mirror { (to_x, x) } else { (x, to_x) };
+        Some([uv + Vec2::new(x, y), uv + Vec2::new(to_x, to_y)])
+    };
+    let east = rect(0., d, d, d + h);
+    let west = rect(d + w, d, d + w + d, d + h);
+    let (east, west) = if mirror { (west, east) } else { (east, west) };
+    [
+        rect(d + w + w, 0., d + w, d),
+        rect(d + w, d, d, 0.),
+        rect(d, d, d + w, d + h),
+        rect(d + w + d, d, d + w + d + w, d + h),
+        west,
+        east,
+    ]
+}
+
+/// Returns a transform that rotates around a pivot.
+fn rotate_around(pivot: Vec3, rotation: Quat) -> Mat4 {
+    Mat4::from_translation(pivot) * Mat4::from_quat(rotation) * Mat4::from_translation(-pivot)
+}
+
+/// Converts a position in a geometry file to the in-game coordinate space.
+fn position(v: Vec3) -> Vec3 {
+    Vec3::new(-v.x, v.y, v.z)
+}
+
+/// Converts rotations in degrees from a geometry file to the in-game coordinate space. Because the
+/// X axis is negated, rotations around the X and Y axes are inverted.
+pub fn rotation(v: Vec3) -> Quat {
+    Quat::from_euler(
+        EulerRot::ZYX,
+        v.z.to_radians(),
+        -v.y.to_radians(),
+        -v.x.to_radians(),
+    )
+}
+
+fn vec2(v: &JsonValue) -> Option<Vec2> {
+    Some(Vec2::new(v[0].as_f32()?, v[1].as_f32()?))
+}
+
+fn vec3(v: &JsonValue) -> Option<Vec3> {
+    Some(Vec3::new(v[0].as_f32()?, v[1].as_f32()?, v[2].as_f32()?))
+}
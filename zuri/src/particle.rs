@@ -0,0 +1,781 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bevy::math::Rect;
+use bevy::prelude::*;
+use json::JsonValue;
+use rand::Rng;
+use zuri_net::proto::packet::level_event::LevelEvent;
+use zuri_net::proto::packet::spawn_particle_effect::SpawnParticleEffect;
+use zuri_net::proto::types::level_event::LevelEventType;
+
+use crate::client::NetworkSet;
+use crate::entity::EntityManager;
+use crate::resource_pack::ResourcePacks;
+use crate::world::component::{Face, Textures};
+use crate::world::texture::BlockAtlas;
+use crate::world::World;
+
+/// Shows the particles of the LevelEvent and SpawnParticleEffect packets.
+///
+/// Particle effects are read from the `particles` directory of the resource packs. Only the parts
+/// of effects that do not depend on MoLang are supported: expressions other than plain numbers and
+/// `math.random` fall back to a default value. The particles of broken blocks are built in, and use
+/// the texture of the block.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ParticleEffects::default())
+            .insert_resource(ParticleAssets::default())
+            .add_system(effects_load_system)
+            .add_systems((level_event_system, spawn_effect_system).in_base_set(NetworkSet::Process))
+            .add_systems((emitter_system, particle_system, billboard_system).chain());
+    }
+}
+
+/// The most particles that may exist at once. Emitters stop spawning particles beyond this.
+const MAX_PARTICLES: usize = 2000;
+/// How many particles a broken block falls apart into.
+const BLOCK_PARTICLES: usize = 16;
+/// How many particles fly off a block while it is being broken, every time it is hit.
+const CRACK_PARTICLES: usize = 2;
+const GRAVITY: Vec3 = Vec3::new(0., -9.8, 0.);
+
+/// The particle effects of the resource packs by their identifier, such as
+/// `minecraft:critical_hit_emitter`.
+#[derive(Resource, Default)]
+pub struct ParticleEffects(HashMap<String, Arc<ParticleEffect>>);
+
+impl ParticleEffects {
+    /// Reads all particle effects from the resource packs. Effects in packs with a higher priority
+    /// replace effects with the same identifier.
+    pub fn load(packs: &ResourcePacks) -> Self {
+        let mut effects = HashMap::new();
+        for path in packs.list_dir("particles") {
+            if !path.ends_with(".json") {
+                continue;
+            }
+            let Some(effect) = packs
+                .read_json(&path)
+                .and_then(|file| ParticleEffect::parse(&file))
+            else {
+                continue;
+            };
+            effects.insert(effect.identifier.clone(), Arc::new(effect));
+        }
+        Self(effects)
+    }
+
+    /// Returns an effect by its identifier.
+    pub fn get(&self, identifier: &str) -> Option<&Arc<ParticleEffect>> {
+        self.0.get(identifier)
+    }
+}
+
+/// A number in a particle effect, which is either constant or picked at random from a range every
+/// time it is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Constant(f32),
+    Random(f32, f32),
+}
+
+impl Value {
+    /// Parses a number, or a MoLang expression that is either a number or `math.random(min, max)`.
+    pub fn parse(value: &JsonValue) -> Option<Self> {
+        if let Some(value) = value.as_f32() {
+            return Some(Value::Constant(value));
+        }
+        let expression = value.as_str()?.trim().to_ascii_lowercase();
+        if let Ok(value) = expression.parse() {
+            return Some(Value::Constant(value));
+        }
+        let (min, max) = expression
+            .strip_prefix("math.random(")?
+            .strip_suffix(')')?
+            .split_once(',')?;
+        Some(Value::Random(
+            min.trim().parse().ok()?,
+            max.trim().parse().ok()?,
+        ))
+    }
+
+    /// Returns the value, picking one at random if it is a range.
+    pub fn sample(self, rng: &mut impl Rng) -> f32 {
+        match self {
+            Value::Constant(value) => value,
+            Value::Random(min, max) if min < max => rng.gen_range(min..max),
+            Value::Random(min, _) => min,
+        }
+    }
+}
+
+/// How an emitter spawns its particles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emission {
+    /// All particles are spawned at once when the emitter starts.
+    Instant(u32),
+    /// Particles are spawned at a steady rate per second while the emitter is active, with at most
+    /// a number of particles alive at once.
+    Steady { rate: f32, max: u32 },
+}
+
+/// Where around the emitter particles are spawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Point,
+    Sphere { radius: f32 },
+    Box { half_dimensions: Vec3 },
+}
+
+/// The direction particles move in when they are spawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Away from the centre of the emitter.
+    Outwards,
+    /// Towards the centre of the emitter.
+    Inwards,
+    Fixed(Vec3),
+}
+
+/// A data-driven particle effect, as defined in a file in the `particles` directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEffect {
+    pub identifier: String,
+    /// The path of the texture of the particles, without an extension.
+    pub texture: String,
+    pub emission: Emission,
+    /// How many seconds the emitter is active for. Looping emitters only run once.
+    pub active_time: f32,
+    pub shape: Shape,
+    pub direction: Direction,
+    pub lifetime: Value,
+    pub speed: Value,
+    pub acceleration: Vec3,
+    pub drag: f32,
+    pub size: (Value, Value),
+    /// The part of the texture shown on particles, in UV coordinates.
+    pub uv: Rect,
+    pub colour: Color,
+}
+
+impl ParticleEffect {
+    /// Parses a particle effect file. Returns None if the file has no identifier.
+    pub fn parse(file: &JsonValue) -> Option<Self> {
+        let effect = &file["particle_effect"];
+        let description = &effect["description"];
+        let components = &effect["components"];
+        let component = |name: &str| &components[format!("minecraft:{name}")];
+        let value = |value: &JsonValue, default: f32| {
+            Value::parse(value).unwrap_or(Value::Constant(default))
+        };
+        let constant = |value: &JsonValue, default: f32| match Value::parse(value) {
+            Some(Value::Constant(value)) => value,
+            Some(Value::Random(min, max)) => (min + max) / 2.,
+            None => default,
+        };
+        let vector = |value: &JsonValue, default: Vec3| {
+            if !value.is_array() {
+                return default;
+            }
+            Vec3::new(
+                constant(&value[0], 0.),
+                constant(&value[1], 0.),
+                constant(&value[2], 0.),
+            )
+        };
+
+        let emission = if component("emitter_rate_steady").is_object() {
+            let steady = component("emitter_rate_steady");
+            Emission::Steady {
+                rate: constant(&steady["spawn_rate"], 1.),
+                max: constant(&steady["max_particles"], 50.) as u32,
+            }
+        } else {
+            Emission::Instant(
+                constant(&component("emitter_rate_instant")["num_particles"], 10.) as u32,
+            )
+        };
+        let active_time = ["emitter_lifetime_once", "emitter_lifetime_looping"]
+            .iter()
+            .map(|name| &component(name)["active_time"])
+            .find(|time| !time.is_null())
+            .map(|time| constant(time, 10.))
+            .unwrap_or(10.);
+
+        let (shape, shape_value) = if component("emitter_shape_sphere").is_object() {
+            let sphere = component("emitter_shape_sphere");
+            (
+                Shape::Sphere {
+                    radius: constant(&sphere["radius"], 1.),
+                },
+                sphere,
+            )
+        } else if component("emitter_shape_box").is_object() {
+            let shape = component("emitter_shape_box");
+            (
+                Shape::Box {
+                    half_dimensions: vector(&shape["half_dimensions"], Vec3::ZERO),
+                },
+                shape,
+            )
+        } else {
+            (Shape::Point, component("emitter_shape_point"))
+        };
+        let direction = match &shape_value["direction"] {
+            direction if direction.is_array() => Direction::Fixed(vector(direction, Vec3::ZERO)),
+            direction if direction.as_str() == Some("inwards") => Direction::Inwards,
+            _ => Direction::Outwards,
+        };
+
+        let motion = component("particle_motion_dynamic");
+        let billboard = component("particle_appearance_billboard");
+        let size = &billboard["size"];
+
+        Some(ParticleEffect {
+            identifier: description["identifier"].as_str()?.to_string(),
+            texture: description["basic_render_parameters"]["texture"]
+                .as_str()
+                .unwrap_or("textures/particle/particles")
+                .to_string(),
+            emission,
+            active_time,
+            shape,
+            direction,
+            lifetime: value(
+                &component("particle_lifetime_expression")["max_lifetime"],
+                1.,
+            ),
+            speed: value(component("particle_initial_speed"), 0.),
+            acceleration: vector(&motion["linear_acceleration"], Vec3::ZERO),
+            drag: constant(&motion["linear_drag_coefficient"], 0.),
+            size: (value(&size[0], 0.1), value(&size[1], 0.1)),
+            uv: parse_uv(&billboard["uv"]),
+            colour: parse_colour(&component("particle_appearance_tinting")["color"]),
+        })
+    }
+}
+
+/// Parses the UV rectangle of a billboard. Flipbooks only show their first frame.
+fn parse_uv(uv: &JsonValue) -> Rect {
+    let size = |value: &JsonValue| value.as_f32().filter(|size| *size > 0.).unwrap_or(1.);
+    let (width, height) = (size(&uv["texture_width"]), size(&uv["texture_height"]));
+    let (start, extent) = if uv["flipbook"].is_object() {
+        (&uv["flipbook"]["base_UV"], &uv["flipbook"]["size_UV"])
+    } else {
+        (&uv["uv"], &uv["uv_size"])
+    };
+    let pair = |value: &JsonValue| -> Option<Vec2> {
+        match (Value::parse(&value[0])?, Value::parse(&value[1])?) {
+            (Value::Constant(x), Value::Constant(y)) => Some(Vec2::new(x, y)),
+            _ => None,
+        }
+    };
+    match (pair(start), pair(extent)) {
+        (Some(start), Some(extent)) => {
+            let scale = Vec2::new(width, height);
+            Rect::from_corners(start / scale, (start + extent) / scale)
+        }
+        _ => Rect::new(0., 0., 1., 1.),
+    }
+}
+
+/// Parses the tint of particles, which is either an array of channels or a hexadecimal `#AARRGGBB`
+/// or `#RRGGBB` string. Gradients and expressions are not supported, and leave particles untinted.
+fn parse_colour(colour: &JsonValue) -> Color {
+    if let Some(hex) = colour.as_str().and_then(|hex| hex.strip_prefix('#')) {
+        let Ok(value) = u32::from_str_radix(hex, 16) else {
+            return Color::WHITE;
+        };
+        let channel = |shift: u32| ((value >> shift) & 0xff) as u8;
+        return match hex.len() {
+            8 => Color::rgba_u8(channel(16), channel(8), channel(0), channel(24)),
+            6 => Color::rgb_u8(channel(16), channel(8), channel(0)),
+            _ => Color::WHITE,
+        };
+    }
+    let channel = |index: usize| match Value::parse(&colour[index]) {
+        Some(Value::Constant(value)) => value,
+        _ => 1.,
+    };
+    if colour.is_array() {
+        Color::rgba(channel(0), channel(1), channel(2), channel(3))
+    } else {
+        Color::WHITE
+    }
+}
+
+/// Returns the identifier of the particle effect shown for a level event, if the level event shows
+/// one of the particle effects of the resource packs.
+pub fn level_event_effect(event: &LevelEventType) -> Option<&'static str> {
+    use LevelEventType::*;
+    Some(match event {
+        ParticlesCritical => "minecraft:critical_hit_emitter",
+        ParticlesExplosion => "minecraft:huge_explosion_emitter",
+        ParticlesBlockExplosion => "minecraft:explosion_particle",
+        ParticlesPotionSplash => "minecraft:splash_spell_emitter",
+        ParticlesEyeOfEnderDeath => "minecraft:eyeofender_death_explode_particle",
+        ParticlesMobBlockSpawn => "minecraft:mob_block_spawn_emitter",
+        ParticleCropGrowth => "minecraft:crop_growth_emitter",
+        ParticleDeathSmoke => "minecraft:death_explosion_emitter",
+        ParticleDenyBlock => "minecraft:basic_smoke_particle",
+        ParticleGenericSpawn => "minecraft:basic_portal_particle",
+        ParticlesTeleport => "minecraft:portal_directional",
+        ParticlesBubble => "minecraft:basic_bubble_particle",
+        ParticlesEvaporate | ParticlesEvaporateWater => {
+            "minecraft:water_evaporation_bucket_emitter"
+        }
+        ParticlesKnockbackRoar => "minecraft:knockback_roar_particle",
+        ParticlesElectricSpark => "minecraft:electric_spark_particle",
+        WaxOn => "minecraft:wax_particle",
+        WaxOff | Scrape => "minecraft:wax_off_particle",
+        SonicExplosion => "minecraft:sonic_explosion",
+        _ => return None,
+    })
+}
+
+/// The meshes, materials and textures shared by particles, created the first time they are needed.
+#[derive(Resource, Default)]
+struct ParticleAssets {
+    /// The mesh and material of each particle effect by its identifier.
+    effects: HashMap<String, (Handle<Mesh>, Handle<StandardMaterial>)>,
+    textures: HashMap<String, Option<Handle<Image>>>,
+    /// The material used for particles of blocks, which shows the block atlas.
+    terrain: Option<Handle<StandardMaterial>>,
+}
+
+/// Spawns the particles of a particle effect.
+#[derive(Component)]
+struct Emitter {
+    effect: Arc<ParticleEffect>,
+    /// The tint of the particles, which replaces the tint of the effect, such as the colour of a
+    /// splash potion.
+    tint: Option<Color>,
+    age: f32,
+    /// The particles owed by a steady emitter, which are spawned once they add up to a whole one.
+    pending: f32,
+    started: bool,
+}
+
+#[derive(Component)]
+struct Particle {
+    /// The emitter that spawned the particle, if it was spawned by one.
+    emitter: Option<Entity>,
+    velocity: Vec3,
+    acceleration: Vec3,
+    drag: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Reloads the particle effects whenever the resource packs change.
+fn effects_load_system(
+    packs: Res<ResourcePacks>,
+    mut effects: ResMut<ParticleEffects>,
+    mut assets: ResMut<ParticleAssets>,
+) {
+    if packs.is_changed() {
+        *effects = ParticleEffects::load(&packs);
+        assets.effects.clear();
+        assets.textures.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn level_event_system(
+    mut commands: Commands,
+    effects: Res<ParticleEffects>,
+    world: Option<Res<World>>,
+    atlas: Res<BlockAtlas>,
+    mut assets: ResMut<ParticleAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut events: EventReader<LevelEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for pk in events.iter() {
+        let data = pk.event_data.0;
+        let block_particles = match pk.event_type {
+            LevelEventType::ParticlesDestroyBlock
+            | LevelEventType::ParticlesDestroyBlockNoSound => {
+                Some((data as u32, None, BLOCK_PARTICLES))
+            }
+            // The face that was hit is in the highest byte of the data.
+            LevelEventType::ParticlesCrackBlock => Some((
+                data as u32 & 0xffffff,
+                Face::ALL.get((data >> 24) as usize).copied(),
+                CRACK_PARTICLES,
+            )),
+            _ => None,
+        };
+        if let Some((runtime_id, face, count)) = block_particles {
+            let Some(world) = &world else {
+                continue;
+            };
+            let textures = world.block_map.components::<Textures>();
+            let uv = atlas.face_uv(textures.get(runtime_id), face.unwrap_or(Face::North));
+            let material = assets
+                .terrain
+                .get_or_insert_with(|| {
+                    materials.add(StandardMaterial {
+                        base_color_texture: Some(atlas.image.clone()),
+                        alpha_mode: AlphaMode::Mask(0.5),
+                        unlit: true,
+                        ..default()
+                    })
+                })
+                .clone();
+            for _ in 0..count {
+                // Every particle shows a random quarter of the texture of the block.
+                let corner = uv.min
+                    + uv.size() / 2.
+                        * Vec2::new(rng.gen_range(0..2) as f32, rng.gen_range(0..2) as f32);
+                let mesh = meshes.add(quad(Rect::from_corners(corner, corner + uv.size() / 2.)));
+                let offset = match face {
+                    Some(face) => face.normal() * 0.55 + random_vector(&mut rng) * 0.4,
+                    None => random_vector(&mut rng) * 0.5,
+                };
+                commands.spawn((
+                    PbrBundle {
+                        mesh,
+                        material: material.clone(),
+                        transform: Transform::from_translation(pk.position + offset)
+                            .with_scale(Vec3::splat(0.1)),
+                        ..default()
+                    },
+                    Particle {
+                        emitter: None,
+                        velocity: offset * 3. + Vec3::Y * 1.5,
+                        acceleration: GRAVITY,
+                        drag: 0.,
+                        age: 0.,
+                        lifetime: rng.gen_range(0.4..1.2),
+                    },
+                ));
+            }
+            continue;
+        }
+
+        let Some(effect) = level_event_effect(&pk.event_type).and_then(|id| effects.get(id)) else {
+            continue;
+        };
+        // Splash potions send their colour as ARGB in the data.
+        let tint = matches!(pk.event_type, LevelEventType::ParticlesPotionSplash).then(|| {
+            let [_, r, g, b] = (data as u32).to_be_bytes();
+            Color::rgb_u8(r, g, b)
+        });
+        spawn_emitter(&mut commands, effect.clone(), tint, pk.position);
+    }
+}
+
+fn spawn_effect_system(
+    mut commands: Commands,
+    effects: Res<ParticleEffects>,
+    entities: Res<EntityManager>,
+    transforms: Query<&Transform>,
+    mut events: EventReader<SpawnParticleEffect>,
+) {
+    for pk in events.iter() {
+        let Some(effect) = effects.get(&pk.particle_name) else {
+            debug!("Unknown particle effect `{}`", pk.particle_name);
+            continue;
+        };
+        // The position is relative to the entity the effect is attached to, if any.
+        let origin = match pk.entity_unique_id.0 {
+            -1 => Vec3::ZERO,
+            id => match entities
+                .entity_by_uid(id)
+                .and_then(|entity| transforms.get(entity).ok())
+            {
+                Some(transform) => transform.translation,
+                None => continue,
+            },
+        };
+        spawn_emitter(&mut commands, effect.clone(), None, origin + pk.position);
+    }
+}
+
+fn spawn_emitter(
+    commands: &mut Commands,
+    effect: Arc<ParticleEffect>,
+    tint: Option<Color>,
+    position: Vec3,
+) {
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        Emitter {
+            effect,
+            tint,
+            age: 0.,
+            pending: 0.,
+            started: false,
+        },
+    ));
+}
+
+/// Spawns the particles of emitters, and removes emitters once they are no longer active.
+#[allow(clippy::too_many_arguments)]
+fn emitter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    packs: Res<ResourcePacks>,
+    mut assets: ResMut<ParticleAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut emitters: Query<(Entity, &Transform, &mut Emitter)>,
+    particles: Query<&Particle>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut alive = 0;
+    let mut alive_per_emitter = HashMap::new();
+    for particle in &particles {
+        alive += 1;
+        if let Some(emitter) = particle.emitter {
+            *alive_per_emitter.entry(emitter).or_insert(0) += 1;
+        }
+    }
+    for (entity, transform, mut emitter) in &mut emitters {
+        let effect = emitter.effect.clone();
+        let count = match effect.emission {
+            Emission::Instant(count) if !emitter.started => count,
+            Emission::Instant(_) => 0,
+            Emission::Steady { rate, max } => {
+                emitter.pending += rate * time.delta_seconds();
+                let room = max.saturating_sub(alive_per_emitter.get(&entity).copied().unwrap_or(0));
+                let count = (emitter.pending as u32).min(room);
+                emitter.pending -= emitter.pending.floor();
+                count
+            }
+        };
+        emitter.started = true;
+        emitter.age += time.delta_seconds();
+        let done = match effect.emission {
+            Emission::Instant(_) => true,
+            Emission::Steady { .. } => emitter.age >= effect.active_time,
+        };
+        if done {
+            commands.entity(entity).despawn();
+        }
+
+        if count == 0 {
+            continue;
+        }
+        let (mesh, material) = match &emitter.tint {
+            None => assets.effect(&effect, &packs, &mut meshes, &mut materials, &mut images),
+            Some(tint) => {
+                let (mesh, material) =
+                    assets.effect(&effect, &packs, &mut meshes, &mut materials, &mut images);
+                let mut tinted = materials.get(&material).cloned().unwrap_or_default();
+                tinted.base_color = *tint;
+                (mesh, materials.add(tinted))
+            }
+        };
+        for _ in 0..count {
+            if alive >= MAX_PARTICLES {
+                break;
+            }
+            alive += 1;
+
+            let offset = match effect.shape {
+                Shape::Point => Vec3::ZERO,
+                Shape::Sphere { radius } => random_vector(&mut rng) * radius,
+                Shape::Box { half_dimensions } => {
+                    (Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.) * half_dimensions
+                }
+            };
+            let direction = match effect.direction {
+                Direction::Outwards if offset == Vec3::ZERO => random_vector(&mut rng),
+                Direction::Outwards => offset,
+                Direction::Inwards => -offset,
+                Direction::Fixed(direction) => direction,
+            }
+            .normalize_or_zero();
+            let size = Vec3::new(
+                effect.size.0.sample(&mut rng),
+                effect.size.1.sample(&mut rng),
+                1.,
+            ) * 2.;
+
+            commands.spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_translation(transform.translation + offset)
+                        .with_scale(size),
+                    ..default()
+                },
+                Particle {
+                    emitter: Some(entity),
+                    velocity: direction * effect.speed.sample(&mut rng),
+                    acceleration: effect.acceleration,
+                    drag: effect.drag,
+                    age: 0.,
+                    lifetime: effect.lifetime.sample(&mut rng),
+                },
+            ));
+        }
+    }
+}
+
+impl ParticleAssets {
+    /// Returns the mesh and material shared by the particles of an effect.
+    fn effect(
+        &mut self,
+        effect: &ParticleEffect,
+        packs: &ResourcePacks,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        images: &mut Assets<Image>,
+    ) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        if let Some(assets) = self.effects.get(&effect.identifier) {
+            return assets.clone();
+        }
+        let texture = self
+            .textures
+            .entry(effect.texture.clone())
+            .or_insert_with(|| {
+                packs
+                    .load_texture(&effect.texture)
+                    .map(|image| images.add(image))
+            })
+            .clone();
+        let assets = (
+            meshes.add(quad(effect.uv)),
+            materials.add(StandardMaterial {
+                base_color: effect.colour,
+                base_color_texture: texture,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+        );
+        self.effects
+            .insert(effect.identifier.clone(), assets.clone());
+        assets
+    }
+}
+
+/// Moves particles and removes them once they are older than their lifetime.
+fn particle_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Transform, &mut Particle)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut particle) in &mut particles {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let drag = particle.velocity * particle.drag;
+        particle.velocity = particle.velocity + (particle.acceleration - drag) * delta;
+        transform.translation += particle.velocity * delta;
+    }
+}
+
+/// Turns particles towards the camera.
+fn billboard_system(
+    camera: Query<&Transform, (With<Camera3d>, Without<Particle>)>,
+    mut particles: Query<&mut Transform, With<Particle>>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    for mut transform in &mut particles {
+        transform.rotation = camera.rotation;
+    }
+}
+
+/// Creates a square facing towards positive Z that shows part of a texture.
+fn quad(uv: Rect) -> Mesh {
+    let mut mesh = Mesh::from(shape::Quad::new(Vec2::ONE));
+    // The vertices go from the bottom left corner clockwise.
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![
+            [uv.min.x, uv.max.y],
+            [uv.min.x, uv.min.y],
+            [uv.max.x, uv.min.y],
+            [uv.max.x, uv.max.y],
+        ],
+    );
+    mesh
+}
+
+/// Returns a random vector within the unit sphere.
+fn random_vector(rng: &mut impl Rng) -> Vec3 {
+    loop {
+        let vector = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.;
+        if vector.length_squared() <= 1. {
+            return vector;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let parse = |value: &str| Value::parse(&json::parse(value).unwrap());
+        assert_eq!(parse("0.5"), Some(Value::Constant(0.5)));
+        assert_eq!(parse(r#""2""#), Some(Value::Constant(2.)));
+        assert_eq!(
+            parse(r#""Math.random(0.1, 0.4)""#),
+            Some(Value::Random(0.1, 0.4))
+        );
+        assert_eq!(parse(r#""variable.particle_random_1 * 2""#), None);
+    }
+
+    #[test]
+    fn effects() {
+        let file = json::parse(
+            r##"{
+                "format_version": "1.10.0",
+                "particle_effect": {
+                    "description": {
+                        "identifier": "minecraft:critical_hit_emitter",
+                        "basic_render_parameters": {"material": "particles_alpha", "texture": "textures/particle/particles"}
+                    },
+                    "components": {
+                        "minecraft:emitter_rate_instant": {"num_particles": 20},
+                        "minecraft:emitter_lifetime_once": {"active_time": 0.5},
+                        "minecraft:emitter_shape_sphere": {"radius": 0.4, "direction": "outwards"},
+                        "minecraft:particle_lifetime_expression": {"max_lifetime": "math.random(0.3, 0.6)"},
+                        "minecraft:particle_initial_speed": 4.0,
+                        "minecraft:particle_motion_dynamic": {"linear_acceleration": [0, -9.8, 0], "linear_drag_coefficient": 1.5},
+                        "minecraft:particle_appearance_billboard": {
+                            "size": [0.1, "variable.particle_random_1 * 0.1"],
+                            "uv": {"texture_width": 128, "texture_height": 128, "uv": [8, 32], "uv_size": [8, 8]}
+                        },
+                        "minecraft:particle_appearance_tinting": {"color": "#FF808080"}
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+        let effect = ParticleEffect::parse(&file).unwrap();
+        assert_eq!(effect.identifier, "minecraft:critical_hit_emitter");
+        assert_eq!(effect.emission, Emission::Instant(20));
+        assert_eq!(effect.active_time, 0.5);
+        assert_eq!(effect.shape, Shape::Sphere { radius: 0.4 });
+        assert_eq!(effect.direction, Direction::Outwards);
+        assert_eq!(effect.lifetime, Value::Random(0.3, 0.6));
+        assert_eq!(effect.speed, Value::Constant(4.));
+        assert_eq!(effect.acceleration, Vec3::new(0., -9.8, 0.));
+        assert_eq!(effect.size, (Value::Constant(0.1), Value::Constant(0.1)));
+        assert_eq!(effect.uv, Rect::new(0.0625, 0.25, 0.125, 0.3125));
+        assert_eq!(effect.colour, Color::rgba_u8(128, 128, 128, 255));
+
+        assert!(ParticleEffect::parse(&json::parse("{}").unwrap()).is_none());
+        assert_eq!(
+            level_event_effect(&LevelEventType::ParticlesCritical),
+            Some(effect.identifier.as_str())
+        );
+        assert_eq!(level_event_effect(&LevelEventType::StartRaining), None);
+    }
+}
@@ -1,8 +1,11 @@
 use bevy::prelude::*;
+use zuri_net::proto::packet::change_dimension::ChangeDimension;
+use zuri_net::proto::packet::respawn::{Respawn, RespawnState};
 use zuri_net::proto::packet::start_game::StartGame;
 
 use crate::entity::Head;
 use crate::input::ClientInput;
+use crate::ui::death::DeathScreen;
 
 pub struct LocalPlayerPlugin;
 
@@ -10,7 +13,8 @@ impl Plugin for LocalPlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_system(move_system)
             .add_system(camera_sync_system)
-            .add_system(initial_position_system);
+            .add_system(initial_position_system)
+            .add_system(respawn_position_system);
     }
 }
 
@@ -49,7 +53,7 @@ fn move_system(
 }
 
 #[allow(clippy::type_complexity)]
-fn camera_sync_system(
+pub(crate) fn camera_sync_system(
     player_query: Query<(&Transform, &Head), (With<Local>, Without<Camera3d>)>,
     mut cam_query: Query<&mut Transform, With<Camera3d>>,
 ) {
@@ -71,3 +75,24 @@ fn initial_position_system(
         }
     }
 }
+
+/// Moves the player to where it ends up after changing dimension or respawning. While the death
+/// screen is shown, the player is moved once the respawn handshake completes instead.
+fn respawn_position_system(
+    death: Res<DeathScreen>,
+    mut dimension_changes: EventReader<ChangeDimension>,
+    mut respawns: EventReader<Respawn>,
+    mut query: Query<&mut Transform, With<Local>>,
+) {
+    let positions = dimension_changes.iter().map(|pk| pk.position).chain(
+        respawns
+            .iter()
+            .filter(|pk| matches!(pk.state, RespawnState::ReadyToSpawn) && !death.is_open())
+            .map(|pk| pk.position),
+    );
+    for position in positions {
+        for mut tr in &mut query {
+            tr.translation = position;
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use json::JsonValue;
+use zuri_net::client::pack::DownloadedPack;
+
+use crate::client::Disconnected;
+
+/// Loads the resource packs that the client uses from disk.
+///
+/// Packs are read from the directory in the `zuri_resource_packs` environment variable, or the
+/// `resource_packs` directory if it is not set. Each subdirectory containing a `manifest.json` is
+/// treated as a pack. A pack named `vanilla` is always given the lowest priority, the remaining
+/// packs are applied on top of it in alphabetical order.
+///
+/// The packs a server sends while logging in are extracted to the directory in the
+/// `zuri_server_packs` environment variable, or the `server_packs` directory if it is not set, and
+/// are applied on top of the local packs until the client leaves the server.
+pub struct ResourcePackPlugin;
+
+impl Plugin for ResourcePackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ResourcePacks::default())
+            .add_event::<ServerResourcePacks>()
+            .add_startup_system(load_packs_system)
+            .add_system(server_packs_system)
+            .add_system(clear_server_packs_system.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// Sent when the client has logged in to a server, with the resource packs the server sent in the
+/// order they should be applied in.
+pub struct ServerResourcePacks(pub Vec<DownloadedPack>);
+
+/// The stack of resource packs that are currently applied.
+///
+/// Packs later in the stack take priority over packs earlier in the stack, and the packs of the
+/// server the client is connected to take priority over all local packs. Systems that derive data
+/// from the packs should watch this resource for changes and rebuild their data when it does.
+#[derive(Resource, Default)]
+pub struct ResourcePacks {
+    packs: Vec<ResourcePack>,
+    server_packs: Vec<ResourcePack>,
+}
+
+impl ResourcePacks {
+    /// Adds a pack on top of the stack, giving it priority over all packs that are already present.
+    pub fn push(&mut self, pack: ResourcePack) {
+        self.packs.push(pack);
+    }
+
+    /// Removes all packs from the stack.
+    pub fn clear(&mut self) {
+        self.packs.clear();
+        self.server_packs.clear();
+    }
+
+    /// Returns an iterator over all packs, from the lowest to the highest priority.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ResourcePack> {
+        self.packs.iter().chain(&self.server_packs)
+    }
+
+    /// Returns true if no packs are present.
+    pub fn is_empty(&self) -> bool {
+        self.packs.is_empty() && self.server_packs.is_empty()
+    }
+
+    /// Reads a file from the pack with the highest priority that contains it.
+    pub fn read(&self, path: &str) -> Option<Vec<u8>> {
+        self.iter().rev().find_map(|pack| pack.read(path))
+    }
+
+    /// Reads a file from the first pack that contains it, trying each of the extensions in order.
+    /// Returns the file contents along with the extension that was found.
+    ///
+    /// Resource packs usually refer to images without an extension, so this can be used to find
+    /// either the `png` or the `tga` version of a texture.
+    pub fn read_any<'a>(&self, path: &str, extensions: &[&'a str]) -> Option<(Vec<u8>, &'a str)> {
+        self.iter().rev().find_map(|pack| {
+            extensions.iter().find_map(|ext| {
+                pack.read(&format!("{path}.{ext}"))
+                    .map(|contents| (contents, *ext))
+            })
+        })
+    }
+
+    /// Reads and parses a JSON file from the pack with the highest priority that contains it.
+    pub fn read_json(&self, path: &str) -> Option<JsonValue> {
+        self.iter().rev().find_map(|pack| pack.read_json(path))
+    }
+
+    /// Loads a texture from the pack with the highest priority that contains it. The path is given
+    /// without an extension, as textures may be either `png` or `tga` files.
+    pub fn load_texture(&self, path: &str) -> Option<Image> {
+        let (contents, ext) = self.read_any(path, &["png", "tga"])?;
+        Image::from_buffer(
+            &contents,
+            ImageType::Extension(ext),
+            CompressedImageFormats::NONE,
+            true,
+        )
+        .map_err(|err| debug!("Could not decode texture `{path}`: {err}"))
+        .ok()
+    }
+
+    /// Reads and parses a JSON file from every pack that contains it, from the lowest to the highest
+    /// priority. Useful for files that are merged together rather than overridden, such as
+    /// `blocks.json`.
+    pub fn read_json_all(&self, path: &str) -> Vec<JsonValue> {
+        self.iter()
+            .filter_map(|pack| pack.read_json(path))
+            .collect()
+    }
+
+    /// Lists the paths of all files in a directory across all packs, relative to the root of the
+    /// pack they are in. Paths are returned from the lowest to the highest priority pack, and
+    /// might contain duplicates.
+    pub fn list_dir(&self, dir: &str) -> Vec<String> {
+        self.iter().flat_map(|pack| pack.list_dir(dir)).collect()
+    }
+}
+
+/// A single resource pack that has been extracted to a directory on disk.
+pub struct ResourcePack {
+    /// The name of the pack as found in its manifest.
+    name: String,
+    /// The root directory of the pack, which contains the manifest.
+    root: PathBuf,
+}
+
+impl ResourcePack {
+    /// Opens a resource pack from its root directory. Returns None if the directory does not
+    /// contain a valid `manifest.json`.
+    pub fn open(root: impl Into<PathBuf>) -> Option<Self> {
+        let root = root.into();
+        let manifest = parse_json(&fs::read(root.join("manifest.json")).ok()?)?;
+        let name = manifest["header"]["name"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| root.file_name().unwrap().to_string_lossy().to_string());
+
+        Some(Self { name, root })
+    }
+
+    /// The name of the pack.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Reads a file from the pack. The path is relative to the root of the pack.
+    pub fn read(&self, path: &str) -> Option<Vec<u8>> {
+        fs::read(self.root.join(path)).ok()
+    }
+
+    /// Reads and parses a JSON file from the pack. Returns None if the file does not exist or is
+    /// not valid JSON.
+    pub fn read_json(&self, path: &str) -> Option<JsonValue> {
+        let contents = self.read(path)?;
+        let value = parse_json(&contents);
+        if value.is_none() {
+            warn!("Invalid JSON in file `{}` of pack `{}`", path, self.name);
+        }
+        value
+    }
+
+    /// Lists the paths of all files in a directory of the pack, relative to the root of the pack.
+    pub fn list_dir(&self, dir: &str) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.root.join(dir)) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| format!("{}/{}", dir, entry.file_name().to_string_lossy()))
+            .collect()
+    }
+}
+
+/// Parses JSON the way the game does. Unlike standard JSON, resource packs are allowed to contain
+/// comments, so these are stripped before parsing.
+pub fn parse_json(contents: &[u8]) -> Option<JsonValue> {
+    let contents = String::from_utf8_lossy(contents);
+    json::parse(&strip_comments(contents.trim_start_matches('\u{feff}'))).ok()
+}
+
+/// Removes `//` and `/* */` comments from a JSON string, leaving string literals untouched.
+fn strip_comments(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Loads all resource packs found in the resource pack directory on startup.
+fn load_packs_system(mut packs: ResMut<ResourcePacks>) {
+    let dir = env::var("zuri_resource_packs").unwrap_or("resource_packs".into());
+    let Ok(entries) = fs::read_dir(Path::new(&dir)) else {
+        warn!("Resource pack directory `{dir}` not found, using fallback textures");
+        return;
+    };
+
+    let mut roots: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    roots.sort_by_key(|path| (!path.ends_with("vanilla"), path.clone()));
+
+    for root in roots {
+        match ResourcePack::open(&root) {
+            Some(pack) => {
+                info!("Loaded resource pack `{}`", pack.name());
+                packs.push(pack);
+            }
+            None => warn!("Skipping `{}`: missing or invalid manifest", root.display()),
+        }
+    }
+}
+
+/// Extracts the packs sent by the server and applies them on top of the local packs. Packs are
+/// extracted once per version, so reconnecting to a server doesn't extract its packs again.
+fn server_packs_system(
+    mut events: EventReader<ServerResourcePacks>,
+    mut packs: ResMut<ResourcePacks>,
+) {
+    let Some(ServerResourcePacks(downloaded)) = events.iter().last() else {
+        return;
+    };
+    let dir = env::var("zuri_server_packs").unwrap_or("server_packs".into());
+
+    let mut server_packs = Vec::with_capacity(downloaded.len());
+    for pack in downloaded {
+        let name: String = format!("{}_{}", pack.uuid, pack.version)
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        let root = Path::new(&dir).join(name);
+        if !root.exists() {
+            if let Err(err) = extract(pack, &root) {
+                warn!("Could not extract server pack `{}`: {err}", pack.uuid);
+                continue;
+            }
+        }
+        match open_extracted(&root) {
+            Some(pack) => {
+                info!("Loaded server resource pack `{}`", pack.name());
+                server_packs.push(pack);
+            }
+            None => warn!(
+                "Skipping server pack `{}`: missing or invalid manifest",
+                pack.uuid
+            ),
+        }
+    }
+    if !server_packs.is_empty() || !packs.server_packs.is_empty() {
+        packs.server_packs = server_packs;
+    }
+}
+
+/// Removes the packs of the server once the client has left it.
+fn clear_server_packs_system(mut packs: ResMut<ResourcePacks>) {
+    if !packs.server_packs.is_empty() {
+        packs.server_packs.clear();
+    }
+}
+
+/// Extracts a pack downloaded from the server to a directory. The files are first written to a
+/// temporary directory, so that a pack that could only be partially extracted is not used later.
+fn extract(pack: &DownloadedPack, root: &Path) -> std::io::Result<()> {
+    let temp = root.with_extension("part");
+    let _ = fs::remove_dir_all(&temp);
+    for (name, contents) in pack.files()? {
+        // The archive comes from the server, so its paths may not point outside of the pack.
+        let path = Path::new(&name);
+        if !path
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            continue;
+        }
+        let path = temp.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    fs::rename(temp, root)
+}
+
+/// Opens a pack extracted from an archive. Packs are often zipped along with the directory they are
+/// in, in which case the manifest is found in a subdirectory.
+fn open_extracted(root: &Path) -> Option<ResourcePack> {
+    ResourcePack::open(root).or_else(|| {
+        fs::read_dir(root)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .find_map(ResourcePack::open)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            strip_comments("{\n  // The name.\n  \"name\": \"a // b\" /* inline */\n}"),
+            "{\n  \n  \"name\": \"a // b\" \n}"
+        );
+        assert_eq!(
+            strip_comments(
+                r#"{"path": "a\"/*b*/", /* multi
+            line */ "x": 1}"#
+            ),
+            r#"{"path": "a\"/*b*/",  "x": 1}"#
+        );
+        assert_eq!(strip_comments("[1, 2] /* unterminated"), "[1, 2] ");
+    }
+
+    #[test]
+    fn json() {
+        let value = parse_json(b"\xef\xbb\xbf{\"a\": 1 // one\n}").unwrap();
+        assert_eq!(value["a"].as_i32(), Some(1));
+        assert!(parse_json(b"{\"a\": }").is_none());
+    }
+}
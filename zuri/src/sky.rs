@@ -0,0 +1,502 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use zuri_net::proto::packet::game_rules_changed::GameRulesChanged;
+use zuri_net::proto::packet::set_time::SetTime;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::types::game_rule::{GameRule, GameRuleValue};
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::resource_pack::ResourcePacks;
+use crate::sky::fog::FogPlugin;
+use crate::sky::weather::{Weather, WeatherPlugin};
+
+mod fog;
+mod weather;
+
+/// Renders the sky with the sun, the moon and the stars, and lights the world according to the
+/// time of day. Time advances on its own unless the `doDaylightCycle` game rule is disabled, and is
+/// kept in sync with the server by the SetTime packet.
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(WeatherPlugin)
+            .add_plugin(FogPlugin)
+            .insert_resource(WorldTime::default())
+            .insert_resource(SkyColours::default())
+            .add_startup_system(sky_setup_system)
+            .add_system(time_update_system.in_base_set(NetworkSet::Process))
+            .add_systems(
+                (
+                    time_advance_system,
+                    sky_colour_system,
+                    sky_follow_system,
+                    sky_render_system,
+                    light_system,
+                )
+                    .chain(),
+            )
+            .add_system(sky_texture_system)
+            .add_system(reset_resource::<WorldTime>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// How many ticks pass every second.
+const TICKS_PER_SECOND: f64 = 20.;
+/// How many ticks a full day takes.
+const DAY_TICKS: f64 = 24000.;
+/// How far from the camera the sky dome, the stars and the sun and moon are. They are kept inside
+/// the far plane of the camera, with the sun and moon in front of the stars and the stars in front
+/// of the dome.
+const DOME_DISTANCE: f32 = 900.;
+const STAR_DISTANCE: f32 = 850.;
+const SUN_DISTANCE: f32 = 800.;
+/// The size of the sun and the moon, relative to their distance.
+const SUN_SIZE: f32 = 0.3;
+const MOON_SIZE: f32 = 0.2;
+/// The seed used for placing stars, so that they are in the same place every time.
+const STAR_SEED: u64 = 10842;
+const STAR_COUNT: usize = 1500;
+
+/// The colour of the sky straight up at noon on a clear day.
+const ZENITH: Vec3 = Vec3::new(0.47, 0.65, 1.);
+/// The colour of the sky at the horizon at noon on a clear day.
+const HORIZON: Vec3 = Vec3::new(0.67, 0.82, 1.);
+/// The colour of the sky at midnight.
+const NIGHT: Vec3 = Vec3::new(0.01, 0.01, 0.03);
+
+/// The time of the world in ticks.
+#[derive(Resource, Debug, Clone)]
+pub struct WorldTime {
+    ticks: f64,
+    /// Whether time advances, as decided by the `doDaylightCycle` game rule.
+    pub daylight_cycle: bool,
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            ticks: 6000.,
+            daylight_cycle: true,
+        }
+    }
+}
+
+impl WorldTime {
+    /// Sets the number of ticks since the world was created.
+    pub fn set(&mut self, ticks: i64) {
+        self.ticks = ticks as f64;
+    }
+
+    /// Advances time by a number of seconds, unless the daylight cycle is disabled.
+    pub fn advance(&mut self, seconds: f64) {
+        if self.daylight_cycle {
+            self.ticks += seconds * TICKS_PER_SECOND;
+        }
+    }
+
+    /// Returns the angle of the sun as a fraction of a full circle. The sun is straight up at zero
+    /// and straight down at one half. The sun moves faster around sunrise and sunset.
+    pub fn celestial_angle(&self) -> f32 {
+        let day = (self.ticks / DAY_TICKS - 0.25).rem_euclid(1.) as f32;
+        (day * 2. + (0.5 - (day * PI).cos() / 2.)) / 3.
+    }
+
+    /// Returns how bright the sky is, from zero at night to one during the day.
+    pub fn daylight(&self) -> f32 {
+        ((self.celestial_angle() * TAU).cos() * 2. + 0.5).clamp(0., 1.)
+    }
+
+    /// Returns how bright the stars are, which only show up at night.
+    pub fn star_brightness(&self) -> f32 {
+        let brightness = 1. - ((self.celestial_angle() * TAU).cos() * 2. + 0.25);
+        brightness.clamp(0., 1.).powi(2) * 0.5
+    }
+
+    /// Returns the phase of the moon, from zero for a full moon to seven. The phase changes every
+    /// day.
+    pub fn moon_phase(&self) -> u32 {
+        (self.ticks / DAY_TICKS).floor().rem_euclid(8.) as u32
+    }
+
+    /// Returns the rotation of the sky, which turns the up direction towards the sun.
+    pub fn sky_rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.celestial_angle() * TAU)
+    }
+}
+
+/// The colours of the sky, which are also used for fog.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct SkyColours {
+    /// The colour of the sky straight up.
+    pub zenith: Color,
+    /// The colour of the sky at the horizon.
+    pub horizon: Color,
+}
+
+/// Returns the colours of the sky straight up and at the horizon. Rain makes the sky grey, thunder
+/// makes it darker and lightning flashes light it up.
+pub fn sky_colours(daylight: f32, rain: f32, thunder: f32, flash: f32) -> SkyColours {
+    let shade = |day: Vec3| {
+        let mut colour = NIGHT.lerp(day, daylight);
+        let grey = colour.dot(Vec3::new(0.3, 0.59, 0.11)) * 0.6;
+        colour = colour.lerp(Vec3::splat(grey), rain * 0.75);
+        colour *= 1. - thunder * 0.5;
+        colour = colour.lerp(Vec3::splat(0.8), flash * 0.5);
+        Color::rgb(colour.x, colour.y, colour.z)
+    };
+    SkyColours {
+        zenith: shade(ZENITH),
+        horizon: shade(HORIZON),
+    }
+}
+
+/// Follows the camera, so that the sky is always around it. Everything in the sky is a child of it.
+#[derive(Component)]
+struct Sky;
+
+#[derive(Component)]
+struct SkyDome;
+
+/// The things that move across the sky over the course of a day.
+#[derive(Component)]
+enum Celestial {
+    Sun,
+    Moon {
+        /// The phase shown by the mesh of the moon.
+        phase: Option<u32>,
+    },
+    Stars,
+}
+
+fn sky_setup_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |colour: Color, alpha_mode: AlphaMode| {
+        materials.add(StandardMaterial {
+            base_color: colour,
+            alpha_mode,
+            unlit: true,
+            fog_enabled: false,
+            cull_mode: None,
+            ..default()
+        })
+    };
+    let dome = PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: DOME_DISTANCE,
+            sectors: 32,
+            stacks: 16,
+        })),
+        material: material(Color::WHITE, AlphaMode::Opaque),
+        ..default()
+    };
+    let sun = PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(
+            SUN_DISTANCE * SUN_SIZE,
+        )))),
+        material: material(Color::WHITE, AlphaMode::Add),
+        ..default()
+    };
+    let moon = PbrBundle {
+        mesh: meshes.add(moon_mesh(0)),
+        material: material(Color::WHITE, AlphaMode::Add),
+        ..default()
+    };
+    let stars = PbrBundle {
+        mesh: meshes.add(star_mesh()),
+        material: material(Color::BLACK, AlphaMode::Add),
+        ..default()
+    };
+
+    commands
+        .spawn((SpatialBundle::default(), Sky))
+        .with_children(|sky| {
+            sky.spawn((dome, SkyDome));
+            sky.spawn((sun, Celestial::Sun));
+            sky.spawn((moon, Celestial::Moon { phase: None }));
+            sky.spawn((stars, Celestial::Stars));
+        });
+}
+
+/// Sets the textures of the sun and the moon whenever the resource packs change.
+fn sky_texture_system(
+    packs: Res<ResourcePacks>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    celestials: Query<(&Handle<StandardMaterial>, &Celestial)>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+    for (material, celestial) in &celestials {
+        let texture = match celestial {
+            Celestial::Sun => "textures/environment/sun",
+            Celestial::Moon { .. } => "textures/environment/moon_phases",
+            Celestial::Stars => continue,
+        };
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color_texture =
+                packs.load_texture(texture).map(|image| images.add(image));
+        }
+    }
+}
+
+/// Reads the time and the `doDaylightCycle` game rule from the server.
+fn time_update_system(
+    mut time: ResMut<WorldTime>,
+    mut start_game: EventReader<StartGame>,
+    mut set_time: EventReader<SetTime>,
+    mut game_rules: EventReader<GameRulesChanged>,
+) {
+    let update_rules = |time: &mut WorldTime, rules: &[GameRule]| {
+        for rule in rules {
+            if let ("dodaylightcycle", GameRuleValue::Bool(enabled)) =
+                (rule.name.to_ascii_lowercase().as_str(), &rule.value)
+            {
+                time.daylight_cycle = *enabled;
+            }
+        }
+    };
+    for pk in start_game.iter() {
+        time.set(pk.time);
+        update_rules(&mut time, &pk.game_rules);
+    }
+    for pk in game_rules.iter() {
+        update_rules(&mut time, &pk.game_rules);
+    }
+    for pk in set_time.iter() {
+        time.set(pk.time.0 as i64);
+    }
+}
+
+fn time_advance_system(real_time: Res<Time>, mut time: ResMut<WorldTime>) {
+    time.advance(real_time.delta_seconds_f64());
+}
+
+fn sky_colour_system(
+    time: Res<WorldTime>,
+    weather: Res<Weather>,
+    mut colours: ResMut<SkyColours>,
+    mut cameras: Query<&mut Camera3d>,
+) {
+    *colours = sky_colours(
+        time.daylight(),
+        weather.rain,
+        weather.thunder,
+        weather.flash,
+    );
+    for mut camera in &mut cameras {
+        camera.clear_color = ClearColorConfig::Custom(colours.horizon);
+    }
+}
+
+/// Keeps the sky centred on the camera.
+fn sky_follow_system(
+    camera: Query<&Transform, With<Camera3d>>,
+    mut sky: Query<&mut Transform, (With<Sky>, Without<Camera3d>)>,
+) {
+    let (Ok(camera), Ok(mut sky)) = (camera.get_single(), sky.get_single_mut()) else {
+        return;
+    };
+    sky.translation = camera.translation;
+}
+
+/// Colours the sky dome, and moves the sun, the moon and the stars across the sky.
+fn sky_render_system(
+    time: Res<WorldTime>,
+    weather: Res<Weather>,
+    colours: Res<SkyColours>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    dome: Query<&Handle<Mesh>, With<SkyDome>>,
+    mut celestials: Query<(
+        &mut Transform,
+        &Handle<StandardMaterial>,
+        &Handle<Mesh>,
+        &mut Celestial,
+    )>,
+) {
+    if colours.is_changed() {
+        for mesh in &dome {
+            if let Some(mesh) = meshes.get_mut(mesh) {
+                paint_dome(mesh, &colours);
+            }
+        }
+    }
+
+    let rotation = time.sky_rotation();
+    let sun_direction = rotation * Vec3::Y;
+    // The sun, the moon and the stars are hidden behind the clouds when it rains.
+    let clear = 1. - weather.rain;
+    let place = |direction: Vec3| {
+        let position = direction * SUN_DISTANCE;
+        // The quad faces towards positive Z, which should point at the camera.
+        Transform::from_translation(position).looking_at(position * 2., Vec3::Z)
+    };
+    for (mut transform, material, mesh, mut celestial) in &mut celestials {
+        let brightness = match &mut *celestial {
+            Celestial::Sun => {
+                *transform = place(sun_direction);
+                clear
+            }
+            Celestial::Moon { phase } => {
+                *transform = place(-sun_direction);
+                let current = time.moon_phase();
+                if *phase != Some(current) {
+                    *phase = Some(current);
+                    if let Some(mesh) = meshes.get_mut(mesh) {
+                        *mesh = moon_mesh(current);
+                    }
+                }
+                clear
+            }
+            Celestial::Stars => {
+                *transform = Transform::from_rotation(rotation);
+                time.star_brightness() * clear
+            }
+        };
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = Color::rgb(brightness, brightness, brightness);
+        }
+    }
+}
+
+/// Lights the world with the sun during the day, and dimly with the moon at night.
+fn light_system(
+    time: Res<WorldTime>,
+    weather: Res<Weather>,
+    mut ambient: ResMut<AmbientLight>,
+    mut lights: Query<(&mut Transform, &mut DirectionalLight)>,
+) {
+    let daylight = time.daylight();
+    let sun_direction = time.sky_rotation() * Vec3::Y;
+    let direction = if daylight > 0. {
+        sun_direction
+    } else {
+        -sun_direction
+    };
+    for (mut transform, mut light) in &mut lights {
+        *transform = Transform::from_translation(direction).looking_at(Vec3::ZERO, Vec3::Z);
+        light.illuminance = (3600. * daylight).max(400.) * (1. - weather.rain * 0.5);
+    }
+    ambient.brightness = (0.1 + 0.2 * daylight) * (1. - weather.thunder * 0.3) + weather.flash;
+}
+
+/// Colours the vertices of the sky dome, blending from the horizon to the zenith.
+fn paint_dome(mesh: &mut Mesh, colours: &SkyColours) {
+    let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return;
+    };
+    let Some(positions) = positions.as_float3() else {
+        return;
+    };
+    let zenith = Vec4::from(colours.zenith.as_rgba_f32());
+    let horizon = Vec4::from(colours.horizon.as_rgba_f32());
+    let colours: Vec<[f32; 4]> = positions
+        .iter()
+        .map(|[_, y, _]| {
+            let height = (y / DOME_DISTANCE).clamp(0., 1.).sqrt();
+            horizon.lerp(zenith, height).to_array()
+        })
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colours);
+}
+
+/// Creates the mesh of the moon, showing one of the phases in the moon texture. The texture has four
+/// phases in the upper row and four in the lower row.
+fn moon_mesh(phase: u32) -> Mesh {
+    let (column, row) = ((phase % 4) as f32, (phase / 4 % 2) as f32);
+    let mut mesh = Mesh::from(shape::Quad::new(Vec2::splat(SUN_DISTANCE * MOON_SIZE)));
+    let (left, right) = (column / 4., (column + 1.) / 4.);
+    let (top, bottom) = (row / 2., (row + 1.) / 2.);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![[left, bottom], [left, top], [right, top], [right, bottom]],
+    );
+    mesh
+}
+
+/// Creates a mesh with a small square for every star, spread across the sky.
+fn star_mesh() -> Mesh {
+    let mut rng = StdRng::seed_from_u64(STAR_SEED);
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::new();
+    while positions.len() < STAR_COUNT * 4 {
+        let direction = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.;
+        if !(0.01..=1.).contains(&direction.length_squared()) {
+            continue;
+        }
+        let direction = direction.normalize();
+        let size = rng.gen_range(0.15..0.25) * STAR_DISTANCE / 100.;
+        let right = direction.any_orthonormal_vector() * size;
+        let up = direction.cross(right);
+        let centre = direction * STAR_DISTANCE;
+
+        let start = positions.len() as u32;
+        for corner in [-right - up, -right + up, right + up, right - up] {
+            positions.push((centre + corner).to_array());
+            normals.push((-direction).to_array());
+        }
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ticks: i64) -> WorldTime {
+        let mut time = WorldTime::default();
+        time.set(ticks);
+        time
+    }
+
+    #[test]
+    fn day_cycle() {
+        assert_eq!(at(6000).celestial_angle(), 0.);
+        assert_eq!(at(18000).celestial_angle(), 0.5);
+        assert_eq!(at(6000).daylight(), 1.);
+        assert_eq!(at(18000).daylight(), 0.);
+        assert_eq!(at(18000).star_brightness(), 0.5);
+        assert_eq!(at(6000).star_brightness(), 0.);
+        assert_eq!(at(24000 * 9 + 100).moon_phase(), 1);
+
+        // The sun rises in the east, towards positive X.
+        let sunrise = at(0).sky_rotation() * Vec3::Y;
+        assert!(sunrise.x > 0.9);
+
+        let mut time = at(1000);
+        time.advance(1.);
+        assert_eq!(time.ticks, 1020.);
+        time.daylight_cycle = false;
+        time.advance(1.);
+        assert_eq!(time.ticks, 1020.);
+    }
+
+    #[test]
+    fn colours() {
+        let noon = sky_colours(1., 0., 0., 0.);
+        let night = sky_colours(0., 0., 0., 0.);
+        let rain = sky_colours(1., 1., 0., 0.);
+        let [r, g, b, _] = noon.zenith.as_rgba_f32();
+        assert!(b > r && b > g);
+        assert!(night.zenith.b() < 0.05);
+        // Rain makes the sky grey.
+        assert!(rain.zenith.b() - rain.zenith.r() < noon.zenith.b() - noon.zenith.r());
+    }
+}
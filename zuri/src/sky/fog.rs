@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use json::JsonValue;
+use zuri_net::proto::packet::player_fog::PlayerFog;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::resource_pack::ResourcePacks;
+use crate::sky::SkyColours;
+use crate::world::ChunkManager;
+
+/// Fades the world into the sky towards the render distance. Fog follows the fog stack sent by the
+/// server, using the fog definitions of the `fogs` directory of the resource packs.
+pub(super) struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FogDefinitions::default())
+            .insert_resource(FogStack::default())
+            .add_system(fog_load_system)
+            .add_system(fog_stack_system.in_base_set(NetworkSet::Process))
+            .add_system(fog_system)
+            .add_system(reset_resource::<FogStack>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// The render distance used before the server has sent one, in blocks.
+const DEFAULT_RENDER_DISTANCE: u32 = 64;
+
+/// The fog used when no fog of the stack has a definition.
+const DEFAULT_FOG: FogDefinition = FogDefinition {
+    start: 0.92,
+    end: 1.,
+    colour: None,
+    fixed: false,
+};
+
+/// How fog looks in the air, as read from a fog definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FogDefinition {
+    /// Where the fog starts.
+    pub start: f32,
+    /// Where the fog covers everything.
+    pub end: f32,
+    /// The colour of the fog. Fog takes on the colour of the sky when unset.
+    pub colour: Option<Color>,
+    /// Whether the start and the end are in blocks, rather than fractions of the render distance.
+    pub fixed: bool,
+}
+
+impl FogDefinition {
+    /// Parses the air fog of a fog definition file, returning its identifier along with it.
+    pub fn parse(file: &JsonValue) -> Option<(String, Self)> {
+        let settings = &file["minecraft:fog_settings"];
+        let identifier = settings["description"]["identifier"].as_str()?;
+        let air = &settings["distance"]["air"];
+        let definition = Self {
+            start: air["fog_start"].as_f32()?,
+            end: air["fog_end"].as_f32()?,
+            colour: air["fog_color"].as_str().and_then(parse_colour),
+            fixed: air["render_distance_type"].as_str() == Some("fixed"),
+        };
+        Some((identifier.to_string(), definition))
+    }
+
+    /// Returns where the fog starts and ends in blocks.
+    pub fn distances(&self, render_distance: f32) -> (f32, f32) {
+        if self.fixed {
+            (self.start, self.end)
+        } else {
+            (self.start * render_distance, self.end * render_distance)
+        }
+    }
+}
+
+/// Parses a hexadecimal `#RRGGBB` colour.
+fn parse_colour(colour: &str) -> Option<Color> {
+    let hex = colour.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let [_, r, g, b] = u32::from_str_radix(hex, 16).ok()?.to_be_bytes();
+    Some(Color::rgb_u8(r, g, b))
+}
+
+/// The fog definitions of the resource packs by their identifier, such as `minecraft:fog_ocean`.
+#[derive(Resource, Default)]
+pub struct FogDefinitions(HashMap<String, FogDefinition>);
+
+impl FogDefinitions {
+    /// Reads all fog definitions from the resource packs. Definitions in packs with a higher
+    /// priority replace definitions with the same identifier.
+    pub fn load(packs: &ResourcePacks) -> Self {
+        let definitions = packs
+            .list_dir("fogs")
+            .into_iter()
+            .filter(|path| path.ends_with(".json"))
+            .filter_map(|path| packs.read_json(&path))
+            .filter_map(|file| FogDefinition::parse(&file))
+            .collect();
+        Self(definitions)
+    }
+
+    /// Returns the fog of a fog stack. Fogs later in the stack take priority, and fogs without a
+    /// definition are skipped.
+    pub fn resolve(&self, stack: &[String]) -> &FogDefinition {
+        stack
+            .iter()
+            .rev()
+            .find_map(|identifier| self.0.get(identifier))
+            .unwrap_or(&DEFAULT_FOG)
+    }
+}
+
+/// The fog stack last sent by the server.
+#[derive(Resource, Default)]
+struct FogStack(Vec<String>);
+
+/// Reloads the fog definitions whenever the resource packs change.
+fn fog_load_system(packs: Res<ResourcePacks>, mut definitions: ResMut<FogDefinitions>) {
+    if packs.is_changed() {
+        *definitions = FogDefinitions::load(&packs);
+    }
+}
+
+fn fog_stack_system(mut stack: ResMut<FogStack>, mut events: EventReader<PlayerFog>) {
+    if let Some(pk) = events.iter().last() {
+        stack.0 = pk.stack.clone();
+    }
+}
+
+/// Sets the fog of the camera according to the fog stack, the render distance and the colour of
+/// the sky.
+fn fog_system(
+    mut commands: Commands,
+    definitions: Res<FogDefinitions>,
+    stack: Res<FogStack>,
+    chunks: Res<ChunkManager>,
+    colours: Res<SkyColours>,
+    mut cameras: Query<(Entity, Option<&mut FogSettings>), With<Camera3d>>,
+) {
+    let definition = definitions.resolve(&stack.0);
+    let render_distance = match chunks.chunk_radius().1 {
+        0 => DEFAULT_RENDER_DISTANCE,
+        radius => radius,
+    };
+    let (start, end) = definition.distances(render_distance as f32);
+    let settings = FogSettings {
+        color: definition.colour.unwrap_or(colours.horizon),
+        falloff: FogFalloff::Linear { start, end },
+        ..default()
+    };
+    for (entity, fog) in &mut cameras {
+        match fog {
+            Some(mut fog) => *fog = settings.clone(),
+            None => {
+                commands.entity(entity).insert(settings.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions() {
+        let file = json::parse(
+            r##"{
+                "format_version": "1.16.100",
+                "minecraft:fog_settings": {
+                    "description": { "identifier": "minecraft:fog_hell" },
+                    "distance": {
+                        "air": {
+                            "fog_start": 10,
+                            "fog_end": 96,
+                            "fog_color": "#330808",
+                            "render_distance_type": "fixed"
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+        let (identifier, definition) = FogDefinition::parse(&file).unwrap();
+        assert_eq!(identifier, "minecraft:fog_hell");
+        assert_eq!(definition.distances(256.), (10., 96.));
+        assert_eq!(definition.colour, Some(Color::rgb_u8(0x33, 0x08, 0x08)));
+
+        let definitions = FogDefinitions(HashMap::from([(identifier, definition.clone())]));
+        let stack = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        assert_eq!(definitions.resolve(&stack(&[])), &DEFAULT_FOG);
+        assert_eq!(DEFAULT_FOG.distances(100.), (92., 100.));
+        assert_eq!(
+            definitions.resolve(&stack(&["minecraft:fog_hell", "minecraft:fog_unknown"])),
+            &definition
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use rand::Rng;
+use zuri_net::proto::packet::level_event::LevelEvent;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::types::level_event::LevelEventType;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+
+/// Follows the rain and thunder levels sent by the server, and shows falling rain and lightning
+/// flashes.
+pub(super) struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Weather::default())
+            .add_startup_system(rain_setup_system)
+            .add_system(weather_update_system.in_base_set(NetworkSet::Process))
+            .add_systems((weather_advance_system, rain_system).chain())
+            .add_system(reset_resource::<Weather>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// How quickly the rain and thunder levels change towards the levels sent by the server, per
+/// second.
+const TRANSITION_SPEED: f32 = 0.2;
+/// How quickly a lightning flash fades, per second.
+const FLASH_FADE: f32 = 3.;
+/// The average amount of lightning flashes per second during a full thunderstorm.
+const FLASH_RATE: f32 = 0.1;
+/// The amount of rain drops around the camera when it rains as hard as it can.
+const MAX_DROPS: usize = 600;
+/// How far around the camera rain drops fall.
+const RAIN_RADIUS: f32 = 16.;
+/// How far above and below the camera rain drops fall.
+const RAIN_HEIGHT: f32 = 12.;
+/// How fast rain drops fall, in blocks per second.
+const RAIN_SPEED: f32 = 15.;
+
+/// The current weather. The levels move slowly towards the levels sent by the server, so that rain
+/// starts and stops gradually.
+#[derive(Resource, Debug, Default)]
+pub struct Weather {
+    /// How hard it rains, from zero to one.
+    pub rain: f32,
+    /// How strong the thunderstorm is, from zero to one. Thunder only happens while it rains.
+    pub thunder: f32,
+    /// How bright the current lightning flash is, from zero to one.
+    pub flash: f32,
+    target_rain: f32,
+    target_thunder: f32,
+}
+
+impl Weather {
+    /// Sets the rain and thunder levels immediately, without a transition.
+    fn set(&mut self, rain: f32, thunder: f32) {
+        self.rain = rain;
+        self.thunder = thunder;
+        self.target_rain = rain;
+        self.target_thunder = thunder;
+    }
+
+    /// Moves the levels towards the levels sent by the server, and fades the lightning flash.
+    fn advance(&mut self, seconds: f32) {
+        let step = TRANSITION_SPEED * seconds;
+        self.rain += (self.target_rain - self.rain).clamp(-step, step);
+        self.thunder += (self.target_thunder - self.thunder).clamp(-step, step);
+        self.flash = (self.flash - FLASH_FADE * seconds).max(0.);
+    }
+}
+
+/// Returns the level of a weather level event, from zero to one. The level is sent as a fraction of
+/// 65535, where zero means the weather is as strong as it can be.
+pub fn event_level(data: i32) -> f32 {
+    match data {
+        ..=0 => 1.,
+        data => (data as f32 / 65535.).min(1.),
+    }
+}
+
+fn weather_update_system(
+    mut weather: ResMut<Weather>,
+    mut start_game: EventReader<StartGame>,
+    mut events: EventReader<LevelEvent>,
+) {
+    for pk in start_game.iter() {
+        weather.set(
+            pk.rain_level.clamp(0., 1.),
+            pk.lightning_level.clamp(0., 1.),
+        );
+    }
+    for pk in events.iter() {
+        match pk.event_type {
+            LevelEventType::StartRaining => weather.target_rain = event_level(pk.event_data.0),
+            LevelEventType::StopRaining => weather.target_rain = 0.,
+            LevelEventType::StartThunderstorm => {
+                weather.target_thunder = event_level(pk.event_data.0)
+            }
+            LevelEventType::StopThunderstorm => weather.target_thunder = 0.,
+            _ => {}
+        }
+    }
+}
+
+fn weather_advance_system(time: Res<Time>, mut weather: ResMut<Weather>) {
+    let delta = time.delta_seconds();
+    weather.advance(delta);
+
+    let chance = weather.thunder.min(weather.rain) * FLASH_RATE * delta;
+    if chance > 0. && rand::thread_rng().gen::<f32>() < chance {
+        weather.flash = 1.;
+    }
+}
+
+/// A single rain drop falling around the camera.
+#[derive(Component)]
+struct RainDrop;
+
+/// The mesh and material shared by all rain drops.
+#[derive(Resource)]
+struct RainAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn rain_setup_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(RainAssets {
+        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(0.03, 0.7)))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.7, 0.9, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None,
+            ..default()
+        }),
+    });
+}
+
+/// Spawns and removes rain drops according to how hard it rains, and lets them fall around the
+/// camera.
+fn rain_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    assets: Res<RainAssets>,
+    camera: Query<&Transform, (With<Camera3d>, Without<RainDrop>)>,
+    mut drops: Query<(Entity, &mut Transform), With<RainDrop>>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    let mut random_position = |height: f32| {
+        let offset = Vec2::new(rng.gen(), rng.gen()) * 2. - 1.;
+        camera.translation + Vec3::new(offset.x * RAIN_RADIUS, height, offset.y * RAIN_RADIUS)
+    };
+
+    let wanted = (weather.rain * MAX_DROPS as f32) as usize;
+    let mut count = 0;
+    let fall = RAIN_SPEED * time.delta_seconds();
+    for (entity, mut transform) in &mut drops {
+        count += 1;
+        if count > wanted {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y -= fall;
+        let offset = transform.translation - camera.translation;
+        if offset.y < -RAIN_HEIGHT || offset.x.abs() > RAIN_RADIUS || offset.z.abs() > RAIN_RADIUS {
+            transform.translation = random_position(RAIN_HEIGHT);
+        }
+        // Rain drops only turn around the vertical axis, so that they always fall straight down.
+        let offset = camera.translation - transform.translation;
+        transform.rotation = Quat::from_rotation_y(offset.x.atan2(offset.z));
+    }
+    for _ in count..wanted {
+        // New drops are spread out over the whole height, so that rain doesn't start as one sheet.
+        let height = (rand::thread_rng().gen::<f32>() * 2. - 1.) * RAIN_HEIGHT;
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(random_position(height)),
+                ..default()
+            },
+            RainDrop,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions() {
+        assert_eq!(event_level(0), 1.);
+        assert_eq!(event_level(65535), 1.);
+        assert_eq!(event_level(32767), 32767. / 65535.);
+
+        let mut weather = Weather {
+            target_rain: 1.,
+            flash: 1.,
+            ..Default::default()
+        };
+        weather.advance(1.);
+        assert_eq!(weather.rain, TRANSITION_SPEED);
+        assert_eq!(weather.flash, 0.);
+        weather.advance(100.);
+        assert_eq!(weather.rain, 1.);
+
+        weather.set(0., 0.);
+        weather.advance(1.);
+        assert_eq!(weather.rain, 0.);
+    }
+}
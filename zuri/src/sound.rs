@@ -0,0 +1,815 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use json::JsonValue;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use zuri_net::proto::packet::level_sound_event::LevelSoundEvent;
+use zuri_net::proto::packet::play_sound::PlaySound;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::packet::stop_sound::StopSound;
+use zuri_net::proto::types::sound_event::SoundEvent;
+
+use crate::client::NetworkSet;
+use crate::resource_pack::ResourcePacks;
+use crate::world::World;
+
+/// Plays the sounds that the server asks for with the LevelSoundEvent and PlaySound packets, and
+/// background music while in game.
+///
+/// Sound events are resolved to sound names with `sounds.json`, and sound names to the files that
+/// are played with `sounds/sound_definitions.json`. Only Ogg Vorbis files can be played.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Sounds::default())
+            .insert_resource(SoundSources::default())
+            .insert_resource(SoundQueue::default())
+            .insert_resource(PlayingSounds::default())
+            .insert_resource(Music::default())
+            .add_system(sounds_load_system)
+            .add_systems(
+                (level_sound_system, play_sound_system, stop_sound_system)
+                    .in_base_set(NetworkSet::Process),
+            )
+            .add_systems((music_system, sound_play_system, listener_system).chain());
+    }
+}
+
+/// How many playing sounds are kept track of, so that they can be stopped by the server. Sounds
+/// played before these keep playing, but can no longer be stopped.
+const MAX_TRACKED: usize = 64;
+/// The distance between the ears of the listener, in blocks.
+const EAR_GAP: f32 = 0.3;
+
+/// The sounds defined by the resource packs.
+#[derive(Resource, Default, Debug)]
+pub struct Sounds {
+    definitions: HashMap<String, SoundDefinition>,
+    individual: HashMap<String, EventSound>,
+    blocks: HashMap<String, HashMap<String, EventSound>>,
+    interactive_blocks: HashMap<String, HashMap<String, EventSound>>,
+    entity_defaults: HashMap<String, EventSound>,
+    entities: HashMap<String, HashMap<String, EventSound>>,
+    /// The sound type of blocks by their identifier, such as `stone` or `wood`.
+    block_types: HashMap<String, String>,
+    music: HashMap<String, MusicDefinition>,
+}
+
+/// A sound name as found in `sound_definitions.json`, along with the files that may be played for
+/// it.
+#[derive(Debug, Clone)]
+pub struct SoundDefinition {
+    pub category: String,
+    /// The distance in blocks from which the sound can no longer be heard. Defaults to 16 times the
+    /// volume of the sound.
+    pub max_distance: Option<f32>,
+    pub sounds: Vec<SoundFile>,
+}
+
+/// A single file that may be played for a sound. One file is picked at random, based on their
+/// weight, every time a sound is played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundFile {
+    /// The path of the file in the resource pack, without an extension.
+    pub name: String,
+    pub volume: f32,
+    pub pitch: f32,
+    pub weight: u32,
+    /// False for sounds that are not positional, such as sounds of the user interface.
+    pub is_3d: bool,
+}
+
+/// The sound played for a sound event, with a range of volumes and pitches to pick from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSound {
+    pub sound: String,
+    pub volume: (f32, f32),
+    pub pitch: (f32, f32),
+}
+
+/// A kind of music, such as the music played in game or in the menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MusicDefinition {
+    /// The name of the sound that plays the music.
+    pub event_name: String,
+    /// The range of seconds waited between tracks.
+    pub delay: (f32, f32),
+}
+
+impl Sounds {
+    /// Reads all sounds from the resource packs, merging the files of all packs.
+    pub fn load(packs: &ResourcePacks) -> Self {
+        let mut sounds = Self::default();
+        for file in packs.read_json_all("sounds/sound_definitions.json") {
+            sounds.load_definitions(&file);
+        }
+        for file in packs.read_json_all("sounds.json") {
+            sounds.load_events(&file);
+        }
+        for file in packs.read_json_all("blocks.json") {
+            sounds.load_block_types(&file);
+        }
+        for file in packs.read_json_all("sounds/music_definitions.json") {
+            sounds.load_music(&file);
+        }
+        sounds
+    }
+
+    /// Adds the sounds of a `sound_definitions.json` file. Older files have the definitions at the
+    /// root of the file rather than in a `sound_definitions` object.
+    pub fn load_definitions(&mut self, file: &JsonValue) {
+        let definitions = if file.has_key("sound_definitions") {
+            &file["sound_definitions"]
+        } else {
+            file
+        };
+        for (name, definition) in definitions.entries() {
+            if name == "format_version" {
+                continue;
+            }
+            let sounds = definition["sounds"]
+                .members()
+                .filter_map(|sound| {
+                    if let Some(name) = sound.as_str() {
+                        return Some(SoundFile {
+                            name: name.to_string(),
+                            volume: 1.,
+                            pitch: 1.,
+                            weight: 1,
+                            is_3d: true,
+                        });
+                    }
+                    Some(SoundFile {
+                        name: sound["name"].as_str()?.to_string(),
+                        volume: sound["volume"].as_f32().unwrap_or(1.),
+                        pitch: sound["pitch"].as_f32().unwrap_or(1.),
+                        weight: sound["weight"].as_u32().unwrap_or(1),
+                        is_3d: sound["is3D"].as_bool().unwrap_or(true),
+                    })
+                })
+                .collect();
+            self.definitions.insert(
+                name.to_string(),
+                SoundDefinition {
+                    category: definition["category"].as_str().unwrap_or("").to_string(),
+                    max_distance: definition["max_distance"].as_f32(),
+                    sounds,
+                },
+            );
+        }
+    }
+
+    /// Adds the sound events of a `sounds.json` file.
+    pub fn load_events(&mut self, file: &JsonValue) {
+        let individual = &file["individual_event_sounds"];
+        self.individual.extend(event_sounds(
+            &individual["events"],
+            group_ranges(individual),
+        ));
+        load_groups(&mut self.blocks, &file["block_sounds"]);
+        load_groups(
+            &mut self.interactive_blocks,
+            &file["interactive_sounds"]["block_sounds"],
+        );
+        for entities in [
+            &file["entity_sounds"],
+            &file["interactive_sounds"]["entity_sounds"],
+        ] {
+            let defaults = &entities["defaults"];
+            self.entity_defaults
+                .extend(event_sounds(&defaults["events"], group_ranges(defaults)));
+            load_groups(&mut self.entities, &entities["entities"]);
+        }
+    }
+
+    /// Adds the sound types of blocks from a `blocks.json` file.
+    pub fn load_block_types(&mut self, file: &JsonValue) {
+        for (name, entry) in file.entries() {
+            if let Some(sound) = entry["sound"].as_str() {
+                self.block_types.insert(name.to_string(), sound.to_string());
+            }
+        }
+    }
+
+    /// Adds the kinds of music of a `music_definitions.json` file.
+    pub fn load_music(&mut self, file: &JsonValue) {
+        for (name, entry) in file.entries() {
+            let Some(event_name) = entry["event_name"].as_str() else {
+                continue;
+            };
+            self.music.insert(
+                name.to_string(),
+                MusicDefinition {
+                    event_name: event_name.to_string(),
+                    delay: (
+                        entry["min_delay"].as_f32().unwrap_or(0.),
+                        entry["max_delay"].as_f32().unwrap_or(0.),
+                    ),
+                },
+            );
+        }
+    }
+
+    /// Returns the definition of a sound by its name.
+    pub fn definition(&self, name: &str) -> Option<&SoundDefinition> {
+        self.definitions.get(name)
+    }
+
+    /// Returns a kind of music by its name, such as `game`.
+    pub fn music(&self, name: &str) -> Option<&MusicDefinition> {
+        self.music.get(name)
+    }
+
+    /// Resolves the sound played for a sound event. Events caused by blocks, such as stepping on or
+    /// breaking them, sound like the block when it is known. Other events sound like the entity
+    /// that caused them, and fall back to the sounds shared by all entities.
+    pub fn event_sound(
+        &self,
+        event: &SoundEvent,
+        block: Option<&str>,
+        entity_type: &str,
+    ) -> Option<&EventSound> {
+        let name = event_name(event);
+        let block_sound = block.filter(|_| uses_block(event)).and_then(|block| {
+            let block_type = lookup(&self.block_types, block)
+                .map(String::as_str)
+                .unwrap_or("normal");
+            self.blocks
+                .get(block_type)
+                .and_then(|events| events.get(&name))
+                .or_else(|| {
+                    self.interactive_blocks
+                        .get(block_type)
+                        .and_then(|events| events.get(&name))
+                })
+        });
+        let entity_sound = || {
+            lookup(&self.entities, entity_type)
+                .and_then(|events| events.get(&name))
+                .or_else(|| self.entity_defaults.get(&name))
+        };
+        let sound = match block_sound {
+            Some(sound) => Some(sound),
+            None if !entity_type.is_empty() => entity_sound(),
+            None => self.individual.get(&name).or_else(entity_sound),
+        };
+        sound.filter(|sound| !sound.sound.is_empty())
+    }
+}
+
+impl SoundDefinition {
+    /// Picks one of the files of the sound at random, based on their weight.
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<&SoundFile> {
+        self.sounds.choose_weighted(rng, |sound| sound.weight).ok()
+    }
+}
+
+/// Returns the name of a sound event as used in `sounds.json`, such as `item.use.on` for
+/// [SoundEvent::ItemUseOn]. The names of imitated mobs are joined with underscores instead, like
+/// `imitate.cave_spider`.
+pub fn event_name(event: &SoundEvent) -> String {
+    let variant = format!("{event:?}");
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in variant.chars() {
+        let after_digit = word.ends_with(|c: char| c.is_ascii_digit());
+        let boundary = c.is_ascii_uppercase() || (c.is_ascii_digit() && !after_digit);
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c.to_ascii_lowercase());
+    }
+    words.push(word);
+    match words.split_first() {
+        Some((first, mob)) if first == "imitate" => format!("imitate.{}", mob.join("_")),
+        _ => words.join("."),
+    }
+}
+
+/// Returns true if the extra data of a sound event holds the runtime ID of the block that caused
+/// it.
+fn uses_block(event: &SoundEvent) -> bool {
+    use SoundEvent::*;
+    matches!(
+        event,
+        ItemUseOn | Hit | Step | Break | Place | HeavyStep | Fall | Land | BreakBlock | Jump
+    )
+}
+
+/// Looks up an identifier, which may also be present without its `minecraft:` namespace.
+fn lookup<'a, T>(map: &'a HashMap<String, T>, identifier: &str) -> Option<&'a T> {
+    map.get(identifier)
+        .or_else(|| map.get(identifier.strip_prefix("minecraft:")?))
+}
+
+/// Parses a volume or pitch, which is either a single number or a range of two numbers.
+fn range(value: &JsonValue, default: (f32, f32)) -> (f32, f32) {
+    if let Some(value) = value.as_f32() {
+        return (value, value);
+    }
+    match (value[0].as_f32(), value[1].as_f32()) {
+        (Some(min), Some(max)) => (min, max),
+        _ => default,
+    }
+}
+
+/// Returns the volume and pitch of a group of events, which are used for events that do not set
+/// their own.
+fn group_ranges(group: &JsonValue) -> ((f32, f32), (f32, f32)) {
+    (
+        range(&group["volume"], (1., 1.)),
+        range(&group["pitch"], (1., 1.)),
+    )
+}
+
+/// Parses the events of a group. Events are either only a sound name, or an object with the sound
+/// name along with its volume and pitch.
+fn event_sounds(
+    events: &JsonValue,
+    (volume, pitch): ((f32, f32), (f32, f32)),
+) -> impl Iterator<Item = (String, EventSound)> + '_ {
+    events.entries().filter_map(move |(name, event)| {
+        let sound = event.as_str().or_else(|| event["sound"].as_str())?;
+        Some((
+            name.to_string(),
+            EventSound {
+                sound: sound.to_string(),
+                volume: range(&event["volume"], volume),
+                pitch: range(&event["pitch"], pitch),
+            },
+        ))
+    })
+}
+
+/// Parses groups of events, such as the events of each block sound type or entity.
+fn load_groups(groups: &mut HashMap<String, HashMap<String, EventSound>>, value: &JsonValue) {
+    for (name, group) in value.entries() {
+        groups
+            .entry(name.to_string())
+            .or_default()
+            .extend(event_sounds(&group["events"], group_ranges(group)));
+    }
+}
+
+/// Returns how many seconds an Ogg Vorbis file plays for. This is the granule position of the last
+/// page of the file, which counts samples, divided by the sample rate of the identification header.
+fn ogg_duration(bytes: &[u8]) -> Option<f32> {
+    let header = bytes.windows(7).position(|w| w == b"\x01vorbis")?;
+    let rate = u32::from_le_bytes(bytes.get(header + 12..header + 16)?.try_into().ok()?);
+    let last_page = bytes.windows(4).rposition(|w| w == b"OggS")?;
+    let granule = u64::from_le_bytes(bytes.get(last_page + 6..last_page + 14)?.try_into().ok()?);
+    (rate > 0).then(|| granule as f32 / rate as f32)
+}
+
+/// The audio of sound files by their path, loaded from the resource packs the first time they are
+/// played.
+#[derive(Resource, Default)]
+struct SoundSources(HashMap<String, Option<Handle<AudioSource>>>);
+
+impl SoundSources {
+    /// Returns the audio of a sound file, or None if the resource packs do not have it as an Ogg
+    /// Vorbis file.
+    fn get(
+        &mut self,
+        packs: &ResourcePacks,
+        sources: &mut Assets<AudioSource>,
+        name: &str,
+    ) -> Option<Handle<AudioSource>> {
+        self.0
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let (contents, _) = packs.read_any(name, &["ogg"])?;
+                // Audio that cannot be decoded would panic once it is played.
+                if !contents.starts_with(b"OggS") {
+                    debug!("Sound `{name}` is not an Ogg file");
+                    return None;
+                }
+                Some(sources.add(AudioSource {
+                    bytes: contents.into(),
+                }))
+            })
+            .clone()
+    }
+}
+
+/// A sound waiting to be played.
+struct QueuedSound {
+    name: String,
+    /// The position of the sound, or None if it should be heard at full volume everywhere.
+    position: Option<Vec3>,
+    volume: f32,
+    pitch: f32,
+}
+
+/// The sounds that will be played at the end of the frame.
+#[derive(Resource, Default)]
+struct SoundQueue(Vec<QueuedSound>);
+
+/// A sound that is still playing, or might be.
+struct Playing {
+    name: String,
+    sink: Sink,
+}
+
+enum Sink {
+    Flat(Handle<AudioSink>),
+    Spatial(Handle<SpatialAudioSink>, Vec3),
+}
+
+/// The sounds that were played most recently.
+#[derive(Resource, Default)]
+struct PlayingSounds(VecDeque<Playing>);
+
+/// The background music.
+#[derive(Resource, Default)]
+struct Music {
+    /// The kind of music being played, such as `game`.
+    kind: Option<String>,
+    /// The time at which the next track starts, in seconds since the start of the app.
+    next: Option<f64>,
+    playing: Option<Handle<AudioSink>>,
+}
+
+impl Music {
+    /// Waits for a random delay of the kind of music before starting the next track.
+    fn schedule(&mut self, sounds: &Sounds, from: f64) {
+        let Some(music) = self.kind.as_deref().and_then(|kind| sounds.music(kind)) else {
+            self.next = None;
+            return;
+        };
+        let (min, max) = music.delay;
+        let delay = if min < max {
+            rand::thread_rng().gen_range(min..max)
+        } else {
+            min
+        };
+        self.next = Some(from + delay as f64);
+    }
+}
+
+/// Reloads the sounds whenever the resource packs change.
+fn sounds_load_system(
+    packs: Res<ResourcePacks>,
+    mut sounds: ResMut<Sounds>,
+    mut sources: ResMut<SoundSources>,
+) {
+    if packs.is_changed() {
+        *sounds = Sounds::load(&packs);
+        sources.0.clear();
+    }
+}
+
+fn level_sound_system(
+    sounds: Res<Sounds>,
+    world: Option<Res<World>>,
+    mut queue: ResMut<SoundQueue>,
+    mut events: EventReader<LevelSoundEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for pk in events.iter() {
+        let block = world
+            .as_ref()
+            .and_then(|world| world.block_map.block(pk.extra_data.0 as u32).ok())
+            .map(|block| block.identifier().to_string());
+        let Some(sound) = sounds.event_sound(&pk.sound, block.as_deref(), &pk.entity_type) else {
+            continue;
+        };
+        let sample = |rng: &mut rand::rngs::ThreadRng, (min, max): (f32, f32)| {
+            if min < max {
+                rng.gen_range(min..max)
+            } else {
+                min
+            }
+        };
+        queue.0.push(QueuedSound {
+            name: sound.sound.clone(),
+            position: (!pk.disable_relative_volume).then_some(pk.position),
+            volume: sample(&mut rng, sound.volume),
+            pitch: sample(&mut rng, sound.pitch),
+        });
+    }
+}
+
+fn play_sound_system(mut queue: ResMut<SoundQueue>, mut events: EventReader<PlaySound>) {
+    for pk in events.iter() {
+        queue.0.push(QueuedSound {
+            name: pk.sound_name.clone(),
+            // The position is sent multiplied by eight.
+            position: Some(pk.position.0.as_vec3() / 8.),
+            volume: pk.volume,
+            pitch: pk.pitch,
+        });
+    }
+}
+
+fn stop_sound_system(
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+    sinks: Res<Assets<AudioSink>>,
+    sounds: Res<Sounds>,
+    time: Res<Time>,
+    mut playing: ResMut<PlayingSounds>,
+    mut music: ResMut<Music>,
+    mut events: EventReader<StopSound>,
+) {
+    for pk in events.iter() {
+        playing.0.retain(|sound| {
+            if !pk.stop_all && sound.name != pk.sound_name {
+                return true;
+            }
+            match &sound.sink {
+                Sink::Flat(sink) => {
+                    if let Some(sink) = sinks.get(sink) {
+                        sink.stop();
+                    }
+                }
+                Sink::Spatial(sink, _) => {
+                    if let Some(sink) = spatial_sinks.get(sink) {
+                        sink.stop();
+                    }
+                }
+            }
+            false
+        });
+        if pk.stop_all {
+            if let Some(sink) = music.playing.take() {
+                if let Some(sink) = sinks.get(&sink) {
+                    sink.stop();
+                }
+                music.schedule(&sounds, time.elapsed_seconds_f64());
+            }
+        }
+    }
+}
+
+/// Starts the music in game, and starts the next track once the previous track is over.
+fn music_system(
+    time: Res<Time>,
+    sounds: Res<Sounds>,
+    mut music: ResMut<Music>,
+    mut queue: ResMut<SoundQueue>,
+    mut start_game: EventReader<StartGame>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if start_game.iter().next().is_some() {
+        music.kind = Some("game".to_string());
+        music.schedule(&sounds, now);
+    }
+    match music.next {
+        Some(next) if now >= next => {}
+        _ => return,
+    }
+    music.next = None;
+    if let Some(definition) = music.kind.as_deref().and_then(|kind| sounds.music(kind)) {
+        queue.0.push(QueuedSound {
+            name: definition.event_name.clone(),
+            position: None,
+            volume: 1.,
+            pitch: 1.,
+        });
+    }
+}
+
+/// Plays the queued sounds. Sounds are quieter the further away they are, and can no longer be
+/// heard once they are further away than the maximum distance of the sound.
+#[allow(clippy::too_many_arguments)]
+fn sound_play_system(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    packs: Res<ResourcePacks>,
+    sounds: Res<Sounds>,
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+    sinks: Res<Assets<AudioSink>>,
+    mut assets: ResMut<Assets<AudioSource>>,
+    mut sources: ResMut<SoundSources>,
+    mut queue: ResMut<SoundQueue>,
+    mut playing: ResMut<PlayingSounds>,
+    mut music: ResMut<Music>,
+    listener: Query<&Transform, With<Camera3d>>,
+) {
+    let Ok(listener) = listener.get_single() else {
+        queue.0.clear();
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    for sound in queue.0.drain(..) {
+        let Some(definition) = sounds.definition(&sound.name) else {
+            debug!("Unknown sound `{}`", sound.name);
+            continue;
+        };
+        let Some(file) = definition.pick(&mut rng) else {
+            continue;
+        };
+        let Some(source) = sources.get(&packs, &mut assets, &file.name) else {
+            continue;
+        };
+
+        let mut volume = sound.volume * file.volume;
+        let position = sound.position.filter(|_| file.is_3d);
+        if let Some(position) = position {
+            let max_distance = definition
+                .max_distance
+                .unwrap_or(16. * sound.volume.max(1.));
+            volume *= 1. - listener.translation.distance(position) / max_distance;
+            if volume <= 0. {
+                continue;
+            }
+        }
+        let settings = PlaybackSettings::ONCE
+            .with_volume(volume)
+            .with_speed(sound.pitch * file.pitch);
+
+        let sink = match position {
+            Some(position) => Sink::Spatial(
+                spatial_sinks.get_handle(audio.play_spatial_with_settings(
+                    source.clone(),
+                    settings,
+                    *listener,
+                    EAR_GAP,
+                    emitter(listener, position),
+                )),
+                position,
+            ),
+            None => {
+                Sink::Flat(sinks.get_handle(audio.play_with_settings(source.clone(), settings)))
+            }
+        };
+
+        if definition.category == "music" && sound.position.is_none() {
+            if let Sink::Flat(sink) = &sink {
+                music.playing = Some(sink.clone());
+            }
+            let duration = assets
+                .get(&source)
+                .and_then(|source| ogg_duration(&source.bytes))
+                .unwrap_or_default();
+            music.schedule(&sounds, time.elapsed_seconds_f64() + duration as f64);
+        }
+        if playing.0.len() >= MAX_TRACKED {
+            playing.0.pop_front();
+        }
+        playing.0.push_back(Playing {
+            name: sound.name,
+            sink,
+        });
+    }
+}
+
+/// Moves the listener of positional sounds that are still playing along with the camera.
+fn listener_system(
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+    playing: Res<PlayingSounds>,
+    listener: Query<&Transform, (With<Camera3d>, Changed<Transform>)>,
+) {
+    let Ok(listener) = listener.get_single() else {
+        return;
+    };
+    for sound in &playing.0 {
+        let Sink::Spatial(sink, position) = &sound.sink else {
+            continue;
+        };
+        if let Some(sink) = spatial_sinks.get(sink) {
+            sink.set_listener_position(*listener, EAR_GAP);
+            sink.set_emitter_position(emitter(listener, *position));
+        }
+    }
+}
+
+/// Returns where to put the emitter of a sound so that it is heard from the direction of its
+/// position. Bevy lowers the volume of sounds by the square of their distance, which makes sounds
+/// a few blocks away nearly silent, so the emitter is kept at a fixed distance from the listener
+/// and the volume is lowered by distance when the sound starts playing instead.
+fn emitter(listener: &Transform, position: Vec3) -> Vec3 {
+    listener.translation + (position - listener.translation).normalize_or_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sounds() -> Sounds {
+        let mut sounds = Sounds::default();
+        sounds.load_definitions(
+            &json::parse(
+                r#"{
+                    "format_version": "1.14.0",
+                    "sound_definitions": {
+                        "dig.stone": {
+                            "category": "block",
+                            "sounds": ["sounds/dig/stone1", {"name": "sounds/dig/stone2", "weight": 0}]
+                        },
+                        "random.explode": {
+                            "category": "weather",
+                            "max_distance": 64.0,
+                            "sounds": [{"name": "sounds/random/explode1", "volume": 0.5, "is3D": true}]
+                        }
+                    }
+                }"#,
+            )
+            .unwrap(),
+        );
+        sounds.load_events(
+            &json::parse(
+                r#"{
+                    "individual_event_sounds": {
+                        "events": {
+                            "explode": {"sound": "random.explode", "volume": 4.0, "pitch": [0.6, 1.0]},
+                            "record.13": {"sound": "record.13"}
+                        }
+                    },
+                    "block_sounds": {
+                        "stone": {"volume": 1.0, "pitch": [0.8, 1.0], "events": {"break": {"sound": "dig.stone"}}}
+                    },
+                    "entity_sounds": {
+                        "defaults": {"events": {"hurt": "game.player.hurt"}},
+                        "entities": {"zombie": {"events": {"hurt": {"sound": "mob.zombie.hurt"}, "step": ""}}}
+                    },
+                    "interactive_sounds": {
+                        "block_sounds": {"stone": {"events": {"step": {"sound": "step.stone"}}}}
+                    }
+                }"#,
+            )
+            .unwrap(),
+        );
+        sounds.load_block_types(
+            &json::parse(r#"{"format_version": [1, 1, 0], "stone": {"sound": "stone"}}"#).unwrap(),
+        );
+        sounds
+    }
+
+    #[test]
+    fn event_names() {
+        assert_eq!(event_name(&SoundEvent::ItemUseOn), "item.use.on");
+        assert_eq!(event_name(&SoundEvent::Record13), "record.13");
+        assert_eq!(
+            event_name(&SoundEvent::ImitateCaveSpider),
+            "imitate.cave_spider"
+        );
+        assert_eq!(event_name(&SoundEvent::Default), "default");
+    }
+
+    #[test]
+    fn event_sounds() {
+        let sounds = sounds();
+        let resolve = |event, block, entity_type| {
+            sounds
+                .event_sound(&event, block, entity_type)
+                .map(|sound| sound.sound.as_str())
+        };
+        assert_eq!(
+            resolve(SoundEvent::Break, Some("minecraft:stone"), ""),
+            Some("dig.stone")
+        );
+        assert_eq!(
+            resolve(SoundEvent::Step, Some("minecraft:stone"), ""),
+            Some("step.stone")
+        );
+        assert_eq!(
+            resolve(SoundEvent::Hurt, None, "minecraft:zombie"),
+            Some("mob.zombie.hurt")
+        );
+        assert_eq!(
+            resolve(SoundEvent::Hurt, None, "minecraft:cow"),
+            Some("game.player.hurt")
+        );
+        assert_eq!(resolve(SoundEvent::Step, None, "minecraft:zombie"), None);
+        assert_eq!(resolve(SoundEvent::Record13, None, ""), Some("record.13"));
+
+        let explode = sounds.event_sound(&SoundEvent::Explode, None, "").unwrap();
+        assert_eq!(explode.volume, (4., 4.));
+        assert_eq!(explode.pitch, (0.6, 1.));
+        let block = sounds
+            .event_sound(&SoundEvent::Break, Some("minecraft:stone"), "")
+            .unwrap();
+        assert_eq!(block.pitch, (0.8, 1.));
+    }
+
+    #[test]
+    fn definitions() {
+        let sounds = sounds();
+        let mut rng = rand::thread_rng();
+        let stone = sounds.definition("dig.stone").unwrap();
+        assert_eq!(stone.category, "block");
+        assert_eq!(stone.pick(&mut rng).unwrap().name, "sounds/dig/stone1");
+
+        let explode = sounds.definition("random.explode").unwrap();
+        assert_eq!(explode.max_distance, Some(64.));
+        assert_eq!(explode.sounds[0].volume, 0.5);
+        assert!(sounds.definition("record.13").is_none());
+    }
+
+    #[test]
+    fn ogg_durations() {
+        let mut file = b"OggS".to_vec();
+        file.extend([0; 24]);
+        file.extend(b"\x01vorbis");
+        file.extend(0u32.to_le_bytes());
+        file.push(2);
+        file.extend(44100u32.to_le_bytes());
+        file.extend(b"OggS\0\x04");
+        file.extend((44100u64 * 3).to_le_bytes());
+        assert_eq!(ogg_duration(&file), Some(3.));
+        assert_eq!(ogg_duration(b"RIFF"), None);
+    }
+}
@@ -0,0 +1,220 @@
+pub mod chat;
+pub mod container;
+pub mod death;
+pub mod disconnect;
+pub mod form;
+pub mod hud;
+pub mod item;
+pub mod lang;
+pub mod overlay;
+pub mod scoreboard;
+pub mod server_list;
+pub mod text;
+
+use bevy::prelude::*;
+use bevy::window::CursorGrabMode;
+
+use crate::input::InputFocus;
+use crate::ui::chat::ChatPlugin;
+use crate::ui::container::ContainerPlugin;
+use crate::ui::death::DeathPlugin;
+use crate::ui::disconnect::DisconnectPlugin;
+use crate::ui::form::FormPlugin;
+use crate::ui::hud::HudPlugin;
+use crate::ui::item::ItemPlugin;
+use crate::ui::lang::LangPlugin;
+use crate::ui::overlay::OverlayPlugin;
+use crate::ui::scoreboard::ScoreboardPlugin;
+use crate::ui::server_list::ServerListPlugin;
+
+/// Adds the user interface that is drawn on top of the world, such as the HUD, the chat and forms.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFont>()
+            .init_resource::<FocusStack>()
+            .add_system(focus_system.in_base_set(CoreSet::PostUpdate))
+            .add_plugin(LangPlugin)
+            .add_plugin(ChatPlugin)
+            .add_plugin(FormPlugin)
+            .add_plugin(ItemPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(ScoreboardPlugin)
+            .add_plugin(OverlayPlugin)
+            .add_plugin(ContainerPlugin)
+            .add_plugin(DeathPlugin)
+            .add_plugin(DisconnectPlugin)
+            .add_plugin(ServerListPlugin);
+    }
+}
+
+/// The size of the text of screens such as forms and the server list, in pixels.
+pub(crate) const FONT_SIZE: f32 = 16.;
+/// The colour of buttons, and of a button when the mouse is over it.
+pub(crate) const BUTTON: Color = Color::rgb(0.32, 0.32, 0.36);
+pub(crate) const BUTTON_HOVERED: Color = Color::rgb(0.45, 0.45, 0.5);
+
+/// The font used for all text in the user interface.
+#[derive(Resource)]
+pub struct UiFont(pub Handle<Font>);
+
+impl FromWorld for UiFont {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load("monocraft.ttf"))
+    }
+}
+
+/// A screen of the user interface that can take input focus away from the game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum FocusOwner {
+    Chat,
+    Container,
+    Death,
+    Disconnect,
+    Form,
+}
+
+/// The screens that hold input focus, in the order in which they took it. Input goes to the game
+/// only once every screen has given focus back, so that closing one screen does not hand control
+/// to the game while another is still shown.
+#[derive(Resource, Default, Debug)]
+pub(crate) struct FocusStack {
+    owners: Vec<FocusOwner>,
+}
+
+impl FocusStack {
+    /// Takes input focus for a screen. Does nothing if the screen already holds it.
+    pub(crate) fn push(&mut self, owner: FocusOwner) {
+        if !self.owners.contains(&owner) {
+            self.owners.push(owner);
+        }
+    }
+
+    /// Gives back the input focus held by a screen, if any.
+    pub(crate) fn pop(&mut self, owner: FocusOwner) {
+        self.owners.retain(|held| *held != owner);
+    }
+
+    /// Checks if no screen holds input focus, so that input goes to the game.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+}
+
+/// Releases the cursor once a screen takes input focus, and gives control back to the game once
+/// the last screen gives it back. The cursor stays released if the last screen was closed with
+/// escape.
+fn focus_system(
+    stack: Res<FocusStack>,
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<InputFocus>,
+    mut taken: Local<bool>,
+    mut windows: Query<&mut Window>,
+) {
+    if !stack.is_changed() {
+        return;
+    }
+    let held = !stack.is_empty();
+    if held && !*taken {
+        *taken = true;
+        *focus = InputFocus::Ui;
+        set_cursor_grab(&mut windows, false);
+    } else if !held && *taken {
+        *taken = false;
+        *focus = InputFocus::Game;
+        set_cursor_grab(&mut windows, !keys.just_pressed(KeyCode::Escape));
+    }
+}
+
+/// Locks or releases the cursor, so that the mouse controls either the camera or the user
+/// interface.
+pub(crate) fn set_cursor_grab(windows: &mut Query<&mut Window>, grab: bool) {
+    if let Some(mut window) = windows.iter_mut().next() {
+        window.cursor.grab_mode = if grab {
+            CursorGrabMode::Locked
+        } else {
+            CursorGrabMode::None
+        };
+        window.cursor.visible = !grab;
+    }
+}
+
+/// Spawns text that may contain formatting codes.
+pub(crate) fn spawn_text(
+    parent: &mut ChildBuilder,
+    content: &str,
+    style: Style,
+    font: &Handle<Font>,
+) {
+    parent.spawn(TextBundle {
+        text: Text::from_sections(text::sections(
+            content,
+            text::Style::default(),
+            font,
+            FONT_SIZE,
+        )),
+        style,
+        ..default()
+    });
+}
+
+/// Spawns a button with a centred label, marked with `marker` so that it can be told apart when it
+/// is pressed. The label wraps at the maximum width of the button.
+pub(crate) fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    marker: impl Component,
+    style: Style,
+    font: &Handle<Font>,
+) {
+    let label_style = Style {
+        max_size: style.max_size,
+        ..default()
+    };
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..style
+                },
+                background_color: BUTTON.into(),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|button| spawn_text(button, label, label_style, font));
+}
+
+/// Highlights the buttons marked with `M` when the mouse is over them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn button_colour_system<M: Component>(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<M>)>,
+) {
+    for (interaction, mut colour) in &mut buttons {
+        *colour = match interaction {
+            Interaction::None => BUTTON,
+            Interaction::Hovered | Interaction::Clicked => BUTTON_HOVERED,
+        }
+        .into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_stack() {
+        let mut stack = FocusStack::default();
+        stack.push(FocusOwner::Chat);
+        stack.push(FocusOwner::Chat);
+        assert!(!stack.is_empty());
+        stack.pop(FocusOwner::Chat);
+        assert!(stack.is_empty());
+        stack.pop(FocusOwner::Chat);
+        assert!(stack.is_empty());
+    }
+}
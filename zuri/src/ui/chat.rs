@@ -0,0 +1,618 @@
+use std::collections::VecDeque;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use uuid::Uuid;
+use zuri_net::command::CommandGraph;
+use zuri_net::proto::packet::available_commands::AvailableCommands;
+use zuri_net::proto::packet::command_output::CommandOutput;
+use zuri_net::proto::packet::command_request::CommandRequest;
+use zuri_net::proto::packet::text::{
+    Text as TextPacket, TextType, TextTypeSimple, TextTypeWithParams, TextTypeWithSource,
+};
+use zuri_net::proto::packet::update_soft_enum::UpdateSoftEnum;
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::command::{CommandOrigin, CommandOriginType};
+
+use crate::client::NetworkSet;
+use crate::ui::lang::Translations;
+use crate::ui::text;
+use crate::ui::{FocusOwner, FocusStack, UiFont};
+
+/// Shows the chat and popups sent by the server, and lets the player send chat messages and
+/// commands.
+///
+/// Chat is opened with `T`, or with `/` to start typing a command. `Enter` sends the line and
+/// `Escape` closes the chat without sending it. Previously sent lines can be recalled with the
+/// arrow keys, and the history can be scrolled with the mouse wheel or the page keys. While typing
+/// a command, the usage of the command is shown above the line, and `Tab` cycles through the
+/// completions of the word being typed.
+pub(super) struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Chat::default())
+            .insert_resource(ServerCommands::default())
+            .add_startup_system(chat_setup_system)
+            .add_systems(
+                (text_receive_system, commands_update_system).in_base_set(NetworkSet::Process),
+            )
+            .add_systems((chat_input_system, chat_render_system, chat_hint_system).chain());
+    }
+}
+
+/// The maximum number of messages kept in the chat history.
+const MAX_HISTORY: usize = 100;
+/// The maximum number of sent lines that can be recalled.
+const MAX_SENT: usize = 50;
+/// The number of messages shown while the chat is closed and while it is open.
+const CLOSED_LINES: usize = 10;
+const OPEN_LINES: usize = 20;
+/// How long messages stay visible after they were received while the chat is closed, in seconds.
+const FADE_TIME: f64 = 10.;
+/// How long popups and tips are shown, in seconds.
+const POPUP_TIME: f64 = 3.;
+/// The size of the chat font and the width of the chat, in pixels.
+const FONT_SIZE: f32 = 16.;
+const CHAT_WIDTH: f32 = 500.;
+/// The background colour behind the chat.
+const BACKGROUND: Color = Color::rgba(0., 0., 0., 0.4);
+/// The maximum number of completions shown above the line being typed.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// The commands that the server allows the player to use. They are used to complete commands typed
+/// in the chat.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ServerCommands(pub CommandGraph);
+
+/// The chat history, along with the line currently being typed.
+#[derive(Resource, Default)]
+pub struct Chat {
+    /// The received messages, from oldest to newest.
+    messages: VecDeque<Message>,
+    /// How many messages the view is scrolled up from the newest message.
+    scroll: usize,
+    /// The line being typed, or None if the chat is closed.
+    input: Option<String>,
+    /// Lines sent by the player, from oldest to newest.
+    sent: VecDeque<String>,
+    /// The index in the sent lines of the line that was recalled into the input.
+    recall: Option<usize>,
+    /// The popup or tip currently shown above the hotbar.
+    popup: Option<Message>,
+    /// The completions being cycled through with `Tab`, along with the offset in the line at
+    /// which they are inserted and the index of the one that was inserted last.
+    completion: Option<(usize, Vec<String>, usize)>,
+}
+
+/// A message shown in the chat, which may contain formatting codes.
+struct Message {
+    text: String,
+    /// The time at which the message was received, in seconds since the start of the app.
+    time: f64,
+}
+
+impl Chat {
+    /// Adds a message to the chat. The message may contain formatting codes.
+    pub fn push(&mut self, time: f64, text: impl Into<String>) {
+        self.messages.push_back(Message {
+            text: text.into(),
+            time,
+        });
+        if self.messages.len() > MAX_HISTORY {
+            self.messages.pop_front();
+        }
+        // Keep the view on the same messages if it is scrolled up.
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+    }
+
+    /// Shows a message above the hotbar for a short while, replacing the previous one.
+    pub fn show_popup(&mut self, time: f64, text: impl Into<String>) {
+        self.popup = Some(Message {
+            text: text.into(),
+            time,
+        });
+    }
+
+    /// Returns true if the player is typing in the chat.
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.messages.len().saturating_sub(OPEN_LINES)
+    }
+}
+
+/// Where a received text message is shown.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Destination {
+    Chat,
+    Popup,
+}
+
+/// Works out the message to show for a received text, translating it where needed.
+fn format_text(text_type: &TextType, translations: &Translations) -> (Destination, String) {
+    let simple = |text: &TextTypeSimple| {
+        if text.needs_translation {
+            translations.translate(&text.message, &[])
+        } else {
+            text.message.clone()
+        }
+    };
+    let with_source = |key: &str, default: &str, text: &TextTypeWithSource| {
+        let message = if text.needs_translation {
+            translations.translate(&text.message, &[])
+        } else {
+            text.message.clone()
+        };
+        if text.source_name.is_empty() {
+            return message;
+        }
+        translations.format(
+            translations.get_or(key, default),
+            &[text.source_name.clone(), message],
+        )
+    };
+    let with_params =
+        |text: &TextTypeWithParams| translations.translate(&text.message, &text.parameters);
+    // Object texts hold a raw text JSON object, as sent by commands such as `/tellraw`.
+    let raw_text = |text: &TextTypeSimple| match json::parse(&text.message) {
+        Ok(raw_text) => translations.resolve_raw_text(&raw_text),
+        Err(_) => text.message.clone(),
+    };
+
+    match text_type {
+        TextType::Raw(text) | TextType::System(text) => (Destination::Chat, simple(text)),
+        TextType::Tip(text) => (Destination::Popup, simple(text)),
+        TextType::Chat(text) => (
+            Destination::Chat,
+            with_source("chat.type.text", "<%s> %s", text),
+        ),
+        TextType::Whisper(text) => (
+            Destination::Chat,
+            with_source(
+                "commands.message.display.incoming",
+                "%s whispers to you: %s",
+                text,
+            ),
+        ),
+        TextType::Announcement(text) => (
+            Destination::Chat,
+            with_source("chat.type.announcement", "[%s] %s", text),
+        ),
+        TextType::Translation(text) => (Destination::Chat, with_params(text)),
+        TextType::Popup(text) | TextType::JukeboxPopup(text) => {
+            (Destination::Popup, with_params(text))
+        }
+        TextType::Object(text)
+        | TextType::ObjectWhisper(text)
+        | TextType::ObjectAnnouncement(text) => (Destination::Chat, raw_text(text)),
+    }
+}
+
+/// Adds the texts and command output sent by the server to the chat.
+fn text_receive_system(
+    time: Res<Time>,
+    translations: Res<Translations>,
+    mut chat: ResMut<Chat>,
+
+    mut texts: EventReader<TextPacket>,
+    mut outputs: EventReader<CommandOutput>,
+) {
+    let now = time.elapsed_seconds_f64();
+    for pk in texts.iter() {
+        match format_text(&pk.text_type, &translations) {
+            (Destination::Chat, text) => chat.push(now, text),
+            (Destination::Popup, text) => chat.show_popup(now, text),
+        }
+    }
+    for pk in outputs.iter() {
+        for message in &pk.output_messages {
+            let text = translations.translate(&message.message, &message.parameters);
+            if message.success {
+                chat.push(now, text);
+            } else {
+                chat.push(now, format!("{}c{}", text::FORMAT_CHAR, text));
+            }
+        }
+    }
+}
+
+/// Replaces the commands when the server sends the commands that are available, and keeps soft
+/// enums up to date.
+fn commands_update_system(
+    mut commands: ResMut<ServerCommands>,
+    mut available: EventReader<AvailableCommands>,
+    mut soft_enums: EventReader<UpdateSoftEnum>,
+) {
+    for pk in available.iter() {
+        match CommandGraph::new(pk) {
+            Ok(graph) => commands.0 = graph,
+            Err(err) => warn!("Received invalid commands: {}", err),
+        }
+    }
+    for pk in soft_enums.iter() {
+        commands.update_soft_enum(pk);
+    }
+}
+
+/// Opens and closes the chat, and handles typing in it.
+fn chat_input_system(
+    keys: Res<Input<KeyCode>>,
+    commands: Res<ServerCommands>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut wheel: EventReader<MouseWheel>,
+    mut chat: ResMut<Chat>,
+    mut focus: ResMut<FocusStack>,
+    mut packets: EventWriter<Packet>,
+) {
+    if !chat.is_open() {
+        let command = keys.just_pressed(KeyCode::Slash);
+        if focus.is_empty() && (command || keys.just_pressed(KeyCode::T)) {
+            chat.input = Some(if command { "/".into() } else { String::new() });
+            // The key that opened the chat should not be typed in it.
+            chars.clear();
+            focus.push(FocusOwner::Chat);
+        }
+        return;
+    }
+
+    let chat = chat.as_mut();
+    let input = chat.input.as_mut().unwrap();
+    for event in chars.iter() {
+        match event.char {
+            // Backspace is received as a character, so that it repeats when held.
+            '\u{8}' => {
+                input.pop();
+            }
+            c if !c.is_control() => input.push(c),
+            _ => continue,
+        }
+        chat.completion = None;
+    }
+
+    if keys.just_pressed(KeyCode::Tab) && input.starts_with('/') {
+        let (start, suggestions, index) = chat.completion.get_or_insert_with(|| {
+            let completion = commands.complete(input);
+            // The first press inserts the first suggestion.
+            (completion.start, completion.suggestions, usize::MAX)
+        });
+        if !suggestions.is_empty() {
+            *index = index.wrapping_add(1) % suggestions.len();
+            input.truncate(*start);
+            input.push_str(&suggestions[*index]);
+        }
+    }
+
+    let recall = if keys.just_pressed(KeyCode::Up) {
+        chat.recall
+            .map(|index| index.saturating_sub(1))
+            .or(chat.sent.len().checked_sub(1))
+    } else if keys.just_pressed(KeyCode::Down) {
+        chat.recall
+            .map(|index| index + 1)
+            .filter(|index| *index < chat.sent.len())
+    } else {
+        chat.recall
+    };
+    if recall != chat.recall {
+        *input = recall
+            .map(|index| chat.sent[index].clone())
+            .unwrap_or_default();
+        chat.recall = recall;
+        chat.completion = None;
+    }
+
+    let mut scroll = wheel.iter().map(|event| event.y).sum::<f32>() as isize;
+    if keys.just_pressed(KeyCode::PageUp) {
+        scroll += OPEN_LINES as isize / 2;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        scroll -= OPEN_LINES as isize / 2;
+    }
+    if scroll != 0 {
+        chat.scroll = chat
+            .scroll
+            .saturating_add_signed(scroll)
+            .min(chat.max_scroll());
+    }
+
+    let send = keys.just_pressed(KeyCode::Return);
+    if send || keys.just_pressed(KeyCode::Escape) {
+        let line = chat.input.take().unwrap();
+        if send && !line.trim().is_empty() {
+            chat.sent.push_back(line.clone());
+            if chat.sent.len() > MAX_SENT {
+                chat.sent.pop_front();
+            }
+            packets.send(line_packet(line));
+        }
+        chat.recall = None;
+        chat.completion = None;
+        chat.scroll = 0;
+        focus.pop(FocusOwner::Chat);
+    }
+}
+
+/// Creates the packet that sends a line typed in the chat. Lines starting with `/` are sent as
+/// commands, and other lines as chat messages.
+fn line_packet(line: String) -> Packet {
+    if line.starts_with('/') {
+        return Packet::CommandRequest(CommandRequest {
+            command_line: line,
+            command_origin: CommandOrigin {
+                origin: CommandOriginType::Player,
+                uuid: Uuid::new_v4(),
+                request_id: String::new(),
+                player_unique_id: 0,
+            },
+            internal: false,
+            version: 0,
+        });
+    }
+    Packet::Text(TextPacket {
+        text_type: TextType::Chat(TextTypeWithSource {
+            needs_translation: false,
+            source_name: String::new(),
+            message: line,
+        }),
+        xuid: String::new(),
+        platform_chat_id: String::new(),
+    })
+}
+
+/// Marks the text that shows the chat messages.
+#[derive(Component)]
+struct ChatMessages;
+
+/// Marks the text that shows the line being typed.
+#[derive(Component)]
+struct ChatInput;
+
+/// Marks the text that shows the usage and completions of the command being typed.
+#[derive(Component)]
+struct ChatHint;
+
+/// Marks the text that shows popups and tips.
+#[derive(Component)]
+struct ChatPopup;
+
+/// Spawns the user interface of the chat. The messages are shown in the bottom left corner, with
+/// the line being typed below them. Popups are shown at the bottom in the middle.
+fn chat_setup_system(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(4.),
+                    bottom: Val::Px(4.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        max_size: Size::width(Val::Px(CHAT_WIDTH)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ChatMessages,
+            ));
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        max_size: Size::width(Val::Px(CHAT_WIDTH)),
+                        margin: UiRect::top(Val::Px(2.)),
+                        ..default()
+                    },
+                    background_color: BACKGROUND.into(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ChatHint,
+            ));
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        min_size: Size::width(Val::Px(CHAT_WIDTH)),
+                        max_size: Size::width(Val::Px(CHAT_WIDTH)),
+                        margin: UiRect::top(Val::Px(2.)),
+                        ..default()
+                    },
+                    background_color: BACKGROUND.into(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ChatInput,
+            ));
+        });
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(80.),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((TextBundle::default(), ChatPopup));
+        });
+}
+
+/// Updates the chat user interface. Messages fade out after a while when the chat is closed, and
+/// all recent messages are shown while it is open.
+#[allow(clippy::type_complexity)]
+fn chat_render_system(
+    time: Res<Time>,
+    font: Res<UiFont>,
+    chat: Res<Chat>,
+    mut shown: Local<Option<(usize, usize, bool)>>,
+    mut messages: Query<
+        (&mut Text, &mut BackgroundColor),
+        (With<ChatMessages>, Without<ChatInput>, Without<ChatPopup>),
+    >,
+    mut input: Query<
+        (&mut Text, &mut Visibility),
+        (With<ChatInput>, Without<ChatMessages>, Without<ChatPopup>),
+    >,
+    mut popup: Query<&mut Text, (With<ChatPopup>, Without<ChatMessages>, Without<ChatInput>)>,
+) {
+    let now = time.elapsed_seconds_f64();
+    let newest = chat.messages.len().saturating_sub(chat.scroll);
+    let count = if chat.is_open() {
+        newest.min(OPEN_LINES)
+    } else {
+        chat.messages
+            .iter()
+            .rev()
+            .take(CLOSED_LINES)
+            .take_while(|message| now - message.time < FADE_TIME)
+            .count()
+    };
+    let popup_shown = chat
+        .popup
+        .as_ref()
+        .filter(|popup| now - popup.time < POPUP_TIME);
+
+    // Only rebuild the text when something changed, as laying out text is expensive.
+    let key = (newest - count, count, popup_shown.is_some());
+    if !chat.is_changed() && !font.is_changed() && *shown == Some(key) {
+        return;
+    }
+    *shown = Some(key);
+
+    let base = text::Style::default();
+    if let Ok((mut text, mut background)) = messages.get_single_mut() {
+        let mut sections = Vec::new();
+        for (i, message) in chat.messages.range(newest - count..newest).enumerate() {
+            if i > 0 {
+                sections.push(TextSection::new("\n", TextStyle::default()));
+            }
+            sections.extend(text::sections(&message.text, base, &font.0, FONT_SIZE));
+        }
+        text.sections = sections;
+        *background = if count > 0 {
+            BACKGROUND.into()
+        } else {
+            Color::NONE.into()
+        };
+    }
+    if let Ok((mut text, mut visibility)) = input.get_single_mut() {
+        *visibility = if chat.is_open() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        text.sections = vec![TextSection::new(
+            format!("{}_", chat.input.as_deref().unwrap_or_default()),
+            TextStyle {
+                font: font.0.clone(),
+                font_size: FONT_SIZE,
+                color: Color::WHITE,
+            },
+        )];
+    }
+    if let Ok(mut text) = popup.get_single_mut() {
+        text.sections = popup_shown
+            .map(|popup| text::sections(&popup.text, base, &font.0, FONT_SIZE))
+            .unwrap_or_default();
+    }
+}
+
+/// Shows the usage of the parameter being typed and the completions of the word being typed while
+/// a command is typed in the chat.
+fn chat_hint_system(
+    font: Res<UiFont>,
+    chat: Res<Chat>,
+    commands: Res<ServerCommands>,
+    mut hint: Query<(&mut Text, &mut Visibility), With<ChatHint>>,
+) {
+    if !chat.is_changed() && !commands.is_changed() && !font.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = hint.get_single_mut() else {
+        return;
+    };
+    let Some(line) = chat.input.as_deref().filter(|line| line.starts_with('/')) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let completion = commands.complete(line);
+    let mut lines: Vec<_> = completion.hints;
+    if !completion.suggestions.is_empty() {
+        let mut suggestions =
+            completion.suggestions[..completion.suggestions.len().min(MAX_SUGGESTIONS)].join("  ");
+        if completion.suggestions.len() > MAX_SUGGESTIONS {
+            suggestions.push_str("  ...");
+        }
+        lines.push(format!("{}e{}", text::FORMAT_CHAR, suggestions));
+    }
+    if lines.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+    let base = text::Style {
+        colour: Color::GRAY,
+        ..default()
+    };
+    text.sections = text::sections(&lines.join("\n"), base, &font.0, FONT_SIZE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let mut translations = Translations::default();
+        translations.parse("chat.type.text=<%s> %s\nrecord.nowPlaying=Now playing: %s");
+
+        let chat = TextType::Chat(TextTypeWithSource {
+            needs_translation: false,
+            source_name: "Steve".into(),
+            message: "Hello".into(),
+        });
+        assert_eq!(
+            format_text(&chat, &translations),
+            (Destination::Chat, "<Steve> Hello".into())
+        );
+
+        let jukebox = TextType::JukeboxPopup(TextTypeWithParams {
+            needs_translation: true,
+            message: "%record.nowPlaying".into(),
+            parameters: vec!["C418 - cat".into()],
+        });
+        assert_eq!(
+            format_text(&jukebox, &translations),
+            (Destination::Popup, "Now playing: C418 - cat".into())
+        );
+
+        let object = TextType::Object(TextTypeSimple {
+            needs_translation: false,
+            message: r#"{"rawtext":[{"text":"Hi"}]}"#.into(),
+        });
+        assert_eq!(
+            format_text(&object, &translations),
+            (Destination::Chat, "Hi".into())
+        );
+    }
+}
@@ -0,0 +1,1126 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use zuri_net::inventory::{is_air, stackable, Inventory, Slot, StackRequest, StackRequestError};
+use zuri_net::proto::packet::container_close::ContainerClose;
+use zuri_net::proto::packet::container_open::ContainerOpen;
+use zuri_net::proto::packet::container_set_data::ContainerSetData;
+use zuri_net::proto::packet::interact::{Interact, InteractionAction, OpenInventory};
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::container::{
+    Container, ContainerDataFurnace, ContainerDataKey, ContainerType,
+};
+use zuri_net::proto::types::inventory::Window;
+use zuri_net::proto::types::item::ItemInstance;
+use zuri_net::proto::types::item_stack::ItemStackRequestEntry;
+use zuri_net::recipe::{CraftingRecipe, RecipeBook};
+use zuri_world::item::ItemMap;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::entity::IdentifiableEntity;
+use crate::inventory::{send_request, PlayerInventory, Recipes};
+use crate::player;
+use crate::resource_pack::ResourcePacks;
+use crate::ui::chat::Chat;
+use crate::ui::form::Forms;
+use crate::ui::item::{tooltip, ItemIcons};
+use crate::ui::lang::Translations;
+use crate::ui::text;
+use crate::ui::{FocusOwner, FocusStack, UiFont};
+
+/// Shows the containers opened by the server, such as chests and furnaces, together with the
+/// inventory of the player.
+///
+/// `E` opens the inventory of the player, and `E` or `Escape` closes the container that is open.
+/// Clicking a slot picks up its items or puts down the items held by the cursor, right clicking
+/// picks up half of them or puts down a single item, and shift clicking moves them between the
+/// container and the inventory. Items can also be dragged from one slot to another, and taking the
+/// result of a crafting grid crafts the recipe matching the grid. All changes are sent to the
+/// server as item stack requests.
+pub(super) struct ContainerPlugin;
+
+impl Plugin for ContainerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ContainerScreen::default())
+            .add_system(container_receive_system.in_base_set(NetworkSet::Process))
+            .add_systems(
+                (
+                    container_input_system,
+                    container_focus_system,
+                    container_setup_system,
+                    container_render_system,
+                )
+                    .chain(),
+            )
+            .add_system(reset_resource::<ContainerScreen>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// The size of slots, in pixels.
+const SLOT_SIZE: f32 = 40.;
+/// The size of the font used for titles, item counts and tooltips, in pixels.
+const FONT_SIZE: f32 = 14.;
+/// The number of ticks it takes to smelt an item in a furnace, and in a blast furnace or smoker.
+const COOK_TIME: i32 = 200;
+const FAST_COOK_TIME: i32 = 100;
+/// The colour that dims the world behind a container, and the colour of the panel itself.
+const OVERLAY: Color = Color::rgba(0., 0., 0., 0.5);
+const PANEL: Color = Color::rgb(0.18, 0.18, 0.2);
+/// The colour of the frame of slots, and of the frame of the slot that the mouse is over.
+const SLOT_FRAME: Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+const HOVERED_FRAME: Color = Color::WHITE;
+const SLOT_BACKGROUND: Color = Color::rgba(0.3, 0.3, 0.3, 0.7);
+/// The colours of progress bars: the background, the cooking progress and the remaining fuel.
+const PROGRESS_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.7);
+const COOK_COLOUR: Color = Color::WHITE;
+const FUEL_COLOUR: Color = Color::rgb(1., 0.55, 0.1);
+const TOOLTIP_BACKGROUND: Color = Color::rgba(0.1, 0., 0.15, 0.9);
+
+/// The container that is shown, if any.
+#[derive(Resource, Default)]
+pub struct ContainerScreen {
+    open: Option<OpenScreen>,
+}
+
+/// A container opened by the server, along with the properties it has set for it.
+pub struct OpenScreen {
+    pub window: Window,
+    pub container_type: ContainerType,
+    /// The properties set with ContainerSetData, such as the progress of a furnace.
+    data: HashMap<i32, i32>,
+}
+
+impl ContainerScreen {
+    /// Returns true if a container is shown.
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Returns the container that is shown, if any.
+    pub fn current(&self) -> Option<&OpenScreen> {
+        self.open.as_ref()
+    }
+}
+
+impl OpenScreen {
+    /// Returns a property of the container, or zero if the server has not set it.
+    pub fn data(&self, key: impl Into<ContainerDataKey>) -> i32 {
+        self.data.get(&key.into().0).copied().unwrap_or_default()
+    }
+
+    /// Returns how far a progress bar is filled, from 0 to 1.
+    fn progress(&self, bar: Bar) -> f32 {
+        let (done, total) = match bar {
+            Bar::Cook => (
+                self.data(ContainerDataFurnace::TickCount),
+                match self.container_type {
+                    ContainerType::BlastFurnace | ContainerType::Smoker => FAST_COOK_TIME,
+                    _ => COOK_TIME,
+                },
+            ),
+            Bar::Fuel => (
+                self.data(ContainerDataFurnace::LitTime),
+                self.data(ContainerDataFurnace::LitDuration),
+            ),
+        };
+        if total <= 0 {
+            return 0.;
+        }
+        (done as f32 / total as f32).clamp(0., 1.)
+    }
+}
+
+/// Something shown in the grid of a container.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Cell {
+    /// A slot that items can be put in and taken out of.
+    Slot(Slot),
+    /// A slot that items can only be taken out of, such as the result of a furnace.
+    Output(Slot),
+    /// The result of the recipe matching the crafting grid, which is crafted when it is taken.
+    Crafted,
+    Progress(Bar),
+    Space,
+}
+
+/// The progress bars of a furnace.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Bar {
+    /// How far the item being smelted is done.
+    Cook,
+    /// How much of the fuel being burnt is left.
+    Fuel,
+}
+
+/// The ways in which a slot can be clicked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Click {
+    Left,
+    Right,
+    /// Left clicking while holding shift, which moves the items to another part of the screen.
+    Shift,
+}
+
+/// Marks a slot that can be clicked.
+#[derive(Component)]
+struct SlotButton(Cell);
+
+/// The parts of the screen that change while it is shown.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+enum ScreenNode {
+    Icon(Cell),
+    Count(Cell),
+    Progress(Bar),
+    /// Follows the mouse around with the items held by the cursor.
+    Held,
+    Tooltip,
+    TooltipText,
+}
+
+/// Marks the root of the user interface of the container being shown.
+#[derive(Component)]
+struct ContainerRoot;
+
+/// Returns the rows of the part of the screen that belongs to a type of container. The size is
+/// the number of slots in the window of the container, or zero if it is not known yet.
+///
+/// The anvil has no output slot, since the client has no way of knowing what it produces.
+fn layout(container_type: ContainerType, size: usize) -> Vec<Vec<Cell>> {
+    let crafting = |index| Cell::Slot(Slot::new(Container::CraftingInput, index));
+    match container_type {
+        ContainerType::Inventory => (0..4)
+            .map(|i| {
+                let mut row = vec![Cell::Slot(Slot::armour(i)), Cell::Space];
+                match i {
+                    0 => row.extend([crafting(28), crafting(29), Cell::Space, Cell::Crafted]),
+                    1 => row.extend([crafting(30), crafting(31)]),
+                    3 => row.push(Cell::Slot(Slot::offhand())),
+                    _ => {}
+                }
+                row
+            })
+            .collect(),
+        ContainerType::Workbench => (0..3)
+            .map(|row| {
+                let mut cells: Vec<_> = (0..3)
+                    .map(|column| crafting(32 + row * 3 + column))
+                    .collect();
+                if row == 1 {
+                    cells.extend([Cell::Space, Cell::Crafted]);
+                }
+                cells
+            })
+            .collect(),
+        ContainerType::Furnace | ContainerType::BlastFurnace | ContainerType::Smoker => {
+            let ingredient = match container_type {
+                ContainerType::BlastFurnace => Container::BlastFurnaceIngredient,
+                ContainerType::Smoker => Container::SmokerIngredient,
+                _ => Container::FurnaceIngredient,
+            };
+            vec![
+                vec![Cell::Slot(Slot::new(ingredient, 0))],
+                vec![
+                    Cell::Progress(Bar::Fuel),
+                    Cell::Progress(Bar::Cook),
+                    Cell::Output(Slot::new(Container::FurnaceResult, 2)),
+                ],
+                vec![Cell::Slot(Slot::new(Container::FurnaceFuel, 1))],
+            ]
+        }
+        ContainerType::Anvil => vec![vec![
+            Cell::Slot(Slot::new(Container::AnvilInput, 1)),
+            Cell::Space,
+            Cell::Slot(Slot::new(Container::AnvilMaterial, 2)),
+        ]],
+        ContainerType::Container
+        | ContainerType::CartChest
+        | ContainerType::ChestBoat
+        | ContainerType::Hopper
+        | ContainerType::CartHopper
+        | ContainerType::Dispenser
+        | ContainerType::Dropper => {
+            let (columns, default_size) = match container_type {
+                ContainerType::Hopper | ContainerType::CartHopper => (5, 5),
+                ContainerType::Dispenser | ContainerType::Dropper => (3, 9),
+                _ => (9, 27),
+            };
+            let size = if size > 0 { size } else { default_size };
+            // Barrels and shulker boxes use their own containers in stack requests, but they are
+            // opened the same way as chests, so there is no way of telling them apart here.
+            (0..size)
+                .step_by(columns)
+                .map(|start| {
+                    (start..size.min(start + columns))
+                        .map(|i| Cell::Slot(Slot::new(Container::LevelEntity, i as u8)))
+                        .collect()
+                })
+                .collect()
+        }
+        // Other containers only show the inventory of the player.
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the rows of the inventory of the player, which are shown below every container: the
+/// main inventory followed by the hotbar.
+fn inventory_layout() -> Vec<Vec<Cell>> {
+    let mut rows: Vec<Vec<Cell>> = (0..3)
+        .map(|row| {
+            (0..9)
+                .map(|column| Cell::Slot(Slot::inventory(9 + row * 9 + column)))
+                .collect()
+        })
+        .collect();
+    rows.push((0..9).map(|i| Cell::Slot(Slot::hotbar(i))).collect());
+    rows
+}
+
+/// Returns the translation key of the title of a container, along with the title used when the
+/// key is not translated.
+fn title(container_type: ContainerType) -> (&'static str, &'static str) {
+    match container_type {
+        ContainerType::Inventory | ContainerType::Workbench => ("container.crafting", "Crafting"),
+        ContainerType::Furnace => ("container.furnace", "Furnace"),
+        ContainerType::BlastFurnace => ("container.blast_furnace", "Blast Furnace"),
+        ContainerType::Smoker => ("container.smoker", "Smoker"),
+        ContainerType::Anvil => ("container.repair", "Repair & Name"),
+        ContainerType::Hopper | ContainerType::CartHopper => ("container.hopper", "Item Hopper"),
+        ContainerType::Dispenser => ("container.dispenser", "Dispenser"),
+        ContainerType::Dropper => ("container.dropper", "Dropper"),
+        _ => ("container.chest", "Chest"),
+    }
+}
+
+/// Returns the slots of the crafting grid of a type of container, row by row, along with the width
+/// of the grid.
+fn crafting_grid(container_type: ContainerType) -> Option<(Vec<Slot>, usize)> {
+    let (first, size) = match container_type {
+        ContainerType::Inventory => (28, 2),
+        ContainerType::Workbench => (32, 3),
+        _ => return None,
+    };
+    let slots = (0..size * size)
+        .map(|i| Slot::new(Container::CraftingInput, first + i as u8))
+        .collect();
+    Some((slots, size))
+}
+
+/// Returns the recipe matching the items in the crafting grid of a type of container, if any.
+fn crafted_recipe<'a>(
+    inventory: &Inventory,
+    recipes: &'a RecipeBook,
+    container_type: ContainerType,
+) -> Option<&'a CraftingRecipe> {
+    let (slots, size) = crafting_grid(container_type)?;
+    let grid: Vec<_> = slots
+        .iter()
+        .map(|slot| inventory.item(*slot).unwrap_or_default().stack)
+        .collect();
+    recipes.find(&grid, size, "crafting_table")
+}
+
+/// Returns the slots that shift clicking a slot moves its items to, in the order they are filled.
+/// The layout is that of the container, without the inventory of the player.
+fn quick_move_targets(
+    container_type: ContainerType,
+    layout: &[Vec<Cell>],
+    slot: Slot,
+) -> Vec<Slot> {
+    let hotbar = (0..9).map(Slot::hotbar);
+    let inventory = (9..36).map(Slot::inventory);
+    match slot.container {
+        Container::HotBar | Container::Inventory => {
+            // Crafting grids and armour slots are never filled by shift clicking, so the items
+            // move between the hotbar and the rest of the inventory instead.
+            if !matches!(
+                container_type,
+                ContainerType::Inventory | ContainerType::Workbench | ContainerType::Anvil
+            ) {
+                let storage: Vec<_> = layout
+                    .iter()
+                    .flatten()
+                    .filter_map(|cell| match cell {
+                        Cell::Slot(slot) => Some(*slot),
+                        _ => None,
+                    })
+                    .collect();
+                if !storage.is_empty() {
+                    return storage;
+                }
+            }
+            if slot.container == Container::HotBar {
+                inventory.collect()
+            } else {
+                hotbar.collect()
+            }
+        }
+        _ => inventory.chain(hotbar).collect(),
+    }
+}
+
+/// Builds the item stack request for a click on a cell, or returns None if the click does nothing.
+fn click(
+    inventory: &mut Inventory,
+    items: &ItemMap,
+    cell: Cell,
+    click: Click,
+    targets: &[Slot],
+) -> Result<Option<ItemStackRequestEntry>, StackRequestError> {
+    let (slot, output) = match cell {
+        Cell::Slot(slot) => (slot, false),
+        Cell::Output(slot) => (slot, true),
+        _ => return Ok(None),
+    };
+    let request = inventory.request();
+    let cursor = request.item(Slot::cursor())?;
+    let item = request.item(slot)?;
+    let held = if is_air(&cursor) {
+        0
+    } else {
+        cursor.stack.count
+    };
+
+    let request = match click {
+        Click::Shift => {
+            if is_air(&item) {
+                return Ok(None);
+            }
+            match quick_move(request, items, slot, &item, targets)? {
+                Some(request) => request,
+                None => return Ok(None),
+            }
+        }
+        // Items are picked up from outputs no matter how they are clicked.
+        _ if output || held == 0 => {
+            if is_air(&item) || (held > 0 && !stackable(&cursor, &item)) {
+                return Ok(None);
+            }
+            let count = match click {
+                Click::Right if !output => item.stack.count.div_ceil(2),
+                _ => item.stack.count,
+            };
+            let max = items.max_stack_size(item.stack.network_id);
+            let count = count.min(max.saturating_sub(held));
+            if count == 0 {
+                return Ok(None);
+            }
+            request.take(count as u8, slot, Slot::cursor())?
+        }
+        Click::Right => {
+            let max = items.max_stack_size(cursor.stack.network_id);
+            if !is_air(&item) && (!stackable(&cursor, &item) || item.stack.count >= max) {
+                return Ok(None);
+            }
+            request.place(1, Slot::cursor(), slot)?
+        }
+        Click::Left => {
+            if is_air(&item) || stackable(&cursor, &item) {
+                let max = items.max_stack_size(cursor.stack.network_id);
+                let space = if is_air(&item) {
+                    max
+                } else {
+                    max.saturating_sub(item.stack.count)
+                };
+                let count = held.min(space);
+                if count == 0 {
+                    return Ok(None);
+                }
+                request.place(count as u8, Slot::cursor(), slot)?
+            } else {
+                request.swap(Slot::cursor(), slot)?
+            }
+        }
+    };
+    Ok(Some(request.finish()))
+}
+
+/// Builds the item stack request for crafting the recipe matching the crafting grid, which uses up
+/// one item in every slot of the grid.
+fn craft(
+    inventory: &mut Inventory,
+    items: &ItemMap,
+    recipes: &RecipeBook,
+    container_type: ContainerType,
+    click: Click,
+    targets: &[Slot],
+) -> Result<Option<ItemStackRequestEntry>, StackRequestError> {
+    let Some(recipe) = crafted_recipe(inventory, recipes, container_type) else {
+        return Ok(None);
+    };
+    let (Some(output), Some((slots, _))) = (recipe.output.first(), crafting_grid(container_type))
+    else {
+        return Ok(None);
+    };
+    let crafted = ItemInstance {
+        stack: output.clone(),
+        ..Default::default()
+    };
+
+    let mut request = inventory.request();
+    let cursor = request.item(Slot::cursor())?;
+    if click != Click::Shift
+        && !is_air(&cursor)
+        && (!stackable(&cursor, &crafted)
+            || cursor.stack.count + output.count > items.max_stack_size(output.network_id))
+    {
+        return Ok(None);
+    }
+    request = request.craft_recipe(recipe.network_id, output.clone())?;
+    for slot in slots {
+        if !is_air(&request.item(slot)?) {
+            request = request.consume(1, slot)?;
+        }
+    }
+    let request = match click {
+        Click::Shift => {
+            match quick_move(request, items, Slot::created_output(), &crafted, targets)? {
+                Some(request) => request,
+                None => return Ok(None),
+            }
+        }
+        _ => request.take(output.count as u8, Slot::created_output(), Slot::cursor())?,
+    };
+    Ok(Some(request.finish()))
+}
+
+/// Moves the items in a slot to the targets, filling up stacks of the same item before empty
+/// slots. Returns None if none of the items fit anywhere.
+fn quick_move<'a>(
+    mut request: StackRequest<'a>,
+    items: &ItemMap,
+    slot: Slot,
+    item: &ItemInstance,
+    targets: &[Slot],
+) -> Result<Option<StackRequest<'a>>, StackRequestError> {
+    let max = items.max_stack_size(item.stack.network_id);
+    let mut left = item.stack.count;
+    for fill_empty in [false, true] {
+        for &target in targets {
+            if left == 0 {
+                break;
+            }
+            let existing = request.item(target)?;
+            let space = match (is_air(&existing), fill_empty) {
+                (true, true) => max,
+                (false, false) if stackable(item, &existing) => {
+                    max.saturating_sub(existing.stack.count)
+                }
+                _ => 0,
+            };
+            let count = left.min(space);
+            if count > 0 {
+                request = request.place(count as u8, slot, target)?;
+                left -= count;
+            }
+        }
+    }
+    Ok((left < item.stack.count).then_some(request))
+}
+
+/// Returns the cell that the mouse is over, if any. While a button is held, the cell it was
+/// pressed on stays clicked, so cells that are only hovered over take precedence.
+fn hovered_cell(buttons: &Query<(&Interaction, &SlotButton)>) -> Option<Cell> {
+    let mut clicked = None;
+    for (interaction, button) in buttons {
+        match interaction {
+            Interaction::Hovered => return Some(button.0),
+            Interaction::Clicked => clicked = Some(button.0),
+            Interaction::None => {}
+        }
+    }
+    clicked
+}
+
+/// Keeps track of the container opened by the server and the properties it sets for it.
+fn container_receive_system(
+    mut screen: ResMut<ContainerScreen>,
+    mut opens: EventReader<ContainerOpen>,
+    mut data: EventReader<ContainerSetData>,
+    mut closes: EventReader<ContainerClose>,
+) {
+    for pk in opens.iter() {
+        screen.open = Some(OpenScreen {
+            window: pk.window,
+            container_type: pk.container_type,
+            data: HashMap::new(),
+        });
+    }
+    for pk in data.iter() {
+        if let Some(open) = screen.open.as_mut().filter(|open| open.window == pk.window) {
+            open.data.insert(pk.key.0, pk.value.0);
+        }
+    }
+    for pk in closes.iter() {
+        if matches!(&screen.open, Some(open) if open.window == pk.window) {
+            screen.open = None;
+        }
+    }
+}
+
+/// Opens the inventory, closes the container that is shown and handles clicks on its slots.
+#[allow(clippy::too_many_arguments)]
+fn container_input_system(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    focus: Res<FocusStack>,
+    chat: Res<Chat>,
+    forms: Res<Forms>,
+    mut screen: ResMut<ContainerScreen>,
+    mut inventory: ResMut<PlayerInventory>,
+    recipes: Res<Recipes>,
+    items: Res<ItemMap>,
+    mut packets: EventWriter<Packet>,
+    mut dragged: Local<Option<Cell>>,
+    buttons: Query<(&Interaction, &SlotButton)>,
+    player: Query<&IdentifiableEntity, With<player::Local>>,
+) {
+    if chat.is_open() || forms.current().is_some() {
+        return;
+    }
+    let Some(open) = &screen.open else {
+        *dragged = None;
+        if focus.is_empty() && keys.just_pressed(KeyCode::E) {
+            // The server answers by opening the inventory as a container.
+            if let Ok(player) = player.get_single() {
+                packets.send(Packet::Interact(Interact {
+                    action_type: InteractionAction::OpenInventory(OpenInventory {
+                        target_entity_runtime_id: player.runtime_id().into(),
+                    }),
+                }));
+            }
+        }
+        return;
+    };
+    if keys.any_just_pressed([KeyCode::E, KeyCode::Escape]) {
+        packets.send(Packet::ContainerClose(ContainerClose {
+            window: open.window,
+            server_side: false,
+        }));
+        screen.open = None;
+        return;
+    }
+
+    let hovered = hovered_cell(&buttons);
+    let mut clicks = Vec::new();
+    if mouse.just_pressed(MouseButton::Left) {
+        if let Some(cell) = hovered {
+            if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+                clicks.push((cell, Click::Shift));
+            } else {
+                // Pressing on a slot with an empty cursor picks up its items, which are put down
+                // again if the button is released over another slot.
+                if is_air(&inventory.item(Slot::cursor()).unwrap_or_default()) {
+                    *dragged = Some(cell);
+                }
+                clicks.push((cell, Click::Left));
+            }
+        }
+    }
+    if mouse.just_pressed(MouseButton::Right) {
+        if let Some(cell) = hovered {
+            clicks.push((cell, Click::Right));
+        }
+    }
+    if mouse.just_released(MouseButton::Left) {
+        if let Some(start) = dragged.take() {
+            if let Some(cell) = hovered.filter(|cell| *cell != start) {
+                clicks.push((cell, Click::Left));
+            }
+        }
+    }
+    if clicks.is_empty() {
+        return;
+    }
+
+    let container_type = open.container_type;
+    let rows = layout(container_type, inventory.items(open.window).len());
+    for (cell, kind) in clicks {
+        let result = match cell {
+            Cell::Slot(slot) | Cell::Output(slot) => {
+                let targets = quick_move_targets(container_type, &rows, slot);
+                click(&mut inventory, &items, cell, kind, &targets)
+            }
+            Cell::Crafted => {
+                let targets = quick_move_targets(container_type, &rows, Slot::created_output());
+                craft(
+                    &mut inventory,
+                    &items,
+                    &recipes,
+                    container_type,
+                    kind,
+                    &targets,
+                )
+            }
+            _ => continue,
+        };
+        match result {
+            Ok(Some(request)) => send_request(&mut packets, request),
+            Ok(None) => {}
+            Err(err) => warn!("Could not move items: {}", err),
+        }
+    }
+}
+
+/// Takes input focus while a container is shown, and gives it back once the container is closed.
+fn container_focus_system(screen: Res<ContainerScreen>, mut focus: ResMut<FocusStack>) {
+    if !screen.is_changed() {
+        return;
+    }
+    if screen.is_open() {
+        focus.push(FocusOwner::Container);
+    } else {
+        focus.pop(FocusOwner::Container);
+    }
+}
+
+/// Rebuilds the user interface whenever another container is opened, or when the number of slots
+/// in the container becomes known.
+#[allow(clippy::type_complexity)]
+fn container_setup_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    translations: Res<Translations>,
+    screen: Res<ContainerScreen>,
+    inventory: Res<PlayerInventory>,
+    mut shown: Local<Option<(Window, ContainerType, usize)>>,
+    roots: Query<Entity, With<ContainerRoot>>,
+) {
+    let current = screen.open.as_ref().map(|open| {
+        let size = inventory.items(open.window).len();
+        (open.window, open.container_type, size)
+    });
+    if current == *shown && !font.is_changed() && !translations.is_changed() {
+        return;
+    }
+    *shown = current;
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some((_, container_type, size)) = current else {
+        return;
+    };
+
+    let font = &font.0;
+    let (key, fallback) = title(container_type);
+    let container_title = translations.get(key).unwrap_or(fallback);
+    let inventory_title = translations
+        .get("container.inventory")
+        .unwrap_or("Inventory");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::all(Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: OVERLAY.into(),
+                ..default()
+            },
+            ContainerRoot,
+        ))
+        .with_children(|root| {
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: PANEL.into(),
+                ..default()
+            })
+            .with_children(|panel| {
+                spawn_title(panel, container_title, font);
+                for row in layout(container_type, size) {
+                    spawn_row(panel, &row, font);
+                }
+                spawn_title(panel, inventory_title, font);
+                let rows = inventory_layout();
+                let (hotbar, main) = rows.split_last().unwrap();
+                for row in main {
+                    spawn_row(panel, row, font);
+                }
+                panel.spawn(NodeBundle {
+                    style: Style {
+                        size: Size::height(Val::Px(6.)),
+                        ..default()
+                    },
+                    ..default()
+                });
+                spawn_row(panel, hotbar, font);
+            });
+
+            root.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::all(Val::Px(SLOT_SIZE)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ScreenNode::Held,
+            ))
+            .with_children(|held| spawn_item(held, Cell::Slot(Slot::cursor()), font));
+
+            root.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        padding: UiRect::all(Val::Px(4.)),
+                        ..default()
+                    },
+                    background_color: TOOLTIP_BACKGROUND.into(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ScreenNode::Tooltip,
+            ))
+            .with_children(|tooltip| {
+                tooltip.spawn((TextBundle::default(), ScreenNode::TooltipText));
+            });
+        });
+}
+
+/// Spawns the title above a part of the screen.
+fn spawn_title(panel: &mut ChildBuilder, title: &str, font: &Handle<Font>) {
+    panel.spawn(
+        TextBundle::from_sections(text::sections(
+            title,
+            text::Style::default(),
+            font,
+            FONT_SIZE,
+        ))
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(4.)),
+            ..default()
+        }),
+    );
+}
+
+/// Spawns a row of cells.
+fn spawn_row(panel: &mut ChildBuilder, cells: &[Cell], font: &Handle<Font>) {
+    panel.spawn(NodeBundle::default()).with_children(|row| {
+        for &cell in cells {
+            match cell {
+                Cell::Slot(_) | Cell::Output(_) | Cell::Crafted => {
+                    row.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                size: Size::all(Val::Px(SLOT_SIZE)),
+                                padding: UiRect::all(Val::Px(2.)),
+                                ..default()
+                            },
+                            background_color: SLOT_FRAME.into(),
+                            ..default()
+                        },
+                        SlotButton(cell),
+                    ))
+                    .with_children(|frame| {
+                        frame
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::all(Val::Percent(100.)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: SLOT_BACKGROUND.into(),
+                                ..default()
+                            })
+                            .with_children(|background| spawn_item(background, cell, font));
+                    });
+                }
+                Cell::Progress(bar) => {
+                    let colour = match bar {
+                        Bar::Cook => COOK_COLOUR,
+                        Bar::Fuel => FUEL_COLOUR,
+                    };
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            size: Size::all(Val::Px(SLOT_SIZE)),
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(4.)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|cell| {
+                        cell.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Px(8.)),
+                                ..default()
+                            },
+                            background_color: PROGRESS_BACKGROUND.into(),
+                            ..default()
+                        })
+                        .with_children(|background| {
+                            background.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                        ..default()
+                                    },
+                                    background_color: colour.into(),
+                                    ..default()
+                                },
+                                ScreenNode::Progress(bar),
+                            ));
+                        });
+                    });
+                }
+                Cell::Space => {
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            size: Size::all(Val::Px(SLOT_SIZE)),
+                            ..default()
+                        },
+                        ..default()
+                    });
+                }
+            }
+        }
+    });
+}
+
+/// Spawns the icon and count of the item in a cell.
+fn spawn_item(parent: &mut ChildBuilder, cell: Cell, font: &Handle<Font>) {
+    parent.spawn((
+        ImageBundle {
+            style: Style {
+                size: Size::all(Val::Px(SLOT_SIZE - 12.)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ScreenNode::Icon(cell),
+    ));
+    parent.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(1.),
+                    bottom: Val::Px(0.),
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        },
+        ScreenNode::Count(cell),
+    ));
+}
+
+/// Updates the items, progress bars and tooltip of the container being shown.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn container_render_system(
+    screen: Res<ContainerScreen>,
+    inventory: Res<PlayerInventory>,
+    recipes: Res<Recipes>,
+    items: Res<ItemMap>,
+    translations: Res<Translations>,
+    packs: Res<ResourcePacks>,
+    font: Res<UiFont>,
+    mut item_icons: ResMut<ItemIcons>,
+    mut images: ResMut<Assets<Image>>,
+    mut tooltip_shown: Local<Option<Cell>>,
+    windows: Query<&bevy::window::Window>,
+    buttons: Query<(&Interaction, &SlotButton)>,
+    mut frames: Query<(&SlotButton, &mut BackgroundColor)>,
+    mut icons: Query<(&ScreenNode, &mut UiImage, &mut Visibility)>,
+    mut texts: Query<(&ScreenNode, &mut Text), Without<UiImage>>,
+    mut boxes: Query<(&ScreenNode, &mut Style, &mut Visibility), (Without<UiImage>, Without<Text>)>,
+) {
+    let Some(open) = &screen.open else {
+        *tooltip_shown = None;
+        return;
+    };
+    let crafted = crafted_recipe(&inventory, &recipes, open.container_type)
+        .and_then(|recipe| recipe.output.first());
+    let item = |cell| match cell {
+        Cell::Slot(slot) | Cell::Output(slot) => inventory.item(slot).unwrap_or_default(),
+        Cell::Crafted => ItemInstance {
+            stack: crafted.cloned().unwrap_or_default(),
+            ..Default::default()
+        },
+        _ => ItemInstance::default(),
+    };
+    let hovered = hovered_cell(&buttons);
+    let hovered_item = hovered
+        .map(|cell| (cell, item(cell)))
+        .filter(|(_, item)| !is_air(item));
+    let cursor = item(Cell::Slot(Slot::cursor()));
+    let mouse = windows.iter().next().and_then(|window| {
+        let position = window.cursor_position()?;
+        Some(Vec2::new(position.x, window.height() - position.y))
+    });
+
+    for (button, mut colour) in &mut frames {
+        let frame = if Some(button.0) == hovered {
+            HOVERED_FRAME
+        } else {
+            SLOT_FRAME
+        };
+        if colour.0 != frame {
+            colour.0 = frame;
+        }
+    }
+
+    for (node, mut image, mut visibility) in &mut icons {
+        let ScreenNode::Icon(cell) = *node else {
+            continue;
+        };
+        let item = item(cell);
+        let icon = Some(item.stack.network_id)
+            .filter(|_| !is_air(&item))
+            .and_then(|network_id| item_icons.get(&packs, &mut images, &items, network_id));
+        let (texture, visibility_now) = match icon {
+            Some(texture) => (texture, Visibility::Inherited),
+            None => (DEFAULT_IMAGE_HANDLE.typed(), Visibility::Hidden),
+        };
+        if image.texture != texture {
+            image.texture = texture;
+        }
+        if *visibility != visibility_now {
+            *visibility = visibility_now;
+        }
+    }
+
+    let tooltip_changed = *tooltip_shown != hovered_item.as_ref().map(|(cell, _)| *cell);
+    *tooltip_shown = hovered_item.as_ref().map(|(cell, _)| *cell);
+    for (node, mut text) in &mut texts {
+        match *node {
+            ScreenNode::Count(cell) => {
+                let item = item(cell);
+                let count = if item.stack.count > 1 && !is_air(&item) {
+                    item.stack.count.to_string()
+                } else {
+                    String::new()
+                };
+                if text.sections.first().map(|section| &section.value) != Some(&count) {
+                    text.sections[0].value = count;
+                }
+            }
+            ScreenNode::TooltipText => {
+                if !tooltip_changed
+                    && !inventory.is_changed()
+                    && !translations.is_changed()
+                    && !font.is_changed()
+                {
+                    continue;
+                }
+                let Some((_, item)) = &hovered_item else {
+                    continue;
+                };
+                let name = items.identifier(item.stack.network_id).unwrap_or_default();
+                let content = tooltip(&translations, name, &item.stack).join("\n");
+                *text = Text::from_sections(text::sections(
+                    &content,
+                    text::Style::default(),
+                    &font.0,
+                    FONT_SIZE,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for (node, mut style, mut visibility) in &mut boxes {
+        let visible = match *node {
+            ScreenNode::Progress(bar) => {
+                let width = Val::Percent(open.progress(bar) * 100.);
+                if style.size.width != width {
+                    style.size.width = width;
+                }
+                continue;
+            }
+            ScreenNode::Held => {
+                if let Some(mouse) = mouse {
+                    style.position.left = Val::Px(mouse.x - SLOT_SIZE / 2.);
+                    style.position.top = Val::Px(mouse.y - SLOT_SIZE / 2.);
+                }
+                !is_air(&cursor)
+            }
+            ScreenNode::Tooltip => {
+                if let Some(mouse) = mouse {
+                    style.position.left = Val::Px(mouse.x + 12.);
+                    style.position.top = Val::Px(mouse.y + 12.);
+                }
+                is_air(&cursor) && hovered_item.is_some()
+            }
+            _ => continue,
+        };
+        let visibility_now = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != visibility_now {
+            *visibility = visibility_now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts() {
+        let chest = layout(ContainerType::Container, 54);
+        assert_eq!(chest.len(), 6);
+        assert!(chest.iter().all(|row| row.len() == 9));
+        assert_eq!(
+            layout(ContainerType::Hopper, 0),
+            [[
+                Cell::Slot(Slot::new(Container::LevelEntity, 0)),
+                Cell::Slot(Slot::new(Container::LevelEntity, 1)),
+                Cell::Slot(Slot::new(Container::LevelEntity, 2)),
+                Cell::Slot(Slot::new(Container::LevelEntity, 3)),
+                Cell::Slot(Slot::new(Container::LevelEntity, 4))
+            ]]
+        );
+
+        let furnace = layout(ContainerType::Furnace, 3);
+        assert_eq!(
+            quick_move_targets(ContainerType::Furnace, &furnace, Slot::hotbar(0)),
+            [
+                Slot::new(Container::FurnaceIngredient, 0),
+                Slot::new(Container::FurnaceFuel, 1)
+            ]
+        );
+        assert_eq!(
+            quick_move_targets(ContainerType::Workbench, &furnace, Slot::hotbar(0))[0],
+            Slot::inventory(9)
+        );
+    }
+
+    #[test]
+    fn furnace_progress() {
+        let mut open = OpenScreen {
+            window: Window::Container(1),
+            container_type: ContainerType::Smoker,
+            data: HashMap::new(),
+        };
+        assert_eq!(open.progress(Bar::Fuel), 0.);
+        open.data.extend([(0, 50), (1, 400), (2, 1600)]);
+        assert_eq!(open.progress(Bar::Cook), 0.5);
+        assert_eq!(open.progress(Bar::Fuel), 0.25);
+    }
+}
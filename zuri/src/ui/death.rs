@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use zuri_net::proto::io::UBlockPos;
+use zuri_net::proto::packet::death_info::DeathInfo;
+use zuri_net::proto::packet::player_action::PlayerAction;
+use zuri_net::proto::packet::respawn::{Respawn, RespawnState};
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::player::PlayerActionType;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::entity::IdentifiableEntity;
+use crate::player;
+use crate::ui::lang::Translations;
+use crate::ui::{button_colour_system, spawn_button, spawn_text, FocusOwner, FocusStack, UiFont};
+
+/// Shows the death screen when the player dies, and respawns the player once the respawn button is
+/// pressed.
+///
+/// Respawning is a handshake: pressing the button asks the server to respawn the player, the server
+/// answers with a Respawn packet once it has found a spawn point, and the client sends a Respawn
+/// packet back once it is ready to be spawned there.
+pub(super) struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DeathScreen::default())
+            .add_system(death_receive_system.in_base_set(NetworkSet::Process))
+            .add_systems(
+                (
+                    death_input_system,
+                    death_focus_system,
+                    death_render_system,
+                    button_colour_system::<RespawnButton>,
+                )
+                    .chain(),
+            )
+            .add_system(reset_resource::<DeathScreen>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// The size of the title of the death screen, in pixels.
+const TITLE_SIZE: f32 = 32.;
+/// The colour that tints the world behind the death screen.
+const OVERLAY: Color = Color::rgba(0.5, 0., 0., 0.4);
+
+/// The state of the death screen and of the respawn handshake.
+#[derive(Resource, Default)]
+pub struct DeathScreen {
+    /// The death message, which is set while the player is dead.
+    message: Option<String>,
+    /// Where the server is ready to respawn the player, once it has found a spawn point.
+    spawn: Option<Vec3>,
+    /// Whether the respawn button has been pressed.
+    respawning: bool,
+}
+
+impl DeathScreen {
+    /// Returns whether the death screen is shown.
+    pub fn is_open(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// Shows the death screen with a death message.
+    fn die(&mut self, message: String) {
+        self.message = Some(message);
+        self.respawning = false;
+    }
+
+    /// Handles a Respawn packet sent by the server. Returns where to respawn if the player was
+    /// already waiting to respawn.
+    fn receive(&mut self, pk: &Respawn) -> Option<Vec3> {
+        match pk.state {
+            RespawnState::SearchingForSpawn => self.spawn = None,
+            RespawnState::ReadyToSpawn => self.spawn = Some(pk.position),
+            RespawnState::ClientReadyToSpawn => {}
+        }
+        self.finish()
+    }
+
+    /// Handles the respawn button being pressed. Returns where to respawn if the server has already
+    /// found a spawn point.
+    fn press(&mut self) -> Option<Vec3> {
+        self.respawning = true;
+        self.finish()
+    }
+
+    /// Closes the death screen once the player wants to respawn and the server is ready for it.
+    fn finish(&mut self) -> Option<Vec3> {
+        if !self.is_open() || !self.respawning {
+            return None;
+        }
+        let spawn = self.spawn.take()?;
+        self.message = None;
+        self.respawning = false;
+        Some(spawn)
+    }
+}
+
+/// Marks the root of the user interface of the death screen.
+#[derive(Component)]
+struct DeathRoot;
+
+/// Marks the respawn button.
+#[derive(Component)]
+struct RespawnButton;
+
+/// Sends the Respawn packet that completes the respawn handshake, and moves the player to where it
+/// respawns.
+fn send_respawn(
+    packets: &mut EventWriter<Packet>,
+    player: &IdentifiableEntity,
+    transform: &mut Transform,
+    position: Vec3,
+) {
+    transform.translation = position;
+    packets.send(Packet::Respawn(Respawn {
+        position,
+        state: RespawnState::ClientReadyToSpawn,
+        entity_runtime_id: player.runtime_id().into(),
+    }));
+}
+
+fn death_receive_system(
+    translations: Res<Translations>,
+    mut screen: ResMut<DeathScreen>,
+    mut deaths: EventReader<DeathInfo>,
+    mut respawns: EventReader<Respawn>,
+    mut packets: EventWriter<Packet>,
+    mut player: Query<(&IdentifiableEntity, &mut Transform), With<player::Local>>,
+) {
+    for pk in deaths.iter() {
+        screen.die(translations.translate(&pk.cause, &pk.messages));
+    }
+    for pk in respawns.iter() {
+        let Some(position) = screen.receive(pk) else {
+            continue;
+        };
+        if let Ok((player, mut transform)) = player.get_single_mut() {
+            send_respawn(&mut packets, player, &mut transform, position);
+        }
+    }
+}
+
+/// Asks the server to respawn the player when the respawn button is pressed.
+fn death_input_system(
+    mut screen: ResMut<DeathScreen>,
+    mut packets: EventWriter<Packet>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<RespawnButton>)>,
+    mut player: Query<(&IdentifiableEntity, &mut Transform), With<player::Local>>,
+) {
+    if !buttons.iter().any(|i| *i == Interaction::Clicked) {
+        return;
+    }
+    let Ok((player, mut transform)) = player.get_single_mut() else {
+        return;
+    };
+    if screen.respawning {
+        return;
+    }
+    packets.send(Packet::PlayerAction(PlayerAction {
+        entity_runtime_id: player.runtime_id().into(),
+        action_type: PlayerActionType::Respawn,
+        block_position: UBlockPos::default(),
+        result_position: UBlockPos::default(),
+        block_face: 0.into(),
+    }));
+    if let Some(position) = screen.press() {
+        send_respawn(&mut packets, player, &mut transform, position);
+    }
+}
+
+/// Takes input focus while the death screen is shown, and gives it back once the player has
+/// respawned.
+fn death_focus_system(screen: Res<DeathScreen>, mut focus: ResMut<FocusStack>) {
+    if !screen.is_changed() {
+        return;
+    }
+    if screen.is_open() {
+        focus.push(FocusOwner::Death);
+    } else {
+        focus.pop(FocusOwner::Death);
+    }
+}
+
+/// Shows the death screen while the player is dead.
+fn death_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    translations: Res<Translations>,
+    screen: Res<DeathScreen>,
+    roots: Query<Entity, With<DeathRoot>>,
+) {
+    if !screen.is_changed() && !font.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(message) = &screen.message else {
+        return;
+    };
+
+    let font = &font.0;
+    let title = translations.get_or("deathScreen.title", "You died!");
+    let respawn = if screen.respawning {
+        translations.get_or("deathScreen.respawning", "Respawning...")
+    } else {
+        translations.get_or("deathScreen.respawn", "Respawn")
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::all(Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::height(Val::Px(12.)),
+                    ..default()
+                },
+                background_color: OVERLAY.into(),
+                ..default()
+            },
+            DeathRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: TITLE_SIZE,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_text(parent, message, default(), font);
+            let style = Style {
+                padding: UiRect::all(Val::Px(6.)),
+                min_size: Size::width(Val::Px(200.)),
+                ..default()
+            };
+            spawn_button(parent, respawn, RespawnButton, style, font);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn respawn(state: RespawnState) -> Respawn {
+        Respawn {
+            position: Vec3::new(1., 2., 3.),
+            state,
+            entity_runtime_id: 1u64.into(),
+        }
+    }
+
+    #[test]
+    fn respawn_handshake() {
+        let mut screen = DeathScreen::default();
+        assert_eq!(screen.receive(&respawn(RespawnState::ReadyToSpawn)), None);
+
+        // The server finds a spawn point only after the button is pressed.
+        screen.die("died".into());
+        assert!(screen.is_open());
+        assert_eq!(
+            screen.receive(&respawn(RespawnState::SearchingForSpawn)),
+            None
+        );
+        assert_eq!(screen.press(), None);
+        assert_eq!(
+            screen.receive(&respawn(RespawnState::ReadyToSpawn)),
+            Some(Vec3::new(1., 2., 3.))
+        );
+        assert!(!screen.is_open());
+
+        // The server found a spawn point before the button is pressed.
+        screen.die("died again".into());
+        assert_eq!(screen.receive(&respawn(RespawnState::ReadyToSpawn)), None);
+        assert_eq!(screen.press(), Some(Vec3::new(1., 2., 3.)));
+        assert!(!screen.is_open());
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::client::{ConnectionState, LeaveServer, Reconnect};
+use crate::ui::lang::Translations;
+use crate::ui::{button_colour_system, spawn_button, spawn_text, FocusOwner, FocusStack, UiFont};
+
+/// Covers the screen while the client is connecting to a server or has lost its connection, showing
+/// why the connection was lost along with buttons to reconnect or to go back to the server list.
+pub(super) struct DisconnectPlugin;
+
+impl Plugin for DisconnectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                disconnect_input_system,
+                disconnect_focus_system,
+                disconnect_render_system,
+                button_colour_system::<DisconnectButton>,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The size of the title of the disconnect screen, in pixels.
+const TITLE_SIZE: f32 = 32.;
+/// The colour behind the disconnect screen.
+const BACKGROUND: Color = Color::rgba(0.08, 0.08, 0.1, 0.9);
+
+/// Marks the root of the user interface of the disconnect screen.
+#[derive(Component)]
+struct DisconnectRoot;
+
+/// The buttons of the disconnect screen.
+#[derive(Component, Copy, Clone)]
+enum DisconnectButton {
+    Reconnect,
+    Leave,
+}
+
+fn disconnect_input_system(
+    mut reconnects: EventWriter<Reconnect>,
+    mut leaves: EventWriter<LeaveServer>,
+    buttons: Query<(&Interaction, &DisconnectButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match button {
+            DisconnectButton::Reconnect => reconnects.send(Reconnect),
+            DisconnectButton::Leave => leaves.send(LeaveServer),
+        }
+    }
+}
+
+/// Takes input focus while the disconnect screen is shown, and gives it back once the client has
+/// logged in.
+fn disconnect_focus_system(state: Res<ConnectionState>, mut focus: ResMut<FocusStack>) {
+    if !state.is_changed() {
+        return;
+    }
+    if *state == ConnectionState::Connected {
+        focus.pop(FocusOwner::Disconnect);
+    } else {
+        focus.push(FocusOwner::Disconnect);
+    }
+}
+
+/// Shows the disconnect screen while the client is not connected to a server.
+fn disconnect_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    translations: Res<Translations>,
+    state: Res<ConnectionState>,
+    roots: Query<Entity, With<DisconnectRoot>>,
+) {
+    if !state.is_changed() && !font.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let (title, message, buttons): (_, _, &[_]) = match &*state {
+        ConnectionState::Idle | ConnectionState::Connected => return,
+        ConnectionState::Connecting(address) => (
+            "Connecting...".to_string(),
+            address.clone(),
+            &[(DisconnectButton::Leave, "Cancel")],
+        ),
+        ConnectionState::Disconnected(message) => (
+            translations
+                .get_or(
+                    "disconnectionScreen.disconnected",
+                    "Disconnected from Server",
+                )
+                .to_string(),
+            // Servers usually send the message as a translation key.
+            translations.translate(message, &[]),
+            &[
+                (DisconnectButton::Reconnect, "Reconnect"),
+                (DisconnectButton::Leave, "Back to Server List"),
+            ],
+        ),
+    };
+
+    let font = &font.0;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::all(Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::height(Val::Px(12.)),
+                    ..default()
+                },
+                background_color: BACKGROUND.into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            DisconnectRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: TITLE_SIZE,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_text(parent, &message, default(), font);
+            for (button, label) in buttons {
+                let style = Style {
+                    padding: UiRect::all(Val::Px(6.)),
+                    min_size: Size::width(Val::Px(200.)),
+                    ..default()
+                };
+                spawn_button(parent, label, *button, style, font);
+            }
+        });
+}
@@ -0,0 +1,496 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use zuri_net::form::{CustomForm, Element, ElementValue, Form, FormResponse};
+use zuri_net::proto::packet::modal_form_request::ModalFormRequest;
+use zuri_net::proto::packet::modal_form_response::{ModalFormCancelReason, ModalFormResponse};
+use zuri_net::proto::packet::Packet;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::ui::chat::Chat;
+use crate::ui::text;
+use crate::ui::{button_colour_system, spawn_button, spawn_text, FocusOwner, FocusStack, UiFont};
+
+/// Shows the forms sent by the server and sends back the answers of the player.
+///
+/// Forms are shown one at a time, in the order in which they were received. `Escape` or the close
+/// button closes a form without submitting it. Systems can answer forms from code by sending an
+/// [AnswerForm] event, which is handled the same way as an answer given in the user interface.
+pub(super) struct FormPlugin;
+
+impl Plugin for FormPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Forms::default())
+            .add_event::<AnswerForm>()
+            .add_system(form_receive_system.in_base_set(NetworkSet::Process))
+            .add_systems(
+                (
+                    form_input_system,
+                    form_answer_system,
+                    form_focus_system,
+                    form_render_system,
+                    button_colour_system::<FormAction>,
+                )
+                    .chain(),
+            )
+            .add_system(reset_resource::<Forms>.run_if(on_event::<Disconnected>()));
+    }
+}
+
+/// The width of the panel that a form is shown in, in pixels.
+const PANEL_WIDTH: f32 = 420.;
+/// The colour that dims the world behind a form, and the colour of the panel itself.
+const OVERLAY: Color = Color::rgba(0., 0., 0., 0.5);
+const PANEL: Color = Color::rgb(0.18, 0.18, 0.2);
+
+/// The forms sent by the server that have not been answered yet.
+#[derive(Resource, Default)]
+pub struct Forms {
+    /// The form being shown, followed by the forms that are shown once it is answered.
+    queue: VecDeque<OpenForm>,
+}
+
+/// A form along with the values the player has entered in it so far.
+pub struct OpenForm {
+    pub id: u32,
+    pub form: Form,
+    /// The values of the elements of a custom form. Other forms have no values.
+    values: Vec<ElementValue>,
+    /// The index of the input that typed text goes into.
+    focus: Option<usize>,
+}
+
+impl Forms {
+    /// Returns the form being shown, if any.
+    pub fn current(&self) -> Option<&OpenForm> {
+        self.queue.front()
+    }
+
+    /// Returns all forms that have not been answered yet, starting with the one being shown.
+    pub fn iter(&self) -> impl Iterator<Item = &OpenForm> {
+        self.queue.iter()
+    }
+}
+
+/// Answers the form with an ID and closes it. If the response is None, the form is closed without
+/// being submitted.
+#[derive(Debug, Clone)]
+pub struct AnswerForm {
+    pub form_id: u32,
+    pub response: Option<FormResponse>,
+}
+
+/// Something that happens when a button in a form is pressed.
+#[derive(Component, Debug, Copy, Clone)]
+enum FormAction {
+    /// Presses the button of a menu form with this index.
+    Menu(usize),
+    /// Presses the first or second button of a modal form.
+    Modal(bool),
+    /// Starts typing in the input with this index.
+    Focus(usize),
+    /// Flips the toggle with this index.
+    Toggle(usize),
+    /// Moves the slider, step slider or dropdown with this index a number of steps.
+    Step(usize, i32),
+    /// Submits a custom form.
+    Submit,
+    /// Closes the form without submitting it.
+    Close,
+}
+
+/// Marks the root of the user interface of the form being shown.
+#[derive(Component)]
+struct FormRoot;
+
+/// Queues the forms sent by the server. Forms that cannot be parsed are closed right away, so that
+/// the server does not keep waiting for an answer.
+fn form_receive_system(
+    mut forms: ResMut<Forms>,
+    mut requests: EventReader<ModalFormRequest>,
+    mut packets: EventWriter<Packet>,
+) {
+    for pk in requests.iter() {
+        match pk.form() {
+            Ok(form) => {
+                let values = match &form {
+                    Form::Custom(form) => form.default_values(),
+                    _ => Vec::new(),
+                };
+                forms.queue.push_back(OpenForm {
+                    id: pk.form_id.0,
+                    form,
+                    values,
+                    focus: None,
+                });
+            }
+            Err(err) => {
+                warn!("Received invalid form {}: {}", pk.form_id.0, err);
+                packets.send(Packet::ModalFormResponse(ModalFormResponse::cancel(
+                    pk.form_id.0,
+                    ModalFormCancelReason::UserClosed,
+                )));
+            }
+        }
+    }
+}
+
+/// Handles the buttons of the form being shown, and typing in its inputs.
+fn form_input_system(
+    keys: Res<Input<KeyCode>>,
+    chat: Res<Chat>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut forms: ResMut<Forms>,
+    mut answers: EventWriter<AnswerForm>,
+    buttons: Query<(&Interaction, &FormAction), Changed<Interaction>>,
+) {
+    let Some(open) = forms.queue.front() else {
+        chars.clear();
+        return;
+    };
+    let form_id = open.id;
+    let answer = |response| AnswerForm { form_id, response };
+
+    // Typing goes to the chat while it is open.
+    if !chat.is_open() {
+        if keys.just_pressed(KeyCode::Escape) {
+            answers.send(answer(None));
+            return;
+        }
+        if let Some(index) = open.focus {
+            let typed: Vec<_> = chars.iter().map(|event| event.char).collect();
+            if !typed.is_empty() {
+                let open = forms.queue.front_mut().unwrap();
+                if let Some(ElementValue::Text(value)) = open.values.get_mut(index) {
+                    for c in typed {
+                        match c {
+                            '\u{8}' => {
+                                value.pop();
+                            }
+                            c if !c.is_control() => value.push(c),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+    chars.clear();
+
+    for (interaction, action) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let open = forms.queue.front_mut().unwrap();
+        match *action {
+            FormAction::Menu(button) => answers.send(answer(Some(FormResponse::Menu(button)))),
+            FormAction::Modal(first) => answers.send(answer(Some(FormResponse::Modal(first)))),
+            FormAction::Focus(index) => open.focus = Some(index),
+            FormAction::Toggle(index) => {
+                if let Some(ElementValue::Toggle(on)) = open.values.get_mut(index) {
+                    *on = !*on;
+                }
+            }
+            FormAction::Step(index, steps) => {
+                if let Form::Custom(form) = &open.form {
+                    step(form, &mut open.values, index, steps);
+                }
+            }
+            FormAction::Submit => {
+                answers.send(answer(Some(FormResponse::Custom(open.values.clone()))))
+            }
+            FormAction::Close => answers.send(answer(None)),
+        }
+    }
+}
+
+/// Moves a slider, step slider or dropdown of a custom form a number of steps. Dropdowns wrap
+/// around, while sliders stop at their ends.
+fn step(form: &CustomForm, values: &mut [ElementValue], index: usize, steps: i32) {
+    match (form.content.get(index), values.get_mut(index)) {
+        (Some(Element::Slider { min, max, step, .. }), Some(ElementValue::Number(value))) => {
+            *value = (*value + *step * steps as f32).clamp(*min, min.max(*max));
+        }
+        (Some(Element::StepSlider { steps: options, .. }), Some(ElementValue::Index(value))) => {
+            *value = value
+                .saturating_add_signed(steps as isize)
+                .min(options.len().saturating_sub(1));
+        }
+        (Some(Element::Dropdown { options, .. }), Some(ElementValue::Index(value)))
+            if !options.is_empty() =>
+        {
+            *value = (*value as isize + steps as isize).rem_euclid(options.len() as isize) as usize;
+        }
+        _ => {}
+    }
+}
+
+/// Sends the answers to forms to the server and closes the forms. Answers that do not fit their
+/// form are not sent, as the server would reject them.
+fn form_answer_system(
+    mut forms: ResMut<Forms>,
+    mut answers: EventReader<AnswerForm>,
+    mut packets: EventWriter<Packet>,
+) {
+    for answer in answers.iter() {
+        let Some(index) = forms
+            .queue
+            .iter()
+            .position(|open| open.id == answer.form_id)
+        else {
+            warn!("Tried to answer form {} which is not open", answer.form_id);
+            continue;
+        };
+        let pk = match &answer.response {
+            Some(response) => {
+                if let Err(err) = forms.queue[index].form.check(response) {
+                    warn!("Invalid answer to form {}: {}", answer.form_id, err);
+                    continue;
+                }
+                ModalFormResponse::submit(answer.form_id, response)
+            }
+            None => ModalFormResponse::cancel(answer.form_id, ModalFormCancelReason::UserClosed),
+        };
+        packets.send(Packet::ModalFormResponse(pk));
+        forms.queue.remove(index);
+    }
+}
+
+/// Takes input focus while a form is shown, and gives it back once all forms are answered.
+fn form_focus_system(forms: Res<Forms>, mut focus: ResMut<FocusStack>) {
+    if !forms.is_changed() {
+        return;
+    }
+    if forms.queue.is_empty() {
+        focus.pop(FocusOwner::Form);
+    } else {
+        focus.push(FocusOwner::Form);
+    }
+}
+
+/// Rebuilds the user interface of the form being shown whenever it changes.
+fn form_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    forms: Res<Forms>,
+    roots: Query<Entity, With<FormRoot>>,
+) {
+    if !forms.is_changed() && !font.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(open) = forms.queue.front() else {
+        return;
+    };
+
+    let font = &font.0;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::all(Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: OVERLAY.into(),
+                ..default()
+            },
+            FormRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Px(PANEL_WIDTH)),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(8.)),
+                        ..default()
+                    },
+                    background_color: PANEL.into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel
+                        .spawn(NodeBundle {
+                            style: Style {
+                                justify_content: JustifyContent::SpaceBetween,
+                                margin: UiRect::bottom(Val::Px(6.)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|header| {
+                            spawn_text(header, open.form.title(), text_style(), font);
+                            spawn_button(header, "X", FormAction::Close, button_style(), font);
+                        });
+                    spawn_content(panel, open, font);
+                });
+        });
+}
+
+/// Spawns the elements of a form below its title.
+fn spawn_content(panel: &mut ChildBuilder, open: &OpenForm, font: &Handle<Font>) {
+    match &open.form {
+        Form::Menu(form) => {
+            if !form.content.is_empty() {
+                spawn_text(panel, &form.content, text_style(), font);
+            }
+            // Images on buttons are not shown.
+            for (i, button) in form.buttons.iter().enumerate() {
+                spawn_button(
+                    panel,
+                    &button.text,
+                    FormAction::Menu(i),
+                    button_style(),
+                    font,
+                );
+            }
+        }
+        Form::Modal(form) => {
+            spawn_text(panel, &form.content, text_style(), font);
+            spawn_button(
+                panel,
+                &form.button1,
+                FormAction::Modal(true),
+                button_style(),
+                font,
+            );
+            spawn_button(
+                panel,
+                &form.button2,
+                FormAction::Modal(false),
+                button_style(),
+                font,
+            );
+        }
+        Form::Custom(form) => {
+            for (i, (element, value)) in form.content.iter().zip(&open.values).enumerate() {
+                spawn_element(panel, i, element, value, open.focus == Some(i), font);
+            }
+            spawn_button(panel, "Submit", FormAction::Submit, button_style(), font);
+        }
+    }
+}
+
+/// Spawns an element of a custom form with its current value.
+fn spawn_element(
+    panel: &mut ChildBuilder,
+    index: usize,
+    element: &Element,
+    value: &ElementValue,
+    focused: bool,
+    font: &Handle<Font>,
+) {
+    let label = element.text();
+    match (element, value) {
+        (Element::Input { placeholder, .. }, ElementValue::Text(text)) => {
+            spawn_text(panel, label, text_style(), font);
+            let shown = match (text.is_empty(), focused) {
+                (_, true) => format!("{}_", text),
+                (true, false) => format!("{}7{}", text::FORMAT_CHAR, placeholder),
+                (false, false) => text.clone(),
+            };
+            spawn_button(
+                panel,
+                &shown,
+                FormAction::Focus(index),
+                button_style(),
+                font,
+            );
+        }
+        (Element::Toggle { .. }, ElementValue::Toggle(on)) => {
+            let check = if *on { "[x]" } else { "[ ]" };
+            spawn_button(
+                panel,
+                &format!("{} {}", check, label),
+                FormAction::Toggle(index),
+                button_style(),
+                font,
+            );
+        }
+        (Element::Slider { .. }, ElementValue::Number(number)) => {
+            spawn_stepper(panel, index, &format!("{}: {}", label, number), font);
+        }
+        (Element::StepSlider { steps: options, .. }, ElementValue::Index(selected))
+        | (Element::Dropdown { options, .. }, ElementValue::Index(selected)) => {
+            let option = options
+                .get(*selected)
+                .map(String::as_str)
+                .unwrap_or_default();
+            spawn_stepper(panel, index, &format!("{}: {}", label, option), font);
+        }
+        _ => spawn_text(panel, label, text_style(), font),
+    }
+}
+
+/// Spawns a row with the current value of a slider or dropdown between buttons that change it.
+fn spawn_stepper(panel: &mut ChildBuilder, index: usize, shown: &str, font: &Handle<Font>) {
+    panel
+        .spawn(NodeBundle {
+            style: Style {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_button(row, "<", FormAction::Step(index, -1), button_style(), font);
+            spawn_text(row, shown, text_style(), font);
+            spawn_button(row, ">", FormAction::Step(index, 1), button_style(), font);
+        });
+}
+
+/// Returns the style of text in a form, which wraps at the width of the panel.
+fn text_style() -> Style {
+    Style {
+        max_size: Size::width(Val::Px(PANEL_WIDTH - 16.)),
+        margin: UiRect::vertical(Val::Px(2.)),
+        ..default()
+    }
+}
+
+/// Returns the style of a button in a form.
+fn button_style() -> Style {
+    Style {
+        padding: UiRect::all(Val::Px(4.)),
+        margin: UiRect::vertical(Val::Px(2.)),
+        max_size: Size::width(Val::Px(PANEL_WIDTH - 16.)),
+        ..default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps() {
+        let form = CustomForm {
+            title: String::new(),
+            content: vec![
+                Element::Slider {
+                    text: String::new(),
+                    min: 0.,
+                    max: 10.,
+                    step: 4.,
+                    default: 0.,
+                },
+                Element::Dropdown {
+                    text: String::new(),
+                    options: vec!["a".into(), "b".into()],
+                    default: 0,
+                },
+            ],
+            icon: None,
+        };
+        let mut values = form.default_values();
+        step(&form, &mut values, 0, 3);
+        step(&form, &mut values, 1, -1);
+        assert_eq!(values, [ElementValue::Number(10.), ElementValue::Index(1)]);
+    }
+}
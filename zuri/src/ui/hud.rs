@@ -0,0 +1,754 @@
+use std::collections::HashMap;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::render::texture::DEFAULT_IMAGE_HANDLE;
+use zuri_net::inventory::{Inventory, Slot};
+use zuri_net::proto::packet::mob_equipment::MobEquipment;
+use zuri_net::proto::packet::player_hot_bar::PlayerHotBar;
+use zuri_net::proto::packet::set_actor_data::SetActorData;
+use zuri_net::proto::packet::set_health::SetHealth;
+use zuri_net::proto::packet::update_attributes::UpdateAttributes;
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::attribute::AttributeValue;
+use zuri_net::proto::types::entity_data::EntityMetadata;
+use zuri_net::proto::types::inventory::Window;
+use zuri_net::proto::types::item::ItemInstance;
+use zuri_world::item::ItemMap;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::entity::IdentifiableEntity;
+use crate::input::InputFocus;
+use crate::inventory::PlayerInventory;
+use crate::player;
+use crate::resource_pack::ResourcePacks;
+use crate::ui::item::{item_name, ItemIcons};
+use crate::ui::lang::Translations;
+use crate::ui::UiFont;
+
+/// Shows the health, hunger, armour, air and experience of the player, along with the hotbar.
+///
+/// The held slot of the hotbar is changed with the mouse wheel or the number keys, and the server
+/// is told about the newly held item.
+pub(super) struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerAttributes::default())
+            .insert_resource(Hotbar::default())
+            .insert_resource(HudTextures::default())
+            .add_startup_system(hud_setup_system)
+            .add_systems(
+                (attributes_update_system, hotbar_update_system).in_base_set(NetworkSet::Process),
+            )
+            .add_systems((hud_textures_system, hotbar_select_system, hud_render_system).chain())
+            .add_systems(
+                (reset_resource::<PlayerAttributes>, reset_resource::<Hotbar>)
+                    .distributive_run_if(on_event::<Disconnected>()),
+            );
+    }
+}
+
+/// The names of the attributes shown in the HUD.
+pub const HEALTH: &str = "minecraft:health";
+pub const HUNGER: &str = "minecraft:player.hunger";
+pub const EXPERIENCE: &str = "minecraft:player.experience";
+pub const LEVEL: &str = "minecraft:player.level";
+
+/// The number of slots in the hotbar.
+pub const HOTBAR_SIZE: usize = 9;
+/// The number of icons in each of the status bars.
+const ICONS: usize = 10;
+/// The size of the status icons and of the hotbar slots, in pixels.
+const ICON_SIZE: f32 = 18.;
+const SLOT_SIZE: f32 = 40.;
+const HUD_WIDTH: f32 = SLOT_SIZE * HOTBAR_SIZE as f32;
+/// The size of the font used for the level and item counts, in pixels.
+const FONT_SIZE: f32 = 14.;
+/// How long the name of a newly held item is shown, in seconds.
+const HELD_NAME_TIME: f64 = 2.;
+/// The colour of the frame of slots, and of the frame of the held slot.
+const SLOT_FRAME: Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+const SELECTED_FRAME: Color = Color::WHITE;
+const SLOT_BACKGROUND: Color = Color::rgba(0.3, 0.3, 0.3, 0.7);
+/// The colours of the experience bar and the level.
+const XP_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.7);
+const XP_COLOUR: Color = Color::rgb(0.5, 1., 0.13);
+
+/// The attributes of the local player, such as its health and hunger, by their name.
+#[derive(Resource, Default, Debug)]
+pub struct PlayerAttributes {
+    attributes: HashMap<String, AttributeValue>,
+    /// The metadata of the local player, which holds how much air it has left.
+    metadata: EntityMetadata,
+}
+
+impl PlayerAttributes {
+    /// Returns an attribute by its name, such as `minecraft:health`.
+    pub fn get(&self, name: &str) -> Option<&AttributeValue> {
+        self.attributes.get(name)
+    }
+
+    /// Returns the current value of an attribute, or the default if it has not been sent yet.
+    pub fn value_or(&self, name: &str, default: f32) -> f32 {
+        self.get(name).map_or(default, |attribute| attribute.value)
+    }
+
+    /// Returns the air the player has left and its maximum air, in ticks.
+    pub fn air(&self) -> Option<(i16, i16)> {
+        self.metadata.air_supply()
+    }
+}
+
+/// The hotbar slot that the local player is holding.
+#[derive(Resource, Default, Debug)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+
+impl Hotbar {
+    /// Returns the item in the held slot.
+    pub fn held(&self, inventory: &Inventory) -> ItemInstance {
+        hotbar_item(inventory, self.selected)
+    }
+}
+
+fn hotbar_item(inventory: &Inventory, slot: usize) -> ItemInstance {
+    inventory.item(Slot::hotbar(slot as u8)).unwrap_or_default()
+}
+
+/// The icons of the status bars, loaded from the `textures/ui` directory of the resource packs.
+/// Icons that are missing are drawn as coloured squares instead.
+#[derive(Resource, Default)]
+struct HudTextures {
+    icons: HashMap<&'static str, Handle<Image>>,
+}
+
+/// The status icons, along with the colour they are drawn with when they are missing.
+const STATUS_ICONS: [(&str, Color); 10] = [
+    ("heart", Color::rgb(0.85, 0.1, 0.1)),
+    ("heart_half", Color::rgb(0.6, 0.1, 0.1)),
+    ("heart_background", Color::rgba(0.2, 0.2, 0.2, 0.8)),
+    ("hunger_full", Color::rgb(0.65, 0.4, 0.15)),
+    ("hunger_half", Color::rgb(0.45, 0.3, 0.1)),
+    ("hunger_background", Color::rgba(0.2, 0.2, 0.2, 0.8)),
+    ("armor_full", Color::rgb(0.8, 0.8, 0.85)),
+    ("armor_half", Color::rgb(0.55, 0.55, 0.6)),
+    ("armor_empty", Color::rgba(0.2, 0.2, 0.2, 0.8)),
+    ("bubble", Color::rgb(0.3, 0.5, 1.)),
+];
+
+impl HudTextures {
+    /// Returns the texture and tint of a status icon.
+    fn icon(&self, name: &str) -> (Handle<Image>, Color) {
+        match self.icons.get(name) {
+            Some(texture) => (texture.clone(), Color::WHITE),
+            None => {
+                let colour = STATUS_ICONS
+                    .iter()
+                    .find(|(icon, _)| *icon == name)
+                    .map_or(Color::NONE, |(_, colour)| *colour);
+                (DEFAULT_IMAGE_HANDLE.typed(), colour)
+            }
+        }
+    }
+}
+
+/// How much of a status icon is filled.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Fill {
+    Full,
+    Half,
+    Empty,
+}
+
+/// Works out how full each icon of a status bar is, where each icon is worth two points.
+fn fills(points: f32) -> [Fill; ICONS] {
+    let points = points.ceil().max(0.) as usize;
+    let mut fills = [Fill::Empty; ICONS];
+    for (i, fill) in fills.iter_mut().enumerate() {
+        if points >= i * 2 + 2 {
+            *fill = Fill::Full;
+        } else if points == i * 2 + 1 {
+            *fill = Fill::Half;
+        }
+    }
+    fills
+}
+
+/// Returns the armour points of a piece of armour, as in vanilla.
+fn armour_points(name: &str) -> f32 {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    if name == "turtle_helmet" {
+        return 2.;
+    }
+    let Some((material, piece)) = name.rsplit_once('_') else {
+        return 0.;
+    };
+    let points = match material {
+        "leather" => [1., 3., 2., 1.],
+        "golden" => [2., 5., 3., 1.],
+        "chainmail" => [2., 5., 4., 1.],
+        "iron" => [2., 6., 5., 2.],
+        "diamond" | "netherite" => [3., 8., 6., 3.],
+        _ => return 0.,
+    };
+    match piece {
+        "helmet" => points[0],
+        "chestplate" => points[1],
+        "leggings" => points[2],
+        "boots" => points[3],
+        _ => 0.,
+    }
+}
+
+/// Identifies the parts of the HUD that change.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+enum HudNode {
+    Heart(usize),
+    Hunger(usize),
+    Armour(usize),
+    Bubble(usize),
+    ExperienceFill,
+    Level,
+    SlotFrame(usize),
+    SlotIcon(usize),
+    SlotCount(usize),
+    HeldName,
+}
+
+/// Tracks when the held slot last changed, so that the name of the held item is only shown for a
+/// short while.
+#[derive(Default)]
+struct HeldName {
+    slot: usize,
+    since: f64,
+    shown: bool,
+}
+
+/// Keeps the attributes and air of the local player up to date.
+fn attributes_update_system(
+    mut attributes: ResMut<PlayerAttributes>,
+    player: Query<&IdentifiableEntity, With<player::Local>>,
+
+    mut update_attributes: EventReader<UpdateAttributes>,
+    mut set_health: EventReader<SetHealth>,
+    mut set_actor_data: EventReader<SetActorData>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let runtime_id = player.runtime_id();
+    for pk in update_attributes.iter() {
+        if runtime_id != pk.entity_runtime_id {
+            continue;
+        }
+        for attribute in &pk.attributes {
+            attributes
+                .attributes
+                .insert(attribute.value.name.clone(), attribute.value.clone());
+        }
+    }
+    for pk in set_health.iter() {
+        let health = attributes
+            .attributes
+            .entry(HEALTH.into())
+            .or_insert_with(|| AttributeValue {
+                name: HEALTH.into(),
+                min: 0.,
+                max: 20.,
+                ..Default::default()
+            });
+        health.value = pk.health.0 as f32;
+    }
+    for pk in set_actor_data.iter() {
+        if runtime_id == pk.entity_runtime_id {
+            attributes.metadata.merge(&pk.entity_metadata);
+        }
+    }
+}
+
+/// Follows the server when it changes the held slot.
+fn hotbar_update_system(
+    mut hotbar: ResMut<Hotbar>,
+    player: Query<&IdentifiableEntity, With<player::Local>>,
+
+    mut equipment: EventReader<MobEquipment>,
+    mut hot_bar: EventReader<PlayerHotBar>,
+) {
+    let runtime_id = player.get_single().ok().map(IdentifiableEntity::runtime_id);
+    for pk in equipment.iter() {
+        let is_local = matches!(runtime_id, Some(id) if id == pk.entity_runtime_id);
+        if is_local && matches!(pk.window, Window::Inventory) {
+            let slot = pk.hotbar_slot as usize;
+            if slot < HOTBAR_SIZE {
+                hotbar.selected = slot;
+            }
+        }
+    }
+    for pk in hot_bar.iter() {
+        let slot = pk.selected_hotbar_slot.0 as usize;
+        if pk.select_hotbar_slot && slot < HOTBAR_SIZE {
+            hotbar.selected = slot;
+        }
+    }
+}
+
+/// Changes the held slot with the mouse wheel and number keys, and tells the server about the
+/// newly held item.
+fn hotbar_select_system(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<InputFocus>,
+    mut wheel: EventReader<MouseWheel>,
+    mut hotbar: ResMut<Hotbar>,
+    inventory: Res<PlayerInventory>,
+    mut packets: EventWriter<Packet>,
+    player: Query<&IdentifiableEntity, With<player::Local>>,
+) {
+    let scroll = wheel.iter().map(|event| event.y).sum::<f32>();
+    if *focus != InputFocus::Game {
+        return;
+    }
+
+    const KEYS: [KeyCode; HOTBAR_SIZE] = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    let mut selected = hotbar.selected;
+    if let Some(slot) = KEYS.iter().position(|key| keys.just_pressed(*key)) {
+        selected = slot;
+    } else if scroll != 0. {
+        // Scrolling up moves the selection to the left, like in the game.
+        let steps = -scroll.signum() as isize;
+        selected = (selected as isize + steps).rem_euclid(HOTBAR_SIZE as isize) as usize;
+    }
+    if selected == hotbar.selected {
+        return;
+    }
+    hotbar.selected = selected;
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    packets.send(Packet::MobEquipment(MobEquipment {
+        entity_runtime_id: player.runtime_id().into(),
+        new_item: hotbar.held(&inventory),
+        inventory_slot: selected as u8,
+        hotbar_slot: selected as u8,
+        window: Window::Inventory,
+    }));
+}
+
+/// Loads the status icons from the resource packs whenever they change.
+fn hud_textures_system(
+    packs: Res<ResourcePacks>,
+    mut textures: ResMut<HudTextures>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+    textures.icons = STATUS_ICONS
+        .iter()
+        .filter_map(|(name, _)| {
+            let image = packs.load_texture(&format!("textures/ui/{}", name))?;
+            Some((*name, images.add(image)))
+        })
+        .collect();
+}
+
+/// Spawns the nodes of the HUD at the bottom of the screen. Their contents are filled in by
+/// [hud_render_system].
+fn hud_setup_system(mut commands: Commands) {
+    let icon = |node: HudNode| {
+        (
+            ImageBundle {
+                style: Style {
+                    size: Size::all(Val::Px(ICON_SIZE)),
+                    ..default()
+                },
+                ..default()
+            },
+            node,
+        )
+    };
+    let bar =
+        |parent: &mut ChildBuilder, left: fn(usize) -> HudNode, right: fn(usize) -> HudNode| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(NodeBundle::default()).with_children(|group| {
+                        for i in 0..ICONS {
+                            group.spawn(icon(left(i)));
+                        }
+                    });
+                    // The bars on the right fill up from the right.
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::RowReverse,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|group| {
+                        for i in 0..ICONS {
+                            group.spawn(icon(right(i)));
+                        }
+                    });
+                });
+        };
+    let text = |node: HudNode| {
+        (
+            TextBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            node,
+        )
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::bottom(Val::Px(4.)),
+                size: Size::width(Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Px(HUD_WIDTH)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Stretch,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|hud| {
+                    hud.spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::Center,
+                            margin: UiRect::bottom(Val::Px(4.)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(text(HudNode::HeldName));
+                    });
+                    bar(hud, HudNode::Armour, HudNode::Bubble);
+                    bar(hud, HudNode::Heart, HudNode::Hunger);
+
+                    hud.spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(text(HudNode::Level));
+                    });
+                    hud.spawn(NodeBundle {
+                        style: Style {
+                            size: Size::height(Val::Px(5.)),
+                            margin: UiRect::vertical(Val::Px(2.)),
+                            ..default()
+                        },
+                        background_color: XP_BACKGROUND.into(),
+                        ..default()
+                    })
+                    .with_children(|bar| {
+                        bar.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                    ..default()
+                                },
+                                background_color: XP_COLOUR.into(),
+                                ..default()
+                            },
+                            HudNode::ExperienceFill,
+                        ));
+                    });
+
+                    hud.spawn(NodeBundle::default()).with_children(|row| {
+                        for i in 0..HOTBAR_SIZE {
+                            row.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::all(Val::Px(SLOT_SIZE)),
+                                        padding: UiRect::all(Val::Px(2.)),
+                                        ..default()
+                                    },
+                                    background_color: SLOT_FRAME.into(),
+                                    ..default()
+                                },
+                                HudNode::SlotFrame(i),
+                            ))
+                            .with_children(|frame| {
+                                frame
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            size: Size::all(Val::Percent(100.)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        background_color: SLOT_BACKGROUND.into(),
+                                        ..default()
+                                    })
+                                    .with_children(|slot| {
+                                        slot.spawn((
+                                            ImageBundle {
+                                                style: Style {
+                                                    size: Size::all(Val::Px(SLOT_SIZE - 12.)),
+                                                    ..default()
+                                                },
+                                                visibility: Visibility::Hidden,
+                                                ..default()
+                                            },
+                                            HudNode::SlotIcon(i),
+                                        ));
+                                        slot.spawn((
+                                            TextBundle {
+                                                style: Style {
+                                                    position_type: PositionType::Absolute,
+                                                    position: UiRect {
+                                                        right: Val::Px(1.),
+                                                        bottom: Val::Px(0.),
+                                                        ..default()
+                                                    },
+                                                    ..default()
+                                                },
+                                                visibility: Visibility::Hidden,
+                                                ..default()
+                                            },
+                                            HudNode::SlotCount(i),
+                                        ));
+                                    });
+                            });
+                        }
+                    });
+                });
+        });
+}
+
+/// Updates the HUD to the current attributes and hotbar of the player.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn hud_render_system(
+    time: Res<Time>,
+    font: Res<UiFont>,
+    attributes: Res<PlayerAttributes>,
+    hotbar: Res<Hotbar>,
+    inventory: Res<PlayerInventory>,
+    items: Res<ItemMap>,
+    translations: Res<Translations>,
+    packs: Res<ResourcePacks>,
+    textures: Res<HudTextures>,
+    mut item_icons: ResMut<ItemIcons>,
+    mut images: ResMut<Assets<Image>>,
+    mut held: Local<HeldName>,
+    mut icons: Query<(
+        &HudNode,
+        &mut UiImage,
+        &mut BackgroundColor,
+        &mut Visibility,
+    )>,
+    mut boxes: Query<(&HudNode, &mut Style, &mut BackgroundColor), Without<UiImage>>,
+    mut texts: Query<(&HudNode, &mut Text, &mut Visibility), Without<UiImage>>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if held.slot != hotbar.selected {
+        held.slot = hotbar.selected;
+        held.since = now;
+    }
+    let show_held_name = now - held.since < HELD_NAME_TIME;
+    let held_name_changed = held.shown != show_held_name;
+    held.shown = show_held_name;
+
+    let health = attributes.get(HEALTH);
+    let hunger = attributes.value_or(HUNGER, 20.);
+    let armour: f32 = inventory
+        .items(Window::Armour)
+        .iter()
+        .filter_map(|item| items.identifier(item.stack.network_id))
+        .map(armour_points)
+        .sum();
+    let air = attributes.air().filter(|(air, max)| air < max);
+
+    let hearts = fills(health.map_or(20., |health| health.value));
+    let max_hearts = health.map_or(ICONS, |health| (health.max / 2.).ceil() as usize);
+    let hungers = fills(hunger);
+    let armours = fills(armour);
+
+    for (node, mut image, mut colour, mut visibility) in &mut icons {
+        let (texture, tint, visible) = match *node {
+            HudNode::Heart(i) => {
+                let name = match hearts[i] {
+                    Fill::Full => "heart",
+                    Fill::Half => "heart_half",
+                    Fill::Empty => "heart_background",
+                };
+                let (texture, tint) = textures.icon(name);
+                (texture, tint, i < max_hearts)
+            }
+            HudNode::Hunger(i) => {
+                let name = match hungers[i] {
+                    Fill::Full => "hunger_full",
+                    Fill::Half => "hunger_half",
+                    Fill::Empty => "hunger_background",
+                };
+                let (texture, tint) = textures.icon(name);
+                (texture, tint, true)
+            }
+            HudNode::Armour(i) => {
+                let name = match armours[i] {
+                    Fill::Full => "armor_full",
+                    Fill::Half => "armor_half",
+                    Fill::Empty => "armor_empty",
+                };
+                let (texture, tint) = textures.icon(name);
+                (texture, tint, armour > 0.)
+            }
+            HudNode::Bubble(i) => {
+                let (texture, tint) = textures.icon("bubble");
+                let visible = match air {
+                    Some((air, max)) => {
+                        let bubbles = (air.max(0) as f32 * ICONS as f32 / max as f32).ceil();
+                        (i as f32) < bubbles
+                    }
+                    None => false,
+                };
+                (texture, tint, visible)
+            }
+            HudNode::SlotIcon(i) => {
+                let item = hotbar_item(&inventory, i);
+                let icon = item_icons.get(&packs, &mut images, &items, item.stack.network_id);
+                match icon {
+                    Some(texture) => (texture, Color::WHITE, item.stack.count > 0),
+                    None => (DEFAULT_IMAGE_HANDLE.typed(), Color::NONE, false),
+                }
+            }
+            _ => continue,
+        };
+        if image.texture != texture {
+            image.texture = texture;
+        }
+        if colour.0 != tint {
+            colour.0 = tint;
+        }
+        let visibility_now = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != visibility_now {
+            *visibility = visibility_now;
+        }
+    }
+
+    for (node, mut style, mut colour) in &mut boxes {
+        match *node {
+            HudNode::ExperienceFill => {
+                let progress = attributes.value_or(EXPERIENCE, 0.).clamp(0., 1.);
+                let width = Val::Percent(progress * 100.);
+                if style.size.width != width {
+                    style.size.width = width;
+                }
+            }
+            HudNode::SlotFrame(i) => {
+                let frame = if i == hotbar.selected {
+                    SELECTED_FRAME
+                } else {
+                    SLOT_FRAME
+                };
+                if colour.0 != frame {
+                    colour.0 = frame;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !attributes.is_changed()
+        && !hotbar.is_changed()
+        && !inventory.is_changed()
+        && !font.is_changed()
+        && !translations.is_changed()
+        && !held_name_changed
+    {
+        return;
+    }
+    let style = |color| TextStyle {
+        font: font.0.clone(),
+        font_size: FONT_SIZE,
+        color,
+    };
+    for (node, mut text, mut visibility) in &mut texts {
+        let content = match *node {
+            HudNode::Level => {
+                let level = attributes.value_or(LEVEL, 0.) as i32;
+                (level > 0).then(|| (level.to_string(), XP_COLOUR))
+            }
+            HudNode::SlotCount(i) => {
+                let count = hotbar_item(&inventory, i).stack.count;
+                (count > 1).then(|| (count.to_string(), Color::WHITE))
+            }
+            HudNode::HeldName => items
+                .identifier(hotbar.held(&inventory).stack.network_id)
+                .filter(|_| show_held_name)
+                .map(|name| (item_name(&translations, name), Color::WHITE)),
+            _ => continue,
+        };
+        match content {
+            Some((content, colour)) => {
+                *text = Text::from_section(content, style(colour));
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_fills() {
+        let fills = fills(7.);
+        assert_eq!(
+            fills[..5],
+            [Fill::Full, Fill::Full, Fill::Full, Fill::Half, Fill::Empty]
+        );
+        assert_eq!(super::fills(0.5)[0], Fill::Half);
+        assert_eq!(super::fills(20.)[9], Fill::Full);
+    }
+
+    #[test]
+    fn armour() {
+        assert_eq!(armour_points("minecraft:diamond_chestplate"), 8.);
+        assert_eq!(armour_points("minecraft:leather_boots"), 1.);
+        assert_eq!(armour_points("minecraft:turtle_helmet"), 2.);
+        assert_eq!(armour_points("minecraft:diamond_sword"), 0.);
+    }
+}
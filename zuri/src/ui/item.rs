@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use zuri_net::proto::packet::item_component::ItemComponent;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::types::item::ItemStack;
+use zuri_world::item::component::Icon;
+use zuri_world::item::ItemMap;
+
+use crate::client::NetworkSet;
+use crate::resource_pack::ResourcePacks;
+use crate::ui::lang::Translations;
+use crate::ui::text::FORMAT_CHAR;
+
+/// Keeps track of the [ItemMap] and the icons of items, which are shared by all parts of the user
+/// interface that show items.
+pub(super) struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ItemMap::default())
+            .insert_resource(ItemIcons::default())
+            .add_system(item_map_system.in_base_set(NetworkSet::Process))
+            .add_system(item_icons_system);
+    }
+}
+
+/// The icons of items by their name, loaded from the resource packs the first time they are
+/// needed.
+#[derive(Resource, Default)]
+pub struct ItemIcons(HashMap<String, Option<Handle<Image>>>);
+
+impl ItemIcons {
+    /// Returns the icon of an item, or None if the resource packs have no icon for it.
+    pub fn get(
+        &mut self,
+        packs: &ResourcePacks,
+        images: &mut Assets<Image>,
+        items: &ItemMap,
+        network_id: i32,
+    ) -> Option<Handle<Image>> {
+        let name = items.identifier(network_id)?;
+        // Custom items name their icon, while vanilla icons are named after the item itself.
+        let short = match items.component::<Icon>(network_id) {
+            Some(icon) => icon.0.as_str(),
+            None => name.strip_prefix("minecraft:").unwrap_or(name),
+        };
+        self.0
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                packs
+                    .load_texture(&format!("textures/items/{}", short))
+                    .or_else(|| packs.load_texture(&format!("textures/blocks/{}", short)))
+                    .map(|image| images.add(image))
+            })
+            .clone()
+    }
+}
+
+/// Builds the [ItemMap] from the StartGame packet and adds the components of custom items to it.
+fn item_map_system(
+    mut items: ResMut<ItemMap>,
+    mut start_game: EventReader<StartGame>,
+    mut item_component: EventReader<ItemComponent>,
+) {
+    for pk in start_game.iter() {
+        *items = ItemMap::new(&pk.items);
+    }
+    for pk in item_component.iter() {
+        items.handle_item_component(pk);
+    }
+}
+
+/// Forgets all icons when the resource packs change, so that they are loaded again.
+fn item_icons_system(packs: Res<ResourcePacks>, mut icons: ResMut<ItemIcons>) {
+    if packs.is_changed() {
+        icons.0.clear();
+    }
+}
+
+/// Returns the translated name of an item.
+pub fn item_name(translations: &Translations, name: &str) -> String {
+    let short = name.strip_prefix("minecraft:").unwrap_or(name);
+    translations
+        .get(&format!("item.{}.name", short))
+        .or_else(|| translations.get(&format!("tile.{}.name", short)))
+        .map(String::from)
+        .unwrap_or_else(|| short.replace('_', " "))
+}
+
+/// The translation keys of enchantments, by their ID.
+const ENCHANTMENTS: [&str; 38] = [
+    "protect.all",
+    "protect.fire",
+    "protect.fall",
+    "protect.explosion",
+    "protect.projectile",
+    "thorns",
+    "oxygen",
+    "waterWalker",
+    "waterWorker",
+    "damage.all",
+    "damage.undead",
+    "damage.arthropods",
+    "knockback",
+    "fire",
+    "lootBonus",
+    "digging",
+    "untouching",
+    "durability",
+    "lootBonusDigger",
+    "arrowDamage",
+    "arrowKnockback",
+    "arrowFire",
+    "arrowInfinite",
+    "lootBonusFishing",
+    "fishingSpeed",
+    "frostwalker",
+    "mending",
+    "curse.binding",
+    "curse.vanishing",
+    "tridentImpaling",
+    "tridentRiptide",
+    "tridentLoyalty",
+    "tridentChanneling",
+    "crossbowMultishot",
+    "crossbowPiercing",
+    "crossbowQuickCharge",
+    "soul_speed",
+    "swift_sneak",
+];
+
+/// Returns the lines of the tooltip of an item: its name, followed by its enchantments and lore.
+/// The lines may contain formatting codes.
+pub fn tooltip(translations: &Translations, name: &str, stack: &ItemStack) -> Vec<String> {
+    let mut lines = vec![match stack.custom_name() {
+        Some(custom) => format!("{}o{}", FORMAT_CHAR, custom),
+        None => item_name(translations, name),
+    }];
+    for (id, level) in stack.enchantments() {
+        let enchantment = match ENCHANTMENTS.get(id as usize) {
+            Some(key) => translations
+                .get(&format!("enchantment.{}", key))
+                .unwrap_or(key)
+                .to_string(),
+            None => format!("enchantment {}", id),
+        };
+        let level = translations
+            .get(&format!("enchantment.level.{}", level))
+            .map(String::from)
+            .unwrap_or_else(|| level.to_string());
+        lines.push(format!("{}7{} {}", FORMAT_CHAR, enchantment, level));
+    }
+    for line in stack.lore() {
+        lines.push(format!("{}5{}", FORMAT_CHAR, line));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zuri_nbt::{tag, NBTTag};
+
+    use super::*;
+
+    #[test]
+    fn tooltips() {
+        let mut translations = Translations::default();
+        translations.parse(
+            "item.diamond_sword.name=Diamond Sword\nenchantment.damage.all=Sharpness\nenchantment.level.2=II",
+        );
+        let stack = ItemStack {
+            nbt_data: NBTTag::Compound(tag::Compound(HashMap::from([(
+                "ench".to_string(),
+                NBTTag::List(tag::List(vec![NBTTag::Compound(tag::Compound(
+                    HashMap::from([
+                        ("id".to_string(), NBTTag::Short(tag::Short(9))),
+                        ("lvl".to_string(), NBTTag::Short(tag::Short(2))),
+                    ]),
+                ))])),
+            )]))),
+            ..Default::default()
+        };
+        assert_eq!(
+            tooltip(&translations, "minecraft:diamond_sword", &stack),
+            ["Diamond Sword", "§7Sharpness II"]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::env;
+
+use bevy::prelude::*;
+use json::JsonValue;
+
+use crate::resource_pack::ResourcePacks;
+
+/// Loads the translations of the selected language from the resource packs.
+///
+/// The language is read from the `zuri_language` environment variable, or `en_US` if it is not
+/// set.
+pub(super) struct LangPlugin;
+
+impl Plugin for LangPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Translations::default())
+            .add_system(translations_build_system);
+    }
+}
+
+/// Maps translation keys to the text they are translated to, as read from `.lang` files.
+#[derive(Resource, Default, Debug)]
+pub struct Translations {
+    entries: HashMap<String, String>,
+}
+
+impl Translations {
+    /// Adds the translations from the contents of a `.lang` file, overriding any existing
+    /// translations with the same key.
+    ///
+    /// Each line holds a key and its translation separated by `=`. Lines starting with `##` are
+    /// comments, and a translation may be followed by a comment starting with a tab and `#`.
+    pub fn parse(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let line = line.trim_start_matches('\u{feff}');
+            if line.starts_with("##") {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = match value.find("\t#") {
+                Some(comment) => &value[..comment],
+                None => value,
+            };
+            self.entries
+                .insert(key.trim().to_string(), value.trim_end().to_string());
+        }
+    }
+
+    /// Returns the translation of a key, if there is one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Returns the translation of a key, or the default if there is none.
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Translates a message and fills in its parameters.
+    ///
+    /// If the message is a key, with or without a leading `%`, its translation is used. Otherwise
+    /// any `%key` inside the message is replaced by its translation, which is how servers combine
+    /// formatting codes with translated text. Parameters that are keys are translated as well, but
+    /// are otherwise left as they are.
+    pub fn translate(&self, message: &str, params: &[String]) -> String {
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| {
+                let key = param.strip_prefix('%').unwrap_or(param);
+                self.get(key).unwrap_or(param).to_string()
+            })
+            .collect();
+        match self.get(message.strip_prefix('%').unwrap_or(message)) {
+            Some(translation) => self.format(translation, &params),
+            None => self.format(&self.translate_inline(message), &params),
+        }
+    }
+
+    /// Replaces each `%key` in a message with its translation. Keys without a translation are
+    /// left as they are.
+    fn translate_inline(&self, message: &str) -> String {
+        let mut out = String::with_capacity(message.len());
+        let mut rest = message;
+        while let Some(start) = rest.find('%') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+                .unwrap_or(rest.len());
+            let key = rest[..end].trim_end_matches('.');
+            match self.get(key).filter(|_| !key.is_empty()) {
+                Some(translation) => {
+                    out.push_str(translation);
+                    rest = &rest[key.len()..];
+                }
+                None => out.push('%'),
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Fills the parameters into a translation. Parameters are referred to in order with `%s` or
+    /// `%d`, or by their position starting at 1 with `%1$s` or `%1`. `%%` is an escaped `%`.
+    /// Parameters that are not present are left empty.
+    pub fn format(&self, translation: &str, params: &[String]) -> String {
+        let param = |index: usize| params.get(index).map(String::as_str).unwrap_or_default();
+
+        let mut out = String::with_capacity(translation.len());
+        let mut next = 0;
+        let mut chars = translation.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.peek() {
+                Some('%') => {
+                    chars.next();
+                    out.push('%');
+                }
+                Some('s' | 'd') => {
+                    chars.next();
+                    out.push_str(param(next));
+                    next += 1;
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    let mut position = 0;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        position = position * 10 + digit as usize;
+                        chars.next();
+                    }
+                    if chars.peek() == Some(&'$') {
+                        chars.next();
+                        chars.next_if(|c| matches!(c, 's' | 'd'));
+                    }
+                    out.push_str(param(position.saturating_sub(1)));
+                }
+                _ => out.push('%'),
+            }
+        }
+        out
+    }
+
+    /// Resolves a raw text JSON object, as sent by commands such as `/tellraw`. Its components may
+    /// contain plain text, or a translation key with parameters.
+    pub fn resolve_raw_text(&self, raw_text: &JsonValue) -> String {
+        raw_text["rawtext"]
+            .members()
+            .map(|component| {
+                if let Some(text) = component["text"].as_str() {
+                    return text.to_string();
+                }
+                if let Some(key) = component["translate"].as_str() {
+                    let with = &component["with"];
+                    let params: Vec<String> = if with.is_array() {
+                        with.members()
+                            .filter_map(|param| param.as_str().map(String::from))
+                            .collect()
+                    } else if with.has_key("rawtext") {
+                        vec![self.resolve_raw_text(with)]
+                    } else {
+                        Vec::new()
+                    };
+                    return self.translate(key, &params);
+                }
+                component["selector"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+/// Reads the translations from the resource packs whenever they change. Packs with a higher
+/// priority override the translations of packs below them.
+fn translations_build_system(packs: Res<ResourcePacks>, mut translations: ResMut<Translations>) {
+    if !packs.is_changed() {
+        return;
+    }
+
+    let language = env::var("zuri_language").unwrap_or("en_US".into());
+    let path = format!("texts/{language}.lang");
+
+    *translations = Translations::default();
+    for contents in packs.iter().filter_map(|pack| pack.read(&path)) {
+        translations.parse(&String::from_utf8_lossy(&contents));
+    }
+    info!("Loaded {} translations", translations.entries.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations() -> Translations {
+        let mut translations = Translations::default();
+        translations.parse(
+            "## Comment\n\
+            chat.type.text=<%s> %s\t#\n\
+            death.attack.generic=%1$s died\n\
+            commands.give.success=Gave %2 * %1 to %3\n\
+            item.apple.name=Apple\n",
+        );
+        translations
+    }
+
+    #[test]
+    fn parse() {
+        let translations = translations();
+        assert_eq!(translations.get("chat.type.text"), Some("<%s> %s"));
+        assert_eq!(translations.get("## Comment"), None);
+    }
+
+    #[test]
+    fn translate() {
+        let translations = translations();
+        let params = ["Steve".to_string(), "Hi".to_string()];
+        assert_eq!(
+            translations.translate("chat.type.text", &params),
+            "<Steve> Hi"
+        );
+        assert_eq!(
+            translations.translate("%death.attack.generic", &params),
+            "Steve died"
+        );
+        assert_eq!(
+            translations.translate(
+                "commands.give.success",
+                &["%item.apple.name".into(), "3".into(), "Alex".into()]
+            ),
+            "Gave 3 * Apple to Alex"
+        );
+        assert_eq!(
+            translations.translate("§e%item.apple.name.", &[]),
+            "§eApple."
+        );
+        assert_eq!(
+            translations.translate("100%% %unknown", &[]),
+            "100% %unknown"
+        );
+    }
+
+    #[test]
+    fn raw_text() {
+        let translations = translations();
+        let raw_text = json::parse(
+            r#"{"rawtext":[{"text":"§a"},{"translate":"chat.type.text","with":["A","B"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(translations.resolve_raw_text(&raw_text), "§a<A> B");
+    }
+}
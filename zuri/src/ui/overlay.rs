@@ -0,0 +1,581 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use zuri_net::proto::packet::boss_event::{BossEvent, BossEventColour, BossEventType};
+use zuri_net::proto::packet::set_title::{SetTitle, TitleAction};
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::packet::toast_request::ToastRequest;
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::ui::chat::Chat;
+use crate::ui::lang::Translations;
+use crate::ui::text;
+use crate::ui::UiFont;
+
+/// Shows the boss bars at the top of the screen, titles and subtitles in the middle of the screen
+/// and toasts that pop up at the top. Action bar texts are shown where the popups of the chat are.
+pub(super) struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BossBars::default())
+            .insert_resource(Titles::default())
+            .insert_resource(Toasts::default())
+            .add_startup_system(overlay_setup_system)
+            .add_systems(
+                (
+                    boss_event_system,
+                    title_receive_system,
+                    toast_receive_system,
+                )
+                    .in_base_set(NetworkSet::Process),
+            )
+            .add_systems((
+                boss_bar_render_system,
+                title_render_system,
+                toast_render_system,
+            ))
+            .add_systems(
+                (
+                    reset_resource::<BossBars>,
+                    reset_resource::<Titles>,
+                    reset_resource::<Toasts>,
+                )
+                    .distributive_run_if(on_event::<Disconnected>()),
+            );
+    }
+}
+
+/// The size of a boss bar, in pixels.
+const BAR_WIDTH: f32 = 364.;
+const BAR_HEIGHT: f32 = 8.;
+/// The size of the font used for the titles of boss bars and for toasts, in pixels.
+const FONT_SIZE: f32 = 14.;
+const TITLE_FONT_SIZE: f32 = 48.;
+const SUBTITLE_FONT_SIZE: f32 = 24.;
+/// How long a toast is shown, in seconds.
+const TOAST_TIME: f64 = 5.;
+/// The colour of the part of boss bars that is not filled, and of the notches of the overlay.
+const BAR_BACKGROUND: Color = Color::rgba(0.15, 0.15, 0.15, 0.8);
+const NOTCH_COLOUR: Color = Color::rgba(0., 0., 0., 0.5);
+const TOAST_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.85);
+
+/// A boss bar shown at the top of the screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBar {
+    /// The unique ID of the entity the boss bar belongs to.
+    pub entity_unique_id: i64,
+    pub title: String,
+    /// How much of the bar is filled, from zero to one.
+    pub health: f32,
+    pub colour: BossEventColour,
+    /// The overlay of the bar, which divides it into segments. Zero means the bar is not divided.
+    pub overlay: u32,
+    /// Whether the sky turns dark while the boss bar is shown.
+    pub screen_darkening: bool,
+}
+
+impl BossBar {
+    /// Returns the number of segments the overlay divides the bar into, or zero if it is not
+    /// divided.
+    pub fn segments(&self) -> u32 {
+        match self.overlay {
+            1 => 6,
+            2 => 10,
+            3 => 12,
+            4 => 20,
+            _ => 0,
+        }
+    }
+}
+
+/// The boss bars currently shown, in the order they were first shown in.
+#[derive(Resource, Default, Debug)]
+pub struct BossBars {
+    bars: Vec<BossBar>,
+}
+
+impl BossBars {
+    /// Returns the boss bar of an entity.
+    pub fn get(&self, entity_unique_id: i64) -> Option<&BossBar> {
+        self.bars
+            .iter()
+            .find(|bar| bar.entity_unique_id == entity_unique_id)
+    }
+
+    /// Returns all boss bars currently shown.
+    pub fn iter(&self) -> impl Iterator<Item = &BossBar> {
+        self.bars.iter()
+    }
+
+    /// Shows, updates or hides a boss bar. Updates for boss bars that are not shown are ignored,
+    /// as are the events that are only sent by the client.
+    pub fn handle(&mut self, pk: &BossEvent) {
+        let id = pk.boss_entity_unique_id.0;
+        if let BossEventType::Show(show) = &pk.event_type {
+            let bar = BossBar {
+                entity_unique_id: id,
+                title: show.boss_bar_title.clone(),
+                health: show.health_percentage.clamp(0., 1.),
+                colour: show.colour.clone(),
+                overlay: show.overlay.0,
+                screen_darkening: show.screen_darkening != 0,
+            };
+            match self.bars.iter_mut().find(|bar| bar.entity_unique_id == id) {
+                Some(existing) => *existing = bar,
+                None => self.bars.push(bar),
+            }
+            return;
+        }
+        if let BossEventType::Hide = pk.event_type {
+            self.bars.retain(|bar| bar.entity_unique_id != id);
+            return;
+        }
+
+        let Some(bar) = self.bars.iter_mut().find(|bar| bar.entity_unique_id == id) else {
+            return;
+        };
+        match &pk.event_type {
+            BossEventType::HealthPercentage(health) => {
+                bar.health = health.health_percentage.clamp(0., 1.);
+            }
+            BossEventType::Title(title) => bar.title = title.boss_bar_title.clone(),
+            BossEventType::AppearanceProperties(appearance) => {
+                bar.screen_darkening = appearance.screen_darkening != 0;
+                bar.colour = appearance.colour.clone();
+                bar.overlay = appearance.overlay.0;
+            }
+            BossEventType::Texture(texture) => {
+                bar.colour = texture.colour.clone();
+                bar.overlay = texture.overlay.0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How long titles fade in, stay on the screen and fade out, in ticks.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TitleTimes {
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+impl Default for TitleTimes {
+    fn default() -> Self {
+        Self {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        }
+    }
+}
+
+impl TitleTimes {
+    /// Returns the opacity of a title that has been shown for a number of seconds, or None if it
+    /// has faded out completely.
+    pub fn alpha(&self, elapsed: f64) -> Option<f32> {
+        let ticks = (elapsed * 20.) as f32;
+        let (fade_in, stay, fade_out) = (
+            self.fade_in.max(0) as f32,
+            self.stay.max(0) as f32,
+            self.fade_out.max(0) as f32,
+        );
+        if ticks < fade_in {
+            Some(ticks / fade_in)
+        } else if ticks < fade_in + stay {
+            Some(1.)
+        } else if ticks < fade_in + stay + fade_out {
+            Some(1. - (ticks - fade_in - stay) / fade_out)
+        } else {
+            None
+        }
+    }
+}
+
+/// The title and subtitle shown in the middle of the screen.
+#[derive(Resource, Default, Debug)]
+pub struct Titles {
+    pub title: String,
+    /// The subtitle shown below the title. It is only shown along with a title.
+    pub subtitle: String,
+    pub times: TitleTimes,
+    /// The time at which the title was shown, in seconds since the start of the app, or None if
+    /// no title is shown.
+    shown: Option<f64>,
+}
+
+impl Titles {
+    /// Returns the opacity of the title at the current time, or None if no title is shown.
+    pub fn alpha(&self, now: f64) -> Option<f32> {
+        self.times.alpha(now - self.shown?)
+    }
+
+    /// Handles a SetTitle packet. Action bar texts are returned, as they are shown elsewhere.
+    fn handle(&mut self, pk: &SetTitle, now: f64, translations: &Translations) -> Option<String> {
+        // Text objects hold a raw text JSON object, like the texts sent by `/titleraw`.
+        let raw_text = || match json::parse(&pk.text) {
+            Ok(raw_text) => translations.resolve_raw_text(&raw_text),
+            Err(_) => pk.text.clone(),
+        };
+        match pk.action_type {
+            TitleAction::Clear => self.shown = None,
+            TitleAction::Reset => *self = Self::default(),
+            TitleAction::SetTitle | TitleAction::TitleTextObject => {
+                self.title = match pk.action_type {
+                    TitleAction::SetTitle => pk.text.clone(),
+                    _ => raw_text(),
+                };
+                self.shown = Some(now);
+            }
+            TitleAction::SetSubtitle => self.subtitle = pk.text.clone(),
+            TitleAction::SubtitleTextObject => self.subtitle = raw_text(),
+            TitleAction::SetDurations => {
+                // Negative durations leave the current duration unchanged.
+                let set = |time: &mut i32, new: i32| {
+                    if new >= 0 {
+                        *time = new;
+                    }
+                };
+                set(&mut self.times.fade_in, pk.fade_in_duration.0);
+                set(&mut self.times.stay, pk.remain_duration.0);
+                set(&mut self.times.fade_out, pk.fade_out_duration.0);
+            }
+            TitleAction::SetActionBar => return Some(pk.text.clone()),
+            TitleAction::ActionbarTextObject => return Some(raw_text()),
+        }
+        None
+    }
+}
+
+/// The toasts waiting to be shown and the toast currently shown.
+#[derive(Resource, Default, Debug)]
+pub struct Toasts {
+    queue: VecDeque<(String, String)>,
+    /// The title and message of the current toast, along with the time at which it was shown.
+    shown: Option<(String, String, f64)>,
+}
+
+impl Toasts {
+    /// Queues a toast, which is shown once the toasts before it have disappeared.
+    pub fn push(&mut self, title: impl Into<String>, message: impl Into<String>) {
+        self.queue.push_back((title.into(), message.into()));
+    }
+}
+
+/// Marks the node that holds the boss bars.
+#[derive(Component)]
+struct BossBarList;
+
+/// Marks the texts of the title and subtitle.
+#[derive(Component, Copy, Clone, PartialEq)]
+enum TitleText {
+    Title,
+    Subtitle,
+}
+
+/// Marks the toast and its text.
+#[derive(Component)]
+struct Toast;
+#[derive(Component)]
+struct ToastText;
+
+fn boss_event_system(
+    mut bars: ResMut<BossBars>,
+    mut start_game: EventReader<StartGame>,
+    mut events: EventReader<BossEvent>,
+) {
+    if start_game.iter().next().is_some() {
+        bars.bars.clear();
+    }
+    for pk in events.iter() {
+        bars.handle(pk);
+    }
+}
+
+fn title_receive_system(
+    time: Res<Time>,
+    translations: Res<Translations>,
+    mut titles: ResMut<Titles>,
+    mut chat: ResMut<Chat>,
+    mut events: EventReader<SetTitle>,
+) {
+    let now = time.elapsed_seconds_f64();
+    for pk in events.iter() {
+        if let Some(action_bar) = titles.handle(pk, now, &translations) {
+            chat.show_popup(now, action_bar);
+        }
+    }
+}
+
+fn toast_receive_system(
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+    mut events: EventReader<ToastRequest>,
+) {
+    for pk in events.iter() {
+        toasts.push(pk.title.clone(), pk.message.clone());
+    }
+
+    let now = time.elapsed_seconds_f64();
+    let expired = match &toasts.shown {
+        Some((_, _, shown)) => now - shown >= TOAST_TIME,
+        None => true,
+    };
+    if expired && (toasts.shown.is_some() || !toasts.queue.is_empty()) {
+        toasts.shown = toasts
+            .queue
+            .pop_front()
+            .map(|(title, message)| (title, message, now));
+    }
+}
+
+/// Spawns the nodes of the overlay. Boss bars are at the top of the screen, with toasts in the top
+/// right corner and titles in the middle.
+fn overlay_setup_system(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::top(Val::Px(4.)),
+                size: Size::width(Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        BossBarList,
+    ));
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::all(Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((TextBundle::default(), TitleText::Title));
+            parent.spawn((TextBundle::default(), TitleText::Subtitle));
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(4.),
+                        right: Val::Px(4.),
+                        ..default()
+                    },
+                    padding: UiRect::all(Val::Px(6.)),
+                    max_size: Size::width(Val::Px(240.)),
+                    ..default()
+                },
+                background_color: TOAST_BACKGROUND.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Toast,
+        ))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::default(), ToastText));
+        });
+}
+
+/// Rebuilds the boss bars whenever they change.
+fn boss_bar_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    bars: Res<BossBars>,
+    list: Query<Entity, With<BossBarList>>,
+) {
+    if !bars.is_changed() {
+        return;
+    }
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|list| {
+        for bar in bars.iter() {
+            list.spawn(TextBundle::from_sections(text::sections(
+                &bar.title,
+                text::Style::default(),
+                &font.0,
+                FONT_SIZE,
+            )));
+            list.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                    margin: UiRect::bottom(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: BAR_BACKGROUND.into(),
+                ..default()
+            })
+            .with_children(|background| {
+                background.spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(bar.health * 100.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    background_color: bar_colour(&bar.colour).into(),
+                    ..default()
+                });
+                let segments = bar.segments();
+                for i in 1..segments {
+                    background.spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect::left(Val::Percent(i as f32 * 100. / segments as f32)),
+                            size: Size::new(Val::Px(1.), Val::Percent(100.)),
+                            ..default()
+                        },
+                        background_color: NOTCH_COLOUR.into(),
+                        ..default()
+                    });
+                }
+            });
+        }
+    });
+}
+
+/// Shows the title and subtitle, fading them in and out.
+fn title_render_system(
+    time: Res<Time>,
+    font: Res<UiFont>,
+    titles: Res<Titles>,
+    mut texts: Query<(&mut Text, &mut Visibility, &TitleText)>,
+) {
+    let alpha = titles.alpha(time.elapsed_seconds_f64());
+    for (mut text, mut visibility, kind) in &mut texts {
+        let Some(alpha) = alpha else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        if titles.is_changed() || font.is_changed() || text.sections.is_empty() {
+            let (content, size) = match kind {
+                TitleText::Title => (&titles.title, TITLE_FONT_SIZE),
+                TitleText::Subtitle => (&titles.subtitle, SUBTITLE_FONT_SIZE),
+            };
+            text.sections = text::sections(content, text::Style::default(), &font.0, size);
+        }
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+/// Shows the current toast, if any.
+fn toast_render_system(
+    font: Res<UiFont>,
+    toasts: Res<Toasts>,
+    mut toast: Query<&mut Visibility, With<Toast>>,
+    mut text: Query<&mut Text, With<ToastText>>,
+) {
+    if !toasts.is_changed() {
+        return;
+    }
+    let (Ok(mut visibility), Ok(mut text)) = (toast.get_single_mut(), text.get_single_mut()) else {
+        return;
+    };
+    let Some((title, message, _)) = &toasts.shown else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let title_style = text::Style {
+        colour: text::colour('e').unwrap(),
+        ..default()
+    };
+    let mut sections = text::sections(title, title_style, &font.0, FONT_SIZE);
+    if !message.is_empty() {
+        sections.push(TextSection::new("\n", TextStyle::default()));
+        sections.extend(text::sections(
+            message,
+            text::Style::default(),
+            &font.0,
+            FONT_SIZE,
+        ));
+    }
+    text.sections = sections;
+}
+
+/// Returns the colour a boss bar is filled with.
+fn bar_colour(colour: &BossEventColour) -> Color {
+    match colour {
+        BossEventColour::Grey => Color::rgb(0.6, 0.6, 0.6),
+        BossEventColour::Blue => Color::rgb(0.2, 0.6, 0.9),
+        BossEventColour::Red => Color::rgb(0.9, 0.2, 0.2),
+        BossEventColour::Green => Color::rgb(0.2, 0.85, 0.2),
+        BossEventColour::Yellow => Color::rgb(0.95, 0.85, 0.2),
+        BossEventColour::Purple => Color::rgb(0.7, 0.2, 0.9),
+        BossEventColour::White => Color::WHITE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zuri_net::proto::ints::{VarI64, VarU32};
+    use zuri_net::proto::packet::boss_event::{BossEventHealthPercentage, BossEventShow};
+
+    use super::*;
+
+    #[test]
+    fn boss_bars() {
+        let event = |event_type| BossEvent {
+            boss_entity_unique_id: VarI64(5),
+            event_type,
+        };
+        let mut bars = BossBars::default();
+        bars.handle(&event(BossEventType::HealthPercentage(
+            BossEventHealthPercentage {
+                health_percentage: 0.5,
+            },
+        )));
+        assert!(bars.get(5).is_none());
+
+        bars.handle(&event(BossEventType::Show(BossEventShow {
+            boss_bar_title: "Wither".into(),
+            health_percentage: 1.,
+            screen_darkening: 0,
+            colour: BossEventColour::Purple,
+            overlay: VarU32(2),
+        })));
+        bars.handle(&event(BossEventType::HealthPercentage(
+            BossEventHealthPercentage {
+                health_percentage: 0.25,
+            },
+        )));
+        let bar = bars.get(5).unwrap();
+        assert_eq!(bar.health, 0.25);
+        assert_eq!(bar.segments(), 10);
+
+        bars.handle(&event(BossEventType::Hide));
+        assert_eq!(bars.iter().count(), 0);
+    }
+
+    #[test]
+    fn title_fading() {
+        let times = TitleTimes {
+            fade_in: 20,
+            stay: 20,
+            fade_out: 20,
+        };
+        assert_eq!(times.alpha(0.5), Some(0.5));
+        assert_eq!(times.alpha(1.5), Some(1.));
+        assert_eq!(times.alpha(2.5), Some(0.5));
+        assert_eq!(times.alpha(3.5), None);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use uuid::Uuid;
+use zuri_net::proto::packet::player_list::{PlayerList, PlayerListAction};
+use zuri_net::proto::packet::remove_objective::RemoveObjective;
+use zuri_net::proto::packet::set_display_objective::SetDisplayObjective;
+use zuri_net::proto::packet::set_score::SetScore;
+use zuri_net::proto::packet::set_scoreboard_identity::SetScoreboardIdentity;
+use zuri_net::proto::packet::start_game::StartGame;
+use zuri_net::proto::types::scoreboard::ScoreboardSlot;
+use zuri_net::scoreboard::{Entry, Identity, Scoreboard};
+
+use crate::client::{reset_resource, Disconnected, NetworkSet};
+use crate::entity::metadata::Metadata;
+use crate::entity::nametag::NametagScore;
+use crate::entity::{EntityManager, IdentifiableEntity};
+use crate::player;
+use crate::ui::text::{self, colour};
+use crate::ui::UiFont;
+
+/// Shows the objective displayed in the sidebar on the right of the screen, and the scores of the
+/// objective displayed below names in the nametags of entities.
+pub(super) struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerScoreboard::default())
+            .insert_resource(PlayerNames::default())
+            .add_startup_system(sidebar_setup_system)
+            .add_systems(
+                (scoreboard_update_system, player_names_system).in_base_set(NetworkSet::Process),
+            )
+            .add_systems((sidebar_render_system, below_name_system))
+            .add_systems(
+                (
+                    reset_resource::<PlayerScoreboard>,
+                    reset_resource::<PlayerNames>,
+                )
+                    .distributive_run_if(on_event::<Disconnected>()),
+            );
+    }
+}
+
+/// The most entries shown in the sidebar. Vanilla does not show the entries sorted after these.
+const SIDEBAR_ENTRIES: usize = 15;
+/// The size of the font used in the sidebar, in pixels.
+const FONT_SIZE: f32 = 14.;
+const BACKGROUND: Color = Color::rgba(0., 0., 0., 0.4);
+const TITLE_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.55);
+
+/// The scoreboard of the local player.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PlayerScoreboard(pub Scoreboard);
+
+/// The names of players in the player list by their UUID, along with their unique entity ID. Scores
+/// of players only refer to their unique entity ID, so their names are looked up here.
+#[derive(Resource, Default)]
+struct PlayerNames(HashMap<Uuid, (i64, String)>);
+
+impl PlayerNames {
+    fn get(&self, unique_id: i64) -> Option<&str> {
+        self.0
+            .values()
+            .find(|(id, _)| *id == unique_id)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// The node that holds the title and the entries of the sidebar.
+#[derive(Component)]
+struct Sidebar;
+
+fn scoreboard_update_system(
+    mut scoreboard: ResMut<PlayerScoreboard>,
+    mut start_game: EventReader<StartGame>,
+    mut display: EventReader<SetDisplayObjective>,
+    mut remove: EventReader<RemoveObjective>,
+    mut scores: EventReader<SetScore>,
+    mut identities: EventReader<SetScoreboardIdentity>,
+) {
+    if start_game.iter().next().is_some() {
+        scoreboard.clear();
+    }
+    for pk in display.iter() {
+        scoreboard.handle_set_display_objective(pk);
+    }
+    for pk in scores.iter() {
+        scoreboard.handle_set_score(pk);
+    }
+    for pk in identities.iter() {
+        scoreboard.handle_set_scoreboard_identity(pk);
+    }
+    for pk in remove.iter() {
+        scoreboard.handle_remove_objective(pk);
+    }
+}
+
+fn player_names_system(mut names: ResMut<PlayerNames>, mut list: EventReader<PlayerList>) {
+    for pk in list.iter() {
+        match &pk.action_type {
+            PlayerListAction::Add(add) => {
+                for entry in &add.entries {
+                    names.0.insert(
+                        entry.uuid,
+                        (entry.entity_unique_id.0, entry.username.clone()),
+                    );
+                }
+            }
+            PlayerListAction::Remove(remove) => {
+                for uuid in &remove.uuids {
+                    names.0.remove(uuid);
+                }
+            }
+        }
+    }
+}
+
+fn sidebar_setup_system(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(4.),
+                    top: Val::Percent(30.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                min_size: Size::width(Val::Px(100.)),
+                ..default()
+            },
+            background_color: BACKGROUND.into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Sidebar,
+    ));
+}
+
+/// Rebuilds the sidebar whenever the scoreboard or the names of players change.
+fn sidebar_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    scoreboard: Res<PlayerScoreboard>,
+    names: Res<PlayerNames>,
+    entities: Res<EntityManager>,
+    metadata: Query<&Metadata>,
+    mut sidebar: Query<(Entity, &mut Visibility), With<Sidebar>>,
+) {
+    if !scoreboard.is_changed() && !names.is_changed() {
+        return;
+    }
+    let Ok((root, mut visibility)) = sidebar.get_single_mut() else {
+        return;
+    };
+    commands.entity(root).despawn_descendants();
+    let Some(objective) = scoreboard.displayed(ScoreboardSlot::Sidebar) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let text = |content: &str, base: text::Style| {
+        TextBundle::from_sections(text::sections(content, base, &font.0, FONT_SIZE))
+    };
+    let entry_name = |entry: &Entry| match &entry.identity {
+        Identity::Player(id) => names.get(*id).unwrap_or_default().to_string(),
+        Identity::Entity(id) => entities
+            .entity_by_uid(*id)
+            .and_then(|entity| metadata.get(entity).ok())
+            .and_then(|metadata| metadata.name_tag().map(str::to_string))
+            .unwrap_or_default(),
+        Identity::FakePlayer(name) => name.clone(),
+    };
+    let score_style = text::Style {
+        colour: colour('c').unwrap(),
+        ..default()
+    };
+
+    commands.entity(root).with_children(|sidebar| {
+        sidebar
+            .spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    padding: UiRect::all(Val::Px(2.)),
+                    ..default()
+                },
+                background_color: TITLE_BACKGROUND.into(),
+                ..default()
+            })
+            .with_children(|title| {
+                title.spawn(text(&objective.display_name, default()));
+            });
+        for entry in objective.entries().into_iter().take(SIDEBAR_ENTRIES) {
+            sidebar
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        gap: Size::width(Val::Px(12.)),
+                        padding: UiRect::horizontal(Val::Px(2.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(text(&entry_name(entry), default()));
+                    row.spawn(text(&entry.score.to_string(), score_style));
+                });
+        }
+    });
+}
+
+/// Shows the scores of the objective displayed below names in the nametags of entities. The local
+/// player has no nametag, so its score is not shown.
+fn below_name_system(
+    mut commands: Commands,
+    scoreboard: Res<PlayerScoreboard>,
+    added: Query<(), Added<IdentifiableEntity>>,
+    mut entities: Query<
+        (Entity, &IdentifiableEntity, Option<&mut NametagScore>),
+        Without<player::Local>,
+    >,
+) {
+    if !scoreboard.is_changed() && added.is_empty() {
+        return;
+    }
+    let objective = scoreboard.displayed(ScoreboardSlot::BelowName);
+    for (entity, identity, score) in &mut entities {
+        let line = objective
+            .and_then(|objective| {
+                let score = objective.score_of(identity.unique_id().into())?;
+                Some(format!("{} {}", score, objective.display_name))
+            })
+            .unwrap_or_default();
+        match score {
+            Some(mut score) if score.0 != line => score.0 = line,
+            None if !line.is_empty() => {
+                commands.entity(entity).insert(NametagScore(line));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use futures_lite::future;
+use json::JsonValue;
+use tokio::net::lookup_host;
+use tokio::task::JoinHandle;
+use zuri_net::ping::{ping, Pong};
+
+use crate::client::{Connect, ConnectionState};
+use crate::ui::{button_colour_system, spawn_button, spawn_text, UiFont};
+
+/// Shows the server list while the client is not connected to a server. Servers in the list are
+/// pinged to show their status and latency, and any address can be connected to directly by typing
+/// it in the direct connect box.
+pub(super) struct ServerListPlugin;
+
+impl Plugin for ServerListPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ServerList::load(SERVERS_FILE))
+            .insert_non_send_resource(PingTasks::default())
+            .add_systems(
+                (
+                    server_list_input_system,
+                    ping_system,
+                    server_list_render_system,
+                    button_colour_system::<ServerListAction>,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The file the servers of the server list are saved to.
+const SERVERS_FILE: &str = "servers.json";
+/// The port used when an address of a server has none.
+const DEFAULT_PORT: u16 = 19132;
+/// How long to wait for a server to answer a ping.
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The size of the title of the server list, in pixels.
+const TITLE_SIZE: f32 = 32.;
+/// The width of the server list, in pixels.
+const LIST_WIDTH: f32 = 600.;
+/// The colour behind the server list.
+const BACKGROUND: Color = Color::rgb(0.08, 0.08, 0.1);
+/// The colour of the direct connect box.
+const INPUT: Color = Color::rgb(0.15, 0.15, 0.18);
+
+/// A server in the server list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerEntry {
+    /// The name shown for the server.
+    pub name: String,
+    /// The address of the server, as a host name or an IP address followed by a port.
+    pub address: String,
+}
+
+/// How a server answered its last ping.
+#[derive(Debug, Clone)]
+enum PingStatus {
+    Pinging,
+    Online(Pong),
+    Offline(String),
+}
+
+/// The servers of the server list, along with their status.
+#[derive(Resource, Default)]
+pub struct ServerList {
+    entries: Vec<ServerEntry>,
+    /// The status of the servers by their address.
+    statuses: HashMap<String, PingStatus>,
+    /// The address typed in the direct connect box.
+    input: String,
+    /// The file the servers are saved to.
+    path: String,
+}
+
+impl ServerList {
+    /// Reads the server list from a file, starting with an empty list if the file does not exist
+    /// or cannot be read.
+    fn load(path: &str) -> Self {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|file| json::parse(&file).ok())
+            .map(|file| parse_entries(&file))
+            .unwrap_or_default();
+        Self {
+            entries,
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Writes the server list to its file.
+    fn save(&self) {
+        let file = json::stringify_pretty(entries_json(&self.entries), 4);
+        if let Err(err) = fs::write(&self.path, file) {
+            error!("Could not save the server list to {}: {err}", self.path);
+        }
+    }
+
+    /// Adds the address in the direct connect box to the list, unless it is already in the list.
+    fn add_input(&mut self) {
+        let address = with_default_port(self.input.trim());
+        self.input.clear();
+        if address.is_empty() || self.entries.iter().any(|entry| entry.address == address) {
+            return;
+        }
+        self.entries.push(ServerEntry {
+            name: address.clone(),
+            address,
+        });
+        self.save();
+    }
+
+    /// Removes a server from the list.
+    fn remove(&mut self, index: usize) {
+        if index < self.entries.len() {
+            let entry = self.entries.remove(index);
+            self.statuses.remove(&entry.address);
+            self.save();
+        }
+    }
+}
+
+/// Reads the servers of a server list file, which is an array of objects with a name and an
+/// address. Servers without an address are skipped.
+fn parse_entries(file: &JsonValue) -> Vec<ServerEntry> {
+    file.members()
+        .filter_map(|entry| {
+            let address = entry["address"].as_str()?;
+            Some(ServerEntry {
+                name: entry["name"].as_str().unwrap_or(address).to_string(),
+                address: address.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Writes the servers of a server list in the format read by [parse_entries].
+fn entries_json(entries: &[ServerEntry]) -> JsonValue {
+    JsonValue::Array(
+        entries
+            .iter()
+            .map(|entry| {
+                json::object! {
+                    name: entry.name.clone(),
+                    address: entry.address.clone(),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Adds the default port to an address without a port. IPv6 addresses without a port may be
+/// written without brackets.
+fn with_default_port(address: &str) -> String {
+    if address.is_empty() {
+        return String::new();
+    }
+    if let Some(host) = address.strip_prefix('[') {
+        return match host.split_once("]:") {
+            Some(_) => address.to_string(),
+            None => format!("[{}]:{DEFAULT_PORT}", host.trim_end_matches(']')),
+        };
+    }
+    match address.matches(':').count() {
+        0 => format!("{address}:{DEFAULT_PORT}"),
+        1 => address.to_string(),
+        _ => format!("[{address}]:{DEFAULT_PORT}"),
+    }
+}
+
+/// The pings that are waiting for an answer, along with the address of the server they were sent
+/// to. Tokio tasks can only be spawned from the main thread, which is why this is not a resource.
+#[derive(Default)]
+struct PingTasks(Vec<(String, JoinHandle<io::Result<Pong>>)>);
+
+impl PingTasks {
+    /// Pings every server of the server list.
+    fn ping_all(&mut self, list: &mut ServerList) {
+        for task in self.0.drain(..) {
+            task.1.abort();
+        }
+        for entry in &list.entries {
+            let address = entry.address.clone();
+            list.statuses.insert(address.clone(), PingStatus::Pinging);
+            self.0.push((
+                address.clone(),
+                tokio::spawn(async move {
+                    let ip = lookup_host(&address).await?.next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::NotFound, "address not found")
+                    })?;
+                    ping(ip, PING_TIMEOUT).await
+                }),
+            ));
+        }
+    }
+}
+
+/// The actions of the buttons of the server list.
+#[derive(Component, Copy, Clone)]
+enum ServerListAction {
+    /// Connects to the server at an index of the list.
+    Join(usize),
+    /// Removes the server at an index from the list.
+    Remove(usize),
+    /// Connects to the address in the direct connect box.
+    Connect,
+    /// Adds the address in the direct connect box to the list.
+    Add,
+    Refresh,
+    Quit,
+}
+
+/// Marks the root of the user interface of the server list.
+#[derive(Component)]
+struct ServerListRoot;
+
+/// Handles the buttons of the server list, and typing in the direct connect box.
+fn server_list_input_system(
+    state: Res<ConnectionState>,
+    mut list: ResMut<ServerList>,
+    mut tasks: NonSendMut<PingTasks>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut connects: EventWriter<Connect>,
+    mut exits: EventWriter<AppExit>,
+    buttons: Query<(&Interaction, &ServerListAction), Changed<Interaction>>,
+) {
+    if *state != ConnectionState::Idle {
+        chars.clear();
+        return;
+    }
+    let mut connect = |list: &mut ServerList, address: String| {
+        list.input.clear();
+        connects.send(Connect(address));
+    };
+
+    let typed: Vec<_> = chars.iter().map(|event| event.char).collect();
+    for c in typed {
+        match c {
+            '\u{8}' => {
+                list.input.pop();
+            }
+            '\r' | '\n' => {
+                let address = with_default_port(list.input.trim());
+                if !address.is_empty() {
+                    connect(&mut list, address);
+                }
+            }
+            c if !c.is_control() => list.input.push(c),
+            _ => {}
+        }
+    }
+
+    for (interaction, action) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        match *action {
+            ServerListAction::Join(index) => {
+                if let Some(entry) = list.entries.get(index) {
+                    let address = entry.address.clone();
+                    connect(&mut list, address);
+                }
+            }
+            ServerListAction::Remove(index) => list.remove(index),
+            ServerListAction::Connect => {
+                let address = with_default_port(list.input.trim());
+                if !address.is_empty() {
+                    connect(&mut list, address);
+                }
+            }
+            ServerListAction::Add => {
+                list.add_input();
+                tasks.ping_all(&mut list);
+            }
+            ServerListAction::Refresh => tasks.ping_all(&mut list),
+            ServerListAction::Quit => exits.send(AppExit),
+        }
+    }
+}
+
+/// Pings the servers of the list whenever the server list is shown, and collects the answers.
+fn ping_system(
+    state: Res<ConnectionState>,
+    mut list: ResMut<ServerList>,
+    mut tasks: NonSendMut<PingTasks>,
+) {
+    if state.is_changed() && *state == ConnectionState::Idle {
+        tasks.ping_all(&mut list);
+    }
+    let mut index = 0;
+    while index < tasks.0.len() {
+        let Some(result) = future::block_on(future::poll_once(&mut tasks.0[index].1)) else {
+            index += 1;
+            continue;
+        };
+        let (address, _) = tasks.0.remove(index);
+        let status = match result {
+            Ok(Ok(pong)) => PingStatus::Online(pong),
+            Ok(Err(err)) => PingStatus::Offline(err.to_string()),
+            Err(err) => PingStatus::Offline(err.to_string()),
+        };
+        list.statuses.insert(address, status);
+    }
+}
+
+/// Returns the text that describes the status of a server.
+fn status_text(status: Option<&PingStatus>) -> String {
+    match status {
+        None | Some(PingStatus::Pinging) => "§7Pinging...".into(),
+        Some(PingStatus::Online(pong)) => format!(
+            "§r{}\n§7{}/{} players, {} ({}), {} ms",
+            pong.status.motd,
+            pong.status.player_count,
+            pong.status.max_player_count,
+            pong.status.version,
+            pong.status.game_mode,
+            pong.latency.as_millis()
+        ),
+        Some(PingStatus::Offline(err)) => format!("§cCould not reach the server: {err}"),
+    }
+}
+
+/// Rebuilds the user interface of the server list whenever it changes, and removes it once the
+/// client connects to a server.
+fn server_list_render_system(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    state: Res<ConnectionState>,
+    list: Res<ServerList>,
+    roots: Query<Entity, With<ServerListRoot>>,
+) {
+    if !state.is_changed() && !list.is_changed() && !font.is_changed() {
+        return;
+    }
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    if *state != ConnectionState::Idle {
+        return;
+    }
+
+    let font = &font.0;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::all(Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::height(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: BACKGROUND.into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            ServerListRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Servers",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: TITLE_SIZE,
+                    color: Color::WHITE,
+                },
+            ));
+            if list.entries.is_empty() {
+                spawn_text(
+                    parent,
+                    "§7No servers yet. Add one with the box below.",
+                    default(),
+                    font,
+                );
+            }
+            for (index, entry) in list.entries.iter().enumerate() {
+                let status = status_text(list.statuses.get(&entry.address));
+                parent.spawn(row()).with_children(|row| {
+                    spawn_button(
+                        row,
+                        &format!("{}\n{}", entry.name, status),
+                        ServerListAction::Join(index),
+                        button_style(LIST_WIDTH - 100.),
+                        font,
+                    );
+                    spawn_button(
+                        row,
+                        "Remove",
+                        ServerListAction::Remove(index),
+                        button_style(92.),
+                        font,
+                    );
+                });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Px(LIST_WIDTH)),
+                        padding: UiRect::all(Val::Px(6.)),
+                        margin: UiRect::top(Val::Px(16.)),
+                        ..default()
+                    },
+                    background_color: INPUT.into(),
+                    ..default()
+                })
+                .with_children(|input| {
+                    let shown = if list.input.is_empty() {
+                        "§7Type an address to connect to...".to_string()
+                    } else {
+                        format!("{}_", list.input)
+                    };
+                    spawn_text(input, &shown, default(), font);
+                });
+            parent.spawn(row()).with_children(|row| {
+                let width = (LIST_WIDTH - 24.) / 4.;
+                for (label, action) in [
+                    ("Connect", ServerListAction::Connect),
+                    ("Add Server", ServerListAction::Add),
+                    ("Refresh", ServerListAction::Refresh),
+                    ("Quit", ServerListAction::Quit),
+                ] {
+                    spawn_button(row, label, action, button_style(width), font);
+                }
+            });
+        });
+}
+
+/// Returns the node of a row of the server list.
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::width(Val::Px(LIST_WIDTH)),
+            justify_content: JustifyContent::SpaceBetween,
+            ..default()
+        },
+        ..default()
+    }
+}
+
+/// Returns the style of a button of the server list that is `width` pixels wide.
+fn button_style(width: f32) -> Style {
+    Style {
+        padding: UiRect::all(Val::Px(6.)),
+        size: Size::width(Val::Px(width)),
+        ..default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses() {
+        assert_eq!(with_default_port(""), "");
+        assert_eq!(
+            with_default_port("play.example.net"),
+            "play.example.net:19132"
+        );
+        assert_eq!(with_default_port("127.0.0.1:19133"), "127.0.0.1:19133");
+        assert_eq!(with_default_port("::1"), "[::1]:19132");
+        assert_eq!(with_default_port("[::1]"), "[::1]:19132");
+        assert_eq!(with_default_port("[::1]:19133"), "[::1]:19133");
+    }
+
+    #[test]
+    fn entries() {
+        let entries = vec![
+            ServerEntry {
+                name: "Local".into(),
+                address: "127.0.0.1:19132".into(),
+            },
+            ServerEntry {
+                name: "Example".into(),
+                address: "play.example.net:19132".into(),
+            },
+        ];
+        assert_eq!(parse_entries(&entries_json(&entries)), entries);
+
+        let file = json::parse(r#"[{"address": "a:1"}, {"name": "No address"}]"#).unwrap();
+        assert_eq!(
+            parse_entries(&file),
+            vec![ServerEntry {
+                name: "a:1".into(),
+                address: "a:1".into(),
+            }]
+        );
+    }
+}
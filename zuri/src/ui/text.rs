@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+/// The character that starts a formatting code, such as `§c` for red text.
+pub const FORMAT_CHAR: char = '§';
+
+/// The style of a piece of formatted text.
+///
+/// Bold, italic and obfuscated text is parsed, but rendered like regular text, as only a single
+/// font is available.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Style {
+    pub colour: Color,
+    pub bold: bool,
+    pub italic: bool,
+    pub obfuscated: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            colour: Color::WHITE,
+            bold: false,
+            italic: false,
+            obfuscated: false,
+        }
+    }
+}
+
+/// A piece of text that has the same style throughout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Returns the colour of a colour code, or None if the code is not a colour. Besides the classic
+/// colours, Bedrock Edition has codes for the colours of materials, like `§q` for emerald.
+pub fn colour(code: char) -> Option<Color> {
+    let hex = match code {
+        '0' => 0x000000,
+        '1' => 0x0000AA,
+        '2' => 0x00AA00,
+        '3' => 0x00AAAA,
+        '4' => 0xAA0000,
+        '5' => 0xAA00AA,
+        '6' => 0xFFAA00,
+        '7' => 0xAAAAAA,
+        '8' => 0x555555,
+        '9' => 0x5555FF,
+        'a' => 0x55FF55,
+        'b' => 0x55FFFF,
+        'c' => 0xFF5555,
+        'd' => 0xFF55FF,
+        'e' => 0xFFFF55,
+        'f' => 0xFFFFFF,
+        'g' => 0xDDD605,
+        'h' => 0xE3D4D1,
+        'i' => 0xCECACA,
+        'j' => 0x443A3B,
+        'm' => 0x971607,
+        'n' => 0xB4684D,
+        'p' => 0xDEB12D,
+        'q' => 0x47A036,
+        's' => 0x2CBAA8,
+        't' => 0x21497B,
+        'u' => 0x9A5CC6,
+        _ => return None,
+    };
+    Some(Color::rgb_u8(
+        (hex >> 16) as u8,
+        (hex >> 8) as u8,
+        hex as u8,
+    ))
+}
+
+/// Splits text with formatting codes into spans of the same style. A colour code resets the
+/// formatting, like it does in the game. Unknown codes are left out.
+pub fn parse(text: &str, base: Style) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut current = Span {
+        text: String::new(),
+        style: base,
+    };
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != FORMAT_CHAR {
+            current.text.push(c);
+            continue;
+        }
+        let Some(code) = chars.next() else {
+            break;
+        };
+
+        let mut style = current.style;
+        match code.to_ascii_lowercase() {
+            'k' => style.obfuscated = true,
+            'l' => style.bold = true,
+            'o' => style.italic = true,
+            'r' => style = base,
+            code => match colour(code) {
+                Some(colour) => {
+                    style = Style { colour, ..base };
+                }
+                None => continue,
+            },
+        }
+        if style != current.style {
+            let previous = std::mem::replace(
+                &mut current,
+                Span {
+                    text: String::new(),
+                    style,
+                },
+            );
+            if !previous.text.is_empty() {
+                spans.push(previous);
+            }
+        }
+    }
+    if !current.text.is_empty() {
+        spans.push(current);
+    }
+    spans
+}
+
+/// Removes all formatting codes from text.
+pub fn strip(text: &str) -> String {
+    parse(text, Style::default())
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
+
+/// Creates text sections for formatted text, which can be used in a [Text].
+pub fn sections(text: &str, base: Style, font: &Handle<Font>, font_size: f32) -> Vec<TextSection> {
+    parse(text, base)
+        .into_iter()
+        .map(|span| {
+            TextSection::new(
+                span.text,
+                TextStyle {
+                    font: font.clone(),
+                    font_size,
+                    color: span.style.colour,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_codes() {
+        let spans = parse("a§cb§lc§rd§ze§", Style::default());
+        let texts: Vec<_> = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(texts, ["a", "b", "c", "de"]);
+
+        assert_eq!(spans[1].style.colour, colour('c').unwrap());
+        assert!(!spans[1].style.bold);
+        assert!(spans[2].style.bold);
+        assert_eq!(spans[2].style.colour, colour('c').unwrap());
+        assert_eq!(spans[3].style, Style::default());
+    }
+
+    #[test]
+    fn colour_resets_format() {
+        let spans = parse("§l§oa§eb", Style::default());
+        assert!(spans[0].style.bold && spans[0].style.italic);
+        assert!(!spans[1].style.bold && !spans[1].style.italic);
+    }
+
+    #[test]
+    fn strip_codes() {
+        assert_eq!(strip("§a§lHello §rworld"), "Hello world");
+    }
+}
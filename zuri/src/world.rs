@@ -1,21 +1,39 @@
 pub mod component;
+mod geometry;
+mod layer;
+mod material;
 mod mesh;
-
-use crate::client::NetworkSet;
+pub mod texture;
+
+use crate::client::{Disconnected, NetworkSet};
+use crate::entity::IdentifiableEntity;
+use crate::resource_pack::ResourcePacks;
+use crate::world::component::RenderLayer;
+use crate::world::mesh::{SectionBlocks, SectionMeshes, SECTION_SIZE};
+use crate::world::texture::{BlockAtlas, FaceTextures};
+use crate::{model, player};
 use bevy::prelude::World as ECSWorld;
 use bevy::prelude::*;
-use bevy::render::mesh::PrimitiveTopology;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use zuri_net::proto::io::Reader;
+use zuri_net::proto::io::{Reader, UBlockPos};
+use zuri_net::proto::packet::change_dimension::ChangeDimension;
+use zuri_net::proto::packet::dimension_data::DimensionData;
 use zuri_net::proto::packet::level_chunk::LevelChunk;
 use zuri_net::proto::packet::network_chunk_publisher_update::NetworkChunkPublisherUpdate;
+use zuri_net::proto::packet::player_action::PlayerAction;
 use zuri_net::proto::packet::start_game::StartGame;
 use zuri_net::proto::packet::update_block::UpdateBlock;
-use zuri_world::block;
+use zuri_net::proto::packet::Packet;
+use zuri_net::proto::types::player::PlayerActionType;
+use zuri_net::proto::types::world::Dimension;
 use zuri_world::block::component::ComponentStorageType;
-use zuri_world::block::{BlockBuilder, BlockMap, BlockMapBuilder, BlockType, PropertyValues};
+use zuri_world::block::{
+    BlockBuilder, BlockMap, BlockMapBuilder, BlockType, PropertyValues, ToRuntimeId,
+};
 use zuri_world::chunk::{Chunk, ChunkPos};
 use zuri_world::pos::ChunkIndex;
 use zuri_world::range::YRange;
@@ -25,35 +43,62 @@ pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(
-            BlockMapBuilder::vanilla()
-                .with_component_type::<component::Geometry>(ComponentStorageType::Vector)
-                .with_build_function(|block_map| {
-                    // Hard-code air for now.
-                    block_map.set_component(
-                        BlockBuilder::new(block::AIR_ID),
-                        component::Geometry {
-                            mesh: Mesh::new(PrimitiveTopology::TriangleList),
-                        },
-                    )
-                }),
-        )
-        .insert_resource(BlockTextures::default())
-        .insert_resource(ChunkManager::default())
-        // Startup systems
-        .add_startup_system(textures_init_system)
-        // Systems
-        .add_system(build_block_map_system)
-        .add_systems((
-            chunk_unload_system.in_base_set(CoreSet::FixedUpdate),
-            update_chunk_radius_system.in_base_set(NetworkSet::Process),
-            chunk_update_system.in_base_set(CoreSet::PostUpdate),
-            block_update_system.in_base_set(CoreSet::PreUpdate),
-        ))
-        .add_systems((chunk_load_system.run_if(world_is_loaded),));
+        app.insert_resource(block_map_builder())
+            .insert_resource(BlockAtlas::default())
+            .insert_resource(ChunkManager::default())
+            .insert_resource(Dimensions::default())
+            .insert_resource(DimensionChange::default())
+            .insert_resource(MeshQueue::default())
+            // Startup systems
+            .add_startup_system(texture::atlas_init_system)
+            // Systems
+            .add_system(texture::atlas_build_system)
+            .add_system(build_block_map_system)
+            .add_system(block_map_reload_system.after(build_block_map_system))
+            .add_systems((
+                chunk_unload_system.in_base_set(CoreSet::FixedUpdate),
+                update_chunk_radius_system.in_base_set(NetworkSet::Process),
+                dimension_data_system.in_base_set(NetworkSet::Process),
+                dimension_change_system
+                    .run_if(world_is_loaded)
+                    .in_base_set(NetworkSet::Process),
+                world_reset_system
+                    .run_if(on_event::<Disconnected>())
+                    .in_base_set(NetworkSet::Process),
+                block_update_system
+                    .run_if(world_is_loaded)
+                    .in_base_set(CoreSet::PreUpdate),
+            ))
+            .add_systems(
+                (
+                    chunk_update_system,
+                    mesh_dispatch_system.run_if(world_is_loaded),
+                    mesh_collect_system,
+                    translucent_sort_system,
+                )
+                    .chain()
+                    .in_base_set(CoreSet::PostUpdate),
+            )
+            .add_systems((
+                chunk_load_system.run_if(world_is_loaded),
+                dimension_ack_system,
+            ));
+        material::register(app);
     }
 }
 
+/// The custom blocks sent by the server in the StartGame packet, which are kept so that the
+/// [BlockMap] can be rebuilt when the resource packs change.
+#[derive(Resource, Default)]
+struct CustomBlocks {
+    block_types: Vec<BlockType>,
+    /// The identifiers of the geometry models of custom blocks, by block identifier.
+    geometry: HashMap<String, String>,
+    /// The textures of the faces of custom blocks, from their material instances.
+    textures: Vec<(String, HashMap<String, String>)>,
+    layers: HashMap<String, RenderLayer>,
+}
+
 /// Contains an arc pointer to the world's [BlockMap].
 #[derive(Resource)]
 pub struct World {
@@ -63,6 +108,45 @@ pub struct World {
     pub y_range: YRange,
 }
 
+/// The dimension the player is in, along with the heights of the dimensions.
+#[derive(Resource)]
+pub struct Dimensions {
+    /// The dimension the player is currently in.
+    pub current: Dimension,
+    /// The heights of the data-driven dimensions sent by the server, by the name of the dimension.
+    heights: HashMap<String, YRange>,
+}
+
+impl Default for Dimensions {
+    fn default() -> Self {
+        Self {
+            current: Dimension::Overworld,
+            heights: HashMap::new(),
+        }
+    }
+}
+
+impl Dimensions {
+    /// Returns how tall a dimension is. Dimensions that the server did not send a definition for
+    /// have their vanilla height.
+    pub fn y_range(&self, dimension: Dimension) -> YRange {
+        let (name, default) = match dimension {
+            Dimension::Overworld => ("minecraft:overworld", YRange::new(-64, 319)),
+            Dimension::Nether => ("minecraft:nether", YRange::new(0, 127)),
+            Dimension::End => ("minecraft:the_end", YRange::new(0, 255)),
+        };
+        self.heights.get(name).copied().unwrap_or(default)
+    }
+}
+
+/// A dimension change that the server has not yet been told is done. The change is done once the
+/// chunk the player is moved to has loaded, or once [DIMENSION_CHANGE_TIMEOUT] has passed.
+#[derive(Resource, Default)]
+struct DimensionChange(Option<(Vec3, f64)>);
+
+/// The most seconds to wait for the chunk the player is moved to during a dimension change.
+const DIMENSION_CHANGE_TIMEOUT: f64 = 5.;
+
 /// Keeps track of all chunks present in the world.
 /// Contains a reference to the entity that contains a chunk's data given a certain chunk position.
 #[derive(Resource, Default, Debug)]
@@ -132,6 +216,89 @@ impl ChunkManager {
     }
 }
 
+/// The entities that render the sections of a chunk, for each [RenderLayer]. Sections are only
+/// given an entity for a layer once they have a mesh for it.
+#[derive(Component)]
+struct ChunkSections {
+    /// The position of the chunk the sections belong to.
+    pos: ChunkPos,
+    entities: Vec<[Option<Entity>; 3]>,
+}
+
+/// Marks the entities that render the translucent layer of a section, whose faces need to be
+/// sorted from back to front.
+#[derive(Component)]
+struct TranslucentSection;
+
+/// Keeps track of the chunk sections whose meshes need to be rebuilt, and the tasks that are
+/// currently building them.
+///
+/// Sections are identified by the position of their chunk and their index within the chunk, from
+/// bottom to top.
+#[derive(Resource, Default)]
+struct MeshQueue {
+    dirty: HashSet<(ChunkPos, usize)>,
+    tasks: HashMap<(ChunkPos, usize), Task<SectionMeshes>>,
+}
+
+impl MeshQueue {
+    /// Queues all sections of a chunk and the sections of its loaded neighbours, whose border faces
+    /// might have changed.
+    fn queue_chunk(&mut self, chunks: &ChunkManager, pos: ChunkPos, sections: usize) {
+        for pos in [
+            pos,
+            pos - IVec2::Y,
+            pos + IVec2::Y,
+            pos - IVec2::X,
+            pos + IVec2::X,
+        ] {
+            if chunks.get(pos).is_some() {
+                self.dirty
+                    .extend((0..sections).map(|section| (pos, section)));
+            }
+        }
+    }
+
+    /// Queues the section that contains a block, along with any neighbouring sections if the block
+    /// is on the edge of its section.
+    fn queue_block(&mut self, pos: IVec3, y_range: YRange) {
+        if !in_range(y_range, pos.y) {
+            return;
+        }
+        let chunk = IVec2::new(pos.x >> 4, pos.z >> 4);
+        let y = pos.y - y_range.min() as i32;
+        let section = y / SECTION_SIZE;
+        let sections = y_range.height() as i32 / SECTION_SIZE;
+        let local = IVec3::new(pos.x & 15, y % SECTION_SIZE, pos.z & 15);
+
+        self.dirty.insert((chunk, section as usize));
+        if local.y == 0 && section > 0 {
+            self.dirty.insert((chunk, section as usize - 1));
+        }
+        if local.y == SECTION_SIZE - 1 && section < sections - 1 {
+            self.dirty.insert((chunk, section as usize + 1));
+        }
+        for (edge, offset) in [
+            (local.x == 0, -IVec2::X),
+            (local.x == SECTION_SIZE - 1, IVec2::X),
+            (local.z == 0, -IVec2::Y),
+            (local.z == SECTION_SIZE - 1, IVec2::Y),
+        ] {
+            if edge {
+                self.dirty.insert((chunk + offset, section as usize));
+            }
+        }
+    }
+}
+
+/// Returns the builder of the [BlockMap], which is built once the StartGame packet is received.
+fn block_map_builder() -> BlockMapBuilder {
+    BlockMapBuilder::vanilla()
+        .with_component_type::<component::Geometry>(ComponentStorageType::Vector)
+        .with_component_type::<component::RenderLayer>(ComponentStorageType::Vector)
+        .with_component_type::<component::Textures>(ComponentStorageType::Vector)
+}
+
 /// Condition system that can be used to only run systems when there is a world loaded.
 pub fn world_is_loaded(world: Option<Res<World>>) -> bool {
     world.is_some()
@@ -144,7 +311,7 @@ fn build_block_map_system(world: &mut ECSWorld) {
         return;
     }
     let mut event_reader = start_game_event.get_reader();
-    let mut builder = world
+    let builder = world
         .remove_resource::<BlockMapBuilder>()
         .expect("BlockMapBuilder is missing on StartGame");
 
@@ -154,11 +321,42 @@ fn build_block_map_system(world: &mut ECSWorld) {
         .unwrap();
 
     // Read custom blocks from the StartGame packet.
+    let mut custom = CustomBlocks::default();
     for entry in &start_game.blocks {
         #[derive(Deserialize, Debug)]
         struct BlockProperties {
             #[serde(default)]
             properties: Vec<BlockProperty>,
+            #[serde(default)]
+            components: BlockComponents,
+        }
+
+        #[derive(Deserialize, Debug, Default)]
+        struct BlockComponents {
+            #[serde(rename = "minecraft:geometry")]
+            geometry: Option<GeometryComponent>,
+            #[serde(rename = "minecraft:material_instances")]
+            material_instances: Option<MaterialInstances>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[serde(untagged)]
+        enum GeometryComponent {
+            Identifier(String),
+            Object { identifier: String },
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct MaterialInstances {
+            #[serde(default)]
+            materials: HashMap<String, MaterialInstance>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct MaterialInstance {
+            texture: String,
+            #[serde(default)]
+            render_method: Option<String>,
         }
 
         #[derive(Deserialize, Debug)]
@@ -198,30 +396,257 @@ fn build_block_map_system(world: &mut ECSWorld) {
             );
         }
 
-        builder.insert_block(block_type);
+        let components = properties.components;
+        if let Some(geometry) = components.geometry {
+            custom.geometry.insert(
+                entry.name.clone(),
+                match geometry {
+                    GeometryComponent::Identifier(identifier) => identifier,
+                    GeometryComponent::Object { identifier } => identifier,
+                },
+            );
+        }
+        if let Some(instances) = components.material_instances {
+            // A block can only be in a single layer, so the most transparent layer of all its
+            // material instances is used.
+            let layer = instances
+                .materials
+                .values()
+                .filter_map(|material| material.render_method.as_deref())
+                .map(RenderLayer::from_render_method)
+                .max_by_key(|layer| *layer as u8);
+            if let Some(layer) = layer {
+                custom.layers.insert(entry.name.clone(), layer);
+            }
+
+            let materials = instances
+                .materials
+                .into_iter()
+                .map(|(face, material)| (face, material.texture))
+                .collect();
+            custom.textures.push((entry.name.clone(), materials));
+        }
+
+        custom.block_types.push(block_type);
     }
 
+    let block_map = build_block_map(builder, &custom, world.resource::<ResourcePacks>());
+    let dimension = start_game.dimension;
+    let mut dimensions = world.resource_mut::<Dimensions>();
+    dimensions.current = dimension;
+    let y_range = dimensions.y_range(dimension);
     world.insert_resource(World {
-        block_map: Arc::new(builder.build()),
-        y_range: YRange::new(-64, 319),
+        block_map: Arc::new(block_map),
+        y_range,
     });
+    world.insert_resource(custom);
     world.resource_mut::<Events<StartGame>>().clear();
 }
 
-/// Updates the mesh of a chunk when it has been modified.
+/// Builds the [BlockMap] with the custom blocks of the server, resolving the textures, render
+/// layers and geometry of all blocks from the resource packs.
+fn build_block_map(
+    mut builder: BlockMapBuilder,
+    custom: &CustomBlocks,
+    packs: &ResourcePacks,
+) -> BlockMap {
+    for block_type in &custom.block_types {
+        builder.insert_block(block_type.clone());
+    }
+
+    // Custom blocks are not in `blocks.json`, so their textures come from their material instances
+    // instead.
+    let mut block_textures = texture::read_block_textures(packs);
+    for (name, materials) in &custom.textures {
+        block_textures.insert(
+            name.clone(),
+            FaceTextures::from_material_instances(materials),
+        );
+    }
+    builder.insert_build_function(move |block_map| {
+        texture::assign_textures(block_map, &block_textures)
+    });
+
+    let custom_layers = custom.layers.clone();
+    builder.insert_build_function(move |block_map| {
+        layer::assign_render_layers(block_map, &custom_layers)
+    });
+
+    let models = model::read_models(packs, "models/blocks");
+    let custom_geometry = custom.geometry.clone();
+    builder.insert_build_function(move |block_map| {
+        geometry::assign_geometry(block_map, &models, &custom_geometry)
+    });
+    builder.build()
+}
+
+/// Rebuilds the [BlockMap] when the resource packs change while in a world, such as when the packs
+/// of the server are applied, and rebuilds the meshes of all loaded chunks with it. The runtime IDs
+/// of the blocks stay the same, so the chunks themselves remain valid.
+fn block_map_reload_system(
+    packs: Res<ResourcePacks>,
+    custom: Option<Res<CustomBlocks>>,
+    world: Option<ResMut<World>>,
+    chunks: Res<ChunkManager>,
+    mut queue: ResMut<MeshQueue>,
+    query: Query<(&Chunk, &ChunkSections)>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+    let (Some(custom), Some(mut world)) = (custom, world) else {
+        return;
+    };
+    world.block_map = Arc::new(build_block_map(block_map_builder(), &custom, &packs));
+    for (chunk, sections) in &query {
+        let count = (chunk.range().height() as i32 / SECTION_SIZE) as usize;
+        queue.queue_chunk(&chunks, sections.pos, count);
+    }
+}
+
+/// Queues the sections of chunks that have been loaded, replaced or otherwise modified as a whole
+/// to have their meshes rebuilt.
 fn chunk_update_system(
-    mut assets: ResMut<Assets<Mesh>>,
-    mut query: Query<(&mut Handle<Mesh>, &Chunk), Changed<Chunk>>,
+    mut queue: ResMut<MeshQueue>,
+    chunks: Res<ChunkManager>,
+    query: Query<(&Chunk, &ChunkSections), Changed<Chunk>>,
 ) {
-    for (mesh, chunk) in &mut query {
-        assets.set_untracked(mesh.id(), mesh::build_mesh(chunk));
+    for (chunk, sections) in &query {
+        let count = (chunk.range().height() as i32 / SECTION_SIZE) as usize;
+        queue.queue_chunk(&chunks, sections.pos, count);
+    }
+}
+
+/// Starts building the meshes of queued sections on the async compute pool. Sections that are
+/// already being built are kept in the queue until their current task has finished, so that the
+/// newest version of the section always ends up being used.
+fn mesh_dispatch_system(
+    mut queue: ResMut<MeshQueue>,
+    chunks: Res<ChunkManager>,
+    atlas: Res<BlockAtlas>,
+    world: Res<World>,
+    query: Query<&Chunk>,
+) {
+    let pool = AsyncComputeTaskPool::get();
+    let empty = BlockBuilder::new(zuri_world::block::AIR_ID)
+        .to_runtime_id(&world.block_map)
+        .expect("Missing air runtime id");
+
+    let queue = &mut *queue;
+    queue.dirty.retain(|&(pos, section)| {
+        if queue.tasks.contains_key(&(pos, section)) {
+            return true;
+        }
+        let Some(chunk) = chunks.get(pos).and_then(|entity| query.get(entity).ok()) else {
+            return false;
+        };
+        let neighbours = [-IVec2::Y, IVec2::Y, -IVec2::X, IVec2::X]
+            .map(|offset| chunks.get(pos + offset).and_then(|e| query.get(e).ok()));
+
+        let blocks = SectionBlocks::copy(chunk, neighbours, section, empty);
+        let block_map = world.block_map.clone();
+        let atlas = atlas.clone();
+        let task = pool.spawn(async move { mesh::build_section_mesh(&blocks, &block_map, &atlas) });
+        queue.tasks.insert((pos, section), task);
+        false
+    });
+}
+
+/// Applies the meshes of sections that have finished building.
+fn mesh_collect_system(
+    mut commands: Commands,
+    mut queue: ResMut<MeshQueue>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunks: Res<ChunkManager>,
+    atlas: Res<BlockAtlas>,
+    mut query: Query<(&Chunk, &mut ChunkSections)>,
+) {
+    queue.tasks.retain(|&(pos, section), task| {
+        let Some(section_meshes) = future::block_on(future::poll_once(task)) else {
+            return true;
+        };
+        // The chunk might have been unloaded while the mesh was being built.
+        let Some(chunk_entity) = chunks.get(pos) else {
+            return false;
+        };
+        let Ok((chunk, mut sections)) = query.get_mut(chunk_entity) else {
+            return false;
+        };
+
+        // The height of the dimension might have changed while the mesh was being built.
+        let Some(entities) = sections.entities.get_mut(section) else {
+            return false;
+        };
+        let y = chunk.range().min() as i32 + section as i32 * SECTION_SIZE;
+        for (layer, mesh) in RenderLayer::ALL.into_iter().zip(section_meshes) {
+            let entity = &mut entities[layer as usize];
+            match (mesh, *entity) {
+                (Some(mesh), Some(existing)) => {
+                    commands.entity(existing).insert(meshes.add(mesh));
+                }
+                (Some(mesh), None) => {
+                    let mut commands = commands.spawn(MaterialMeshBundle {
+                        mesh: meshes.add(mesh),
+                        material: atlas.material(layer),
+                        transform: Transform::from_xyz(0., y as f32, 0.),
+                        ..default()
+                    });
+                    if layer == RenderLayer::Translucent {
+                        commands.insert(TranslucentSection);
+                    }
+                    *entity = Some(commands.id());
+                }
+                (None, Some(existing)) => {
+                    commands.entity(existing).despawn_recursive();
+                    *entity = None;
+                }
+                (None, None) => {}
+            }
+        }
+        let children: Vec<_> = sections.entities[section]
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        commands.entity(chunk_entity).push_children(&children);
+        false
+    });
+}
+
+/// Sorts the faces of translucent sections from back to front whenever the camera moves to another
+/// block, or when the mesh of a section has changed.
+fn translucent_sort_system(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut last_eye: Local<Option<IVec3>>,
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+    sections: Query<(Ref<Handle<Mesh>>, &GlobalTransform), With<TranslucentSection>>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let eye = camera.translation();
+    let eye_block = eye.floor().as_ivec3();
+    let moved = *last_eye != Some(eye_block);
+    *last_eye = Some(eye_block);
+
+    for (handle, transform) in &sections {
+        if !moved && !handle.is_changed() {
+            continue;
+        }
+        if let Some(mesh) = meshes.get_mut(&handle) {
+            mesh::sort_triangles(mesh, eye - transform.translation());
+        }
     }
 }
 
 /// Updates a block in the world when the server sends a block update.
+///
+/// Only the sections around the block are remeshed, so change detection of the chunk is bypassed.
 fn block_update_system(
     mut pks: EventReader<UpdateBlock>,
     chunks: Res<ChunkManager>,
+    world: Res<World>,
+    mut queue: ResMut<MeshQueue>,
     mut query: Query<&mut Chunk>,
 ) {
     for pk in pks.iter() {
@@ -229,40 +654,31 @@ fn block_update_system(
         if pk.layer.0 != 0 {
             continue;
         }
-        if let Some(chunk_entity) = chunks.at_block_pos(pk.position.into()) {
+        let pos: IVec3 = pk.position.into();
+        if !in_range(world.y_range, pos.y) {
+            continue;
+        }
+        if let Some(chunk_entity) = chunks.at_block_pos(pos) {
             query
                 .get_mut(chunk_entity)
                 .unwrap()
+                .bypass_change_detection()
                 .set(
-                    <ChunkIndex as From<IVec3>>::from(pk.position.into()),
+                    <ChunkIndex as From<IVec3>>::from(pos),
                     pk.new_block_runtime_id.0,
                 )
                 .unwrap();
+            queue.queue_block(pos, world.y_range);
         }
     }
 }
 
-/// Loads textures. For now, this is only dirt.
-fn textures_init_system(mut block_tex: ResMut<BlockTextures>, asset_server: Res<AssetServer>) {
-    let texture_handle = asset_server.load("dirt.png");
-    block_tex.dirt = Some(texture_handle);
-}
-
-#[derive(Resource, Default)]
-pub struct BlockTextures {
-    // only support one block for now
-    dirt: Option<Handle<Image>>,
-}
-
 /// Decodes and spawns chunks sent by the server
 fn chunk_load_system(
     mut commands: Commands,
     mut events: EventReader<LevelChunk>,
     mut chunks: ResMut<ChunkManager>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     mut world_chunks: Query<&mut Chunk>,
-    block_tex: Res<BlockTextures>,
     world: Res<World>,
 ) {
     if events.is_empty() {
@@ -287,19 +703,13 @@ fn chunk_load_system(
 
         let pos = event.position * 16;
 
+        let sections = (chunk.range().height() as i32 / SECTION_SIZE) as usize;
         let entity = commands
             .spawn((
-                PbrBundle {
-                    mesh: meshes.add(mesh::build_mesh(&chunk)),
-                    material: materials.add(StandardMaterial {
-                        base_color_texture: Some(block_tex.dirt.clone().unwrap()),
-                        base_color: Color::WHITE,
-                        alpha_mode: AlphaMode::Opaque,
-                        perceptual_roughness: 0.94,
-                        ..default()
-                    }),
-                    transform: Transform::from_xyz(pos.x as f32, 0., pos.y as f32),
-                    ..default()
+                SpatialBundle::from_transform(Transform::from_xyz(pos.x as f32, 0., pos.y as f32)),
+                ChunkSections {
+                    pos: event.position,
+                    entities: vec![[None; 3]; sections],
                 },
                 chunk,
             ))
@@ -322,7 +732,7 @@ fn chunk_unload_system(mut commands: Commands, mut chunks: ResMut<ChunkManager>)
             return true;
         }
         debug!("Unloading chunk {pos}");
-        commands.entity(entity.clone()).despawn();
+        commands.entity(*entity).despawn_recursive();
         false
     });
 }
@@ -336,3 +746,155 @@ fn update_chunk_radius_system(
         chunks.set_chunk_radius(event.position.0, event.radius.0);
     }
 }
+
+/// Reads the heights of the data-driven dimensions sent by the server.
+fn dimension_data_system(
+    mut events: EventReader<DimensionData>,
+    mut dimensions: ResMut<Dimensions>,
+    world: Option<ResMut<World>>,
+) {
+    let mut changed = false;
+    for pk in events.iter() {
+        for definition in &pk.definitions {
+            let [min, max] = definition.range.map(|y| y.0);
+            let Some(range) = dimension_range(min, max) else {
+                warn!(
+                    "Ignoring invalid height {min}..{max} of dimension `{}`",
+                    definition.name
+                );
+                continue;
+            };
+            dimensions.heights.insert(definition.name.clone(), range);
+            changed = true;
+        }
+    }
+    if let (true, Some(mut world)) = (changed, world) {
+        world.y_range = dimensions.y_range(dimensions.current);
+    }
+}
+
+/// Checks if a Y coordinate is within the height of a dimension.
+fn in_range(range: YRange, y: i32) -> bool {
+    (range.min() as i32..=range.max() as i32).contains(&y)
+}
+
+/// Converts the height of a dimension definition, of which the maximum is exclusive, to a [YRange].
+/// Returns None if the height is empty, out of bounds, or not a whole number of sections.
+fn dimension_range(min: i32, max: i32) -> Option<YRange> {
+    let height = max.checked_sub(min)?;
+    if height <= 0 || height % SECTION_SIZE != 0 {
+        return None;
+    }
+    Some(YRange::new(
+        min.try_into().ok()?,
+        (max - 1).try_into().ok()?,
+    ))
+}
+
+/// Unloads all chunks when the player is moved to another dimension. The server is told the change
+/// is done by [dimension_ack_system].
+#[allow(clippy::too_many_arguments)]
+fn dimension_change_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut events: EventReader<ChangeDimension>,
+    mut dimensions: ResMut<Dimensions>,
+    mut change: ResMut<DimensionChange>,
+    mut world: ResMut<World>,
+    mut chunks: ResMut<ChunkManager>,
+    mut queue: ResMut<MeshQueue>,
+) {
+    for pk in events.iter() {
+        debug!("Changing dimension to {:?}", pk.dimension);
+        unload_chunks(&mut commands, &mut chunks, &mut queue);
+
+        dimensions.current = pk.dimension;
+        world.y_range = dimensions.y_range(pk.dimension);
+        change.0 = Some((pk.position, time.elapsed_seconds_f64()));
+    }
+}
+
+/// Tells the server that a dimension change is done once the chunk the player was moved to has
+/// loaded.
+fn dimension_ack_system(
+    time: Res<Time>,
+    chunks: Res<ChunkManager>,
+    mut change: ResMut<DimensionChange>,
+    mut packets: EventWriter<Packet>,
+    player: Query<&IdentifiableEntity, With<player::Local>>,
+) {
+    let Some((position, since)) = change.0 else {
+        return;
+    };
+    let timed_out = time.elapsed_seconds_f64() - since > DIMENSION_CHANGE_TIMEOUT;
+    if chunks.at_pos(position).is_none() && !timed_out {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    packets.send(Packet::PlayerAction(PlayerAction {
+        entity_runtime_id: player.runtime_id().into(),
+        action_type: PlayerActionType::DimensionChangeDone,
+        block_position: UBlockPos::default(),
+        result_position: UBlockPos::default(),
+        block_face: 0.into(),
+    }));
+    change.0 = None;
+}
+
+/// Despawns all chunks, and stops building the meshes of their sections.
+fn unload_chunks(commands: &mut Commands, chunks: &mut ChunkManager, queue: &mut MeshQueue) {
+    for (_, entity) in chunks.iter() {
+        commands.entity(*entity).despawn_recursive();
+    }
+    chunks.clear();
+    queue.dirty.clear();
+    queue.tasks.clear();
+}
+
+/// Unloads the world once the client is disconnected, so that the next server can start a new one.
+fn world_reset_system(
+    mut commands: Commands,
+    mut chunks: ResMut<ChunkManager>,
+    mut queue: ResMut<MeshQueue>,
+    mut dimensions: ResMut<Dimensions>,
+    mut change: ResMut<DimensionChange>,
+) {
+    unload_chunks(&mut commands, &mut chunks, &mut queue);
+    *chunks = ChunkManager::default();
+    *dimensions = Dimensions::default();
+    change.0 = None;
+    commands.remove_resource::<World>();
+    commands.remove_resource::<CustomBlocks>();
+    commands.insert_resource(block_map_builder());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimension_ranges() {
+        let overworld = dimension_range(-64, 320).unwrap();
+        assert_eq!((overworld.min(), overworld.max()), (-64, 319));
+        let default = Dimensions::default().y_range(Dimension::Overworld);
+        assert_eq!((default.min(), default.max()), (-64, 319));
+        assert!(dimension_range(0, 0).is_none());
+        assert!(dimension_range(128, 0).is_none());
+        assert!(dimension_range(0, 100).is_none());
+        assert!(dimension_range(0, 1 << 16).is_none());
+    }
+
+    #[test]
+    fn queue_block_outside_range() {
+        let range = dimension_range(-64, 320).unwrap();
+        let mut queue = MeshQueue::default();
+        queue.queue_block(IVec3::new(8, -65, 8), range);
+        queue.queue_block(IVec3::new(8, 320, 8), range);
+        assert!(queue.dirty.is_empty());
+        queue.queue_block(IVec3::new(8, -64, 8), range);
+        assert_eq!(queue.dirty.len(), 1);
+        assert!(queue.dirty.contains(&(IVec2::ZERO, 0)));
+    }
+}
@@ -0,0 +1,67 @@
+#import bevy_pbr::mesh_view_bindings
+#import bevy_pbr::mesh_bindings
+
+#import bevy_pbr::pbr_types
+#import bevy_pbr::utils
+#import bevy_pbr::clustered_forward
+#import bevy_pbr::lighting
+#import bevy_pbr::pbr_ambient
+#import bevy_pbr::shadows
+#import bevy_pbr::fog
+#import bevy_pbr::pbr_functions
+
+@group(1) @binding(0)
+var atlas_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+struct FragmentInput {
+    @builtin(front_facing) is_front: bool,
+    @builtin(position) frag_coord: vec4<f32>,
+    #import bevy_pbr::mesh_vertex_output
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    // Quads that were merged by the mesher span multiple blocks, so their UVs go past 1 and the
+    // texture repeats for every block. The vertex colour holds the rectangle of the texture in the
+    // atlas as (x, y, width, height).
+    let tile = in.color;
+    let uv = tile.xy + fract(in.uv) * tile.zw;
+    // The derivatives of the unwrapped UV are used, as fract makes the derivatives jump at the
+    // edges of each repetition.
+    let base_color = textureSampleGrad(
+        atlas_texture,
+        atlas_sampler,
+        uv,
+        dpdx(in.uv * tile.zw),
+        dpdy(in.uv * tile.zw),
+    );
+
+    var pbr_input: PbrInput = pbr_input_new();
+    pbr_input.material.base_color = base_color;
+    pbr_input.material.perceptual_roughness = 0.94;
+#ifdef CUTOUT
+    pbr_input.material.flags = STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK;
+    pbr_input.material.alpha_cutoff = 0.5;
+#endif
+#ifdef TRANSLUCENT
+    pbr_input.material.flags = STANDARD_MATERIAL_FLAGS_ALPHA_MODE_BLEND;
+#endif
+    pbr_input.frag_coord = in.frag_coord;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = prepare_world_normal(in.world_normal, false, in.is_front);
+    pbr_input.is_orthographic = view.projection[3].w == 1.0;
+    pbr_input.N = normalize(pbr_input.world_normal);
+    pbr_input.V = calculate_view(in.world_position, pbr_input.is_orthographic);
+    pbr_input.flags = mesh.flags;
+
+    var output_color = pbr(pbr_input);
+    if (fog.mode != FOG_MODE_OFF) {
+        output_color = apply_fog(output_color, in.world_position.xyz, view.world_position.xyz);
+    }
+#ifdef TONEMAP_IN_SHADER
+    output_color = tone_mapping(output_color);
+#endif
+    return output_color;
+}
@@ -1,10 +1,162 @@
-use bevy::prelude::Mesh;
+use bevy::prelude::{Mesh, Vec3};
 use zuri_world::block;
 
-/// Contains a block's geometry.
+/// Contains the geometry of a block that is not a full cube. Blocks without this component are
+/// rendered as a full cube.
 ///
-/// todo: actually load geometry for all blocks
+/// UVs of the mesh are relative to the texture of the face that each vertex is facing, as decided by
+/// its normal.
 #[derive(Debug, block::Component)]
 pub struct Geometry {
     pub mesh: Mesh,
+    /// Extra meshes that are only added towards horizontal neighbours that the block connects to,
+    /// such as the bars of fences and glass panes. A block connects to full cubes and to other
+    /// blocks that have connections.
+    pub connections: Vec<(Face, Mesh)>,
+}
+
+/// Decides how transparency in the textures of a block is handled. Blocks without this component
+/// are opaque.
+///
+/// Each chunk section has a separate mesh for every layer, as they are rendered with different
+/// materials.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, block::Component)]
+pub enum RenderLayer {
+    /// Transparent pixels are rendered black. Faces towards opaque full cubes are not rendered.
+    #[default]
+    Opaque,
+    /// Pixels are either fully opaque or fully transparent, such as for leaves and flowers.
+    Cutout,
+    /// Pixels are blended with whatever is behind them, such as for water and stained glass. Faces
+    /// are sorted from back to front before being rendered.
+    Translucent,
+}
+
+impl RenderLayer {
+    /// All layers, in the order of their indices.
+    pub const ALL: [RenderLayer; 3] = [
+        RenderLayer::Opaque,
+        RenderLayer::Cutout,
+        RenderLayer::Translucent,
+    ];
+
+    /// Returns the layer with the provided name, as used in `render_layers.json`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "opaque" => RenderLayer::Opaque,
+            "cutout" => RenderLayer::Cutout,
+            "translucent" => RenderLayer::Translucent,
+            _ => return None,
+        })
+    }
+
+    /// Returns the layer for the `render_method` of a material instance of a custom block.
+    pub fn from_render_method(method: &str) -> Self {
+        if method.starts_with("blend") {
+            RenderLayer::Translucent
+        } else if method.starts_with("alpha_test") {
+            RenderLayer::Cutout
+        } else {
+            RenderLayer::Opaque
+        }
+    }
+}
+
+/// The textures used for each of the faces of a block. Blocks without this component are rendered
+/// using the fallback texture of the [BlockAtlas](super::texture::BlockAtlas).
+///
+/// The texture references are resolved from `blocks.json` when the block map is built. They are
+/// only turned into actual UV coordinates by the atlas, so that the atlas can be rebuilt without
+/// having to rebuild the block map.
+#[derive(Debug, Clone, block::Component)]
+pub struct Textures {
+    /// The texture for each face, indexed by [Face].
+    pub faces: [TextureRef; 6],
+}
+
+impl Textures {
+    /// Returns the texture used for a certain face of the block.
+    pub fn face(&self, face: Face) -> &TextureRef {
+        &self.faces[face as usize]
+    }
+}
+
+/// A reference to a texture defined in `terrain_texture.json`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TextureRef {
+    /// The short name of the texture, such as `grass_side`.
+    pub name: Box<str>,
+    /// Some textures have multiple variants, which are used for different states of the same block.
+    /// This is the index of the variant to use.
+    pub variant: usize,
+}
+
+/// One of the six faces of a cube.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Face {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Face {
+    /// All faces, in the order of their indices.
+    pub const ALL: [Face; 6] = [
+        Face::Down,
+        Face::Up,
+        Face::North,
+        Face::South,
+        Face::West,
+        Face::East,
+    ];
+
+    /// Returns the unit vector pointing outwards from this face.
+    pub fn normal(self) -> Vec3 {
+        match self {
+            Face::Down => Vec3::NEG_Y,
+            Face::Up => Vec3::Y,
+            Face::North => Vec3::NEG_Z,
+            Face::South => Vec3::Z,
+            Face::West => Vec3::NEG_X,
+            Face::East => Vec3::X,
+        }
+    }
+
+    /// Returns the lowercase name of the face, as used in resource packs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::Down => "down",
+            Face::Up => "up",
+            Face::North => "north",
+            Face::South => "south",
+            Face::West => "west",
+            Face::East => "east",
+        }
+    }
+
+    /// Returns the face that points most closely in the direction of the provided vector. North is
+    /// towards negative Z and west is towards negative X.
+    pub fn from_normal(normal: [f32; 3]) -> Face {
+        let [x, y, z] = normal;
+        if y.abs() >= x.abs() && y.abs() >= z.abs() {
+            if y < 0. {
+                Face::Down
+            } else {
+                Face::Up
+            }
+        } else if x.abs() >= z.abs() {
+            if x < 0. {
+                Face::West
+            } else {
+                Face::East
+            }
+        } else if z < 0. {
+            Face::North
+        } else {
+            Face::South
+        }
+    }
 }
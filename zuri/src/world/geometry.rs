@@ -0,0 +1,399 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_8;
+
+use bevy::prelude::*;
+use zuri_world::block::{Block, BlockMap, PropertyValue};
+
+use crate::model::{MeshBuilder, Model, Quad};
+use crate::world::component::{Face, Geometry};
+
+/// Maps block identifier patterns to the shape of the block, in order of priority. Blocks that do
+/// not match any of the patterns are full cubes.
+const BLOCK_SHAPES: &str = include_str!("../../assets/block_shapes.json");
+
+/// The identifier of the model that custom blocks use to be rendered as a full cube.
+const FULL_BLOCK_MODEL: &str = "minecraft:geometry.full_block";
+
+/// The shape of a block, which decides what [Geometry] is built for each of its states.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Shape {
+    /// The block is a full cube, and does not need a [Geometry] component.
+    Cube,
+    /// The block is not rendered at all.
+    None,
+    Slab,
+    Stairs,
+    Fence,
+    Pane,
+    Torch,
+    Cross,
+    Liquid,
+    Rail,
+}
+
+impl Shape {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "cube" => Shape::Cube,
+            "none" => Shape::None,
+            "slab" => Shape::Slab,
+            "stairs" => Shape::Stairs,
+            "fence" => Shape::Fence,
+            "pane" => Shape::Pane,
+            "torch" => Shape::Torch,
+            "cross" => Shape::Cross,
+            "liquid" => Shape::Liquid,
+            "rail" => Shape::Rail,
+            _ => return None,
+        })
+    }
+}
+
+/// Gives every block that is not a full cube a [Geometry] component.
+///
+/// Vanilla blocks get their shape from the patterns in `block_shapes.json`. Custom blocks with a
+/// `minecraft:geometry` component instead use the model with that identifier, which are passed in
+/// `custom` mapped by block identifier.
+pub(super) fn assign_geometry(
+    block_map: &mut BlockMap,
+    models: &HashMap<Box<str>, Model>,
+    custom: &HashMap<String, String>,
+) {
+    let rules = shape_rules();
+
+    let mut geometries = Vec::new();
+    let mut custom_meshes = HashMap::new();
+    for runtime_id in 0..block_map.runtime_ids() {
+        let block = block_map.block(runtime_id).unwrap();
+        let identifier = block.identifier();
+
+        if let Some(model) = custom.get(identifier) {
+            if model == FULL_BLOCK_MODEL {
+                continue;
+            }
+            let Some(model) = models.get(model.as_str()) else {
+                warn!("Model `{model}` for custom block `{identifier}` not found");
+                continue;
+            };
+            let mesh = custom_meshes
+                .entry(identifier)
+                .or_insert_with(|| model_mesh(model));
+            geometries.push((
+                runtime_id,
+                Geometry {
+                    mesh: mesh.clone(),
+                    connections: Vec::new(),
+                },
+            ));
+            continue;
+        }
+
+        let name = identifier.trim_start_matches("minecraft:");
+        let shape = rules
+            .iter()
+            .find(|(pattern, _)| glob_match(pattern, name))
+            .map(|(_, shape)| *shape)
+            .unwrap_or(Shape::Cube);
+        if let Some(geometry) = shape_geometry(shape, &block) {
+            geometries.push((runtime_id, geometry));
+        }
+    }
+
+    for (runtime_id, geometry) in geometries {
+        block_map.set_component(runtime_id, geometry);
+    }
+}
+
+/// Parses the embedded list of block shape patterns.
+fn shape_rules() -> Vec<(String, Shape)> {
+    json::parse(BLOCK_SHAPES)
+        .expect("Invalid block shapes")
+        .members()
+        .map(|rule| {
+            let pattern = rule[0].as_str().expect("Invalid block shape pattern");
+            let shape = rule[1].as_str().and_then(Shape::from_name);
+            (
+                pattern.to_string(),
+                shape.unwrap_or_else(|| panic!("Invalid shape for pattern `{pattern}`")),
+            )
+        })
+        .collect()
+}
+
+/// Matches a name against a pattern in which `*` matches any sequence of characters.
+pub(super) fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=name.len())
+                .filter(|i| name.is_char_boundary(*i))
+                .any(|i| glob_match(rest, &name[i..]))
+        }
+    }
+}
+
+/// Builds the geometry of a block state with a certain shape. Returns None for full cubes.
+fn shape_geometry(shape: Shape, block: &Block) -> Option<Geometry> {
+    let mut mesh = MeshBuilder::default();
+    let mut connections = Vec::new();
+    match shape {
+        Shape::Cube => return None,
+        Shape::None => {}
+        Shape::Slab => {
+            let top = bool_property(block, "top_slot_bit")
+                || string_property(block, "minecraft:vertical_half").as_deref() == Some("top");
+            let y = if top { 0.5 } else { 0. };
+            cuboid(&mut mesh, Vec3::new(0., y, 0.), Vec3::new(1., y + 0.5, 1.));
+        }
+        Shape::Stairs => {
+            let (base, step) = if bool_property(block, "upside_down_bit") {
+                (0.5, 0.)
+            } else {
+                (0., 0.5)
+            };
+            cuboid(
+                &mut mesh,
+                Vec3::new(0., base, 0.),
+                Vec3::new(1., base + 0.5, 1.),
+            );
+
+            // The step is built on the north side, and then rotated to the side the stairs ascend
+            // towards.
+            let start = mesh.vertex_count();
+            cuboid(
+                &mut mesh,
+                Vec3::new(0., step, 0.),
+                Vec3::new(1., step + 0.5, 0.5),
+            );
+            let face = match int_property(block, "weirdo_direction") {
+                0 => Face::East,
+                1 => Face::West,
+                2 => Face::South,
+                _ => Face::North,
+            };
+            mesh.transform(start, rotate_towards(face));
+        }
+        Shape::Fence => {
+            cuboid(
+                &mut mesh,
+                Vec3::new(0.375, 0., 0.375),
+                Vec3::new(0.625, 1., 0.625),
+            );
+            connections = connection_meshes(|mesh| {
+                for y in [0.375, 0.75] {
+                    cuboid(
+                        mesh,
+                        Vec3::new(0.4375, y, 0.),
+                        Vec3::new(0.5625, y + 0.1875, 0.375),
+                    );
+                }
+            });
+        }
+        Shape::Pane => {
+            cuboid(
+                &mut mesh,
+                Vec3::new(0.4375, 0., 0.4375),
+                Vec3::new(0.5625, 1., 0.5625),
+            );
+            connections = connection_meshes(|mesh| {
+                cuboid(
+                    mesh,
+                    Vec3::new(0.4375, 0., 0.),
+                    Vec3::new(0.5625, 1., 0.4375),
+                );
+            });
+        }
+        Shape::Torch => {
+            cuboid(
+                &mut mesh,
+                Vec3::new(0.4375, 0., 0.4375),
+                Vec3::new(0.5625, 0.625, 0.5625),
+            );
+
+            // Wall torches lean away from the block they are attached to. They are built leaning
+            // north, and then rotated.
+            let face = match string_property(block, "torch_facing_direction").as_deref() {
+                Some("north") => Face::North,
+                Some("south") => Face::South,
+                Some("west") => Face::West,
+                Some("east") => Face::East,
+                _ => Face::Up,
+            };
+            if face != Face::Up {
+                let lean = Mat4::from_translation(Vec3::new(0., 0.21875, 0.4375))
+                    * Mat4::from_translation(Vec3::new(0.5, 0., 0.5))
+                    * Mat4::from_rotation_x(-FRAC_PI_8)
+                    * Mat4::from_translation(Vec3::new(-0.5, 0., -0.5));
+                mesh.transform(0, rotate_towards(face) * lean);
+            }
+        }
+        Shape::Cross => {
+            // Two diagonal planes that are as wide as the block.
+            let (min, max) = (0.5 - 0.5 / 2f32.sqrt(), 0.5 + 0.5 / 2f32.sqrt());
+            for (from, to) in [(min, max), (max, min)] {
+                mesh.double_sided(Quad {
+                    corners: [
+                        Vec3::new(from, 1., from),
+                        Vec3::new(to, 1., to),
+                        Vec3::new(to, 0., to),
+                        Vec3::new(from, 0., from),
+                    ],
+                    uvs: square_uvs(),
+                    normal: Vec3::new(to - from, 0., from - to).normalize(),
+                });
+            }
+        }
+        Shape::Liquid => {
+            // Source blocks and flowing liquids become lower the further they are from the source.
+            // Falling liquids, with a depth of 8 or more, fill the whole block.
+            let depth = int_property(block, "liquid_depth");
+            let height = if depth >= 8 {
+                1.
+            } else {
+                (8 - depth) as f32 / 9.
+            };
+            cuboid(&mut mesh, Vec3::ZERO, Vec3::new(1., height, 1.));
+        }
+        Shape::Rail => {
+            // Rails are built running from north to south, ascending north if they are sloped, and
+            // then rotated. Curved rails use the curved texture, which curves from south to east
+            // before it is rotated.
+            let (ascending, face) = match int_property(block, "rail_direction") {
+                1 => (false, Face::East),
+                2 => (true, Face::East),
+                3 => (true, Face::West),
+                4 => (true, Face::North),
+                5 => (true, Face::South),
+                6 => (false, Face::North),
+                7 => (false, Face::East),
+                8 => (false, Face::South),
+                9 => (false, Face::West),
+                _ => (false, Face::North),
+            };
+            let (north, south) = (if ascending { 1.0625 } else { 0.0625 }, 0.0625);
+            let corners = [
+                Vec3::new(0., north, 0.),
+                Vec3::new(1., north, 0.),
+                Vec3::new(1., south, 1.),
+                Vec3::new(0., south, 1.),
+            ];
+            mesh.double_sided(Quad {
+                corners,
+                uvs: square_uvs(),
+                normal: (corners[3] - corners[0])
+                    .cross(corners[1] - corners[0])
+                    .normalize(),
+            });
+            mesh.transform(0, rotate_towards(face));
+        }
+    }
+
+    Some(Geometry {
+        mesh: mesh.build(),
+        connections,
+    })
+}
+
+/// Builds the meshes that connect a block to its horizontal neighbours. The part towards the north
+/// is built by the closure, and then rotated for the other sides.
+fn connection_meshes(build: impl Fn(&mut MeshBuilder)) -> Vec<(Face, Mesh)> {
+    [Face::North, Face::South, Face::West, Face::East]
+        .into_iter()
+        .map(|face| {
+            let mut mesh = MeshBuilder::default();
+            build(&mut mesh);
+            mesh.transform(0, rotate_towards(face));
+            (face, mesh.build())
+        })
+        .collect()
+}
+
+/// Builds the mesh of a custom block model. Block models are 16 pixels wide, with the origin at the
+/// bottom centre of the block.
+fn model_mesh(model: &Model) -> Mesh {
+    let transform =
+        Mat4::from_translation(Vec3::new(0.5, 0., 0.5)) * Mat4::from_scale(Vec3::splat(1. / 16.));
+    let mut mesh = MeshBuilder::default();
+    for quad in model.quads() {
+        mesh.quad(quad.transformed(transform));
+    }
+    mesh.build()
+}
+
+/// Adds a cuboid to the mesh. The UVs of each face are taken from the position of the face within
+/// the block, so that a smaller cuboid shows the same part of the texture as the full block would.
+fn cuboid(mesh: &mut MeshBuilder, min: Vec3, max: Vec3) {
+    for face in Face::ALL {
+        let mut quad = Quad::face(min, max, face, [Vec2::ZERO; 4]);
+        quad.uvs = quad.corners.map(|c| match face {
+            Face::Down | Face::Up => Vec2::new(c.x, c.z),
+            Face::North => Vec2::new(1. - c.x, 1. - c.y),
+            Face::South => Vec2::new(c.x, 1. - c.y),
+            Face::West => Vec2::new(c.z, 1. - c.y),
+            Face::East => Vec2::new(1. - c.z, 1. - c.y),
+        });
+        mesh.quad(quad);
+    }
+}
+
+/// The UVs of a quad that shows the full texture.
+fn square_uvs() -> [Vec2; 4] {
+    [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y]
+}
+
+/// Returns a rotation around the centre of the block that turns something facing north to face
+/// in the direction of a horizontal face.
+fn rotate_towards(face: Face) -> Mat4 {
+    let angle = match face {
+        Face::West => 90f32,
+        Face::South => 180.,
+        Face::East => 270.,
+        _ => 0.,
+    };
+    Mat4::from_translation(Vec3::new(0.5, 0., 0.5))
+        * Mat4::from_rotation_y(angle.to_radians())
+        * Mat4::from_translation(Vec3::new(-0.5, 0., -0.5))
+}
+
+fn bool_property(block: &Block, name: &str) -> bool {
+    matches!(block.property_value(name), Some(PropertyValue::Bool(true)))
+}
+
+fn int_property(block: &Block, name: &str) -> i32 {
+    match block.property_value(name) {
+        Some(PropertyValue::Int(v)) => v,
+        _ => 0,
+    }
+}
+
+fn string_property<'a>(block: &Block<'a>, name: &str) -> Option<Cow<'a, str>> {
+    match block.property_value(name) {
+        Some(PropertyValue::String(v)) => Some(v),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("air", "air"));
+        assert!(!glob_match("air", "airs"));
+        assert!(glob_match("*_slab", "stone_slab"));
+        assert!(!glob_match("*_slab", "stone_slab2"));
+        assert!(glob_match("double_*slab*", "double_stone_block_slab2"));
+        assert!(glob_match("colored_torch_*", "colored_torch_red"));
+        assert!(!glob_match("*fence", "fence_gate"));
+    }
+
+    #[test]
+    fn shape_rules_are_valid() {
+        assert!(!shape_rules().is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use zuri_world::block::BlockMap;
+
+use crate::world::component::RenderLayer;
+use crate::world::geometry::glob_match;
+
+/// Maps block identifier patterns to the render layer of the block, in order of priority. Blocks
+/// that do not match any of the patterns are opaque.
+const RENDER_LAYERS: &str = include_str!("../../assets/render_layers.json");
+
+/// Gives every block that is not opaque a [RenderLayer] component.
+///
+/// Vanilla blocks get their layer from the patterns in `render_layers.json`. Custom blocks instead
+/// use the render methods of their material instances, which are passed in `custom` mapped by
+/// block identifier.
+pub(super) fn assign_render_layers(
+    block_map: &mut BlockMap,
+    custom: &HashMap<String, RenderLayer>,
+) {
+    let rules = layer_rules();
+
+    let mut layers = Vec::new();
+    for runtime_id in 0..block_map.runtime_ids() {
+        let block = block_map.block(runtime_id).unwrap();
+        let identifier = block.identifier();
+
+        let layer = custom.get(identifier).copied().unwrap_or_else(|| {
+            let name = identifier.trim_start_matches("minecraft:");
+            rules
+                .iter()
+                .find(|(pattern, _)| glob_match(pattern, name))
+                .map(|(_, layer)| *layer)
+                .unwrap_or_default()
+        });
+        if layer != RenderLayer::Opaque {
+            layers.push((runtime_id, layer));
+        }
+    }
+
+    for (runtime_id, layer) in layers {
+        block_map.set_component(runtime_id, layer);
+    }
+}
+
+/// Parses the embedded list of render layer patterns.
+fn layer_rules() -> Vec<(String, RenderLayer)> {
+    json::parse(RENDER_LAYERS)
+        .expect("Invalid render layers")
+        .members()
+        .map(|rule| {
+            let pattern = rule[0].as_str().expect("Invalid render layer pattern");
+            let layer = rule[1].as_str().and_then(RenderLayer::from_name);
+            (
+                pattern.to_string(),
+                layer.unwrap_or_else(|| panic!("Invalid render layer for pattern `{pattern}`")),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_rules_are_valid() {
+        assert!(!layer_rules().is_empty());
+    }
+}
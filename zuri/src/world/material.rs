@@ -0,0 +1,76 @@
+use bevy::asset::load_internal_asset;
+use bevy::pbr::{MaterialPipeline, MaterialPipelineKey};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+};
+
+use crate::world::component::RenderLayer;
+
+/// The shader used to render chunks, which is embedded in the binary.
+const CHUNK_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x5a0f_3c1e_9b7d_4e21);
+
+/// Registers the [ChunkMaterial] and its shader.
+pub(super) fn register(app: &mut App) {
+    load_internal_asset!(app, CHUNK_SHADER_HANDLE, "chunk.wgsl", Shader::from_wgsl);
+    app.add_plugin(MaterialPlugin::<ChunkMaterial>::default());
+}
+
+/// The material used to render chunk meshes.
+///
+/// It samples the block atlas like a [StandardMaterial], except that it lets textures repeat within
+/// a single quad. This allows the mesher to merge the faces of neighbouring blocks with the same
+/// texture. Meshes using this material store the UV rectangle of the texture in the atlas in their
+/// vertex colours, and their UVs count in blocks rather than in atlas coordinates.
+///
+/// There is a separate material for each [RenderLayer], which decides how transparent pixels are
+/// handled.
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "3f1b9a52-8c4e-4d6b-a0c7-2e9d5f8b1c34"]
+#[bind_group_data(RenderLayer)]
+pub struct ChunkMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub atlas: Handle<Image>,
+    pub layer: RenderLayer,
+}
+
+impl From<&ChunkMaterial> for RenderLayer {
+    fn from(material: &ChunkMaterial) -> Self {
+        material.layer
+    }
+}
+
+impl Material for ChunkMaterial {
+    fn fragment_shader() -> ShaderRef {
+        CHUNK_SHADER_HANDLE.typed().into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        match self.layer {
+            RenderLayer::Opaque => AlphaMode::Opaque,
+            RenderLayer::Cutout => AlphaMode::Mask(0.5),
+            RenderLayer::Translucent => AlphaMode::Blend,
+        }
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let def = match key.bind_group_data {
+            RenderLayer::Opaque => return Ok(()),
+            RenderLayer::Cutout => "CUTOUT",
+            RenderLayer::Translucent => "TRANSLUCENT",
+        };
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader_defs.push(def.into());
+        }
+        Ok(())
+    }
+}
@@ -1,258 +1,316 @@
-use crate::world::component::Geometry;
+use crate::model::{MeshBuilder, Quad};
+use crate::world::component::{Face, Geometry, RenderLayer, Textures};
+use crate::world::texture::BlockAtlas;
+use bevy::math::{IVec3, Rect, Vec2, Vec3, Vec4};
 use bevy::prelude::Mesh;
-use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::mesh::{Indices, VertexAttributeValues};
 use zuri_world::block::component::ComponentStorage;
+use zuri_world::block::{BlockMap, RuntimeId};
 use zuri_world::chunk::Chunk;
 use zuri_world::pos::ChunkIndex;
 
-pub fn build_mesh(chunk: &Chunk) -> Mesh {
-    let geometries = chunk.block_map().components::<Geometry>();
-
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
-
-    let mut uv = Vec::<[f32; 2]>::new();
-    let mut normals = Vec::<[f32; 3]>::new();
-    let mut vertices = Vec::<[f32; 3]>::new();
-    let mut triangles = Vec::<u32>::new();
-    for x in 0..(16 as u8) {
-        for y in chunk.range() {
-            for z in 0..(16 as u8) {
-                let mut start_index = vertices.len() as u32;
-                if let Some(geo) = geometries.get(chunk.at(ChunkIndex::new(x, y, z))) {
-                    // The block has a custom geometry (it is not a cube) for now, this means
-                    // it is treated as visible.
-                    let geo_vertices = geo.mesh.attribute(Mesh::ATTRIBUTE_POSITION);
-                    if geo_vertices.is_some() {
-                        if let VertexAttributeValues::Float32x3(positions) = geo_vertices.unwrap() {
-                            for vertex in positions {
-                                vertices.push([
-                                    vertex[0] + x as f32,
-                                    vertex[1] + y as f32,
-                                    vertex[2] + z as f32,
-                                ]);
-                            }
-                        } else {
-                            unreachable!();
-                        }
-                    }
-                    let geo_normals = geo.mesh.attribute(Mesh::ATTRIBUTE_NORMAL);
-                    if geo_normals.is_some() {
-                        if let VertexAttributeValues::Float32x3(x) = geo_normals.unwrap() {
-                            for normal in x {
-                                normals.push(*normal);
-                            }
-                        } else {
-                            unreachable!();
-                        }
-                    }
-                    let geo_uvs = geo.mesh.attribute(Mesh::ATTRIBUTE_UV_0);
-                    if geo_uvs.is_some() {
-                        if let VertexAttributeValues::Float32x2(x) = geo_uvs.unwrap() {
-                            for val in x {
-                                uv.push(*val);
-                            }
-                        } else {
-                            unreachable!();
-                        }
-                    }
-                    let trias_opt = geo.mesh.indices();
-                    if let Some(trias) = trias_opt {
-                        for tria in trias.iter() {
-                            triangles.push(tria as u32 + start_index);
+/// The amount of blocks along each axis of a section.
+pub const SECTION_SIZE: i32 = 16;
+
+/// The size of a [SectionBlocks] along each axis. This includes a border of one block around the
+/// section, which is taken from the neighbouring sections.
+const PADDED_SIZE: i32 = SECTION_SIZE + 2;
+
+/// A copy of the blocks in a 16x16x16 section of a chunk, along with the blocks directly around it.
+///
+/// Meshes are built from a copy, so that they can be built on another thread while the chunk can
+/// still be modified. The border allows faces to be culled against the blocks of neighbouring
+/// sections and chunks.
+pub struct SectionBlocks {
+    blocks: Box<[RuntimeId]>,
+}
+
+impl SectionBlocks {
+    /// Copies a section of a chunk. The horizontal neighbours of the chunk are in the order north,
+    /// south, west, east. Blocks in neighbours that are not loaded, or that are above or below the
+    /// chunk, are treated as the `empty` block.
+    pub fn copy(
+        chunk: &Chunk,
+        neighbours: [Option<&Chunk>; 4],
+        section: usize,
+        empty: RuntimeId,
+    ) -> Self {
+        let range = chunk.range();
+        let min_y = range.min() as i32 + section as i32 * SECTION_SIZE;
+
+        let mut blocks = Vec::with_capacity((PADDED_SIZE * PADDED_SIZE * PADDED_SIZE) as usize);
+        for x in -1..=SECTION_SIZE {
+            for y in -1..=SECTION_SIZE {
+                for z in -1..=SECTION_SIZE {
+                    let inside_x = (0..SECTION_SIZE).contains(&x);
+                    let inside_z = (0..SECTION_SIZE).contains(&z);
+                    let source = match (inside_x, inside_z) {
+                        (true, true) => Some(chunk),
+                        (true, false) if z < 0 => neighbours[0],
+                        (true, false) => neighbours[1],
+                        (false, true) if x < 0 => neighbours[2],
+                        (false, true) => neighbours[3],
+                        // The corners are never needed.
+                        (false, false) => None,
+                    };
+
+                    let y = min_y + y;
+                    let id = match source {
+                        Some(source) if y >= range.min() as i32 && y <= range.max() as i32 => {
+                            source.at(ChunkIndex::new(
+                                x.rem_euclid(SECTION_SIZE) as u8,
+                                y as i16,
+                                z.rem_euclid(SECTION_SIZE) as u8,
+                            ))
                         }
-                    }
-                    continue;
-                }
-                if face_visible(chunk, geometries, x, y, z, 0, -1, 0) {
-                    vertices.push([x as f32, y as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, y as f32, z as f32]);
-                    vertices.push([x as f32, y as f32, (z + 1) as f32]);
-                    vertices.push([(x + 1) as f32, y as f32, (z + 1) as f32]);
-
-                    uv.push([0., 0.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([1., 1.]);
-
-                    normals.push([0., -1., 0.]);
-                    normals.push([0., -1., 0.]);
-                    normals.push([0., -1., 0.]);
-                    normals.push([0., -1., 0.]);
-
-                    // Down 1
-                    triangles.push(start_index + 0);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-                    // Down 2
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 3);
-
-                    start_index += 4;
+                        _ => empty,
+                    };
+                    blocks.push(id);
                 }
-                if face_visible(chunk, geometries, x, y, z, 0, 1, 0) {
-                    vertices.push([x as f32, (y + 1) as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, z as f32]);
-                    vertices.push([x as f32, (y + 1) as f32, (z + 1) as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, (z + 1) as f32]);
-
-                    uv.push([0., 0.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([1., 1.]);
-
-                    normals.push([0., 1.0, 0.]);
-                    normals.push([0., 1.0, 0.]);
-                    normals.push([0., 1.0, 0.]);
-                    normals.push([0., 1.0, 0.]);
-
-                    // Up 1
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 0);
-                    // Up 2
-                    triangles.push(start_index + 3);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-
-                    start_index += 4;
-                }
-                if face_visible(chunk, geometries, x, y, z, 0, 0, -1) {
-                    vertices.push([x as f32, y as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, y as f32, z as f32]);
-                    vertices.push([x as f32, (y + 1) as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, z as f32]);
-
-                    uv.push([0., 0.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([1., 1.]);
-
-                    normals.push([0., 0., -1.0]);
-                    normals.push([0., 0., -1.0]);
-                    normals.push([0., 0., -1.0]);
-                    normals.push([0., 0., -1.0]);
-
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 0);
-
-                    triangles.push(start_index + 3);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-
-                    start_index += 4;
-                }
-                if face_visible(chunk, geometries, x, y, z, 0, 0, 1) {
-                    vertices.push([x as f32, y as f32, (z + 1) as f32]);
-                    vertices.push([(x + 1) as f32, y as f32, (z + 1) as f32]);
-                    vertices.push([x as f32, (y + 1) as f32, (z + 1) as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, (z + 1) as f32]);
-
-                    uv.push([0., 0.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([1., 1.]);
-
-                    normals.push([0., 0., 1.0]);
-                    normals.push([0., 0., 1.0]);
-                    normals.push([0., 0., 1.0]);
-                    normals.push([0., 0., 1.0]);
-                    triangles.push(start_index + 0);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 3);
-
-                    start_index += 4;
-                }
-                if face_visible(chunk, geometries, x, y, z, 1, 0, 0) {
-                    vertices.push([(x + 1) as f32, y as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, y as f32, (z + 1) as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, z as f32]);
-                    vertices.push([(x + 1) as f32, (y + 1) as f32, (z + 1) as f32]);
-
-                    uv.push([0., 0.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([1., 1.]);
-
-                    normals.push([1., 0., 0.]);
-                    normals.push([1., 0., 0.]);
-                    normals.push([1., 0., 0.]);
-                    normals.push([1., 0., 0.]);
-
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 0);
-
-                    triangles.push(start_index + 3);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-
-                    start_index += 4;
+            }
+        }
+        Self {
+            blocks: blocks.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the block at a position relative to the section. Each coordinate can range from -1
+    /// to 16 to access the blocks around the section.
+    fn at(&self, pos: IVec3) -> RuntimeId {
+        let pos = pos + 1;
+        self.blocks[((pos.x * PADDED_SIZE + pos.y) * PADDED_SIZE + pos.z) as usize]
+    }
+}
+
+/// The meshes of a section for each [RenderLayer]. Layers without any faces have no mesh.
+pub type SectionMeshes = [Option<Mesh>; 3];
+
+/// Builds the meshes of a section, split up by the [RenderLayer] of each block.
+///
+/// Faces of full cubes are only added if they are not hidden by the neighbouring block, as decided
+/// by [face_hidden]. Neighbouring faces with the same texture are merged into a single quad, with
+/// the texture repeating for each block, as done by the
+/// [ChunkMaterial](super::material::ChunkMaterial).
+pub fn build_section_mesh(
+    blocks: &SectionBlocks,
+    block_map: &BlockMap,
+    atlas: &BlockAtlas,
+) -> SectionMeshes {
+    let geometries = block_map.components::<Geometry>();
+    let textures = block_map.components::<Textures>();
+    let layers = block_map.components::<RenderLayer>();
+    let layer = |runtime_id| layers.get(runtime_id).copied().unwrap_or_default();
+
+    let mut meshes: [MeshBuilder; 3] = Default::default();
+    for face in Face::ALL {
+        let normal = face.normal().as_ivec3();
+        let (u_axis, v_axis) = face_axes(face);
+        let n_axis = 3 - u_axis - v_axis;
+
+        for depth in 0..SECTION_SIZE {
+            let mut mask = [None; (SECTION_SIZE * SECTION_SIZE) as usize];
+            for v in 0..SECTION_SIZE {
+                for u in 0..SECTION_SIZE {
+                    let mut pos = IVec3::ZERO;
+                    pos[n_axis] = depth;
+                    pos[u_axis] = u;
+                    pos[v_axis] = v;
+
+                    let runtime_id = blocks.at(pos);
+                    if geometries.get(runtime_id).is_some()
+                        || face_hidden(runtime_id, blocks.at(pos + normal), geometries, layers)
+                    {
+                        continue;
+                    }
+                    let tile = atlas.face_uv(textures.get(runtime_id), face);
+                    mask[(v * SECTION_SIZE + u) as usize] = Some((layer(runtime_id), tile));
                 }
-                if face_visible(chunk, geometries, x, y, z, -1, 0, 0) {
-                    vertices.push([x as f32, y as f32, z as f32]);
-                    vertices.push([x as f32, y as f32, (z + 1) as f32]);
-                    vertices.push([x as f32, (y + 1) as f32, z as f32]);
-                    vertices.push([x as f32, (y + 1) as f32, (z + 1) as f32]);
-
-                    uv.push([1., 1.]);
-                    uv.push([1., 0.]);
-                    uv.push([0., 1.]);
-                    uv.push([0., 0.]);
-
-                    normals.push([-1., 0., 0.]);
-                    normals.push([-1., 0., 0.]);
-                    normals.push([-1., 0., 0.]);
-                    normals.push([-1., 0., 0.]);
-
-                    triangles.push(start_index + 0);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 2);
-
-                    triangles.push(start_index + 2);
-                    triangles.push(start_index + 1);
-                    triangles.push(start_index + 3);
+            }
+
+            for (u, v, width, height, (layer, tile)) in merge_faces(&mut mask) {
+                let (mut min, mut max) = (Vec3::ZERO, Vec3::ZERO);
+                min[n_axis] = depth as f32;
+                max[n_axis] = (depth + 1) as f32;
+                min[u_axis] = u as f32;
+                max[u_axis] = (u + width) as f32;
+                min[v_axis] = v as f32;
+                max[v_axis] = (v + height) as f32;
+
+                let (w, h) = (width as f32, height as f32);
+                let uvs = [
+                    Vec2::ZERO,
+                    Vec2::new(w, 0.),
+                    Vec2::new(w, h),
+                    Vec2::new(0., h),
+                ];
+                meshes[layer as usize]
+                    .coloured_quad(Quad::face(min, max, face, uvs), tile_color(tile));
+            }
+        }
+    }
+
+    for x in 0..SECTION_SIZE {
+        for y in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                let pos = IVec3::new(x, y, z);
+                let runtime_id = blocks.at(pos);
+                let Some(geo) = geometries.get(runtime_id) else {
+                    continue;
+                };
+
+                // Geometry UVs are relative to the texture of the face each vertex is facing, so
+                // the texture is picked based on the normal.
+                let mesh = &mut meshes[layer(runtime_id) as usize];
+                let block_textures = textures.get(runtime_id);
+                let color = |normal: Vec3| {
+                    let face = Face::from_normal(normal.to_array());
+                    tile_color(atlas.face_uv(block_textures, face))
+                };
+                mesh.append(&geo.mesh, pos.as_vec3(), color);
+                for (face, connection) in &geo.connections {
+                    if connects(blocks, geometries, pos + face.normal().as_ivec3()) {
+                        mesh.append(connection, pos.as_vec3(), color);
+                    }
                 }
             }
         }
     }
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uv);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    mesh.set_indices(Some(Indices::U32(triangles)));
 
-    mesh
+    meshes.map(|mesh| (!mesh.is_empty()).then(|| mesh.build()))
 }
 
-fn face_visible(
-    chunk: &Chunk,
+/// Checks if the face of a full cube is hidden by the neighbouring block it faces. Only full cubes
+/// hide faces: opaque ones hide all faces, while translucent ones only hide the faces of the same
+/// block, so that there are no faces inside bodies of water or between panes of glass. Cutout
+/// blocks never hide faces.
+fn face_hidden(
+    block: RuntimeId,
+    neighbour: RuntimeId,
     geometries: &ComponentStorage<Geometry>,
-    x: u8,
-    y: i16,
-    z: u8,
-    x_off: i8,
-    y_off: i16,
-    z_off: i8,
+    layers: &ComponentStorage<RenderLayer>,
 ) -> bool {
-    let max = 16 as u8 - 1;
-    if x_off < 0 && x == 0
-        || x_off > 0 && x == max
-        || y_off < 0 && y == chunk.range().min()
-        || y_off > 0 && y == chunk.range().max()
-        || z_off < 0 && z == 0
-        || z_off > 0 && z == max
-    {
-        return true;
+    if geometries.get(neighbour).is_some() {
+        return false;
+    }
+    match layers.get(neighbour).copied().unwrap_or_default() {
+        RenderLayer::Opaque => true,
+        RenderLayer::Cutout => false,
+        RenderLayer::Translucent => block == neighbour,
+    }
+}
+
+/// Sorts the triangles of a mesh from back to front as seen from a position relative to the mesh,
+/// so that overlapping translucent faces are blended in the right order.
+pub fn sort_triangles(mesh: &mut Mesh, eye: Vec3) {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return;
+    };
+    let Some(Indices::U32(indices)) = mesh.indices() else {
+        return;
+    };
+
+    let mut triangles: Vec<(f32, [u32; 3])> = indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let centre = triangle
+                .iter()
+                .map(|&i| Vec3::from(positions[i as usize]))
+                .sum::<Vec3>()
+                / 3.;
+            (
+                centre.distance_squared(eye),
+                [triangle[0], triangle[1], triangle[2]],
+            )
+        })
+        .collect();
+    triangles.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let indices = triangles.into_iter().flat_map(|(_, t)| t).collect();
+    mesh.set_indices(Some(Indices::U32(indices)));
+}
+
+/// Returns the axes along which the U and V coordinates of a face run.
+fn face_axes(face: Face) -> (usize, usize) {
+    match face {
+        Face::Down | Face::Up => (0, 2),
+        Face::North | Face::South => (0, 1),
+        Face::West | Face::East => (2, 1),
+    }
+}
+
+/// Greedily merges the faces in a 16x16 mask into rectangles of faces with the same texture. The
+/// mask is cleared in the process. Rectangles are returned as `(u, v, width, height, texture)`.
+fn merge_faces<T: Copy + PartialEq>(mask: &mut [Option<T>]) -> Vec<(i32, i32, i32, i32, T)> {
+    let size = SECTION_SIZE;
+    let index = |u: i32, v: i32| (v * size + u) as usize;
+
+    let mut rects = Vec::new();
+    for v in 0..size {
+        let mut u = 0;
+        while u < size {
+            let Some(tile) = mask[index(u, v)] else {
+                u += 1;
+                continue;
+            };
+
+            let mut width = 1;
+            while u + width < size && mask[index(u + width, v)] == Some(tile) {
+                width += 1;
+            }
+            let mut height = 1;
+            while v + height < size
+                && (u..u + width).all(|u| mask[index(u, v + height)] == Some(tile))
+            {
+                height += 1;
+            }
+
+            for dv in 0..height {
+                for du in 0..width {
+                    mask[index(u + du, v + dv)] = None;
+                }
+            }
+            rects.push((u, v, width, height, tile));
+            u += width;
+        }
+    }
+    rects
+}
+
+/// Encodes the UV rectangle of a texture in the atlas as a vertex colour.
+fn tile_color(tile: Rect) -> Vec4 {
+    Vec4::new(tile.min.x, tile.min.y, tile.width(), tile.height())
+}
+
+/// Checks if a block with connections connects to the neighbour at a position. Blocks connect to
+/// full cubes and to other blocks that have connections.
+fn connects(blocks: &SectionBlocks, geometries: &ComponentStorage<Geometry>, pos: IVec3) -> bool {
+    match geometries.get(blocks.at(pos)) {
+        Some(geo) => !geo.connections.is_empty(),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let stone = Rect::new(0., 0., 0.5, 0.5);
+        let dirt = Rect::new(0.5, 0., 1., 0.5);
+
+        let mut mask = [None; 256];
+        for v in 0..4 {
+            for u in 0..3 {
+                mask[v * 16 + u] = Some(stone);
+            }
+        }
+        mask[3] = Some(dirt);
+
+        let rects = merge_faces(&mut mask);
+        assert_eq!(rects, vec![(0, 0, 3, 4, stone), (3, 0, 1, 1, dirt)]);
+        assert!(mask.iter().all(Option::is_none));
     }
-    let neighbour_geo = geometries.get(chunk.at(ChunkIndex::new(
-        (x as i8 + x_off) as u8,
-        (y + y_off) as i16,
-        (z as i8 + z_off) as u8,
-    )));
-    neighbour_geo.is_some() // todo: have a smarter system for this
 }
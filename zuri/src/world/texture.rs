@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bevy::math::Rect;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use json::JsonValue;
+use zuri_world::block::{BlockMap, PropertyValue};
+use zuri_world::chunk::Chunk;
+
+use crate::resource_pack::ResourcePacks;
+use crate::world::component::{Face, RenderLayer, TextureRef, Textures};
+use crate::world::material::ChunkMaterial;
+
+/// The texture used for faces that do not have a texture, or whose texture could not be loaded.
+const FALLBACK_TEXTURE: &[u8] = include_bytes!("../../assets/dirt.png");
+
+/// Integer block properties that select a variant of a block's texture, in order of priority. The
+/// value of the property is used as the index of the variant. This is how crops show their growth
+/// stages, for example.
+const VARIANT_PROPERTIES: &[&str] = &["growth", "age"];
+
+/// Contains all block textures from the resource packs stitched into a single image, along with the
+/// materials used to render chunks.
+///
+/// Cloning the atlas is cheap, so that it can be sent to the tasks that build chunk meshes.
+#[derive(Resource, Default, Clone)]
+pub struct BlockAtlas {
+    /// The stitched texture atlas. The handle stays the same when the atlas is rebuilt.
+    pub image: Handle<Image>,
+    /// The materials used for rendering chunks for each [RenderLayer], which use [Self::image] as
+    /// their texture.
+    pub materials: [Handle<ChunkMaterial>; 3],
+    /// Maps the short name of each texture to the UV rectangles of its variants.
+    uvs: Arc<HashMap<Box<str>, Vec<Rect>>>,
+    /// The UV rectangle of the fallback texture.
+    fallback: Rect,
+}
+
+impl BlockAtlas {
+    /// Returns the material used to render the faces of a certain layer.
+    pub fn material(&self, layer: RenderLayer) -> Handle<ChunkMaterial> {
+        self.materials[layer as usize].clone()
+    }
+
+    /// Returns the UV rectangle in the atlas for a texture. If the texture does not exist, the UV
+    /// rectangle of the fallback texture is returned. If the variant does not exist, the last
+    /// variant of the texture is used instead.
+    pub fn uv(&self, texture: &TextureRef) -> Rect {
+        self.uvs
+            .get(texture.name.as_ref())
+            .and_then(|variants| variants.get(texture.variant).or(variants.last()))
+            .copied()
+            .unwrap_or(self.fallback)
+    }
+
+    /// Returns the UV rectangle of a face of a block with the provided textures. If the block has
+    /// no textures, the fallback texture is used.
+    pub fn face_uv(&self, textures: Option<&Textures>, face: Face) -> Rect {
+        textures
+            .map(|textures| self.uv(textures.face(face)))
+            .unwrap_or(self.fallback)
+    }
+}
+
+/// Creates the handles for the atlas image and the chunk materials. The actual atlas is built by
+/// [atlas_build_system] once the resource packs are available.
+pub(super) fn atlas_init_system(
+    mut atlas: ResMut<BlockAtlas>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+) {
+    atlas.image = images.add(fallback_image());
+    atlas.materials = RenderLayer::ALL.map(|layer| {
+        materials.add(ChunkMaterial {
+            atlas: atlas.image.clone(),
+            layer,
+        })
+    });
+}
+
+/// Rebuilds the [BlockAtlas] whenever the resource packs change, and marks all chunks as changed
+/// so that their meshes are rebuilt using the new UVs.
+pub(super) fn atlas_build_system(
+    packs: Res<ResourcePacks>,
+    mut atlas: ResMut<BlockAtlas>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+    mut chunks: Query<&mut Chunk>,
+) {
+    if !packs.is_changed() {
+        return;
+    }
+
+    let (image, uvs, fallback) = stitch(&packs);
+    info!(
+        "Built block atlas with {} textures ({}x{})",
+        uvs.len(),
+        image.texture_descriptor.size.width,
+        image.texture_descriptor.size.height,
+    );
+    images.set_untracked(atlas.image.id(), image);
+    atlas.uvs = Arc::new(uvs);
+    atlas.fallback = fallback;
+    // Touching the materials makes sure their bind groups are recreated with the new image.
+    for material in &atlas.materials {
+        materials.get_mut(material);
+    }
+
+    for mut chunk in &mut chunks {
+        chunk.set_changed();
+    }
+}
+
+/// Reads the face textures of all blocks from `blocks.json`, merging the definitions of all packs.
+///
+/// The returned map can be passed to [assign_textures] to give each block its [Textures] component.
+pub(super) fn read_block_textures(packs: &ResourcePacks) -> HashMap<String, FaceTextures> {
+    let mut blocks = HashMap::new();
+    for file in packs.read_json_all("blocks.json") {
+        for (name, entry) in file.entries() {
+            if name == "format_version" {
+                continue;
+            }
+            if let Some(faces) = FaceTextures::parse(&entry["textures"]) {
+                blocks.insert(name.to_string(), faces);
+            }
+        }
+    }
+    blocks
+}
+
+/// Gives every block in the block map that has an entry in `blocks.json` a [Textures] component.
+///
+/// Block states affect the textures too: blocks with a `pillar_axis` property, such as logs, have
+/// their textures rotated, integer properties such as `growth` select the texture variant and
+/// curved rails use the curved rail texture.
+pub(super) fn assign_textures(block_map: &mut BlockMap, blocks: &HashMap<String, FaceTextures>) {
+    let mut textures = Vec::new();
+    for runtime_id in 0..block_map.runtime_ids() {
+        let block = block_map.block(runtime_id).unwrap();
+        let identifier = block.identifier();
+        let faces = blocks
+            .get(identifier)
+            .or_else(|| blocks.get(identifier.trim_start_matches("minecraft:")));
+        let Some(faces) = faces else {
+            continue;
+        };
+
+        let variant = VARIANT_PROPERTIES
+            .iter()
+            .find_map(|name| match block.property_value(name) {
+                Some(PropertyValue::Int(v)) => Some(v.max(0) as usize),
+                _ => None,
+            })
+            .unwrap_or(0);
+        let axis = match block.property_value("pillar_axis") {
+            Some(PropertyValue::String(axis)) => axis.chars().next().unwrap_or('y'),
+            _ => 'y',
+        };
+        // Rails define their curved texture as the texture of their bottom face.
+        let curved = matches!(
+            block.property_value("rail_direction"),
+            Some(PropertyValue::Int(6..=9))
+        );
+
+        textures.push((
+            runtime_id,
+            Textures {
+                faces: Face::ALL.map(|face| {
+                    let face = if curved {
+                        Face::Down
+                    } else {
+                        rotate_face(face, axis)
+                    };
+                    TextureRef {
+                        name: faces.get(face).into(),
+                        variant,
+                    }
+                }),
+            },
+        ));
+    }
+
+    for (runtime_id, component) in textures {
+        block_map.set_component(runtime_id, component);
+    }
+}
+
+/// Maps a face of a rotated pillar-like block to the face of an upright block that has the same
+/// texture.
+fn rotate_face(face: Face, axis: char) -> Face {
+    match (axis, face) {
+        ('x', Face::West | Face::East) => Face::Up,
+        ('x', Face::Up | Face::Down) => Face::East,
+        ('z', Face::North | Face::South) => Face::Up,
+        ('z', Face::Up | Face::Down) => Face::North,
+        _ => face,
+    }
+}
+
+/// The short texture names used for the faces of a block, as defined in `blocks.json`.
+pub(super) struct FaceTextures {
+    faces: [Box<str>; 6],
+}
+
+impl FaceTextures {
+    /// Parses the `textures` field of a `blocks.json` entry. It is either a single texture name for
+    /// all faces, or an object with `up`, `down` and `side` fields. Sides can be overridden
+    /// individually with `north`, `south`, `east` and `west`.
+    fn parse(textures: &JsonValue) -> Option<Self> {
+        if let Some(name) = textures.as_str() {
+            return Some(Self {
+                faces: Face::ALL.map(|_| name.into()),
+            });
+        }
+        if !textures.is_object() {
+            return None;
+        }
+
+        let side = textures["side"].as_str();
+        let faces = Face::ALL.map(|face| {
+            textures[face.name()]
+                .as_str()
+                .or(side)
+                .unwrap_or_default()
+                .into()
+        });
+        Some(Self { faces })
+    }
+
+    /// Creates the face textures of a custom block from its `minecraft:material_instances`
+    /// component, which maps face names to texture names. The `*` instance is used for all faces
+    /// that do not have their own instance.
+    pub(super) fn from_material_instances(materials: &HashMap<String, String>) -> Self {
+        let default = materials.get("*");
+        let side = materials.get("side").or(default);
+        Self {
+            faces: Face::ALL.map(|face| {
+                let fallback = match face {
+                    Face::Down | Face::Up => default,
+                    _ => side,
+                };
+                materials
+                    .get(face.name())
+                    .or(fallback)
+                    .map(String::as_str)
+                    .unwrap_or_default()
+                    .into()
+            }),
+        }
+    }
+
+    fn get(&self, face: Face) -> &str {
+        &self.faces[face as usize]
+    }
+}
+
+/// Stitches all textures from `terrain_texture.json` into a single atlas image. Every texture is
+/// placed in a grid cell of the same size. Returns the atlas, the UV rectangles of all textures and
+/// the UV rectangle of the fallback texture.
+fn stitch(packs: &ResourcePacks) -> (Image, HashMap<Box<str>, Vec<Rect>>, Rect) {
+    // Later packs override the textures of earlier packs, but only per texture name.
+    let mut definitions = HashMap::new();
+    for file in packs.read_json_all("textures/terrain_texture.json") {
+        for (name, entry) in file["texture_data"].entries() {
+            definitions.insert(name.to_string(), texture_paths(&entry["textures"]));
+        }
+    }
+
+    // Load all images, sharing cells between textures that use the same path. The first cell is
+    // reserved for the fallback texture.
+    let mut tiles = vec![fallback_image()];
+    let mut cells = HashMap::<String, Option<usize>>::new();
+    for path in definitions.values().flatten() {
+        if cells.contains_key(path) {
+            continue;
+        }
+        let cell = load_tile(packs, path).map(|tile| {
+            tiles.push(tile);
+            tiles.len() - 1
+        });
+        cells.insert(path.clone(), cell);
+    }
+
+    let tile_size = tiles
+        .iter()
+        .map(|tile| tile.texture_descriptor.size.width)
+        .max()
+        .unwrap();
+    let columns = (tiles.len() as f32).sqrt().ceil() as u32;
+    let rows = (tiles.len() as f32 / columns as f32).ceil() as u32;
+    let size = Extent3d {
+        width: columns * tile_size,
+        height: rows * tile_size,
+        depth_or_array_layers: 1,
+    };
+
+    let mut atlas = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    let mut cell_uvs = Vec::with_capacity(tiles.len());
+    for (i, tile) in tiles.iter().enumerate() {
+        let (x, y) = (
+            i as u32 % columns * tile_size,
+            i as u32 / columns * tile_size,
+        );
+        blit(&mut atlas, tile, x, y, tile_size);
+        cell_uvs.push(Rect::new(
+            x as f32 / size.width as f32,
+            y as f32 / size.height as f32,
+            (x + tile_size) as f32 / size.width as f32,
+            (y + tile_size) as f32 / size.height as f32,
+        ));
+    }
+
+    let uvs = definitions
+        .into_iter()
+        .map(|(name, paths)| {
+            let variants = paths
+                .iter()
+                .map(|path| cell_uvs[cells[path].unwrap_or(0)])
+                .collect();
+            (name.into_boxed_str(), variants)
+        })
+        .collect();
+    (atlas, uvs, cell_uvs[0])
+}
+
+/// Reads the image paths of a texture definition. The definition can be a single path, an object
+/// with a `path` field, or an array of either for textures with multiple variants. Objects may also
+/// list `variations`, of which only the first is used.
+fn texture_paths(textures: &JsonValue) -> Vec<String> {
+    fn path(entry: &JsonValue) -> Option<String> {
+        if let Some(path) = entry.as_str() {
+            return Some(path.to_string());
+        }
+        entry["path"]
+            .as_str()
+            .or(entry["variations"][0]["path"].as_str())
+            .map(String::from)
+    }
+
+    if textures.is_array() {
+        textures.members().filter_map(path).collect()
+    } else {
+        path(textures).into_iter().collect()
+    }
+}
+
+/// Loads a single texture from the resource packs as a square RGBA image. Animated textures are
+/// stored as a vertical strip of frames, so only the first frame is kept.
+fn load_tile(packs: &ResourcePacks, path: &str) -> Option<Image> {
+    let (contents, ext) = packs.read_any(path, &["png", "tga"])?;
+    let image = Image::from_buffer(
+        &contents,
+        ImageType::Extension(ext),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(|err| debug!("Could not decode texture `{path}`: {err}"))
+    .ok()?
+    .convert(TextureFormat::Rgba8UnormSrgb)?;
+
+    let width = image.texture_descriptor.size.width;
+    let height = image.texture_descriptor.size.height;
+    if width == 0 || height < width {
+        debug!("Skipping texture `{path}` with unsupported size {width}x{height}");
+        return None;
+    }
+    let mut tile = image;
+    tile.data.truncate((width * width * 4) as usize);
+    tile.texture_descriptor.size.height = width;
+    Some(tile)
+}
+
+/// Decodes the embedded fallback texture.
+fn fallback_image() -> Image {
+    Image::from_buffer(
+        FALLBACK_TEXTURE,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .expect("Could not decode fallback texture")
+    .convert(TextureFormat::Rgba8UnormSrgb)
+    .unwrap()
+}
+
+/// Copies a square tile into the atlas at the provided pixel position. Tiles smaller than the cell
+/// size are scaled up using nearest neighbour sampling.
+fn blit(atlas: &mut Image, tile: &Image, x: u32, y: u32, cell_size: u32) {
+    let atlas_width = atlas.texture_descriptor.size.width;
+    let tile_size = tile.texture_descriptor.size.width;
+    for dy in 0..cell_size {
+        for dx in 0..cell_size {
+            let src = ((dy * tile_size / cell_size * tile_size + dx * tile_size / cell_size) * 4)
+                as usize;
+            let dst = (((y + dy) * atlas_width + x + dx) * 4) as usize;
+            atlas.data[dst..dst + 4].copy_from_slice(&tile.data[src..src + 4]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_textures() {
+        let all = FaceTextures::parse(&json::parse(r#""stone""#).unwrap()).unwrap();
+        assert!(Face::ALL.iter().all(|face| all.get(*face) == "stone"));
+
+        let faces = FaceTextures::parse(
+            &json::parse(
+                r#"{"up": "log_top", "down": "log_top", "side": "log", "east": "log_east"}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(faces.get(Face::Up), "log_top");
+        assert_eq!(faces.get(Face::Down), "log_top");
+        assert_eq!(faces.get(Face::North), "log");
+        assert_eq!(faces.get(Face::East), "log_east");
+
+        assert!(FaceTextures::parse(&json::parse("[]").unwrap()).is_none());
+    }
+
+    #[test]
+    fn paths() {
+        let paths = |src: &str| texture_paths(&json::parse(src).unwrap());
+        assert_eq!(
+            paths(r#""textures/blocks/stone""#),
+            ["textures/blocks/stone"]
+        );
+        assert_eq!(
+            paths(r##"{"path": "textures/blocks/dirt", "overlay_color": "#79c05a"}"##),
+            ["textures/blocks/dirt"]
+        );
+        assert_eq!(
+            paths(r#"{"variations": [{"path": "textures/blocks/stone_1"}]}"#),
+            ["textures/blocks/stone_1"]
+        );
+        assert_eq!(
+            paths(r#"["textures/blocks/wheat_0", {"path": "textures/blocks/wheat_1"}, 3]"#),
+            ["textures/blocks/wheat_0", "textures/blocks/wheat_1"]
+        );
+        assert!(paths("{}").is_empty());
+    }
+
+    #[test]
+    fn pillar_faces() {
+        assert_eq!(rotate_face(Face::Up, 'y'), Face::Up);
+        assert_eq!(rotate_face(Face::North, 'y'), Face::North);
+        assert_eq!(rotate_face(Face::East, 'x'), Face::Up);
+        assert_eq!(rotate_face(Face::Up, 'x'), Face::East);
+        assert_eq!(rotate_face(Face::North, 'x'), Face::North);
+        assert_eq!(rotate_face(Face::South, 'z'), Face::Up);
+        assert_eq!(rotate_face(Face::Down, 'z'), Face::North);
+        assert_eq!(rotate_face(Face::West, 'z'), Face::West);
+    }
+}
@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use base64ct::{Base64, Base64Unpadded, Encoding};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation};
+use log::warn;
 use oauth2::basic::BasicTokenResponse;
 use p384::ecdsa::VerifyingKey;
 use p384::pkcs8::{DecodePublicKey, EncodePrivateKey, EncodePublicKey};
@@ -14,6 +16,7 @@ use zuri_xbox::{minecraft, xbox};
 use crate::chan::PkReceiver;
 use crate::client::auth::{IdentityClaims, IdentityPublicKeyClaims, Request, SaltClaims};
 use crate::client::data::{ClientData, IdentityData};
+use crate::client::pack::DownloadedPack;
 use crate::connection::*;
 use crate::encryption::Encryption;
 use crate::proto::packet::chunk_radius_updated::ChunkRadiusUpdated;
@@ -24,7 +27,10 @@ use crate::proto::packet::network_settings::NetworkSettings;
 use crate::proto::packet::play_status::{PlayStatus, PlayStatusType};
 use crate::proto::packet::request_chunk_radius::RequestChunkRadius;
 use crate::proto::packet::request_network_settings::RequestNetworkSettings;
+use crate::proto::packet::resource_pack_chunk_data::ResourcePackChunkData;
+use crate::proto::packet::resource_pack_chunk_request::ResourcePackChunkRequest;
 use crate::proto::packet::resource_pack_client_response::ResourcePackClientResponse;
+use crate::proto::packet::resource_pack_data_info::ResourcePackDataInfo;
 use crate::proto::packet::resource_pack_stack::ResourcePackStack;
 use crate::proto::packet::resource_packs_info::ResourcePacksInfo;
 use crate::proto::packet::server_to_client_handshake::ServerToClientHandshake;
@@ -44,13 +50,15 @@ pub struct LoginSequence<'a> {
 }
 
 #[async_trait]
-impl<'a> Sequence<Result<(), ConnError>> for LoginSequence<'a> {
+impl<'a> Sequence<Result<Vec<DownloadedPack>, ConnError>> for LoginSequence<'a> {
+    /// Logs in to the server, returning the resource packs downloaded from the server in the order
+    /// they should be applied in.
     async fn execute(
         self,
         mut reader: PkReceiver,
         conn: Arc<Connection>,
         expectancies: Arc<ExpectedPackets>,
-    ) -> Result<(), ConnError> {
+    ) -> Result<Vec<DownloadedPack>, ConnError> {
         // The first bit of the login sequence requires us to request the network settings the
         // server is using from the server. These dictate options for mostly compression, but also
         // various other things that aren't relevant to us.
@@ -105,7 +113,9 @@ impl<'a> Sequence<Result<(), ConnError>> for LoginSequence<'a> {
         // containing all the information about the resource packs the server is using.
         expectancies.queue::<StartGame>().await;
         expectancies.queue::<ResourcePackStack>().await;
-        self.download_resource_packs(&mut reader, &conn).await?;
+        let packs = self
+            .download_resource_packs(&mut reader, &conn, &expectancies)
+            .await?;
 
         // The StartGame packet contains our runtime ID which we need later in the sequence.
         let mut rid = 0;
@@ -144,7 +154,7 @@ impl<'a> Sequence<Result<(), ConnError>> for LoginSequence<'a> {
         conn.flush().await?;
 
         // We're done!
-        Ok(())
+        Ok(packs)
     }
 }
 
@@ -256,10 +266,40 @@ impl<'a> LoginSequence<'a> {
         &self,
         reader: &mut PkReceiver,
         conn: &Connection,
-    ) -> Result<(), ConnError> {
-        ResourcePacksInfo::try_from(reader.recv().await).unwrap();
-
-        // TODO: Implement proper resource pack downloading
+        expectancies: &ExpectedPackets,
+    ) -> Result<Vec<DownloadedPack>, ConnError> {
+        let info = ResourcePacksInfo::try_from(reader.recv().await)
+            .map_err(|_| ConnError::UnexpectedPacket("ResourcePacksInfo".into()))?;
+
+        // Encrypted packs can't be read without the keys of the marketplace, so there is no use in
+        // downloading them.
+        let versions: HashMap<_, _> = info
+            .texture_packs
+            .iter()
+            .filter(|pack| pack.content_key.is_empty())
+            .map(|pack| (pack.uuid.clone(), pack.version.clone()))
+            .collect();
+        let mut packs = Vec::new();
+        if !versions.is_empty() {
+            for _ in 0..versions.len() {
+                expectancies.queue::<ResourcePackDataInfo>().await;
+            }
+            conn.write_packet(
+                &ResourcePackClientResponse {
+                    response: ResourcePackResponse::SendPacks,
+                    packs_to_download: versions
+                        .iter()
+                        .map(|(uuid, version)| format!("{}_{}", uuid, version))
+                        .collect(),
+                }
+                .into(),
+            )
+            .await;
+            conn.flush().await?;
+            packs = self
+                .download_packs(reader, conn, expectancies, &versions)
+                .await?;
+        }
 
         conn.write_packet(
             &ResourcePackClientResponse {
@@ -271,7 +311,16 @@ impl<'a> LoginSequence<'a> {
         .await;
         conn.flush().await?;
 
-        ResourcePackStack::try_from(reader.recv().await).unwrap();
+        // The stack holds the order in which the packs are applied, starting with the pack with
+        // the lowest priority.
+        let stack = ResourcePackStack::try_from(reader.recv().await)
+            .map_err(|_| ConnError::UnexpectedPacket("ResourcePackStack".into()))?;
+        packs.sort_by_key(|pack| {
+            stack
+                .texture_packs
+                .iter()
+                .position(|stacked| stacked.uuid == pack.uuid)
+        });
 
         conn.write_packet(
             &ResourcePackClientResponse {
@@ -283,7 +332,75 @@ impl<'a> LoginSequence<'a> {
         .await;
         conn.flush().await?;
 
-        Ok(())
+        Ok(packs)
+    }
+
+    /// Downloads the packs requested from the server, chunk by chunk. The server first sends the
+    /// data info of every pack, after which the chunks of a pack are requested one at a time. Packs
+    /// that do not match their hash are left out.
+    async fn download_packs(
+        &self,
+        reader: &mut PkReceiver,
+        conn: &Connection,
+        expectancies: &ExpectedPackets,
+        versions: &HashMap<String, String>,
+    ) -> Result<Vec<DownloadedPack>, ConnError> {
+        let mut downloads = HashMap::new();
+        let mut packs = Vec::new();
+        let mut remaining = versions.len();
+        while remaining > 0 {
+            let request = match reader.recv().await {
+                Packet::ResourcePackDataInfo(info) => {
+                    let request = ResourcePackChunkRequest {
+                        uuid: info.uuid.clone(),
+                        chunk_index: 0,
+                    };
+                    downloads.insert(info.uuid.clone(), (info, Vec::new()));
+                    request
+                }
+                Packet::ResourcePackChunkData(chunk) => {
+                    let Some((_, data)) = downloads.get_mut(&chunk.uuid) else {
+                        continue;
+                    };
+                    data.extend_from_slice(&chunk.data);
+                    ResourcePackChunkRequest {
+                        uuid: chunk.uuid,
+                        chunk_index: chunk.chunk_index + 1,
+                    }
+                }
+                _ => {
+                    return Err(ConnError::UnexpectedPacket(
+                        "ResourcePackDataInfo or ResourcePackChunkData".into(),
+                    ))
+                }
+            };
+
+            let Some((info, _)) = downloads.get(&request.uuid) else {
+                continue;
+            };
+            if request.chunk_index < info.chunk_count {
+                expectancies.queue::<ResourcePackChunkData>().await;
+                conn.write_packet(&request.into()).await;
+                conn.flush().await?;
+                continue;
+            }
+            let Some((info, data)) = downloads.remove(&request.uuid) else {
+                continue;
+            };
+            remaining -= 1;
+            // The UUID of the data info may carry the version of the pack after an underscore.
+            let uuid = info.uuid.split('_').next().unwrap_or_default().to_string();
+            if !info.hash.is_empty() && Sha256::digest(&data).as_slice() != info.hash.as_ref() {
+                warn!("Resource pack {uuid} does not match its hash, leaving it out");
+                continue;
+            }
+            packs.push(DownloadedPack {
+                version: versions.get(&uuid).cloned().unwrap_or_default(),
+                uuid,
+                data,
+            });
+        }
+        Ok(packs)
     }
 
     async fn await_start_game(
@@ -1,21 +1,26 @@
+use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use oauth2::basic::BasicTokenResponse;
 use rust_raknet::RaknetSocket;
+use tokio::net::lookup_host;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::Mutex;
 
 use crate::chan::{pk_chan, PkSender};
 use crate::client::data::{ClientData, IdentityData};
 use crate::client::login::LoginSequence;
+use crate::client::pack::DownloadedPack;
 use crate::connection::{ConnError, Connection, ExpectedPackets, Sequence};
+use crate::proto::packet::disconnect::Disconnect;
 use crate::proto::packet::Packet;
 
 mod auth;
 pub mod data;
 pub mod login;
+pub mod pack;
 
 pub struct Client<H: Handler + Send + 'static> {
     conn: Arc<Connection>,
@@ -24,6 +29,8 @@ pub struct Client<H: Handler + Send + 'static> {
 
     client_data: ClientData,
     identity_data: IdentityData,
+    live_token: Option<BasicTokenResponse>,
+    resource_packs: Vec<DownloadedPack>,
 }
 
 impl<H: Handler + Send + 'static> Client<H> {
@@ -45,19 +52,19 @@ impl<H: Handler + Send + 'static> Client<H> {
         guaranteed_client_data.server_address = ip.to_string();
         guaranteed_client_data.third_party_name = guaranteed_identity_data.display_name.clone();
 
-        let socket = RaknetSocket::connect_with_version(&ip, 11)
-            .await
-            .expect("TODO: panic message"); // TODO: panic message
+        let socket = RaknetSocket::connect_with_version(&ip, 11).await?;
 
         let (send, recv) = channel(1);
         let (seq_send, seq_recv) = channel(1);
-        let client = Self {
+        let mut client = Self {
             conn: Arc::new(Connection::new(socket)),
             handler: Arc::new(Mutex::new(handler)),
             seq_chan: seq_send,
 
             client_data: guaranteed_client_data,
             identity_data: guaranteed_identity_data,
+            live_token,
+            resource_packs: Vec::new(),
         };
         tokio::spawn(Self::read_loop(send, client.conn.clone(), seq_recv));
         tokio::spawn(Self::handle_loop(
@@ -66,19 +73,59 @@ impl<H: Handler + Send + 'static> Client<H> {
             client.conn.clone(),
         ));
 
-        client
+        client.resource_packs = client
             .exec_sequence(LoginSequence::new(
                 &client.client_data,
                 &client.identity_data,
-                live_token,
+                client.live_token.clone(),
                 false,
             ))
             .await?;
         Ok(client)
     }
 
-    pub async fn disconnect(&self) {
-        let _ = self.conn.close().await.map_err(|_| unreachable!());
+    /// Takes the resource packs that were downloaded from the server while logging in, in the order
+    /// they should be applied in. The packs are only returned once.
+    pub fn take_resource_packs(&mut self) -> Vec<DownloadedPack> {
+        std::mem::take(&mut self.resource_packs)
+    }
+
+    /// Disconnects from the server and connects to another server, as the Transfer packet asks the
+    /// client to do. The new connection logs in with the same client and identity data, and its
+    /// packets are handled by a new handler.
+    pub async fn transfer(&self, address: &str, port: u16, handler: H) -> Result<Self, ConnError> {
+        // The old connection may already be gone, which doesn't stop us from connecting to the new
+        // server.
+        let _ = self.disconnect().await;
+
+        let ip = lookup_host((address, port))
+            .await
+            .map_err(|err| ConnError::AddressError(format!("{}:{}: {}", address, port, err)))?
+            .next()
+            .ok_or_else(|| ConnError::AddressError(format!("{}:{}", address, port)))?;
+        Self::connect(
+            ip,
+            self.client_data.clone(),
+            Some(self.identity_data.clone()),
+            self.live_token.clone(),
+            handler,
+        )
+        .await
+    }
+
+    /// Tells the server that the client is leaving with a Disconnect packet, and closes the
+    /// connection. The handler is notified with [DisconnectReason::Local] once the connection has
+    /// closed.
+    pub async fn disconnect(&self) -> Result<(), ConnError> {
+        self.conn
+            .write_packet(&Packet::Disconnect(Disconnect {
+                reason: 0.into(),
+                message: None,
+            }))
+            .await;
+        let flushed = self.conn.flush().await;
+        self.conn.close().await?;
+        flushed
     }
 
     pub async fn write_packet(&self, packet: &mut Packet) -> Result<(), ConnError> {
@@ -105,7 +152,7 @@ impl<H: Handler + Send + 'static> Client<H> {
     }
 
     async fn read_loop(
-        chan: Sender<Packet>,
+        chan: Sender<Result<Packet, DisconnectReason>>,
         conn: Arc<Connection>,
         mut seq_recv: Receiver<(PkSender, Arc<ExpectedPackets>)>,
     ) {
@@ -135,12 +182,27 @@ impl<H: Handler + Send + 'static> Client<H> {
                             expecter = None;
                         }
                     }
+                    let kicked = match &pk {
+                        Packet::Disconnect(pk) => Some(pk.message.clone()),
+                        _ => None,
+                    };
                     // We can call expect here: the handler stops if the read loop stops.
-                    chan.send(pk)
+                    chan.send(Ok(pk))
                         .await
                         .expect("Could not send packet to handler");
+                    if let Some(message) = kicked {
+                        let _ = conn.close().await;
+                        let _ = chan.send(Err(DisconnectReason::Kicked(message))).await;
+                        return;
+                    }
                 }
-                Err(_) => {
+                Err(err) => {
+                    let reason = match err {
+                        _ if conn.is_closed() => DisconnectReason::Local,
+                        ConnError::DecodeError(err) => DisconnectReason::DecodeError(err),
+                        _ => DisconnectReason::Timeout,
+                    };
+                    let _ = chan.send(Err(reason)).await;
                     return;
                 }
             };
@@ -148,22 +210,63 @@ impl<H: Handler + Send + 'static> Client<H> {
     }
 
     async fn handle_loop<T: Handler + Send>(
-        mut chan: Receiver<Packet>,
+        mut chan: Receiver<Result<Packet, DisconnectReason>>,
         handler: Arc<Mutex<T>>,
         conn: Arc<Connection>,
     ) {
         loop {
-            if let Some(pk) = chan.recv().await {
-                let mut response = handler.lock().await.handle_incoming(pk).await;
+            match chan.recv().await {
+                Some(Ok(pk)) => {
+                    let mut response = handler.lock().await.handle_incoming(pk).await;
 
-                for pk in &mut response {
-                    conn.write_packet(pk).await;
+                    for pk in &mut response {
+                        conn.write_packet(pk).await;
+                    }
+                    // A failed flush means the connection is gone, which the read loop reports.
+                    let _ = conn.flush().await;
+                }
+                Some(Err(reason)) => {
+                    handler.lock().await.handle_disconnect(reason).await;
+                    return;
                 }
-                conn.flush().await.unwrap();
-            } else {
-                handler.lock().await.handle_disconnect(None).await; // todo: reason
-                return;
+                None => {
+                    handler
+                        .lock()
+                        .await
+                        .handle_disconnect(DisconnectReason::Timeout)
+                        .await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Why the connection to the server ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The server disconnected the client with a Disconnect packet. The message is not set if the
+    /// server asked for the disconnection screen to be hidden.
+    Kicked(Option<String>),
+    /// The connection was lost without the server saying why, usually because the server stopped
+    /// responding.
+    Timeout,
+    /// A batch of packets sent by the server could not be decoded.
+    DecodeError(String),
+    /// The client closed the connection itself.
+    Local,
+}
+
+impl Display for DisconnectReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Kicked(Some(message)) => write!(f, "{}", message),
+            Self::Kicked(None) => write!(f, "Disconnected by the server"),
+            Self::Timeout => write!(f, "Connection timed out"),
+            Self::DecodeError(err) => {
+                write!(f, "Could not decode packets from the server: {}", err)
             }
+            Self::Local => write!(f, "Disconnected"),
         }
     }
 }
@@ -176,5 +279,5 @@ pub trait Handler {
     }
     async fn handle_outgoing(&mut self, _: &mut Packet) {}
 
-    async fn handle_disconnect(&mut self, _: Option<String>) {}
+    async fn handle_disconnect(&mut self, _: DisconnectReason) {}
 }
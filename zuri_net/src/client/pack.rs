@@ -0,0 +1,220 @@
+use std::io::{Error, ErrorKind, Result};
+
+/// A resource pack downloaded from the server while logging in. The pack is kept as the zip archive
+/// it was sent as.
+#[derive(Debug, Clone)]
+pub struct DownloadedPack {
+    /// The UUID of the pack.
+    pub uuid: String,
+    /// The version of the pack. Together with the UUID, this identifies the contents of the pack.
+    pub version: String,
+    /// The zip archive of the pack.
+    pub data: Vec<u8>,
+}
+
+impl DownloadedPack {
+    /// Extracts all files from the archive of the pack, returning their paths along with their
+    /// contents. Directories are left out.
+    pub fn files(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        unzip(&self.data)
+    }
+}
+
+/// The signatures of the records in a zip archive.
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+/// The compression methods of files in a zip archive that can be extracted.
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+/// The largest size of a single extracted file, and of all extracted files together. The sizes are
+/// read from the archive, which comes from the server, so they may not be trusted.
+const MAX_FILE_SIZE: usize = 64 << 20;
+const MAX_TOTAL_SIZE: usize = 512 << 20;
+
+/// Extracts all files from a zip archive. Only stored and deflated files are supported, which are
+/// the only methods used for resource packs. Archives that extract to more than [MAX_FILE_SIZE] for
+/// a single file or [MAX_TOTAL_SIZE] in total are rejected.
+fn unzip(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+    // The end of central directory record is at the end of the archive, followed only by a comment
+    // of at most 65535 bytes.
+    let end = (0..archive.len().saturating_sub(21))
+        .rev()
+        .take(65536)
+        .find(|&offset| u32_at(archive, offset) == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or_else(|| invalid("missing end of central directory"))?;
+    let count = u16_at(archive, end + 10).unwrap_or_default() as usize;
+    let mut offset = u32_at(archive, end + 16).unwrap_or_default() as usize;
+
+    let mut files = Vec::with_capacity(count);
+    let mut total_size = 0;
+    let mut decompressor = libdeflater::Decompressor::new();
+    for _ in 0..count {
+        if u32_at(archive, offset) != Some(CENTRAL_DIRECTORY_HEADER) {
+            return Err(invalid("invalid central directory header"));
+        }
+        let header = |at: usize| u16_at(archive, offset + at).unwrap_or_default() as usize;
+        let method = header(10) as u16;
+        let compressed_size = u32_at(archive, offset + 20).unwrap_or_default() as usize;
+        let size = u32_at(archive, offset + 24).unwrap_or_default() as usize;
+        let (name_len, extra_len, comment_len) = (header(28), header(30), header(32));
+        let local = u32_at(archive, offset + 42).unwrap_or_default() as usize;
+        let name = archive
+            .get(offset + 46..offset + 46 + name_len)
+            .ok_or_else(|| invalid("truncated file name"))?;
+        let name = String::from_utf8_lossy(name).replace('\\', "/");
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with('/') {
+            continue;
+        }
+        let extracted_size = match method {
+            STORED => compressed_size,
+            _ => size,
+        };
+        total_size += extracted_size;
+        if extracted_size > MAX_FILE_SIZE || total_size > MAX_TOTAL_SIZE {
+            return Err(invalid(&format!("{} is too large to extract", name)));
+        }
+        if u32_at(archive, local) != Some(LOCAL_FILE_HEADER) {
+            return Err(invalid("invalid local file header"));
+        }
+        // The local header repeats the name, but its extra field may differ from the one in the
+        // central directory.
+        let start = local
+            + 30
+            + u16_at(archive, local + 26).unwrap_or_default() as usize
+            + u16_at(archive, local + 28).unwrap_or_default() as usize;
+        let data = archive
+            .get(start..start + compressed_size)
+            .ok_or_else(|| invalid("truncated file data"))?;
+        let contents = match method {
+            STORED => data.to_vec(),
+            DEFLATED => {
+                let mut contents = vec![0; size];
+                let len = decompressor
+                    .deflate_decompress(data, &mut contents)
+                    .map_err(|err| invalid(&format!("could not inflate {}: {}", name, err)))?;
+                contents.truncate(len);
+                contents.shrink_to_fit();
+                contents
+            }
+            method => {
+                return Err(invalid(&format!(
+                    "unsupported compression method {} for {}",
+                    method, name
+                )))
+            }
+        };
+        files.push((name, contents));
+    }
+    Ok(files)
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        buf.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        buf.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a zip archive holding the files with the compression method given for each.
+    fn zip(files: &[(&str, &[u8], u16)]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut central = Vec::new();
+        for (name, contents, method) in files {
+            let data = match *method {
+                DEFLATED => {
+                    let mut compressor = libdeflater::Compressor::new(Default::default());
+                    let mut data = vec![0; compressor.deflate_compress_bound(contents.len())];
+                    let len = compressor.deflate_compress(contents, &mut data).unwrap();
+                    data.truncate(len);
+                    data
+                }
+                _ => contents.to_vec(),
+            };
+            let local = archive.len() as u32;
+            archive.extend_from_slice(&LOCAL_FILE_HEADER.to_le_bytes());
+            archive.extend_from_slice(&[0; 22]);
+            archive.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            archive.extend_from_slice(&0u16.to_le_bytes());
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(&data);
+
+            central.extend_from_slice(&CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+            central.extend_from_slice(&[0; 6]);
+            central.extend_from_slice(&method.to_le_bytes());
+            central.extend_from_slice(&[0; 8]);
+            central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&local.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_offset = archive.len() as u32;
+        archive.extend_from_slice(&central);
+        archive.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        archive.extend_from_slice(&[0; 6]);
+        archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&central_offset.to_le_bytes());
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive
+    }
+
+    #[test]
+    fn extract() {
+        let manifest = br#"{"header": {"name": "Pack"}}"#.repeat(10);
+        let archive = zip(&[
+            ("pack/", b"", STORED),
+            ("pack/manifest.json", &manifest, DEFLATED),
+            ("pack/textures/terrain_texture.json", b"{}", STORED),
+        ]);
+        let pack = DownloadedPack {
+            uuid: String::new(),
+            version: String::new(),
+            data: archive,
+        };
+        assert_eq!(
+            pack.files().unwrap(),
+            [
+                ("pack/manifest.json".to_string(), manifest),
+                (
+                    "pack/textures/terrain_texture.json".to_string(),
+                    b"{}".to_vec()
+                ),
+            ]
+        );
+        assert!(unzip(b"not a zip archive").is_err());
+    }
+
+    #[test]
+    fn size_limits() {
+        // The archive claims its files are far larger than they are, which is only found out
+        // after allocating room for them.
+        let claim = |count: usize, size: u32| {
+            let mut archive = zip(&vec![("file", b"{}".as_slice(), DEFLATED); count]);
+            let central = archive.len() - 22 - count * (46 + "file".len());
+            for i in 0..count {
+                let offset = central + i * (46 + "file".len()) + 24;
+                archive[offset..offset + 4].copy_from_slice(&size.to_le_bytes());
+            }
+            unzip(&archive)
+        };
+        assert!(claim(1, u32::MAX).is_err());
+        assert!(claim(1, MAX_FILE_SIZE as u32).is_ok());
+        assert!(claim(MAX_TOTAL_SIZE / MAX_FILE_SIZE + 1, MAX_FILE_SIZE as u32).is_err());
+    }
+}
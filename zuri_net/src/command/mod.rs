@@ -0,0 +1,614 @@
+//! Resolves the commands sent in the AvailableCommands packet into a tree that can be navigated,
+//! and parses and completes command lines against it.
+//!
+//! The AvailableCommands packet refers to enums, suffixes and other values by their index in
+//! tables that are shared between all commands. A [CommandGraph] looks these up once, so that each
+//! parameter knows exactly which values it accepts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use num_traits::FromPrimitive;
+
+use crate::proto::packet::available_commands::{
+    AvailableCommands, Command as CommandData, CommandParameterOption,
+};
+use crate::proto::packet::update_soft_enum::UpdateSoftEnum;
+use crate::proto::types::command::{CommandArg, CommandEnumConstraints, SoftEnumAction};
+
+pub mod parse;
+
+/// The commands that the client can use, resolved from an AvailableCommands packet.
+#[derive(Debug, Clone, Default)]
+pub struct CommandGraph {
+    /// The commands by their name.
+    commands: BTreeMap<String, Command>,
+    /// The names of commands by their aliases.
+    aliases: BTreeMap<String, String>,
+    /// The values of soft enums by their name. These may be changed by the server at any time
+    /// using the UpdateSoftEnum packet.
+    soft_enums: HashMap<String, Vec<String>>,
+}
+
+/// A command along with all the ways in which it can be used.
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub name: String,
+    pub description: String,
+    pub flags: u16,
+    pub permission_level: u8,
+    /// Other names that can be used to run the command.
+    pub aliases: Vec<String>,
+    /// The different sets of parameters that the command accepts.
+    pub overloads: Vec<Overload>,
+    pub chained_subcommands: Vec<ChainedSubcommand>,
+}
+
+/// One way to use a command, which is a list of parameters in the order in which they are typed.
+#[derive(Debug, Clone)]
+pub struct Overload {
+    /// If true, the overload uses chained subcommands.
+    pub chaining: bool,
+    pub parameters: Vec<Parameter>,
+}
+
+/// A parameter of a command overload.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    /// The values that the parameter accepts.
+    pub kind: ParameterKind,
+    /// If true, the parameter may be left out. Only the trailing parameters of an overload can be
+    /// left out, as parameters are identified by their position.
+    pub optional: bool,
+    pub option: CommandParameterOption,
+}
+
+/// The kind of values accepted by a parameter.
+#[derive(Debug, Clone)]
+pub enum ParameterKind {
+    /// A value of a basic type, such as an integer or a target selector.
+    Arg(CommandArg),
+    /// One of a fixed list of values.
+    Enum(Arc<Enum>),
+    /// One of the values of the soft enum with this name, which may change while connected.
+    SoftEnum(String),
+    /// An integer followed by this suffix, such as `10L` for the levels in `/xp`.
+    Suffixed(String),
+    /// A type of value that is not known to the client. Any single word is accepted.
+    Unknown(u32),
+}
+
+/// A fixed list of values, such as the game modes in `/gamemode`.
+#[derive(Debug, Clone, Default)]
+pub struct Enum {
+    pub name: String,
+    pub values: Vec<String>,
+    /// The constraints on values of the enum, by the index of the value.
+    constraints: HashMap<usize, Vec<CommandEnumConstraints>>,
+}
+
+/// A subcommand that can be chained after other subcommands, as used by commands like `/execute`.
+#[derive(Debug, Clone)]
+pub struct ChainedSubcommand {
+    pub name: String,
+    pub values: Vec<ChainedSubcommandValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainedSubcommandValue {
+    pub name: String,
+    pub value: u16,
+}
+
+impl Enum {
+    /// Returns the value of the enum that matches the text, ignoring case.
+    pub fn find(&self, text: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|value| value.eq_ignore_ascii_case(text))
+            .map(String::as_str)
+    }
+
+    /// Returns the constraints that must be met before a value of the enum can be used.
+    pub fn constraints(&self, value: &str) -> &[CommandEnumConstraints] {
+        self.values
+            .iter()
+            .position(|v| v == value)
+            .and_then(|index| self.constraints.get(&index))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl Parameter {
+    /// Returns how the parameter is shown in the usage of a command, such as `<mode: GameMode>`
+    /// or `[amount: int]` for optional parameters.
+    pub fn usage(&self) -> String {
+        let kind = match &self.kind {
+            ParameterKind::Arg(arg) => arg_name(*arg).to_string(),
+            ParameterKind::Enum(e) => e.name.clone(),
+            ParameterKind::SoftEnum(name) => name.clone(),
+            ParameterKind::Suffixed(suffix) => format!("int{}", suffix),
+            ParameterKind::Unknown(_) => "value".into(),
+        };
+        if self.optional {
+            format!("[{}: {}]", self.name, kind)
+        } else {
+            format!("<{}: {}>", self.name, kind)
+        }
+    }
+}
+
+impl Overload {
+    /// Returns the parameters of the overload as they are shown in the usage of a command.
+    pub fn usage(&self) -> String {
+        self.parameters
+            .iter()
+            .map(Parameter::usage)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Returns the name under which a type of argument is shown to the player.
+fn arg_name(arg: CommandArg) -> &'static str {
+    match arg {
+        CommandArg::TypeInt => "int",
+        CommandArg::TypeFloat | CommandArg::TypeValue => "float",
+        CommandArg::TypeWildcardInt => "int|*",
+        CommandArg::TypeOperator | CommandArg::TypeCompareOperator => "operator",
+        CommandArg::TypeTarget => "target",
+        CommandArg::TypeWildcardTarget => "target|*",
+        CommandArg::TypeFilepath => "filepath",
+        CommandArg::TypeIntegerRange => "range",
+        CommandArg::TypeEquipmentSlots => "slot",
+        CommandArg::TypeString => "string",
+        CommandArg::TypeBlockPosition => "x y z",
+        CommandArg::TypePosition => "x y z",
+        CommandArg::TypeMessage => "message",
+        CommandArg::TypeRawText => "text",
+        CommandArg::TypeJSON => "json",
+        CommandArg::TypeBlockStates => "block states",
+        CommandArg::TypeCommand => "command",
+        _ => "value",
+    }
+}
+
+/// The lower bits of a parameter type hold either the type of argument, or an index in one of the
+/// tables of the AvailableCommands packet, depending on which flag is set.
+const ARG_INDEX_MASK: u32 = 0xffff;
+
+impl CommandGraph {
+    /// Resolves the commands in an AvailableCommands packet. An error is returned if the packet
+    /// refers to values that are not in its tables.
+    pub fn new(pk: &AvailableCommands) -> Result<Self, CommandGraphError> {
+        let string = |table: &'static str, values: &[String], index: usize| {
+            values
+                .get(index)
+                .cloned()
+                .ok_or(CommandGraphError::InvalidIndex { table, index })
+        };
+
+        let mut enums = pk
+            .enums
+            .iter()
+            .map(|e| {
+                Ok(Enum {
+                    name: e.enum_type.clone(),
+                    values: e
+                        .value_indices
+                        .iter()
+                        .map(|index| string("enum_values", &pk.enum_values, *index as usize))
+                        .collect::<Result<_, _>>()?,
+                    constraints: HashMap::new(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for constraint in &pk.constraints {
+            let index = constraint.enum_index as usize;
+            let value = string(
+                "enum_values",
+                &pk.enum_values,
+                constraint.enum_value_index as usize,
+            )?;
+            let e = enums
+                .get_mut(index)
+                .ok_or(CommandGraphError::InvalidIndex {
+                    table: "enums",
+                    index,
+                })?;
+            if let Some(position) = e.values.iter().position(|v| *v == value) {
+                e.constraints
+                    .insert(position, constraint.constraints.clone());
+            }
+        }
+        let enums: Vec<_> = enums.into_iter().map(Arc::new).collect();
+
+        let chained_subcommands = pk
+            .chained_subcommands
+            .iter()
+            .map(|subcommand| {
+                Ok(ChainedSubcommand {
+                    name: subcommand.name.clone(),
+                    values: subcommand
+                        .values
+                        .iter()
+                        .map(|value| {
+                            Ok(ChainedSubcommandValue {
+                                name: string(
+                                    "chained_subcommand_values",
+                                    &pk.chained_subcommand_values,
+                                    value.index as usize,
+                                )?,
+                                value: value.value,
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut graph = Self {
+            soft_enums: pk
+                .dynamic_enums
+                .iter()
+                .map(|e| (e.type_name.clone(), e.values.clone()))
+                .collect(),
+            ..Default::default()
+        };
+        for data in &pk.commands {
+            let command = Self::resolve_command(pk, data, &enums, &chained_subcommands)?;
+            for alias in &command.aliases {
+                if *alias != command.name {
+                    graph.aliases.insert(alias.clone(), command.name.clone());
+                }
+            }
+            graph.commands.insert(command.name.clone(), command);
+        }
+        Ok(graph)
+    }
+
+    fn resolve_command(
+        pk: &AvailableCommands,
+        data: &CommandData,
+        enums: &[Arc<Enum>],
+        chained_subcommands: &[ChainedSubcommand],
+    ) -> Result<Command, CommandGraphError> {
+        let aliases = match data.aliases_offset {
+            u32::MAX => Vec::new(),
+            index => enums
+                .get(index as usize)
+                .ok_or(CommandGraphError::InvalidIndex {
+                    table: "enums",
+                    index: index as usize,
+                })?
+                .values
+                .clone(),
+        };
+        let chained_subcommands = data
+            .chained_subcommand_offsets
+            .iter()
+            .map(|index| {
+                chained_subcommands.get(*index as usize).cloned().ok_or(
+                    CommandGraphError::InvalidIndex {
+                        table: "chained_subcommands",
+                        index: *index as usize,
+                    },
+                )
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut overloads = Vec::with_capacity(data.overloads.len());
+        for overload in &data.overloads {
+            let mut parameters = Vec::with_capacity(overload.parameters.len());
+            for parameter in &overload.parameters {
+                let t = parameter.parameter_type;
+                let index = (t & ARG_INDEX_MASK) as usize;
+                let invalid = |table| CommandGraphError::InvalidIndex { table, index };
+                let kind = if t & CommandArg::SoftEnum as u32 != 0 {
+                    let e = pk
+                        .dynamic_enums
+                        .get(index)
+                        .ok_or(invalid("dynamic_enums"))?;
+                    ParameterKind::SoftEnum(e.type_name.clone())
+                } else if t & CommandArg::Enum as u32 != 0 {
+                    ParameterKind::Enum(enums.get(index).ok_or(invalid("enums"))?.clone())
+                } else if t & CommandArg::Suffixed as u32 != 0 {
+                    let suffix = pk.suffixes.get(index).ok_or(invalid("suffixes"))?;
+                    ParameterKind::Suffixed(suffix.clone())
+                } else {
+                    match CommandArg::from_u32(index as u32) {
+                        Some(arg) => ParameterKind::Arg(arg),
+                        None => ParameterKind::Unknown(index as u32),
+                    }
+                };
+                parameters.push(Parameter {
+                    name: parameter.name.clone(),
+                    kind,
+                    optional: parameter.optional,
+                    option: parameter.options.clone(),
+                });
+            }
+            overloads.push(Overload {
+                chaining: overload.chaining,
+                parameters,
+            });
+        }
+
+        Ok(Command {
+            name: data.name.clone(),
+            description: data.description.clone(),
+            flags: data.flags,
+            permission_level: data.permission_level,
+            aliases,
+            overloads,
+            chained_subcommands,
+        })
+    }
+
+    /// Applies the changes to a soft enum sent in an UpdateSoftEnum packet.
+    pub fn update_soft_enum(&mut self, pk: &UpdateSoftEnum) {
+        let values = self.soft_enums.entry(pk.enum_type.clone()).or_default();
+        match pk.action_type {
+            SoftEnumAction::Add => {
+                for option in &pk.options {
+                    if !values.contains(option) {
+                        values.push(option.clone());
+                    }
+                }
+            }
+            SoftEnumAction::Remove => values.retain(|value| !pk.options.contains(value)),
+            SoftEnumAction::Set => *values = pk.options.clone(),
+        }
+    }
+
+    /// Returns the command with a name or alias, ignoring case.
+    pub fn command(&self, name: &str) -> Option<&Command> {
+        let name = name.to_lowercase();
+        let name = self.aliases.get(&name).unwrap_or(&name);
+        self.commands.get(name)
+    }
+
+    /// Returns all commands, sorted by their name.
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+
+    /// Returns the names and aliases of all commands, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        let mut names: Vec<_> = self
+            .commands
+            .keys()
+            .chain(self.aliases.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names.into_iter()
+    }
+
+    /// Returns the current values of a soft enum.
+    pub fn soft_enum(&self, name: &str) -> &[String] {
+        self.soft_enums
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// An error that occurred while resolving the commands in an AvailableCommands packet.
+#[derive(Debug)]
+pub enum CommandGraphError {
+    /// A value referred to an index that is not in one of the tables of the packet.
+    InvalidIndex { table: &'static str, index: usize },
+}
+
+impl Display for CommandGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandGraphError::InvalidIndex { table, index } => {
+                write!(f, "Index {} is out of range for {}", index, table)
+            }
+        }
+    }
+}
+
+impl Error for CommandGraphError {}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::proto::packet::available_commands::{
+        CommandOverload, CommandParameter, DynamicEnum,
+    };
+    use crate::proto::types::command::{CommandEnum, CommandEnumConstraint};
+
+    fn parameter(name: &str, parameter_type: u32, optional: bool) -> CommandParameter {
+        CommandParameter {
+            name: name.into(),
+            parameter_type,
+            optional,
+            options: CommandParameterOption::None,
+        }
+    }
+
+    fn command(
+        name: &str,
+        aliases_offset: u32,
+        overloads: Vec<Vec<CommandParameter>>,
+    ) -> CommandData {
+        CommandData {
+            name: name.into(),
+            description: String::new(),
+            flags: 0,
+            permission_level: 0,
+            aliases_offset,
+            chained_subcommand_offsets: Vec::new(),
+            overloads: overloads
+                .into_iter()
+                .map(|parameters| CommandOverload {
+                    chaining: false,
+                    parameters,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns a packet with a few commands modelled after the vanilla ones.
+    pub(crate) fn available_commands() -> AvailableCommands {
+        let valid = CommandArg::Valid as u32;
+        let arg = |arg: CommandArg| valid | arg as u32;
+        let enum_arg = |index: u32| valid | CommandArg::Enum as u32 | index;
+        AvailableCommands {
+            enum_values: [
+                "survival",
+                "creative",
+                "adventure",
+                "gamemode",
+                "gm",
+                "spectator",
+            ]
+            .map(String::from)
+            .to_vec(),
+            chained_subcommand_values: Vec::new(),
+            suffixes: vec!["L".into()],
+            enums: vec![
+                CommandEnum {
+                    enum_type: "GameMode".into(),
+                    value_indices: vec![0, 1, 2, 5],
+                },
+                CommandEnum {
+                    enum_type: "GamemodeAliases".into(),
+                    value_indices: vec![3, 4],
+                },
+            ],
+            chained_subcommands: Vec::new(),
+            commands: vec![
+                command(
+                    "gamemode",
+                    1,
+                    vec![vec![
+                        parameter("gameMode", enum_arg(0), false),
+                        parameter("player", arg(CommandArg::TypeTarget), true),
+                    ]],
+                ),
+                command(
+                    "tp",
+                    u32::MAX,
+                    vec![
+                        vec![parameter(
+                            "destination",
+                            arg(CommandArg::TypePosition),
+                            false,
+                        )],
+                        vec![
+                            parameter("victim", arg(CommandArg::TypeTarget), false),
+                            parameter("destination", arg(CommandArg::TypePosition), false),
+                        ],
+                    ],
+                ),
+                command(
+                    "xp",
+                    u32::MAX,
+                    vec![vec![
+                        parameter("amount", valid | CommandArg::Suffixed as u32, false),
+                        parameter("player", arg(CommandArg::TypeTarget), true),
+                    ]],
+                ),
+                command(
+                    "say",
+                    u32::MAX,
+                    vec![vec![parameter(
+                        "message",
+                        arg(CommandArg::TypeMessage),
+                        false,
+                    )]],
+                ),
+                command(
+                    "execute",
+                    u32::MAX,
+                    vec![vec![
+                        parameter("origin", arg(CommandArg::TypeTarget), false),
+                        parameter("command", arg(CommandArg::TypeCommand), false),
+                    ]],
+                ),
+                command(
+                    "function",
+                    u32::MAX,
+                    vec![vec![parameter(
+                        "name",
+                        valid | CommandArg::SoftEnum as u32,
+                        false,
+                    )]],
+                ),
+            ],
+            dynamic_enums: vec![DynamicEnum {
+                type_name: "FunctionName".into(),
+                values: vec!["setup".into()],
+            }],
+            constraints: vec![CommandEnumConstraint {
+                enum_value_index: 5,
+                enum_index: 0,
+                constraints: vec![CommandEnumConstraints::CheatsEnabled],
+            }],
+        }
+    }
+
+    #[test]
+    fn resolve() {
+        let graph = CommandGraph::new(&available_commands()).unwrap();
+
+        let gamemode = graph.command("GM").unwrap();
+        assert_eq!(gamemode.name, "gamemode");
+        assert_eq!(
+            gamemode.overloads[0].usage(),
+            "<gameMode: GameMode> [player: target]"
+        );
+        let ParameterKind::Enum(game_mode) = &gamemode.overloads[0].parameters[0].kind else {
+            panic!("expected an enum parameter");
+        };
+        assert_eq!(game_mode.find("Creative"), Some("creative"));
+        assert_eq!(
+            game_mode.constraints("spectator"),
+            [CommandEnumConstraints::CheatsEnabled]
+        );
+        assert!(game_mode.constraints("survival").is_empty());
+
+        let xp = graph.command("xp").unwrap();
+        assert!(
+            matches!(&xp.overloads[0].parameters[0].kind, ParameterKind::Suffixed(s) if s == "L")
+        );
+        assert_eq!(
+            graph.names().collect::<Vec<_>>()[..3],
+            ["execute", "function", "gamemode"]
+        );
+
+        let mut pk = available_commands();
+        pk.commands[0].aliases_offset = 7;
+        assert!(CommandGraph::new(&pk).is_err());
+    }
+
+    #[test]
+    fn soft_enum() {
+        let mut graph = CommandGraph::new(&available_commands()).unwrap();
+        let mut update = |options: &[&str], action_type| {
+            graph.update_soft_enum(&UpdateSoftEnum {
+                enum_type: "FunctionName".into(),
+                options: options.iter().map(|o| o.to_string()).collect(),
+                action_type,
+            });
+        };
+        update(&["reset", "setup"], SoftEnumAction::Add);
+        update(&["setup"], SoftEnumAction::Remove);
+        assert_eq!(graph.soft_enum("FunctionName"), ["reset"]);
+
+        graph.update_soft_enum(&UpdateSoftEnum {
+            enum_type: "FunctionName".into(),
+            options: vec!["a".into()],
+            action_type: SoftEnumAction::Set,
+        });
+        assert_eq!(graph.soft_enum("FunctionName"), ["a"]);
+    }
+}
@@ -0,0 +1,616 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::command::{Command, CommandGraph, Overload, Parameter, ParameterKind};
+use crate::proto::types::command::CommandArg;
+
+/// The target selectors that can be used in place of a player name.
+const SELECTORS: [&str; 6] = ["@a", "@e", "@initiator", "@p", "@r", "@s"];
+/// The operators accepted by `/scoreboard players operation`.
+const OPERATORS: [&str; 9] = ["%=", "*=", "+=", "-=", "/=", "<", "=", ">", "><"];
+/// The operators that compare two values, as used by `/execute if score`.
+const COMPARE_OPERATORS: [&str; 5] = ["<", "<=", "=", ">", ">="];
+/// The equipment slots accepted by commands such as `/replaceitem`.
+const EQUIPMENT_SLOTS: [&str; 13] = [
+    "slot.armor",
+    "slot.armor.chest",
+    "slot.armor.feet",
+    "slot.armor.head",
+    "slot.armor.legs",
+    "slot.chest",
+    "slot.enderchest",
+    "slot.equippable",
+    "slot.hotbar",
+    "slot.inventory",
+    "slot.saddle",
+    "slot.weapon.mainhand",
+    "slot.weapon.offhand",
+];
+
+/// A command line that was matched to one of the overloads of a command.
+#[derive(Debug)]
+pub struct ParsedCommand<'a> {
+    pub command: &'a Command,
+    pub overload: &'a Overload,
+    /// The parameters that were given a value, along with the text of the value. Arguments that
+    /// span multiple words, such as positions and messages, are given as a single piece of text.
+    pub arguments: Vec<(&'a Parameter, &'a str)>,
+}
+
+/// The reason a command line could not be matched to a command. If none of the overloads of a
+/// command match, the error of the overload that matched the most arguments is returned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The command line is empty.
+    Empty,
+    /// There is no command with this name.
+    UnknownCommand(String),
+    /// The argument given for a parameter is not a valid value for it.
+    InvalidArgument { parameter: String, argument: String },
+    /// No argument was given for a parameter that is not optional.
+    MissingArgument(String),
+    /// All parameters have been given a value, but this argument was left over.
+    TooManyArguments(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("No command was given"),
+            ParseError::UnknownCommand(name) => write!(f, "Unknown command: {}", name),
+            ParseError::InvalidArgument {
+                parameter,
+                argument,
+            } => write!(f, "Invalid value for {}: {}", parameter, argument),
+            ParseError::MissingArgument(parameter) => write!(f, "Missing value for {}", parameter),
+            ParseError::TooManyArguments(argument) => {
+                write!(f, "Unexpected argument: {}", argument)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The suggestions for the word being typed at the end of a command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Completion {
+    /// The byte offset in the line at which the word being typed starts. A suggestion replaces the
+    /// line from this offset on.
+    pub start: usize,
+    /// The words that the word being typed could be completed to, sorted.
+    pub suggestions: Vec<String>,
+    /// The usage of the parameters that the word being typed could be a value for, such as
+    /// `<gameMode: GameMode>`.
+    pub hints: Vec<String>,
+}
+
+/// A word in a command line, given by its byte range in the line.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Token {
+    start: usize,
+    end: usize,
+}
+
+/// The result of matching the words of a command line to a parameter.
+enum Consumed {
+    /// The parameter accepts this many words.
+    Tokens(usize),
+    /// The words are valid so far, but the parameter needs more of them.
+    Incomplete,
+    /// The words are not a valid value for the parameter.
+    Invalid,
+}
+
+impl CommandGraph {
+    /// Matches a command line to a command and one of its overloads. The line may start with a
+    /// `/`, which is left out.
+    pub fn parse<'a>(&'a self, line: &'a str) -> Result<ParsedCommand<'a>, ParseError> {
+        let line = line.trim_start();
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let tokens = tokenize(line);
+        let Some((name, args)) = tokens.split_first() else {
+            return Err(ParseError::Empty);
+        };
+        let name = &line[name.start..name.end];
+        let command = self
+            .command(name)
+            .ok_or_else(|| ParseError::UnknownCommand(name.into()))?;
+
+        let mut best: Option<(usize, ParseError)> = None;
+        for overload in &command.overloads {
+            match self.parse_overload(line, args, overload) {
+                Ok(arguments) => {
+                    return Ok(ParsedCommand {
+                        command,
+                        overload,
+                        arguments,
+                    });
+                }
+                Err((progress, err)) => {
+                    if !matches!(&best, Some((best, _)) if progress <= *best) {
+                        best = Some((progress, err));
+                    }
+                }
+            }
+        }
+        Err(best
+            .map(|(_, err)| err)
+            .unwrap_or_else(|| ParseError::UnknownCommand(name.into())))
+    }
+
+    /// Matches the arguments of a command line to an overload. If they do not match, the error is
+    /// returned along with the number of words that did match.
+    fn parse_overload<'a>(
+        &self,
+        line: &'a str,
+        tokens: &[Token],
+        overload: &'a Overload,
+    ) -> Result<Vec<(&'a Parameter, &'a str)>, (usize, ParseError)> {
+        let mut arguments = Vec::new();
+        let mut pos = 0;
+        for parameter in &overload.parameters {
+            if pos == tokens.len() {
+                if parameter.optional {
+                    break;
+                }
+                return Err((pos, ParseError::MissingArgument(parameter.name.clone())));
+            }
+            match self.consume(&parameter.kind, line, &tokens[pos..]) {
+                Consumed::Tokens(n) => {
+                    arguments.push((parameter, &line[tokens[pos].start..tokens[pos + n - 1].end]));
+                    pos += n;
+                }
+                Consumed::Incomplete => {
+                    return Err((
+                        tokens.len(),
+                        ParseError::MissingArgument(parameter.name.clone()),
+                    ));
+                }
+                Consumed::Invalid => {
+                    return Err((
+                        pos,
+                        ParseError::InvalidArgument {
+                            parameter: parameter.name.clone(),
+                            argument: line[tokens[pos].start..tokens[pos].end].into(),
+                        },
+                    ));
+                }
+            }
+        }
+        match tokens.get(pos) {
+            Some(token) => Err((
+                pos,
+                ParseError::TooManyArguments(line[token.start..token.end].into()),
+            )),
+            None => Ok(arguments),
+        }
+    }
+
+    /// Matches the first of the words to a parameter, along with any following words that are
+    /// part of the same argument. There must be at least one word.
+    fn consume(&self, kind: &ParameterKind, line: &str, tokens: &[Token]) -> Consumed {
+        match kind {
+            ParameterKind::Arg(CommandArg::TypePosition) => coordinates(line, tokens, false),
+            ParameterKind::Arg(CommandArg::TypeBlockPosition) => coordinates(line, tokens, true),
+            ParameterKind::Arg(CommandArg::TypeMessage | CommandArg::TypeRawText) => {
+                Consumed::Tokens(tokens.len())
+            }
+            ParameterKind::Arg(CommandArg::TypeCommand) => {
+                match self.parse(&line[tokens[0].start..]) {
+                    Ok(_) => Consumed::Tokens(tokens.len()),
+                    Err(_) => Consumed::Invalid,
+                }
+            }
+            kind => {
+                let word = &line[tokens[0].start..tokens[0].end];
+                if self.accepts(kind, word) {
+                    Consumed::Tokens(1)
+                } else {
+                    Consumed::Invalid
+                }
+            }
+        }
+    }
+
+    /// Returns true if a single word is a valid value for a parameter.
+    fn accepts(&self, kind: &ParameterKind, word: &str) -> bool {
+        match kind {
+            ParameterKind::Enum(e) => e.find(word).is_some(),
+            ParameterKind::SoftEnum(name) => self
+                .soft_enum(name)
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(word)),
+            ParameterKind::Suffixed(suffix) => {
+                // The suffix may be left out.
+                let number = match word.len().checked_sub(suffix.len()) {
+                    Some(end) if matches!(word.get(end..), Some(s) if s.eq_ignore_ascii_case(suffix)) => {
+                        &word[..end]
+                    }
+                    _ => word,
+                };
+                number.parse::<i32>().is_ok()
+            }
+            ParameterKind::Arg(arg) => accepts_arg(*arg, word),
+            ParameterKind::Unknown(_) => true,
+        }
+    }
+
+    /// Suggests how to complete the word being typed at the end of a command line. The line may
+    /// start with a `/`. If the command name is still being typed, the names of commands are
+    /// suggested, and otherwise the values accepted by the parameters of all overloads that the
+    /// line matches so far.
+    pub fn complete(&self, line: &str) -> Completion {
+        let trimmed = line.trim_start();
+        let body = trimmed.strip_prefix('/').unwrap_or(trimmed);
+        let offset = line.len() - body.len();
+
+        // The word being typed is the last one, unless the line ends with whitespace, in which
+        // case a new word is started.
+        let tokens = tokenize(body);
+        let (complete, word_start) = match tokens.last() {
+            Some(last) if last.end == body.len() => (&tokens[..tokens.len() - 1], last.start),
+            _ => (&tokens[..], body.len()),
+        };
+        let word = &body[word_start..];
+        let mut completion = Completion {
+            start: offset + word_start,
+            ..Default::default()
+        };
+
+        let Some((name, args)) = complete.split_first() else {
+            completion.suggestions = self
+                .names()
+                .filter(|name| starts_with_ignore_case(name, word))
+                .map(String::from)
+                .collect();
+            return completion;
+        };
+        let Some(command) = self.command(&body[name.start..name.end]) else {
+            return completion;
+        };
+        for overload in &command.overloads {
+            self.complete_overload(body, args, word, overload, &mut completion);
+        }
+
+        completion.suggestions.sort();
+        completion.suggestions.dedup();
+        let mut hints = Vec::with_capacity(completion.hints.len());
+        for hint in completion.hints.drain(..) {
+            if !hints.contains(&hint) {
+                hints.push(hint);
+            }
+        }
+        completion.hints = hints;
+        completion
+    }
+
+    /// Adds the suggestions of an overload for the word being typed, if the words before it match
+    /// the overload.
+    fn complete_overload(
+        &self,
+        line: &str,
+        tokens: &[Token],
+        word: &str,
+        overload: &Overload,
+        completion: &mut Completion,
+    ) {
+        let mut pos = 0;
+        for parameter in &overload.parameters {
+            if pos == tokens.len() {
+                self.suggest(parameter, word, completion);
+                return;
+            }
+            match parameter.kind {
+                ParameterKind::Arg(CommandArg::TypeCommand) => {
+                    // The rest of the line is a command of its own. The word being typed is the
+                    // same, so its suggestions start at the same place.
+                    let nested = self.complete(&line[tokens[pos].start..]);
+                    completion.suggestions.extend(nested.suggestions);
+                    completion.hints.extend(nested.hints);
+                    return;
+                }
+                ParameterKind::Arg(CommandArg::TypeMessage | CommandArg::TypeRawText) => {
+                    completion.hints.push(parameter.usage());
+                    return;
+                }
+                _ => {}
+            }
+            match self.consume(&parameter.kind, line, &tokens[pos..]) {
+                Consumed::Tokens(n) => pos += n,
+                // The word being typed is part of a value that spans multiple words.
+                Consumed::Incomplete => {
+                    self.suggest(parameter, word, completion);
+                    return;
+                }
+                Consumed::Invalid => return,
+            }
+        }
+    }
+
+    /// Adds the values of a parameter that start with the word being typed.
+    fn suggest(&self, parameter: &Parameter, word: &str, completion: &mut Completion) {
+        completion.hints.push(parameter.usage());
+        let values: Vec<&str> = match &parameter.kind {
+            ParameterKind::Enum(e) => e.values.iter().map(String::as_str).collect(),
+            ParameterKind::SoftEnum(name) => {
+                self.soft_enum(name).iter().map(String::as_str).collect()
+            }
+            ParameterKind::Arg(arg) => match arg {
+                CommandArg::TypeTarget => SELECTORS.to_vec(),
+                CommandArg::TypeWildcardTarget => SELECTORS.iter().copied().chain(["*"]).collect(),
+                CommandArg::TypeWildcardInt => vec!["*"],
+                CommandArg::TypeOperator => OPERATORS.to_vec(),
+                CommandArg::TypeCompareOperator => COMPARE_OPERATORS.to_vec(),
+                CommandArg::TypeEquipmentSlots => EQUIPMENT_SLOTS.to_vec(),
+                CommandArg::TypePosition | CommandArg::TypeBlockPosition => vec!["~"],
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        completion.suggestions.extend(
+            values
+                .into_iter()
+                .filter(|value| starts_with_ignore_case(value, word))
+                .map(String::from),
+        );
+    }
+}
+
+/// Splits a command line into words. Words are separated by whitespace, except for whitespace
+/// inside quotes or brackets, so that strings like `"a b"` and selectors like `@a[name="a b"]`
+/// are a single word.
+fn tokenize(line: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut end = line.len();
+        let mut depth = 0usize;
+        let (mut quoted, mut escaped) = (false, false);
+        while let Some(&(i, c)) = chars.peek() {
+            if quoted {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    quoted = false;
+                }
+            } else {
+                match c {
+                    '"' => quoted = true,
+                    '[' | '{' => depth += 1,
+                    ']' | '}' => depth = depth.saturating_sub(1),
+                    c if c.is_whitespace() && depth == 0 => {
+                        end = i;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            chars.next();
+        }
+        tokens.push(Token { start, end });
+    }
+    tokens
+}
+
+/// Returns true if a word is a valid value for a basic type of argument. Types that accept any
+/// word, such as strings, always return true.
+fn accepts_arg(arg: CommandArg, word: &str) -> bool {
+    match arg {
+        CommandArg::TypeInt => word.parse::<i32>().is_ok(),
+        CommandArg::TypeFloat | CommandArg::TypeValue => word.parse::<f32>().is_ok(),
+        CommandArg::TypeWildcardInt => word == "*" || word.parse::<i32>().is_ok(),
+        CommandArg::TypeOperator => OPERATORS.contains(&word),
+        CommandArg::TypeCompareOperator => COMPARE_OPERATORS.contains(&word),
+        CommandArg::TypeTarget => is_target(word),
+        CommandArg::TypeWildcardTarget => word == "*" || is_target(word),
+        CommandArg::TypeIntegerRange => is_integer_range(word),
+        CommandArg::TypeJSON => serde_json::from_str::<serde_json::Value>(word).is_ok(),
+        CommandArg::TypeBlockStates => word.starts_with('[') && word.ends_with(']'),
+        _ => true,
+    }
+}
+
+/// Returns true if a word is a target selector, such as `@a[r=10]`, or a player name.
+fn is_target(word: &str) -> bool {
+    if !word.starts_with('@') {
+        return !word.is_empty();
+    }
+    match word.find('[') {
+        Some(index) => SELECTORS.contains(&&word[..index]) && word.ends_with(']'),
+        None => SELECTORS.contains(&word),
+    }
+}
+
+/// Returns true if a word is an integer range, such as `5`, `1..5`, `..5` or `!5..`.
+fn is_integer_range(word: &str) -> bool {
+    let word = word.strip_prefix('!').unwrap_or(word);
+    let bound = |s: &str| s.is_empty() || s.parse::<i32>().is_ok();
+    match word.split_once("..") {
+        Some((min, max)) => !(min.is_empty() && max.is_empty()) && bound(min) && bound(max),
+        None => word.parse::<i32>().is_ok(),
+    }
+}
+
+/// Matches the words that make up a position of three coordinates. Coordinates may be absolute,
+/// relative to the position of the executor (`~1`) or relative to where it is looking (`^1`), but
+/// local coordinates cannot be mixed with the others. Relative coordinates do not need to be
+/// separated by whitespace, so `~~1~` is a valid position.
+fn coordinates(line: &str, tokens: &[Token], block: bool) -> Consumed {
+    let mut count = 0;
+    let mut local = None;
+    for (i, token) in tokens.iter().enumerate() {
+        for coordinate in split_coordinates(&line[token.start..token.end]) {
+            count += 1;
+            let is_local = coordinate.starts_with('^');
+            if count > 3
+                || *local.get_or_insert(is_local) != is_local
+                || !is_coordinate(coordinate, block)
+            {
+                return Consumed::Invalid;
+            }
+        }
+        if count == 3 {
+            return Consumed::Tokens(i + 1);
+        }
+    }
+    Consumed::Incomplete
+}
+
+/// Splits a word into the coordinates it holds, each starting at a `~` or `^`.
+fn split_coordinates(word: &str) -> Vec<&str> {
+    let mut coordinates = Vec::new();
+    let mut start = 0;
+    for (i, c) in word.char_indices().skip(1) {
+        if c == '~' || c == '^' {
+            coordinates.push(&word[start..i]);
+            start = i;
+        }
+    }
+    coordinates.push(&word[start..]);
+    coordinates
+}
+
+/// Returns true if a word is a single coordinate. Absolute coordinates of block positions must be
+/// integers.
+fn is_coordinate(coordinate: &str, block: bool) -> bool {
+    match coordinate.strip_prefix(|c| c == '~' || c == '^') {
+        Some(offset) => offset.is_empty() || offset.parse::<f32>().is_ok(),
+        None if block => coordinate.parse::<i32>().is_ok(),
+        None => coordinate.parse::<f32>().is_ok(),
+    }
+}
+
+fn starts_with_ignore_case(value: &str, prefix: &str) -> bool {
+    matches!(value.get(..prefix.len()), Some(start) if start.eq_ignore_ascii_case(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::tests::available_commands;
+
+    fn graph() -> CommandGraph {
+        CommandGraph::new(&available_commands()).unwrap()
+    }
+
+    fn arguments<'a>(parsed: &ParsedCommand<'a>) -> Vec<&'a str> {
+        parsed.arguments.iter().map(|(_, text)| *text).collect()
+    }
+
+    #[test]
+    fn parse() {
+        let graph = graph();
+
+        let parsed = graph.parse("/gamemode Creative @a[name=\"a b\"]").unwrap();
+        assert_eq!(parsed.command.name, "gamemode");
+        assert_eq!(arguments(&parsed), ["Creative", "@a[name=\"a b\"]"]);
+        assert_eq!(
+            arguments(&graph.parse("gm survival").unwrap()),
+            ["survival"]
+        );
+
+        assert_eq!(graph.parse("  ").unwrap_err(), ParseError::Empty);
+        assert_eq!(
+            graph.parse("/fly").unwrap_err(),
+            ParseError::UnknownCommand("fly".into())
+        );
+        assert_eq!(
+            graph.parse("/gamemode 7").unwrap_err(),
+            ParseError::InvalidArgument {
+                parameter: "gameMode".into(),
+                argument: "7".into()
+            }
+        );
+        assert_eq!(
+            graph.parse("/gamemode").unwrap_err(),
+            ParseError::MissingArgument("gameMode".into())
+        );
+        assert_eq!(
+            graph.parse("/gamemode creative @s x").unwrap_err(),
+            ParseError::TooManyArguments("x".into())
+        );
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let graph = graph();
+
+        assert_eq!(arguments(&graph.parse("/tp ~ ~1 5").unwrap()), ["~ ~1 5"]);
+        assert_eq!(arguments(&graph.parse("/tp ^^^2").unwrap()), ["^^^2"]);
+        assert_eq!(
+            arguments(&graph.parse("/tp Steve 1 2 3").unwrap()),
+            ["Steve", "1 2 3"]
+        );
+        assert!(graph.parse("/tp ^ ~ ^").is_err());
+        assert_eq!(
+            graph.parse("/tp 1 2").unwrap_err(),
+            ParseError::MissingArgument("destination".into())
+        );
+
+        assert!(graph.parse("/xp 10L @s").is_ok());
+        assert!(graph.parse("/xp 10").is_ok());
+        assert!(graph.parse("/xp ten").is_err());
+
+        assert_eq!(
+            arguments(&graph.parse("/say hello  world").unwrap()),
+            ["hello  world"]
+        );
+        assert!(graph.parse("/execute @a gamemode creative").is_ok());
+        assert!(graph.parse("/execute @a gamemode nope").is_err());
+        assert!(graph.parse("/execute @x say hi").is_err());
+
+        assert!(graph.parse("/function SETUP").is_ok());
+        assert!(graph.parse("/function reset").is_err());
+    }
+
+    #[test]
+    fn complete() {
+        let graph = graph();
+
+        let completion = graph.complete("/g");
+        assert_eq!(completion.start, 1);
+        assert_eq!(completion.suggestions, ["gamemode", "gm"]);
+
+        let completion = graph.complete("/gamemode c");
+        assert_eq!(completion.start, 10);
+        assert_eq!(completion.suggestions, ["creative"]);
+        assert_eq!(completion.hints, ["<gameMode: GameMode>"]);
+
+        let completion = graph.complete("/gamemode creative @");
+        assert_eq!(completion.suggestions, SELECTORS);
+
+        let completion = graph.complete("/execute @a gamemode s");
+        assert_eq!(completion.start, 21);
+        assert_eq!(completion.suggestions, ["spectator", "survival"]);
+
+        assert_eq!(graph.complete("/tp 1 ").suggestions, ["~"]);
+        assert_eq!(graph.complete("/say hi ").hints, ["<message: message>"]);
+        assert!(graph.complete("/gamemode nope ").suggestions.is_empty());
+    }
+
+    #[test]
+    fn tokens() {
+        let line = r#"a  "b \" c" @e[name="x y",r=3] {"a": [1, 2]}"#;
+        let words: Vec<_> = tokenize(line)
+            .iter()
+            .map(|token| &line[token.start..token.end])
+            .collect();
+        assert_eq!(
+            words,
+            [
+                "a",
+                r#""b \" c""#,
+                r#"@e[name="x y",r=3]"#,
+                r#"{"a": [1, 2]}"#
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::any::TypeId;
 use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -27,6 +28,8 @@ pub struct Connection {
     signing_key: SigningKey,
 
     encoder: Mutex<Encoder>,
+
+    closed: AtomicBool,
 }
 
 impl Connection {
@@ -40,14 +43,22 @@ impl Connection {
             signing_key: SigningKey::random(&mut rand::thread_rng()),
 
             encoder: Mutex::new(Encoder::default()),
+
+            closed: AtomicBool::new(false),
         }
     }
 
     pub async fn close(&self) -> Result<(), ConnError> {
+        self.closed.store(true, Ordering::SeqCst);
         self.socket.close().await?;
         Ok(())
     }
 
+    /// Returns whether the connection was closed by this side of the connection.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
     pub fn signing_key(&self) -> &SigningKey {
         &self.signing_key
     }
@@ -172,6 +183,8 @@ pub enum ConnError {
     EncodeError(String),
     DecodeError(String),
     RakNetError(RaknetError),
+    AddressError(String),
+    UnexpectedPacket(String),
 }
 
 impl Display for ConnError {
@@ -180,6 +193,8 @@ impl Display for ConnError {
             ConnError::EncodeError(s) => f.write_str(&format!("Error encoding packet: {}", s)),
             ConnError::DecodeError(s) => f.write_str(&format!("Error decoding packet: {}", s)),
             ConnError::RakNetError(err) => f.write_str(&format!("RakNet error: {:?}", err)),
+            ConnError::AddressError(s) => f.write_str(&format!("Could not resolve address: {}", s)),
+            ConnError::UnexpectedPacket(s) => f.write_str(&format!("Expected a {} packet", s)),
         }
     }
 }
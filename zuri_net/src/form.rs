@@ -0,0 +1,378 @@
+//! Typed models of the forms sent in the ModalFormRequest packet, and of the responses sent back in
+//! the ModalFormResponse packet.
+//!
+//! Forms are sent as JSON. Servers are not always strict about the format, so fields that are
+//! commonly left out are optional.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// A form that the server asks the player to fill in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Form {
+    /// A form with a list of buttons, of which the player picks one. Also known as a simple form or
+    /// action form.
+    #[serde(rename = "form")]
+    Menu(MenuForm),
+    /// A form with a message and two buttons, such as yes and no.
+    #[serde(rename = "modal")]
+    Modal(ModalForm),
+    /// A form with a list of elements that the player can fill in.
+    #[serde(rename = "custom_form")]
+    Custom(CustomForm),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MenuForm {
+    pub title: String,
+    /// The text shown above the buttons.
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub buttons: Vec<Button>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Button {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+}
+
+/// An image shown on a button or as the icon of a form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Image {
+    #[serde(rename = "type")]
+    pub kind: ImageKind,
+    /// The path of the texture in the resource packs, or the URL of the image.
+    pub data: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageKind {
+    /// A texture from the resource packs, such as `textures/items/apple`.
+    Path,
+    /// An image downloaded from the internet.
+    Url,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModalForm {
+    pub title: String,
+    pub content: String,
+    /// The text of the first button. Pressing it responds with true.
+    pub button1: String,
+    /// The text of the second button. Pressing it responds with false.
+    pub button2: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomForm {
+    pub title: String,
+    /// The elements of the form, from top to bottom.
+    pub content: Vec<Element>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Image>,
+}
+
+/// An element of a custom form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Element {
+    /// Text that cannot be interacted with.
+    Label { text: String },
+    /// A field in which text can be typed.
+    Input {
+        text: String,
+        #[serde(default)]
+        placeholder: String,
+        #[serde(default)]
+        default: String,
+    },
+    /// A switch that is either on or off.
+    Toggle {
+        text: String,
+        #[serde(default)]
+        default: bool,
+    },
+    /// A slider for a number between a minimum and maximum, in steps of a fixed size.
+    Slider {
+        text: String,
+        min: f32,
+        max: f32,
+        #[serde(default = "default_step")]
+        step: f32,
+        #[serde(default)]
+        default: f32,
+    },
+    /// A slider that picks one of a list of options.
+    StepSlider {
+        text: String,
+        steps: Vec<String>,
+        #[serde(default)]
+        default: usize,
+    },
+    /// A list of options that the player picks one of.
+    Dropdown {
+        text: String,
+        options: Vec<String>,
+        #[serde(default)]
+        default: usize,
+    },
+}
+
+fn default_step() -> f32 {
+    1.
+}
+
+/// The answer of the player to a form.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum FormResponse {
+    /// The index of the button that was pressed in a menu form.
+    Menu(usize),
+    /// True if the first button of a modal form was pressed, or false for the second button.
+    Modal(bool),
+    /// The values of the elements of a custom form, in the same order as the elements.
+    Custom(Vec<ElementValue>),
+}
+
+/// The value of an element of a custom form.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ElementValue {
+    /// The value of a label, which has no value.
+    None,
+    /// The text typed in an input.
+    Text(String),
+    /// Whether a toggle is on.
+    Toggle(bool),
+    /// The number picked with a slider.
+    Number(f32),
+    /// The index of the option picked with a step slider or dropdown.
+    Index(usize),
+}
+
+/// The reason a response does not fit the form it answers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormError {
+    /// The response is for a different type of form.
+    WrongType,
+    /// A menu form has no button with this index.
+    InvalidButton(usize),
+    /// A custom form has a different number of elements than the response has values.
+    WrongLength { expected: usize, found: usize },
+    /// The value at this index does not fit the element at the same index.
+    InvalidValue(usize),
+}
+
+impl Display for FormError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormError::WrongType => f.write_str("Response is for a different type of form"),
+            FormError::InvalidButton(button) => write!(f, "Form has no button {}", button),
+            FormError::WrongLength { expected, found } => {
+                write!(f, "Expected {} values, found {}", expected, found)
+            }
+            FormError::InvalidValue(index) => write!(f, "Invalid value for element {}", index),
+        }
+    }
+}
+
+impl Error for FormError {}
+
+impl Element {
+    /// Returns the text shown with the element.
+    pub fn text(&self) -> &str {
+        match self {
+            Element::Label { text }
+            | Element::Input { text, .. }
+            | Element::Toggle { text, .. }
+            | Element::Slider { text, .. }
+            | Element::StepSlider { text, .. }
+            | Element::Dropdown { text, .. } => text,
+        }
+    }
+
+    /// Returns the value of the element before the player changes it.
+    pub fn default_value(&self) -> ElementValue {
+        match self {
+            Element::Label { .. } => ElementValue::None,
+            Element::Input { default, .. } => ElementValue::Text(default.clone()),
+            Element::Toggle { default, .. } => ElementValue::Toggle(*default),
+            Element::Slider {
+                min, max, default, ..
+            } => ElementValue::Number(default.clamp(*min, min.max(*max))),
+            Element::StepSlider { steps, default, .. } => {
+                ElementValue::Index((*default).min(steps.len().saturating_sub(1)))
+            }
+            Element::Dropdown {
+                options, default, ..
+            } => ElementValue::Index((*default).min(options.len().saturating_sub(1))),
+        }
+    }
+
+    /// Returns true if a value can be given for the element.
+    pub fn accepts(&self, value: &ElementValue) -> bool {
+        match (self, value) {
+            (Element::Label { .. }, ElementValue::None)
+            | (Element::Input { .. }, ElementValue::Text(_))
+            | (Element::Toggle { .. }, ElementValue::Toggle(_)) => true,
+            (Element::Slider { min, max, .. }, ElementValue::Number(n)) => *n >= *min && *n <= *max,
+            (Element::StepSlider { steps, .. }, ElementValue::Index(i)) => *i < steps.len(),
+            (Element::Dropdown { options, .. }, ElementValue::Index(i)) => *i < options.len(),
+            _ => false,
+        }
+    }
+}
+
+impl CustomForm {
+    /// Returns the values of all elements before the player changes them. Submitting these values
+    /// is the same as submitting the form right away.
+    pub fn default_values(&self) -> Vec<ElementValue> {
+        self.content.iter().map(Element::default_value).collect()
+    }
+}
+
+impl Form {
+    /// Parses a form from the JSON sent in a ModalFormRequest.
+    pub fn parse(data: &[u8]) -> serde_json::Result<Form> {
+        serde_json::from_slice(data)
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            Form::Menu(form) => &form.title,
+            Form::Modal(form) => &form.title,
+            Form::Custom(form) => &form.title,
+        }
+    }
+
+    /// Checks that a response fits the form, so that the server will accept it.
+    pub fn check(&self, response: &FormResponse) -> Result<(), FormError> {
+        match (self, response) {
+            (Form::Menu(form), FormResponse::Menu(button)) => {
+                if *button >= form.buttons.len() {
+                    return Err(FormError::InvalidButton(*button));
+                }
+            }
+            (Form::Modal(_), FormResponse::Modal(_)) => {}
+            (Form::Custom(form), FormResponse::Custom(values)) => {
+                if values.len() != form.content.len() {
+                    return Err(FormError::WrongLength {
+                        expected: form.content.len(),
+                        found: values.len(),
+                    });
+                }
+                if let Some(index) = form
+                    .content
+                    .iter()
+                    .zip(values)
+                    .position(|(element, value)| !element.accepts(value))
+                {
+                    return Err(FormError::InvalidValue(index));
+                }
+            }
+            _ => return Err(FormError::WrongType),
+        }
+        Ok(())
+    }
+}
+
+impl FormResponse {
+    /// Encodes the response as the JSON sent in a ModalFormResponse.
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("form responses can always be encoded")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_forms() {
+        let menu = Form::parse(
+            br#"{"type":"form","title":"Menu","content":"Pick one","buttons":[
+                {"text":"A"},{"text":"B","image":{"type":"path","data":"textures/items/apple"}}
+            ]}"#,
+        )
+        .unwrap();
+        let Form::Menu(menu) = menu else {
+            panic!("expected a menu form");
+        };
+        assert_eq!(menu.buttons.len(), 2);
+        assert_eq!(
+            menu.buttons[1].image.as_ref().unwrap().kind,
+            ImageKind::Path
+        );
+
+        let modal = Form::parse(
+            br#"{"type":"modal","title":"Sure?","content":"","button1":"Yes","button2":"No"}"#,
+        )
+        .unwrap();
+        assert_eq!(modal.title(), "Sure?");
+
+        let custom = Form::parse(
+            br#"{"type":"custom_form","title":"Settings","content":[
+                {"type":"label","text":"Hi"},
+                {"type":"input","text":"Name","placeholder":"Steve"},
+                {"type":"toggle","text":"PvP","default":true},
+                {"type":"slider","text":"Volume","min":0,"max":10,"default":20},
+                {"type":"step_slider","text":"Difficulty","steps":["Easy","Hard"],"default":1},
+                {"type":"dropdown","text":"Mode","options":["A","B"]}
+            ]}"#,
+        )
+        .unwrap();
+        let Form::Custom(custom) = custom else {
+            panic!("expected a custom form");
+        };
+        assert_eq!(
+            custom.default_values(),
+            [
+                ElementValue::None,
+                ElementValue::Text(String::new()),
+                ElementValue::Toggle(true),
+                ElementValue::Number(10.),
+                ElementValue::Index(1),
+                ElementValue::Index(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn responses() {
+        let form = Form::Custom(CustomForm {
+            title: String::new(),
+            content: vec![
+                Element::Label { text: "a".into() },
+                Element::Dropdown {
+                    text: "b".into(),
+                    options: vec!["x".into()],
+                    default: 0,
+                },
+            ],
+            icon: None,
+        });
+        let response = FormResponse::Custom(vec![ElementValue::None, ElementValue::Index(0)]);
+        assert_eq!(form.check(&response), Ok(()));
+        assert_eq!(response.encode(), b"[null,0]");
+        assert_eq!(
+            form.check(&FormResponse::Custom(vec![
+                ElementValue::None,
+                ElementValue::Index(1)
+            ])),
+            Err(FormError::InvalidValue(1))
+        );
+        assert_eq!(
+            form.check(&FormResponse::Modal(true)),
+            Err(FormError::WrongType)
+        );
+        assert_eq!(FormResponse::Modal(false).encode(), b"false");
+        assert_eq!(FormResponse::Menu(2).encode(), b"2");
+    }
+}
@@ -0,0 +1,402 @@
+//! Tracks the contents of the windows of the player, and predicts the changes made to them by item
+//! stack requests until the server confirms or rejects them.
+//!
+//! The server sends the contents of windows with the InventoryContent and InventorySlot packets,
+//! and opens and closes containers with ContainerOpen and ContainerClose. Changes made by the
+//! client are built with a [StackRequest], which are applied right away and undone again if the
+//! server rejects them in an ItemStackResponse.
+
+use std::collections::HashMap;
+
+use glam::IVec3;
+use num_traits::FromPrimitive;
+
+use crate::proto::packet::container_close::ContainerClose;
+use crate::proto::packet::container_open::ContainerOpen;
+use crate::proto::packet::inventory_content::InventoryContent;
+use crate::proto::packet::inventory_slot::InventorySlot;
+use crate::proto::packet::item_stack_response::ItemStackResponse;
+use crate::proto::types::container::{Container, ContainerType};
+use crate::proto::types::inventory::Window;
+use crate::proto::types::item::ItemInstance;
+use crate::proto::types::item_stack::ItemStackResponseStatus;
+
+pub use request::{StackRequest, StackRequestError};
+
+mod request;
+
+/// A slot as it is referred to in item stack requests: the container it is in and its index in
+/// that container.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Slot {
+    pub container: Container,
+    pub index: u8,
+}
+
+impl Slot {
+    pub fn new(container: Container, index: u8) -> Self {
+        Self { container, index }
+    }
+
+    /// A slot in the hotbar, from 0 to 8.
+    pub fn hotbar(index: u8) -> Self {
+        Self::new(Container::HotBar, index)
+    }
+
+    /// A slot in the main inventory, from 9 to 35.
+    pub fn inventory(index: u8) -> Self {
+        Self::new(Container::Inventory, index)
+    }
+
+    /// An armour slot, from the helmet at 0 to the boots at 3.
+    pub fn armour(index: u8) -> Self {
+        Self::new(Container::Armor, index)
+    }
+
+    pub fn offhand() -> Self {
+        Self::new(Container::Offhand, 1)
+    }
+
+    /// The slot holding the item that is being dragged around with the cursor.
+    pub fn cursor() -> Self {
+        Self::new(Container::Cursor, 0)
+    }
+
+    /// The slot in which the results of crafting are created, before they are taken out.
+    pub fn created_output() -> Self {
+        Self::new(Container::CreatedOutput, 50)
+    }
+}
+
+/// A container that was opened by the server with a ContainerOpen packet.
+#[derive(Debug, Clone)]
+pub struct OpenContainer {
+    pub window: Window,
+    pub container_type: ContainerType,
+    pub position: IVec3,
+    /// The unique ID of the entity holding the container, such as a horse, or -1 for blocks.
+    pub entity_unique_id: i64,
+}
+
+/// The result of an item stack request, as reported by the server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RequestResult {
+    /// The server accepted the request, and the predicted changes were kept.
+    Accepted(i32),
+    /// The server rejected the request, and the predicted changes were undone.
+    Rejected(i32),
+}
+
+/// The contents of all windows of the player.
+#[derive(Debug)]
+pub struct Inventory {
+    windows: HashMap<Window, Vec<ItemInstance>>,
+    open: Option<OpenContainer>,
+    pending: Vec<PendingRequest>,
+    last_request_id: i32,
+}
+
+/// A request that the server has not answered yet.
+#[derive(Debug)]
+struct PendingRequest {
+    id: i32,
+    /// The items that were in the slots changed by the request, before the changes were predicted.
+    previous: Vec<(Window, usize, ItemInstance)>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            windows: HashMap::new(),
+            open: None,
+            pending: Vec::new(),
+            // Request IDs are negative, like those of the vanilla client, so that they can be used
+            // as stack network IDs of predicted items without clashing with the IDs of the server.
+            last_request_id: 1,
+        }
+    }
+}
+
+impl Inventory {
+    /// Returns all items in a window, or an empty slice if its contents are not known.
+    pub fn items(&self, window: Window) -> &[ItemInstance] {
+        self.windows.get(&window).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the item in a slot, or None if the slot is not in any of the open windows. Slots
+    /// that the server has not sent yet are empty.
+    pub fn item(&self, slot: Slot) -> Option<ItemInstance> {
+        let (window, index) = self.location(slot)?;
+        Some(self.items(window).get(index).cloned().unwrap_or_default())
+    }
+
+    /// Returns the container that is currently opened, if any.
+    pub fn open_container(&self) -> Option<&OpenContainer> {
+        self.open.as_ref()
+    }
+
+    /// Returns true if there are requests that the server has not answered yet.
+    pub fn has_pending_requests(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Starts a new item stack request. The request is only applied once it is finished.
+    pub fn request(&mut self) -> StackRequest<'_> {
+        self.last_request_id -= 2;
+        let id = self.last_request_id;
+        StackRequest::new(self, id)
+    }
+
+    /// Finds the window and index in that window of a slot.
+    pub fn location(&self, slot: Slot) -> Option<(Window, usize)> {
+        let index = slot.index as usize;
+        let window = match slot.container {
+            Container::HotBar | Container::Inventory | Container::CombinedHotBarAndInventory => {
+                Window::Inventory
+            }
+            Container::Armor => Window::Armour,
+            // The offhand window only has a single slot.
+            Container::Offhand => return Some((Window::OffHand, 0)),
+            Container::LevelEntity
+            | Container::Barrel
+            | Container::ShulkerBox
+            | Container::FurnaceIngredient
+            | Container::FurnaceFuel
+            | Container::FurnaceResult
+            | Container::BlastFurnaceIngredient
+            | Container::SmokerIngredient
+            | Container::BrewingStandInput
+            | Container::BrewingStandResult
+            | Container::BrewingStandFuel
+            | Container::HorseEquip
+            | Container::CrafterLevelEntity => self.open.as_ref()?.window,
+            // Everything else, such as the cursor and the crafting grid, is part of the UI window.
+            _ => Window::UI,
+        };
+        Some((window, index))
+    }
+
+    /// Replaces the contents of a window.
+    pub fn handle_content(&mut self, pk: &InventoryContent) {
+        self.windows.insert(pk.window, pk.content.clone());
+        self.forget(|window, _| window == pk.window);
+    }
+
+    /// Replaces the item in a single slot of a window.
+    pub fn handle_slot(&mut self, pk: &InventorySlot) {
+        let index = pk.slot.0 as usize;
+        self.set(pk.window, index, pk.new_item.clone());
+        self.forget(|window, i| window == pk.window && i == index);
+    }
+
+    pub fn handle_open(&mut self, pk: &ContainerOpen) {
+        self.open = Some(OpenContainer {
+            window: pk.window,
+            container_type: pk.container_type,
+            position: pk.container_position,
+            entity_unique_id: pk.container_entity_unique_id,
+        });
+    }
+
+    /// Closes a container. The contents of windows opened for containers are forgotten, as the
+    /// server sends them again when the container is opened again.
+    pub fn handle_close(&mut self, pk: &ContainerClose) {
+        if matches!(&self.open, Some(open) if open.window == pk.window) {
+            self.open = None;
+        }
+        if let Window::Container(_) = pk.window {
+            self.windows.remove(&pk.window);
+        }
+    }
+
+    /// Confirms or undoes the changes predicted for the requests answered in the response.
+    pub fn handle_response(&mut self, pk: &ItemStackResponse) -> Vec<RequestResult> {
+        let mut results = Vec::with_capacity(pk.responses.len());
+        for response in &pk.responses {
+            let Some(position) = self
+                .pending
+                .iter()
+                .position(|request| request.id == response.request_id)
+            else {
+                continue;
+            };
+            let PendingRequest { id, previous } = self.pending.remove(position);
+
+            if response.status != ItemStackResponseStatus::Ok {
+                for (window, index, item) in previous.into_iter().rev() {
+                    self.set(window, index, item);
+                }
+                results.push(RequestResult::Rejected(id));
+                continue;
+            }
+            // The server tells us the actual count and stack network ID of every changed slot.
+            for info in &response.container_info {
+                let Some(container) = Container::from_u8(info.container_id) else {
+                    continue;
+                };
+                for slot_info in &info.slot_info {
+                    let Some((window, index)) = self.location(Slot::new(container, slot_info.slot))
+                    else {
+                        continue;
+                    };
+                    if slot_info.count == 0 {
+                        self.set(window, index, ItemInstance::default());
+                        continue;
+                    }
+                    if let Some(item) = self.windows.get_mut(&window).and_then(|w| w.get_mut(index))
+                    {
+                        item.stack.count = slot_info.count as u16;
+                        item.stack_network_id = slot_info.stack_network_id;
+                    }
+                }
+            }
+            results.push(RequestResult::Accepted(id));
+        }
+        results
+    }
+
+    fn set(&mut self, window: Window, index: usize, item: ItemInstance) {
+        let items = self.windows.entry(window).or_default();
+        if items.len() <= index {
+            items.resize_with(index + 1, Default::default);
+        }
+        items[index] = item;
+    }
+
+    /// Stops undoing changes to slots that were overwritten by the server, as the server is always
+    /// right about them.
+    fn forget(&mut self, overwritten: impl Fn(Window, usize) -> bool) {
+        for request in &mut self.pending {
+            request
+                .previous
+                .retain(|(window, index, _)| !overwritten(*window, *index));
+        }
+    }
+}
+
+/// Returns true if two items are the same apart from their count, so that they can be stacked.
+pub fn stackable(a: &ItemInstance, b: &ItemInstance) -> bool {
+    a.stack.network_id == b.stack.network_id
+        && a.stack.metadata_value == b.stack.metadata_value
+        && a.stack.block_runtime_id == b.stack.block_runtime_id
+        && a.stack.nbt_data == b.stack.nbt_data
+}
+
+/// Returns true if there is no item in a slot.
+pub fn is_air(item: &ItemInstance) -> bool {
+    item.stack.network_id == 0 || item.stack.count == 0
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num_traits::ToPrimitive;
+
+    use crate::proto::ints::VarU32;
+    use crate::proto::types::item::ItemStack;
+    use crate::proto::types::item_stack::{
+        ItemStackResponseEntry, StackResponseContainerInfo, StackResponseSlotInfo,
+    };
+
+    use super::*;
+
+    pub(crate) fn item(network_id: i32, count: u16, stack_network_id: i32) -> ItemInstance {
+        ItemInstance {
+            stack_network_id,
+            stack: ItemStack {
+                network_id,
+                count,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub(crate) fn inventory() -> Inventory {
+        let mut inventory = Inventory::default();
+        let mut content = vec![ItemInstance::default(); 36];
+        content[0] = item(1, 10, 100);
+        content[1] = item(2, 5, 101);
+        inventory.handle_content(&InventoryContent {
+            window: Window::Inventory,
+            content,
+        });
+        inventory
+    }
+
+    #[test]
+    fn contents() {
+        let mut inventory = inventory();
+        assert_eq!(inventory.item(Slot::hotbar(0)).unwrap().stack.count, 10);
+        assert!(is_air(&inventory.item(Slot::cursor()).unwrap()));
+        assert!(inventory
+            .item(Slot::new(Container::LevelEntity, 0))
+            .is_none());
+
+        inventory.handle_slot(&InventorySlot {
+            window: Window::Inventory,
+            slot: VarU32(20),
+            new_item: item(3, 1, 102),
+        });
+        assert_eq!(
+            inventory
+                .item(Slot::inventory(20))
+                .unwrap()
+                .stack
+                .network_id,
+            3
+        );
+    }
+
+    #[test]
+    fn responses() {
+        let mut inventory = inventory();
+        let entry = inventory
+            .request()
+            .take(4, Slot::hotbar(0), Slot::cursor())
+            .unwrap()
+            .finish();
+        let id = entry.request_id.0;
+        assert_eq!(inventory.item(Slot::cursor()).unwrap().stack.count, 4);
+        assert_eq!(inventory.item(Slot::cursor()).unwrap().stack_network_id, id);
+
+        let results = inventory.handle_response(&ItemStackResponse {
+            responses: vec![ItemStackResponseEntry {
+                status: ItemStackResponseStatus::Ok,
+                request_id: id,
+                container_info: vec![StackResponseContainerInfo {
+                    container_id: Container::Cursor.to_u8().unwrap(),
+                    slot_info: vec![StackResponseSlotInfo {
+                        slot: 0,
+                        hotbar_slot: 0,
+                        count: 4,
+                        stack_network_id: 103,
+                        custom_name: String::new(),
+                        durability_correction: 0,
+                    }],
+                }],
+            }],
+        });
+        assert_eq!(results, [RequestResult::Accepted(id)]);
+        assert_eq!(
+            inventory.item(Slot::cursor()).unwrap().stack_network_id,
+            103
+        );
+        assert!(!inventory.has_pending_requests());
+
+        let entry = inventory
+            .request()
+            .swap(Slot::hotbar(0), Slot::hotbar(1))
+            .unwrap()
+            .finish();
+        assert_eq!(inventory.item(Slot::hotbar(0)).unwrap().stack.network_id, 2);
+        let results = inventory.handle_response(&ItemStackResponse {
+            responses: vec![ItemStackResponseEntry {
+                status: ItemStackResponseStatus::Error,
+                request_id: entry.request_id.0,
+                container_info: Vec::new(),
+            }],
+        });
+        assert_eq!(results, [RequestResult::Rejected(entry.request_id.0)]);
+        assert_eq!(inventory.item(Slot::hotbar(0)).unwrap().stack.network_id, 1);
+        assert_eq!(inventory.item(Slot::hotbar(0)).unwrap().stack.count, 6);
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use num_traits::ToPrimitive;
+
+use crate::inventory::{is_air, stackable, Inventory, PendingRequest, Slot};
+use crate::proto::ints::VarI32;
+use crate::proto::types::inventory::Window;
+use crate::proto::types::item::{ItemInstance, ItemStack};
+use crate::proto::types::item_stack::{
+    ConsumeStackRequestAction, CraftCreativeStackRequestAction, CraftRecipeStackRequestAction,
+    DestroyStackRequestAction, DropStackRequestAction, ItemStackRequestEntry,
+    PlaceStackRequestAction, StackRequestAction, StackRequestSlotInfo, SwapStackRequestAction,
+    TakeStackRequestAction,
+};
+use crate::recipe::CraftingRecipe;
+
+/// Builds an item stack request out of actions, predicting the changes each action makes so that
+/// later actions see them. Nothing changes in the inventory until the request is finished.
+///
+/// ```ignore
+/// let entry = inventory
+///     .request()
+///     .take(16, Slot::hotbar(0), Slot::cursor())?
+///     .place(16, Slot::cursor(), Slot::inventory(9))?
+///     .finish();
+/// ```
+pub struct StackRequest<'a> {
+    inventory: &'a mut Inventory,
+    id: i32,
+    actions: Vec<StackRequestAction>,
+    /// The predicted items of the slots changed by the actions so far.
+    changes: HashMap<(Window, usize), ItemInstance>,
+}
+
+/// The reason an action cannot be added to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackRequestError {
+    /// The slot is not in any of the windows that are open.
+    UnknownSlot(Slot),
+    /// The slot has no item in it.
+    EmptySlot(Slot),
+    /// The slot has fewer items in it than the action needs.
+    NotEnoughItems { slot: Slot, count: u8 },
+    /// The slot holds an item that cannot be stacked with the item moved into it.
+    Occupied(Slot),
+}
+
+impl Display for StackRequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackRequestError::UnknownSlot(slot) => write!(f, "Slot {:?} is not open", slot),
+            StackRequestError::EmptySlot(slot) => write!(f, "Slot {:?} is empty", slot),
+            StackRequestError::NotEnoughItems { slot, count } => {
+                write!(f, "Slot {:?} holds less than {} items", slot, count)
+            }
+            StackRequestError::Occupied(slot) => {
+                write!(f, "Slot {:?} holds a different item", slot)
+            }
+        }
+    }
+}
+
+impl Error for StackRequestError {}
+
+impl<'a> StackRequest<'a> {
+    pub(super) fn new(inventory: &'a mut Inventory, id: i32) -> Self {
+        Self {
+            inventory,
+            id,
+            actions: Vec::new(),
+            changes: HashMap::new(),
+        }
+    }
+
+    /// Returns the ID of the request, which the server answers with.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Returns the item in a slot as predicted by the actions added so far.
+    pub fn item(&self, slot: Slot) -> Result<ItemInstance, StackRequestError> {
+        let location = self
+            .inventory
+            .location(slot)
+            .ok_or(StackRequestError::UnknownSlot(slot))?;
+        Ok(match self.changes.get(&location) {
+            Some(item) => item.clone(),
+            None => self.inventory.item(slot).unwrap_or_default(),
+        })
+    }
+
+    /// Takes items out of a slot, usually into the cursor.
+    pub fn take(mut self, count: u8, from: Slot, to: Slot) -> Result<Self, StackRequestError> {
+        let (source, destination) = self.transfer(count, from, to)?;
+        self.actions.push(
+            TakeStackRequestAction {
+                count,
+                source,
+                destination,
+            }
+            .into(),
+        );
+        Ok(self)
+    }
+
+    /// Places items in a slot, usually from the cursor.
+    pub fn place(mut self, count: u8, from: Slot, to: Slot) -> Result<Self, StackRequestError> {
+        let (source, destination) = self.transfer(count, from, to)?;
+        self.actions.push(
+            PlaceStackRequestAction {
+                count,
+                source,
+                destination,
+            }
+            .into(),
+        );
+        Ok(self)
+    }
+
+    /// Swaps the items in two slots.
+    pub fn swap(mut self, a: Slot, b: Slot) -> Result<Self, StackRequestError> {
+        let (item_a, item_b) = (self.item(a)?, self.item(b)?);
+        let (source, destination) = (self.slot_info(a, &item_a), self.slot_info(b, &item_b));
+        self.predict(a, item_b)?;
+        self.predict(b, item_a)?;
+        self.actions.push(
+            SwapStackRequestAction {
+                source,
+                destination,
+            }
+            .into(),
+        );
+        Ok(self)
+    }
+
+    /// Drops items from a slot on the ground.
+    pub fn drop(mut self, count: u8, from: Slot) -> Result<Self, StackRequestError> {
+        let source = self.remove(count, from)?;
+        self.actions.push(
+            DropStackRequestAction {
+                count,
+                source,
+                randomly: false,
+            }
+            .into(),
+        );
+        Ok(self)
+    }
+
+    /// Destroys items in a slot, which is only allowed in creative mode.
+    pub fn destroy(mut self, count: u8, from: Slot) -> Result<Self, StackRequestError> {
+        let source = self.remove(count, from)?;
+        self.actions
+            .push(DestroyStackRequestAction { count, source }.into());
+        Ok(self)
+    }
+
+    /// Uses up items as the ingredients of a recipe crafted in the same request.
+    pub fn consume(mut self, count: u8, from: Slot) -> Result<Self, StackRequestError> {
+        let source = self.remove(count, from)?;
+        self.actions
+            .push(ConsumeStackRequestAction { count, source }.into());
+        Ok(self)
+    }
+
+    /// Crafts a recipe by its network ID. The result is predicted to appear in the
+    /// [Slot::created_output], from which it should be taken in the same request. The ingredients
+    /// should be consumed with [StackRequest::consume].
+    pub fn craft_recipe(
+        mut self,
+        recipe_network_id: u32,
+        result: ItemStack,
+    ) -> Result<Self, StackRequestError> {
+        self.create(result)?;
+        self.actions
+            .push(CraftRecipeStackRequestAction { recipe_network_id }.into());
+        Ok(self)
+    }
+
+    /// Automatically crafts a recipe a number of times, as done by the recipe book. Like with
+    /// [StackRequest::craft_recipe], the output is predicted to appear in the
+    /// [Slot::created_output], and the ingredients should be consumed in the same request.
+    pub fn auto_craft(
+        mut self,
+        recipe: &CraftingRecipe,
+        times: u8,
+    ) -> Result<Self, StackRequestError> {
+        if let Some(output) = recipe.output.first() {
+            self.create(ItemStack {
+                count: output.count * times as u16,
+                ..output.clone()
+            })?;
+        }
+        self.actions.push(recipe.auto_craft(times).into());
+        Ok(self)
+    }
+
+    /// Takes an item out of the creative inventory. Like with recipes, the item is predicted to
+    /// appear in the [Slot::created_output].
+    pub fn craft_creative(
+        mut self,
+        creative_item_network_id: u32,
+        item: ItemStack,
+    ) -> Result<Self, StackRequestError> {
+        self.create(item)?;
+        self.actions.push(
+            CraftCreativeStackRequestAction {
+                creative_item_network_id,
+            }
+            .into(),
+        );
+        Ok(self)
+    }
+
+    /// Applies the predicted changes to the inventory and returns the request, to be sent to the
+    /// server in an ItemStackRequest packet.
+    pub fn finish(self) -> ItemStackRequestEntry {
+        let mut previous = Vec::with_capacity(self.changes.len());
+        for ((window, index), item) in self.changes {
+            let old = self.inventory.items(window).get(index).cloned();
+            previous.push((window, index, old.unwrap_or_default()));
+            self.inventory.set(window, index, item);
+        }
+        self.inventory.pending.push(PendingRequest {
+            id: self.id,
+            previous,
+        });
+
+        ItemStackRequestEntry {
+            request_id: VarI32(self.id),
+            actions: self.actions,
+            ..Default::default()
+        }
+    }
+
+    /// Moves items from one slot to another, returning the slot info of both slots.
+    fn transfer(
+        &mut self,
+        count: u8,
+        from: Slot,
+        to: Slot,
+    ) -> Result<(StackRequestSlotInfo, StackRequestSlotInfo), StackRequestError> {
+        let source_item = self.item(from)?;
+        let destination_item = self.item(to)?;
+        if !is_air(&destination_item) && !stackable(&source_item, &destination_item) {
+            return Err(StackRequestError::Occupied(to));
+        }
+        let source = self.remove(count, from)?;
+        let destination = self.slot_info(to, &destination_item);
+
+        let mut moved = if is_air(&destination_item) {
+            ItemInstance {
+                stack: ItemStack {
+                    count: 0,
+                    ..source_item.stack
+                },
+                ..Default::default()
+            }
+        } else {
+            destination_item
+        };
+        moved.stack.count += count as u16;
+        moved.stack_network_id = self.id;
+        self.predict(to, moved)?;
+        Ok((source, destination))
+    }
+
+    /// Removes items from a slot, returning the slot info of the slot before they were removed.
+    fn remove(&mut self, count: u8, from: Slot) -> Result<StackRequestSlotInfo, StackRequestError> {
+        let mut item = self.item(from)?;
+        if is_air(&item) {
+            return Err(StackRequestError::EmptySlot(from));
+        }
+        if item.stack.count < count as u16 {
+            return Err(StackRequestError::NotEnoughItems { slot: from, count });
+        }
+        let info = self.slot_info(from, &item);
+
+        item.stack.count -= count as u16;
+        if item.stack.count == 0 {
+            item = ItemInstance::default();
+        } else {
+            item.stack_network_id = self.id;
+        }
+        self.predict(from, item)?;
+        Ok(info)
+    }
+
+    /// Predicts a newly created item in the created output slot.
+    fn create(&mut self, stack: ItemStack) -> Result<(), StackRequestError> {
+        let item = ItemInstance {
+            stack_network_id: self.id,
+            stack,
+        };
+        self.predict(Slot::created_output(), item)
+    }
+
+    fn predict(&mut self, slot: Slot, item: ItemInstance) -> Result<(), StackRequestError> {
+        let location = self
+            .inventory
+            .location(slot)
+            .ok_or(StackRequestError::UnknownSlot(slot))?;
+        self.changes.insert(location, item);
+        Ok(())
+    }
+
+    fn slot_info(&self, slot: Slot, item: &ItemInstance) -> StackRequestSlotInfo {
+        StackRequestSlotInfo {
+            container_id: slot.container.to_u8().unwrap(),
+            slot: slot.index,
+            stack_network_id: item.stack_network_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inventory::tests::inventory;
+
+    use super::*;
+
+    #[test]
+    fn predictions() {
+        let mut inventory = inventory();
+        let request = inventory
+            .request()
+            .take(10, Slot::hotbar(0), Slot::cursor())
+            .unwrap()
+            .place(4, Slot::cursor(), Slot::inventory(9))
+            .unwrap();
+        assert!(is_air(&request.item(Slot::hotbar(0)).unwrap()));
+        assert_eq!(request.item(Slot::cursor()).unwrap().stack.count, 6);
+        assert_eq!(
+            request.place(1, Slot::cursor(), Slot::hotbar(1)).err(),
+            Some(StackRequestError::Occupied(Slot::hotbar(1)))
+        );
+
+        let entry = inventory
+            .request()
+            .take(11, Slot::hotbar(0), Slot::cursor())
+            .err();
+        assert_eq!(
+            entry,
+            Some(StackRequestError::NotEnoughItems {
+                slot: Slot::hotbar(0),
+                count: 11
+            })
+        );
+        // Nothing changes until a request is finished.
+        assert_eq!(inventory.item(Slot::hotbar(0)).unwrap().stack.count, 10);
+    }
+}
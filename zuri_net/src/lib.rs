@@ -2,11 +2,17 @@ extern crate core;
 
 pub mod chan;
 pub mod client;
+pub mod command;
 pub mod compression;
 pub mod connection;
 pub mod encode;
 pub mod encryption;
+pub mod form;
+pub mod inventory;
+pub mod ping;
 pub mod proto;
+pub mod recipe;
+pub mod scoreboard;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,181 @@
+//! Queries the status of a server with a RakNet unconnected ping, without connecting to it.
+
+use std::io::{Error, ErrorKind, Result};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+/// The ID of the unconnected ping and unconnected pong packets.
+const UNCONNECTED_PING: u8 = 0x01;
+const UNCONNECTED_PONG: u8 = 0x1c;
+/// The magic that every offline RakNet message carries.
+const MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+
+/// The status a Bedrock server advertises in its pong, as shown in the server list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStatus {
+    /// The edition of the server, which is `MCPE` for Bedrock servers and `MCEE` for Education
+    /// Edition servers.
+    pub edition: String,
+    /// The message of the day, which is the name of the server shown in the server list.
+    pub motd: String,
+    /// The protocol version of the server.
+    pub protocol: i32,
+    /// The version of the game the server runs, such as `1.19.70`.
+    pub version: String,
+    /// The amount of players online, and how many players may be online at once.
+    pub player_count: i32,
+    pub max_player_count: i32,
+    /// The ID of the server, which is also sent as the GUID of the pong.
+    pub server_id: String,
+    /// The name of the world the server runs.
+    pub level_name: String,
+    /// The default game mode of the server, such as `Survival`.
+    pub game_mode: String,
+    /// The ports the server listens on for IPv4 and IPv6 connections, if the server sent them.
+    pub port_v4: Option<u16>,
+    pub port_v6: Option<u16>,
+}
+
+impl ServerStatus {
+    /// Parses the status string of a pong, which holds fields separated by semicolons:
+    /// `MCPE;Dedicated Server;390;1.14.60;0;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;`.
+    /// Only the fields up to the player counts are required, as not all servers send the rest.
+    pub fn parse(status: &str) -> Option<Self> {
+        let fields: Vec<_> = status.split(';').collect();
+        let field = |index: usize| fields.get(index).copied().unwrap_or_default().to_string();
+        let port = |index: usize| fields.get(index).and_then(|port| port.parse().ok());
+        Some(Self {
+            edition: field(0),
+            motd: fields.get(1)?.to_string(),
+            protocol: fields.get(2)?.parse().ok()?,
+            version: fields.get(3)?.to_string(),
+            player_count: fields.get(4)?.parse().ok()?,
+            max_player_count: fields.get(5)?.parse().ok()?,
+            server_id: field(6),
+            level_name: field(7),
+            game_mode: field(8),
+            port_v4: port(10),
+            port_v6: port(11),
+        })
+    }
+}
+
+/// The answer of a server to an unconnected ping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pong {
+    pub status: ServerStatus,
+    /// How long it took for the server to answer.
+    pub latency: Duration,
+}
+
+/// Sends an unconnected ping to a server and waits for its pong. Fails if the server does not
+/// answer within the timeout, or if its answer is not a valid pong.
+pub async fn ping(address: SocketAddr, wait: Duration) -> Result<Pong> {
+    let local: SocketAddr = match address {
+        SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+        SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(address).await?;
+
+    let start = Instant::now();
+    let time = rand::random::<i64>() & i64::MAX;
+    socket
+        .send(&encode_ping(time, rand::random::<i64>()))
+        .await?;
+
+    let mut buf = [0; 1500];
+    timeout(wait, async {
+        loop {
+            let len = socket.recv(&mut buf).await?;
+            // Anything but the pong to our ping is ignored, in case an older pong arrives late.
+            let Some((pong_time, status)) = decode_pong(&buf[..len]) else {
+                continue;
+            };
+            if pong_time != time {
+                continue;
+            }
+            let status = ServerStatus::parse(&status)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid server status"))?;
+            return Ok(Pong {
+                status,
+                latency: start.elapsed(),
+            });
+        }
+    })
+    .await
+    .map_err(|_| Error::new(ErrorKind::TimedOut, "server did not answer the ping"))?
+}
+
+/// Encodes an unconnected ping with the time it was sent and the GUID of the client.
+fn encode_ping(time: i64, client_guid: i64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(33);
+    buf.push(UNCONNECTED_PING);
+    buf.extend_from_slice(&time.to_be_bytes());
+    buf.extend_from_slice(&MAGIC);
+    buf.extend_from_slice(&client_guid.to_be_bytes());
+    buf
+}
+
+/// Decodes an unconnected pong, returning the time of the ping it answers and the status string of
+/// the server.
+fn decode_pong(buf: &[u8]) -> Option<(i64, String)> {
+    let (&id, buf) = buf.split_first()?;
+    if id != UNCONNECTED_PONG || buf.len() < 34 || buf[16..32] != MAGIC {
+        return None;
+    }
+    let time = i64::from_be_bytes(buf[..8].try_into().ok()?);
+    let len = u16::from_be_bytes(buf[32..34].try_into().ok()?) as usize;
+    let status = buf.get(34..34 + len)?;
+    Some((time, String::from_utf8_lossy(status).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status() {
+        let status = ServerStatus::parse(
+            "MCPE;Dedicated Server;390;1.14.60;3;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;",
+        )
+        .unwrap();
+        assert_eq!(status.motd, "Dedicated Server");
+        assert_eq!(status.protocol, 390);
+        assert_eq!(status.version, "1.14.60");
+        assert_eq!((status.player_count, status.max_player_count), (3, 10));
+        assert_eq!(status.server_id, "13253860892328930865");
+        assert_eq!(status.level_name, "Bedrock level");
+        assert_eq!(status.game_mode, "Survival");
+        assert_eq!((status.port_v4, status.port_v6), (Some(19132), Some(19133)));
+
+        let short = ServerStatus::parse("MCPE;Proxy;575;1.19.70;0;100").unwrap();
+        assert_eq!(short.level_name, "");
+        assert_eq!(short.port_v4, None);
+        assert_eq!(ServerStatus::parse("MCPE;Proxy;unknown"), None);
+    }
+
+    #[test]
+    fn packets() {
+        let ping = encode_ping(12, 34);
+        assert_eq!(ping.len(), 33);
+        assert_eq!(ping[0], UNCONNECTED_PING);
+        assert_eq!(ping[9..25], MAGIC);
+
+        let status = "MCPE;Server;575;1.19.70;0;10;";
+        let mut pong = vec![UNCONNECTED_PONG];
+        pong.extend_from_slice(&12i64.to_be_bytes());
+        pong.extend_from_slice(&56i64.to_be_bytes());
+        pong.extend_from_slice(&MAGIC);
+        pong.extend_from_slice(&(status.len() as u16).to_be_bytes());
+        pong.extend_from_slice(status.as_bytes());
+        assert_eq!(decode_pong(&pong), Some((12, status.to_string())));
+        assert_eq!(decode_pong(&pong[..pong.len() - 1]), None);
+        assert_eq!(decode_pong(&ping), None);
+    }
+}
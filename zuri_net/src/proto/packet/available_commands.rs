@@ -93,7 +93,8 @@ pub struct ChainedSubcommandValue {
 #[derive(Debug, Clone)]
 pub struct DynamicEnum {
     pub type_name: String,
-    pub values: String,
+    #[len_type(VarU32)]
+    pub values: Vec<String>,
 }
 
 impl Readable<AvailableCommands> for AvailableCommands {
@@ -29,16 +29,16 @@ pub struct ContainerOpen {
 
 impl PacketType for ContainerOpen {
     fn write(&self, writer: &mut Writer) {
-        writer.u8(self.window.to_u8().unwrap());
-        writer.u8(self.container_type.to_u8().unwrap());
+        writer.u8(self.window.id());
+        writer.u8(self.container_type.to_i8().unwrap() as u8);
         writer.u_block_pos(self.container_position);
         writer.var_i64(self.container_entity_unique_id);
     }
 
     fn read(reader: &mut Reader) -> Self {
         Self {
-            window: Window::from_u8(reader.u8()).unwrap(),
-            container_type: ContainerType::from_u8(reader.u8()).unwrap(),
+            window: reader.u8().into(),
+            container_type: ContainerType::from_i8(reader.u8() as i8).unwrap(),
             container_position: reader.u_block_pos(),
             container_entity_unique_id: reader.var_i64(),
         }
@@ -16,7 +16,7 @@ pub struct Disconnect {
 impl PacketType for Disconnect {
     fn write(&self, writer: &mut Writer) {
         self.reason.write(writer);
-        writer.bool(self.message.is_some());
+        writer.bool(self.message.is_none());
         if self.message.is_some() {
             writer.string(self.message.as_ref().unwrap().as_str());
         }
@@ -33,3 +33,25 @@ impl PacketType for Disconnect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for message in [Some("disconnectionScreen.serverFull".to_string()), None] {
+            let mut writer = Writer::new(0);
+            Disconnect {
+                reason: 0.into(),
+                message: message.clone(),
+            }
+            .write(&mut writer);
+            let buf: Vec<u8> = writer.into();
+            let pk = Disconnect::read(&mut Reader::from_buf(Bytes::from(buf), 0));
+            assert_eq!(pk.message, message);
+        }
+    }
+}
@@ -2,6 +2,7 @@ use bytes::Bytes;
 
 use zuri_net_derive::proto;
 
+use crate::form::Form;
 use crate::proto::ints::VarU32;
 
 /// Sent by the server to make the client open a form. This form may be either a modal form which
@@ -16,3 +17,10 @@ pub struct ModalFormRequest {
     /// of the form sent, which is also set in the JSON.
     pub form_data: Bytes,
 }
+
+impl ModalFormRequest {
+    /// Parses the form data into a typed form.
+    pub fn form(&self) -> serde_json::Result<Form> {
+        Form::parse(&self.form_data)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::form::FormResponse;
 use crate::proto::ints::VarU32;
 use bytes::Bytes;
 use zuri_net_derive::proto;
@@ -20,8 +21,28 @@ pub struct ModalFormResponse {
     pub cancel_reason: Option<ModalFormCancelReason>,
 }
 
+impl ModalFormResponse {
+    /// Creates the response that submits a form with the answer of the player.
+    pub fn submit(form_id: u32, response: &FormResponse) -> Self {
+        Self {
+            form_id: VarU32(form_id),
+            response_data: Some(response.encode().into()),
+            cancel_reason: None,
+        }
+    }
+
+    /// Creates the response that closes a form without submitting it.
+    pub fn cancel(form_id: u32, reason: ModalFormCancelReason) -> Self {
+        Self {
+            form_id: VarU32(form_id),
+            response_data: None,
+            cancel_reason: Some(reason),
+        }
+    }
+}
+
 #[proto(u8)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ModalFormCancelReason {
     UserClosed,
     UserBusy,
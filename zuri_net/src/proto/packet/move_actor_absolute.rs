@@ -10,6 +10,12 @@ pub enum MoveFlag {
     Teleport,
 }
 
+impl MoveFlag {
+    pub fn flag(&self) -> u8 {
+        1 << (self.clone() as u8)
+    }
+}
+
 /// Sent by the server to move an entity to an absolute position. It is typically used for movements
 /// where high accuracy isn't needed, such as for long range teleporting.
 #[derive(Debug, Clone)]
@@ -4,8 +4,9 @@ use uuid::Uuid;
 use zuri_net_derive::proto;
 
 use crate::proto::ints::VarU32;
+use crate::proto::io::{Readable, Reader, Writable, Writer};
 
-#[derive(Debug, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum CommandArg {
     TypeInt = 1,
     TypeFloat = 3,
@@ -105,32 +106,72 @@ pub struct CommandEnum {
     pub value_indices: Vec<u32>,
 }
 
+/// Constrains a single value of an enum, so that it can only be used when the constraints are met.
 #[proto]
 #[derive(Debug, Clone)]
 pub struct CommandEnumConstraint {
-    pub enum_option: String,
-    pub enum_name: String,
+    /// The index of the constrained value in the `enum_values` of the AvailableCommands packet.
+    pub enum_value_index: u32,
+    /// The index of the enum holding the value in the `enums` of the AvailableCommands packet.
+    pub enum_index: u32,
     #[len_type(VarU32)]
     pub constraints: Vec<CommandEnumConstraints>,
 }
 
 #[proto(u8)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CommandEnumConstraints {
     CheatsEnabled,
     OperatorPermissions,
     HostPermissions,
 }
 
-#[proto]
+/// The origin of a command, which is sent back with the output of the command.
 #[derive(Debug, Clone)]
 pub struct CommandOrigin {
     pub origin: CommandOriginType,
     pub uuid: Uuid,
     pub request_id: String,
+    /// The unique ID of the player that ran the command. It is only written for commands run from
+    /// the dev console or by tests.
     pub player_unique_id: i64,
 }
 
+impl CommandOrigin {
+    fn has_player_unique_id(&self) -> bool {
+        matches!(
+            self.origin,
+            CommandOriginType::DevConsole | CommandOriginType::Test
+        )
+    }
+}
+
+impl Writable for CommandOrigin {
+    fn write(&self, writer: &mut Writer) {
+        self.origin.write(writer);
+        writer.uuid(self.uuid);
+        writer.string(&self.request_id);
+        if self.has_player_unique_id() {
+            writer.var_i64(self.player_unique_id);
+        }
+    }
+}
+
+impl Readable<CommandOrigin> for CommandOrigin {
+    fn read(reader: &mut Reader) -> Self {
+        let mut origin = Self {
+            origin: CommandOriginType::read(reader),
+            uuid: reader.uuid(),
+            request_id: reader.string(),
+            player_unique_id: 0,
+        };
+        if origin.has_player_unique_id() {
+            origin.player_unique_id = reader.var_i64();
+        }
+        origin
+    }
+}
+
 #[proto]
 #[derive(Debug, Clone)]
 pub struct CommandOutputMessage {
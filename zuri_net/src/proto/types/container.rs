@@ -3,7 +3,7 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::ToPrimitive;
 use zuri_net_derive::proto;
 
-#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromPrimitive, ToPrimitive)]
 pub enum Container {
     AnvilInput,
     AnvilMaterial,
@@ -86,7 +86,7 @@ pub enum ContainerDataBrewingStand {
 }
 
 #[proto(VarI32)]
-#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum ContainerType {
     Inventory = -1,
     Container = 0,
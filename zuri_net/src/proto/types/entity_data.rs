@@ -11,6 +11,96 @@ use crate::proto::io::{Readable, Reader, Writable, Writer};
 #[derive(Clone, Default, Debug)]
 pub struct EntityMetadata(pub HashMap<u32, EntityDataEntry>);
 
+impl EntityMetadata {
+    /// Returns the entry for a key, if it is present.
+    pub fn get(&self, key: EntityDataKey) -> Option<&EntityDataEntry> {
+        self.0.get(&(key as u32))
+    }
+
+    /// Sets the entry for a key, replacing the previous entry.
+    pub fn set(&mut self, key: EntityDataKey, entry: EntityDataEntry) {
+        self.0.insert(key as u32, entry);
+    }
+
+    /// Overwrites the entries of this metadata with those present in another. This is used to
+    /// apply updates, which only contain the entries that changed.
+    pub fn merge(&mut self, other: &EntityMetadata) {
+        self.0
+            .extend(other.0.iter().map(|(key, entry)| (*key, entry.clone())));
+    }
+
+    /// Returns the flags of the entity, combined from both flag words. Flags that were not sent
+    /// are unset.
+    pub fn flags(&self) -> EntityFlags {
+        let word = |key| self.get(key).and_then(EntityDataEntry::as_i64).unwrap_or(0) as u64;
+        EntityFlags([word(EntityDataKey::Flags), word(EntityDataKey::FlagsTwo)])
+    }
+
+    /// Sets the flags of the entity, writing both flag words.
+    pub fn set_flags(&mut self, flags: EntityFlags) {
+        self.set(
+            EntityDataKey::Flags,
+            EntityDataEntry::I64(flags.0[0] as i64),
+        );
+        self.set(
+            EntityDataKey::FlagsTwo,
+            EntityDataEntry::I64(flags.0[1] as i64),
+        );
+    }
+
+    /// The name tag displayed above the entity.
+    pub fn name_tag(&self) -> Option<&str> {
+        self.get(EntityDataKey::Name)
+            .and_then(EntityDataEntry::as_str)
+    }
+
+    /// The scale of the entity, where 1 is the default size.
+    pub fn scale(&self) -> Option<f32> {
+        self.get(EntityDataKey::Scale)
+            .and_then(EntityDataEntry::as_f32)
+    }
+
+    /// The width and height of the bounding box of the entity, in blocks.
+    pub fn bounding_box(&self) -> Option<(f32, f32)> {
+        let width = self.get(EntityDataKey::Width)?.as_f32()?;
+        let height = self.get(EntityDataKey::Height)?.as_f32()?;
+        Some((width, height))
+    }
+
+    /// The variant of the entity, such as the colour of a cat or the type of a villager.
+    pub fn variant(&self) -> Option<i32> {
+        self.get(EntityDataKey::Variant)
+            .and_then(EntityDataEntry::as_i32)
+    }
+
+    /// The amount of air the entity has left while under water, in ticks, and the amount of air it
+    /// has when it is not under water.
+    pub fn air_supply(&self) -> Option<(i16, i16)> {
+        let air = self.get(EntityDataKey::AirSupply)?.as_i16()?;
+        let max = self.get(EntityDataKey::AirSupplyMax)?.as_i16()?;
+        Some((air, max))
+    }
+
+    /// The unique ID of the entity that owns this entity, such as the owner of a tamed wolf or the
+    /// shooter of an arrow.
+    pub fn owner(&self) -> Option<i64> {
+        self.unique_id(EntityDataKey::Owner)
+    }
+
+    /// The unique ID of the entity holding the leash of this entity.
+    pub fn leash_holder(&self) -> Option<i64> {
+        self.unique_id(EntityDataKey::LeashHolder)
+    }
+
+    /// Returns a unique ID stored under a key. A value of -1 is used to signal that there is no
+    /// entity, so it is returned as None.
+    fn unique_id(&self, key: EntityDataKey) -> Option<i64> {
+        self.get(key)
+            .and_then(EntityDataEntry::as_i64)
+            .filter(|id| *id != -1)
+    }
+}
+
 impl Writable for EntityMetadata {
     #[inline]
     fn write(&self, writer: &mut Writer) {
@@ -61,6 +151,48 @@ pub enum EntityDataEntry {
     Vec3(Vec3),
 }
 
+impl EntityDataEntry {
+    /// Returns the value of the entry if it is an I16.
+    pub fn as_i16(&self) -> Option<i16> {
+        match self {
+            EntityDataEntry::I16(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the entry if it is an I32.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            EntityDataEntry::I32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the entry if it is an I64.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            EntityDataEntry::I64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the entry if it is an F32.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            EntityDataEntry::F32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the entry if it is a String.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            EntityDataEntry::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromPrimitive, ToPrimitive)]
 pub enum EntityDataType {
     U8,
@@ -330,3 +462,83 @@ pub enum EntityDataFlag {
     TimerFlag2,
     TimerFlag3,
 }
+
+impl EntityDataFlag {
+    /// The index of the flag word that holds this flag: 0 for [EntityDataKey::Flags] and 1 for
+    /// [EntityDataKey::FlagsTwo].
+    pub fn word(&self) -> usize {
+        *self as usize / 64
+    }
+
+    /// The bit of this flag within its flag word.
+    pub fn flag(&self) -> u64 {
+        1 << (*self as u64 % 64)
+    }
+}
+
+/// A set of [EntityDataFlag]s. The flags are sent as two 64-bit words, as there are more than 64
+/// of them.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct EntityFlags(pub [u64; 2]);
+
+impl EntityFlags {
+    /// Checks if a flag is set.
+    pub fn contains(&self, flag: EntityDataFlag) -> bool {
+        self.0[flag.word()] & flag.flag() != 0
+    }
+
+    /// Sets a flag.
+    pub fn insert(&mut self, flag: EntityDataFlag) {
+        self.0[flag.word()] |= flag.flag();
+    }
+
+    /// Unsets a flag.
+    pub fn remove(&mut self, flag: EntityDataFlag) {
+        self.0[flag.word()] &= !flag.flag();
+    }
+
+    /// Sets or unsets a flag.
+    pub fn set(&mut self, flag: EntityDataFlag, value: bool) {
+        if value {
+            self.insert(flag);
+        } else {
+            self.remove(flag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mut flags = EntityFlags::default();
+        flags.insert(EntityDataFlag::OnFire);
+        flags.insert(EntityDataFlag::Crawling);
+        assert_eq!(flags.0, [1, 1 << (EntityDataFlag::Crawling as u64 - 64)]);
+
+        let mut metadata = EntityMetadata::default();
+        metadata.set_flags(flags);
+        assert_eq!(metadata.flags(), flags);
+        assert!(metadata.flags().contains(EntityDataFlag::Crawling));
+        assert!(!metadata.flags().contains(EntityDataFlag::Sneaking));
+
+        flags.remove(EntityDataFlag::OnFire);
+        assert!(!flags.contains(EntityDataFlag::OnFire));
+    }
+
+    #[test]
+    fn merge() {
+        let mut metadata = EntityMetadata::default();
+        metadata.set(EntityDataKey::Scale, EntityDataEntry::F32(1.));
+        metadata.set(EntityDataKey::LeashHolder, EntityDataEntry::I64(-1));
+
+        let mut update = EntityMetadata::default();
+        update.set(EntityDataKey::Scale, EntityDataEntry::F32(0.5));
+        metadata.merge(&update);
+
+        assert_eq!(metadata.scale(), Some(0.5));
+        assert_eq!(metadata.leash_holder(), None);
+    }
+}
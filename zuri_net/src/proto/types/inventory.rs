@@ -7,16 +7,74 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use zuri_net_derive::proto;
 
 use crate::proto::ints::{VarI32, VarU32, VarU64};
-use crate::proto::io::{Readable, Reader, Writable, Writer};
+use crate::proto::io::{EnumReadable, EnumWritable, Readable, Reader, Writable, Writer};
 use crate::proto::types::item::ItemInstance;
 
-#[proto(u8)]
-#[derive(Debug, Clone, FromPrimitive, ToPrimitive)]
+/// A window of the player, which holds the items of an inventory. Besides the fixed windows of the
+/// player itself, the server picks an ID for every container it opens with ContainerOpen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Window {
-    Inventory = 0,
-    OffHand = 119,
-    Armour = 120,
-    UI = 124,
+    Inventory,
+    /// A container opened by the server, such as a chest or furnace.
+    Container(u8),
+    OffHand,
+    Armour,
+    UI,
+}
+
+impl Window {
+    /// Returns the ID of the window as it is written in packets.
+    pub fn id(&self) -> u8 {
+        match self {
+            Window::Inventory => 0,
+            Window::Container(id) => *id,
+            Window::OffHand => 119,
+            Window::Armour => 120,
+            Window::UI => 124,
+        }
+    }
+}
+
+impl From<u8> for Window {
+    fn from(id: u8) -> Self {
+        match id {
+            0 => Window::Inventory,
+            119 => Window::OffHand,
+            120 => Window::Armour,
+            124 => Window::UI,
+            id => Window::Container(id),
+        }
+    }
+}
+
+impl Writable for Window {
+    fn write(&self, writer: &mut Writer) {
+        writer.u8(self.id());
+    }
+}
+
+impl Readable<Window> for Window {
+    fn read(reader: &mut Reader) -> Window {
+        reader.u8().into()
+    }
+}
+
+impl<D: Writable + TryFrom<u8>> EnumWritable<D> for Window
+where
+    <D as TryFrom<u8>>::Error: Debug,
+{
+    fn write(&self, writer: &mut Writer) {
+        D::try_from(self.id()).unwrap().write(writer);
+    }
+}
+
+impl<D: Readable<D> + TryInto<u8>> EnumReadable<Window, D> for Window
+where
+    <D as TryInto<u8>>::Error: Debug,
+{
+    fn read(reader: &mut Reader) -> Window {
+        D::read(reader).try_into().unwrap().into()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, FromPrimitive, ToPrimitive)]
@@ -43,7 +101,7 @@ impl InventoryAction {
         match self.source_type {
             InventoryActionSource::Container | InventoryActionSource::TODO => {
                 // todo: this can be done with an enum
-                writer.var_i32(self.window.to_i32().unwrap());
+                writer.var_i32(self.window.id() as i32);
             }
             InventoryActionSource::World => {
                 writer.var_u32(self.source_flags);
@@ -62,7 +120,7 @@ impl InventoryAction {
             window: if source_type == InventoryActionSource::Container
                 || source_type == InventoryActionSource::TODO
             {
-                Window::from_i32(reader.var_i32()).unwrap()
+                Window::from(reader.var_i32() as u8)
             } else {
                 Window::Inventory
             },
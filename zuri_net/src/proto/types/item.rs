@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use bytes::BytesMut;
 use num_derive::{FromPrimitive, ToPrimitive};
 
@@ -158,6 +160,105 @@ pub struct ItemStack {
     pub has_network_id: bool,
 }
 
+impl ItemStack {
+    /// Returns the name given to the item with an anvil or by the server, if any.
+    pub fn custom_name(&self) -> Option<&str> {
+        match self.display()?.get("Name")? {
+            NBTTag::String(name) => Some(&name.0),
+            _ => None,
+        }
+    }
+
+    /// Returns the lines of text shown below the name of the item.
+    pub fn lore(&self) -> Vec<&str> {
+        match self.display().and_then(|display| display.get("Lore")) {
+            Some(NBTTag::List(lines)) => lines
+                .0
+                .iter()
+                .filter_map(|line| match line {
+                    NBTTag::String(line) => Some(line.0.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the enchantments of the item as pairs of their ID and level.
+    pub fn enchantments(&self) -> Vec<(i16, i16)> {
+        let NBTTag::Compound(root) = &self.nbt_data else {
+            return Vec::new();
+        };
+        let Some(NBTTag::List(enchantments)) = root.0.get("ench") else {
+            return Vec::new();
+        };
+        enchantments
+            .0
+            .iter()
+            .filter_map(|enchantment| {
+                let NBTTag::Compound(enchantment) = enchantment else {
+                    return None;
+                };
+                match (enchantment.0.get("id")?, enchantment.0.get("lvl")?) {
+                    (NBTTag::Short(id), NBTTag::Short(level)) => Some((id.0, level.0)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns how many uses of the item have been spent. Zero for items that do not break with
+    /// use.
+    pub fn damage(&self) -> i32 {
+        let NBTTag::Compound(root) = &self.nbt_data else {
+            return 0;
+        };
+        match root.0.get("Damage") {
+            Some(NBTTag::Int(damage)) => damage.0,
+            Some(NBTTag::Short(damage)) => damage.0 as i32,
+            _ => 0,
+        }
+    }
+
+    /// Returns the identifiers of the blocks the item may be placed on in adventure mode. These
+    /// are sent both alongside the stack and in its `CanPlaceOn` tag.
+    pub fn can_place_on(&self) -> Vec<&str> {
+        self.block_list(&self.can_be_placed_on, "CanPlaceOn")
+    }
+
+    /// Returns the identifiers of the blocks the item may break in adventure mode. These are sent
+    /// both alongside the stack and in its `CanDestroy` tag.
+    pub fn can_destroy(&self) -> Vec<&str> {
+        self.block_list(&self.can_break, "CanDestroy")
+    }
+
+    fn block_list<'a>(&'a self, sent: &'a [String], key: &str) -> Vec<&'a str> {
+        let mut blocks: Vec<&str> = sent.iter().map(String::as_str).collect();
+        if let NBTTag::Compound(root) = &self.nbt_data {
+            if let Some(NBTTag::List(list)) = root.0.get(key) {
+                for block in &list.0 {
+                    if let NBTTag::String(block) = block {
+                        if !blocks.contains(&block.0.as_str()) {
+                            blocks.push(&block.0);
+                        }
+                    }
+                }
+            }
+        }
+        blocks
+    }
+
+    fn display(&self) -> Option<&HashMap<String, NBTTag>> {
+        let NBTTag::Compound(root) = &self.nbt_data else {
+            return None;
+        };
+        match root.0.get("display")? {
+            NBTTag::Compound(display) => Some(&display.0),
+            _ => None,
+        }
+    }
+}
+
 impl Writable for ItemStack {
     fn write(&self, writer: &mut Writer) {
         writer.var_i32(self.network_id);
@@ -243,3 +344,59 @@ impl Readable<ItemStack> for ItemStack {
         stack
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zuri_nbt::tag;
+
+    use super::*;
+
+    #[test]
+    fn display_data() {
+        let display = HashMap::from([
+            (
+                "Name".to_string(),
+                NBTTag::String(tag::String("Sword".into())),
+            ),
+            (
+                "Lore".to_string(),
+                NBTTag::List(tag::List(vec![NBTTag::String(tag::String("Sharp".into()))])),
+            ),
+        ]);
+        let enchantment = HashMap::from([
+            ("id".to_string(), NBTTag::Short(tag::Short(9))),
+            ("lvl".to_string(), NBTTag::Short(tag::Short(3))),
+        ]);
+        let stack = ItemStack {
+            nbt_data: NBTTag::Compound(tag::Compound(HashMap::from([
+                (
+                    "display".to_string(),
+                    NBTTag::Compound(tag::Compound(display)),
+                ),
+                (
+                    "ench".to_string(),
+                    NBTTag::List(tag::List(vec![NBTTag::Compound(tag::Compound(
+                        enchantment,
+                    ))])),
+                ),
+                ("Damage".to_string(), NBTTag::Int(tag::Int(12))),
+                (
+                    "CanDestroy".to_string(),
+                    NBTTag::List(tag::List(vec![
+                        NBTTag::String(tag::String("minecraft:stone".into())),
+                        NBTTag::String(tag::String("minecraft:dirt".into())),
+                    ])),
+                ),
+            ]))),
+            can_break: vec!["minecraft:stone".into()],
+            ..Default::default()
+        };
+        assert_eq!(stack.custom_name(), Some("Sword"));
+        assert_eq!(stack.lore(), ["Sharp"]);
+        assert_eq!(stack.enchantments(), [(9, 3)]);
+        assert_eq!(stack.damage(), 12);
+        assert_eq!(stack.can_destroy(), ["minecraft:stone", "minecraft:dirt"]);
+        assert!(stack.can_place_on().is_empty());
+        assert_eq!(ItemStack::default().custom_name(), None);
+    }
+}
@@ -54,28 +54,32 @@ pub struct InvalidDescriptor;
 
 #[derive(Debug, Clone)]
 pub struct DefaultDescriptor {
-    network_id: i16,
-    metadata: i16,
+    pub network_id: i16,
+    pub metadata: i16,
 }
 
 impl DefaultDescriptor {
     pub fn read(reader: &mut Reader) -> Self {
+        let network_id = reader.i16();
         Self {
-            network_id: reader.i16(),
-            metadata: reader.i16(),
+            network_id,
+            // The metadata is only present for items other than air.
+            metadata: if network_id != 0 { reader.i16() } else { 0 },
         }
     }
 
     pub fn write(&self, writer: &mut Writer) {
         writer.i16(self.network_id);
-        writer.i16(self.metadata);
+        if self.network_id != 0 {
+            writer.i16(self.metadata);
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct MoLangDescriptor {
-    expression: String,
-    version: u8,
+    pub expression: String,
+    pub version: u8,
 }
 
 impl MoLangDescriptor {
@@ -94,7 +98,7 @@ impl MoLangDescriptor {
 
 #[derive(Debug, Clone)]
 pub struct ItemTagDescriptor {
-    tag: String,
+    pub tag: String,
 }
 
 impl ItemTagDescriptor {
@@ -111,8 +115,8 @@ impl ItemTagDescriptor {
 
 #[derive(Debug, Clone)]
 pub struct DeferredDescriptor {
-    name: String,
-    metadata: i16,
+    pub name: String,
+    pub metadata: i16,
 }
 
 impl DeferredDescriptor {
@@ -135,7 +139,7 @@ impl DeferredDescriptor {
 #[derive(Debug, Clone)]
 pub struct ComplexAliasDescriptor {
     /// The name of the item, which is a name like 'minecraft:stick'.
-    name: String,
+    pub name: String,
 }
 
 impl ComplexAliasDescriptor {
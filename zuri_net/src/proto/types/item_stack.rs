@@ -312,7 +312,7 @@ pub struct AutoCraftRecipeStackRequestAction {
 impl AutoCraftRecipeStackRequestAction {
     pub fn read(reader: &mut Reader) -> Self {
         Self {
-            recipe_network_id: reader.u32(),
+            recipe_network_id: reader.var_u32(),
             times_crafted: reader.u8(),
             ingredients: (0..reader.var_u32())
                 .map(|_| ItemDescriptorCount::read(reader))
@@ -321,7 +321,7 @@ impl AutoCraftRecipeStackRequestAction {
     }
 
     pub fn write(&self, writer: &mut Writer) {
-        writer.u32(self.recipe_network_id);
+        writer.var_u32(self.recipe_network_id);
         writer.u8(self.times_crafted);
         writer.var_u32(self.ingredients.len() as u32);
         self.ingredients
@@ -478,7 +478,7 @@ pub struct CraftCreativeStackRequestAction {
 impl CraftCreativeStackRequestAction {
     pub fn read(reader: &mut Reader) -> Self {
         Self {
-            creative_item_network_id: reader.u32(),
+            creative_item_network_id: reader.var_u32(),
         }
     }
 
@@ -496,8 +496,8 @@ pub struct CraftGrindstoneRecipeStackRequestAction {
 impl CraftGrindstoneRecipeStackRequestAction {
     pub fn read(reader: &mut Reader) -> Self {
         Self {
-            recipe_network_id: reader.u32(),
-            cost: reader.i32(),
+            recipe_network_id: reader.var_u32(),
+            cost: reader.var_i32(),
         }
     }
 
@@ -544,7 +544,7 @@ pub struct CraftRecipeOptionalStackRequestAction {
 impl CraftRecipeOptionalStackRequestAction {
     pub fn read(reader: &mut Reader) -> Self {
         Self {
-            recipe_network_id: reader.u32(),
+            recipe_network_id: reader.var_u32(),
             filter_string_index: reader.i32(),
         }
     }
@@ -563,7 +563,7 @@ pub struct CraftRecipeStackRequestAction {
 impl CraftRecipeStackRequestAction {
     pub fn read(reader: &mut Reader) -> Self {
         Self {
-            recipe_network_id: reader.u32(),
+            recipe_network_id: reader.var_u32(),
         }
     }
 
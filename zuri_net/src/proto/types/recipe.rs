@@ -6,14 +6,14 @@ use crate::proto::io::{Readable, Reader, Writable, Writer};
 use crate::proto::types::item::ItemStack;
 use crate::proto::types::item_descriptor::ItemDescriptorCount;
 
-#[proto(VarU32)]
-#[repr(u32)]
+#[proto(VarI32)]
+#[repr(i32)]
 #[derive(Debug, Clone)]
 pub enum Recipe {
     ShapelessRecipe(ShapelessRecipe),
     ShapedRecipe(ShapedRecipe),
     FurnaceRecipe(FurnaceRecipe),
-    FurnaceDataRecipe(FurnaceRecipe),
+    FurnaceDataRecipe(FurnaceDataRecipe),
     MultiRecipe(MultiRecipe),
     ShulkerBoxRecipe(ShulkerBoxRecipe),
     ShapelessChemistryRecipe(ShapelessChemistryRecipe),
@@ -30,7 +30,7 @@ pub struct SmithingTransformRecipe {
     /// A unique ID used to identify the recipe over network. Each recipe must have a unique network
     /// ID. Recommended is to just increment a variable for each unique recipe registered. This
     /// field must never be 0.
-    pub recipe_network_id: u32,
+    pub recipe_network_id: VarU32,
     /// A unique ID of the recipe. This ID must be unique amongst all other types of recipes too,
     /// but its functionality is not exactly known.
     pub recipe_id: String,
@@ -47,7 +47,22 @@ pub struct SmithingTransformRecipe {
     pub block: String,
 }
 
-pub type SmithingTrimRecipe = ShapelessRecipe;
+/// A recipe specifically used for applying armour trims to an armour piece inside a smithing table.
+/// Unlike the transform recipe, the result is not known up front: the trim is added to the NBT of
+/// the base item.
+#[proto]
+#[derive(Debug, Clone)]
+pub struct SmithingTrimRecipe {
+    pub recipe_network_id: VarU32,
+    pub recipe_id: String,
+    /// The trim template applied to the base item.
+    pub template: ItemDescriptorCount,
+    /// The armour piece that the trim is applied to.
+    pub base: ItemDescriptorCount,
+    /// The material that determines the colour of the trim.
+    pub addition: ItemDescriptorCount,
+    pub block: String,
+}
 
 #[proto]
 #[derive(Debug, Clone)]
@@ -56,16 +71,23 @@ pub struct MultiRecipe {
     pub recipe_network_id: VarU32,
 }
 
+/// A recipe for furnaces and similar blocks, which smelts any variant of an input item.
 #[proto]
 #[derive(Debug, Clone)]
-pub struct FurnaceDataRecipe {
-    pub furnace_recipe: FurnaceRecipe,
+pub struct FurnaceRecipe {
+    /// The network ID of the item that is smelted.
+    pub network_id: VarI32,
+    pub output: ItemStack,
+    /// The block the recipe is for, such as 'furnace' or 'blast_furnace'.
+    pub block: String,
 }
 
+/// A furnace recipe that only smelts the variant of the input item with a specific metadata value.
 #[proto]
 #[derive(Debug, Clone)]
-pub struct FurnaceRecipe {
+pub struct FurnaceDataRecipe {
     pub network_id: VarI32,
+    pub metadata_value: VarI32,
     pub output: ItemStack,
     pub block: String,
 }
@@ -99,8 +121,8 @@ pub struct ShapedRecipe {
 impl Writable for ShapedRecipe {
     fn write(&self, writer: &mut Writer) {
         writer.string(self.recipe_id.as_str());
-        writer.i32(self.width);
-        writer.i32(self.height);
+        writer.var_i32(self.width);
+        writer.var_i32(self.height);
         for i in 0..self.width * self.height {
             if i >= self.input.len() as i32 {
                 ItemDescriptorCount::default().write(writer);
@@ -120,8 +142,8 @@ impl Writable for ShapedRecipe {
 impl Readable<ShapedRecipe> for ShapedRecipe {
     fn read(reader: &mut Reader) -> Self {
         let recipe_id = reader.string();
-        let width = reader.i32();
-        let height = reader.i32();
+        let width = reader.var_i32();
+        let height = reader.var_i32();
         Self {
             recipe_id,
             width,
@@ -159,8 +181,7 @@ pub struct ShapelessRecipe {
 #[proto]
 #[derive(Debug, Clone)]
 pub struct MaterialReducer {
-    pub network_id: i32,
-    pub metadata_value: u32,
+    pub input: ItemType,
     #[len_type(VarU32)]
     pub outputs: Vec<MaterialReducerOutput>,
 }
@@ -181,7 +202,7 @@ impl Readable<ItemType> for ItemType {
     fn read(reader: &mut Reader) -> Self {
         let value = reader.var_i32();
         Self {
-            network_id: value << 16,
+            network_id: value >> 16,
             metadata_value: (value & 0x7fff) as u32,
         }
     }
@@ -11,7 +11,7 @@ pub enum ScoreboardAction {
     Remove,
 }
 
-#[derive(Debug, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum ScoreboardIdentity {
     Player = 1,
     Entity = 2,
@@ -24,7 +24,7 @@ pub enum ScoreboardIdentityAction {
     Clear,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ScoreboardSlot {
     List,
     Sidebar,
@@ -50,7 +50,7 @@ impl ScoreboardSlot {
     }
 }
 
-#[derive(Debug, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum ScoreboardSortOrder {
     Ascending,
     Descending,
@@ -43,7 +43,7 @@ pub enum Difficulty {
 }
 
 #[proto(VarU32)]
-#[derive(Debug, Copy, Clone, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum Dimension {
     Overworld,
     Nether,
@@ -135,9 +135,12 @@ pub struct GenerationFeature {
 #[proto]
 #[derive(Debug, Clone)]
 pub struct DimensionDefinition {
-    name: String,
-    range: [VarI32; 2],
-    generator: VarI32,
+    /// The name of the dimension, such as `minecraft:overworld`.
+    pub name: String,
+    /// The lowest and the highest Y coordinate of the dimension.
+    pub range: [VarI32; 2],
+    /// The generator used for the dimension.
+    pub generator: VarI32,
 }
 
 #[proto]
@@ -0,0 +1,752 @@
+//! Keeps track of the recipes sent by the server in the CraftingData packet, and answers which of
+//! them can be crafted with the items of the player.
+//!
+//! Ingredients are described with item descriptors, which refer to items by network ID, by name or
+//! by tag. They are resolved against the items sent in the StartGame packet as soon as the recipes
+//! are received, so that recipes can be looked up by the network IDs of their inputs and outputs.
+
+use std::collections::HashMap;
+
+use crate::proto::packet::crafting_data::CraftingData;
+use crate::proto::types::item::ItemStack;
+use crate::proto::types::item_descriptor::{ItemDescriptor, ItemDescriptorCount};
+use crate::proto::types::item_stack::{AutoCraftRecipeStackRequestAction, ItemEntry};
+use crate::proto::types::recipe::{
+    MaterialReducer, MultiRecipe, PotionContainerChangeRecipe, PotionRecipe, Recipe,
+};
+
+/// The metadata value of descriptors that accept any variant of an item.
+const ANY_METADATA: i16 = 0x7fff;
+
+/// The item tags used by vanilla recipes. The server does not send the items in each tag, so they
+/// are found by matching these patterns against the names of all items. A leading `*` matches any
+/// prefix.
+const TAGS: [(&str, &[&str]); 7] = [
+    ("minecraft:planks", &["planks", "*_planks"]),
+    (
+        "minecraft:logs",
+        &["log", "log2", "*_log", "*_wood", "*_stem", "*_hyphae"],
+    ),
+    ("minecraft:wool", &["wool", "*_wool"]),
+    ("minecraft:coals", &["coal", "charcoal"]),
+    (
+        "minecraft:stone_tool_materials",
+        &["cobblestone", "blackstone", "cobbled_deepslate"],
+    ),
+    (
+        "minecraft:stone_crafting_materials",
+        &["cobblestone", "blackstone", "cobbled_deepslate"],
+    ),
+    (
+        "minecraft:soul_fire_base_blocks",
+        &["soul_sand", "soul_soil"],
+    ),
+];
+
+/// An item that an ingredient accepts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ItemKind {
+    pub network_id: i32,
+    /// The metadata value the item must have, or None if any variant of the item is accepted.
+    pub metadata: Option<u32>,
+}
+
+impl ItemKind {
+    /// Returns true if a stack holds this kind of item.
+    pub fn matches(&self, stack: &ItemStack) -> bool {
+        stack.network_id == self.network_id
+            && match self.metadata {
+                Some(metadata) => metadata == stack.metadata_value,
+                None => true,
+            }
+    }
+}
+
+/// An ingredient of a recipe, with the items it may be made of.
+#[derive(Debug, Clone)]
+pub struct Ingredient {
+    /// The items accepted by the ingredient. If none of the items in the descriptor are known, the
+    /// ingredient cannot be satisfied.
+    pub items: Vec<ItemKind>,
+    /// The number of items needed. Empty slots of shaped recipes need zero items.
+    pub count: u16,
+    /// The descriptor the ingredient was resolved from.
+    pub descriptor: ItemDescriptorCount,
+}
+
+impl Ingredient {
+    /// Returns true if the ingredient is an empty slot in a shaped recipe.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns true if a stack holds one of the items of the ingredient.
+    pub fn accepts(&self, stack: &ItemStack) -> bool {
+        self.items.iter().any(|kind| kind.matches(stack))
+    }
+}
+
+/// How the ingredients of a crafting recipe must be laid out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RecipeShape {
+    /// The ingredients form a grid of this size, which may be mirrored horizontally.
+    Shaped { width: usize, height: usize },
+    /// The ingredients may be put anywhere in the crafting grid.
+    Shapeless,
+    /// The template, base and addition of a smithing table.
+    Smithing,
+}
+
+/// A recipe of a crafting table, a smithing table or a similar block.
+#[derive(Debug, Clone)]
+pub struct CraftingRecipe {
+    /// The ID that the recipe is referred to with in item stack requests.
+    pub network_id: u32,
+    pub id: String,
+    pub shape: RecipeShape,
+    /// The ingredients of the recipe. Those of shaped recipes are ordered row by row.
+    pub ingredients: Vec<Ingredient>,
+    /// The items produced. Smithing trim recipes produce nothing known up front, as they change
+    /// the base item instead.
+    pub output: Vec<ItemStack>,
+    /// The block the recipe is crafted in, such as `crafting_table`.
+    pub block: String,
+    pub priority: i32,
+}
+
+impl CraftingRecipe {
+    /// Returns the action that automatically crafts the recipe a number of times, as used by the
+    /// recipe book. The ingredients should be consumed and the output taken in the same request.
+    pub fn auto_craft(&self, times: u8) -> AutoCraftRecipeStackRequestAction {
+        AutoCraftRecipeStackRequestAction {
+            recipe_network_id: self.network_id,
+            times_crafted: times,
+            ingredients: self
+                .ingredients
+                .iter()
+                .filter(|ingredient| !ingredient.is_empty())
+                .map(|ingredient| ingredient.descriptor.clone())
+                .collect(),
+        }
+    }
+
+    /// Returns true if the items in a square crafting grid, ordered row by row, match the recipe.
+    pub fn matches_grid(&self, grid: &[ItemStack], size: usize) -> bool {
+        let filled: Vec<_> = (0..grid.len()).filter(|i| !is_air(&grid[*i])).collect();
+        if filled.is_empty() {
+            return false;
+        }
+        match self.shape {
+            RecipeShape::Shaped { width, height } => {
+                let columns = filled.iter().map(|i| i % size);
+                let rows = filled.iter().map(|i| i / size);
+                let (left, right) = (columns.clone().min().unwrap(), columns.max().unwrap());
+                let (top, bottom) = (rows.clone().min().unwrap(), rows.max().unwrap());
+                if right - left + 1 != width || bottom - top + 1 != height {
+                    return false;
+                }
+                [false, true].into_iter().any(|mirrored| {
+                    (0..height).all(|y| {
+                        (0..width).all(|x| {
+                            let column = if mirrored { width - 1 - x } else { x };
+                            let stack = &grid[(top + y) * size + left + x];
+                            match self.ingredients.get(y * width + column) {
+                                Some(ingredient) if !ingredient.is_empty() => {
+                                    ingredient.accepts(stack) && stack.count >= ingredient.count
+                                }
+                                _ => is_air(stack),
+                            }
+                        })
+                    })
+                })
+            }
+            RecipeShape::Shapeless => {
+                let ingredients: Vec<_> = self
+                    .ingredients
+                    .iter()
+                    .filter(|ingredient| !ingredient.is_empty())
+                    .collect();
+                let stacks: Vec<_> = filled.iter().map(|i| &grid[*i]).collect();
+                ingredients.len() == stacks.len()
+                    && assign(&ingredients, &stacks, &mut vec![false; stacks.len()])
+            }
+            RecipeShape::Smithing => false,
+        }
+    }
+}
+
+/// Returns true if every ingredient can be given a stack of its own.
+fn assign(ingredients: &[&Ingredient], stacks: &[&ItemStack], used: &mut [bool]) -> bool {
+    let Some((ingredient, rest)) = ingredients.split_first() else {
+        return true;
+    };
+    for (i, stack) in stacks.iter().enumerate() {
+        if used[i] || !ingredient.accepts(stack) || stack.count < ingredient.count {
+            continue;
+        }
+        used[i] = true;
+        if assign(rest, stacks, used) {
+            return true;
+        }
+        used[i] = false;
+    }
+    false
+}
+
+/// A recipe of a furnace, blast furnace, smoker or campfire.
+#[derive(Debug, Clone)]
+pub struct SmeltingRecipe {
+    pub input: ItemKind,
+    pub output: ItemStack,
+    pub block: String,
+}
+
+/// All recipes known to the client.
+#[derive(Debug, Default)]
+pub struct RecipeBook {
+    /// The network IDs of all items by their name, as sent in the StartGame packet.
+    items: HashMap<String, i32>,
+    /// The network IDs of the items in each tag.
+    tags: HashMap<String, Vec<i32>>,
+    recipes: HashMap<u32, CraftingRecipe>,
+    /// The network IDs of recipes by the network IDs of the items they produce and use.
+    by_output: HashMap<i32, Vec<u32>>,
+    by_input: HashMap<i32, Vec<u32>>,
+    smelting: Vec<SmeltingRecipe>,
+    potions: Vec<PotionRecipe>,
+    potion_containers: Vec<PotionContainerChangeRecipe>,
+    material_reducers: Vec<MaterialReducer>,
+    /// Recipes implemented by the client itself, such as cloning maps or dyeing armour.
+    multi: Vec<MultiRecipe>,
+}
+
+impl RecipeBook {
+    /// Creates an empty recipe book that resolves ingredients against the items of a StartGame
+    /// packet.
+    pub fn new(items: &[ItemEntry]) -> Self {
+        let items: HashMap<_, _> = items
+            .iter()
+            .map(|item| (item.name.clone(), item.runtime_id as i32))
+            .collect();
+        let tags = TAGS
+            .iter()
+            .map(|(tag, patterns)| {
+                let mut members: Vec<_> = items
+                    .iter()
+                    .filter(|(name, _)| {
+                        let short = name.strip_prefix("minecraft:").unwrap_or(name);
+                        patterns
+                            .iter()
+                            .any(|pattern| match pattern.strip_prefix('*') {
+                                Some(suffix) => short.ends_with(suffix),
+                                None => short == *pattern,
+                            })
+                    })
+                    .map(|(_, id)| *id)
+                    .collect();
+                members.sort_unstable();
+                (tag.to_string(), members)
+            })
+            .collect();
+        Self {
+            items,
+            tags,
+            ..Default::default()
+        }
+    }
+
+    /// Adds items to a tag, for tags that are not known to the client. Only recipes received
+    /// afterwards see the new items.
+    pub fn add_tag(&mut self, tag: &str, names: &[&str]) {
+        let ids: Vec<_> = names
+            .iter()
+            .filter_map(|name| self.items.get(*name))
+            .copied()
+            .collect();
+        self.tags.entry(tag.to_string()).or_default().extend(ids);
+    }
+
+    /// Adds the recipes of a CraftingData packet, first forgetting all known recipes if the server
+    /// asks for it.
+    pub fn handle_crafting_data(&mut self, pk: &CraftingData) {
+        if pk.clear_recipes {
+            self.recipes.clear();
+            self.by_output.clear();
+            self.by_input.clear();
+            self.smelting.clear();
+            self.potions.clear();
+            self.potion_containers.clear();
+            self.material_reducers.clear();
+            self.multi.clear();
+        }
+        for recipe in &pk.recipes {
+            self.add(recipe);
+        }
+        self.potions.extend(pk.potion_recipes.iter().cloned());
+        self.potion_containers
+            .extend(pk.potion_container_change_recipes.iter().cloned());
+        self.material_reducers
+            .extend(pk.material_reducers.iter().cloned());
+    }
+
+    /// Returns a crafting recipe by its network ID.
+    pub fn recipe(&self, network_id: u32) -> Option<&CraftingRecipe> {
+        self.recipes.get(&network_id)
+    }
+
+    /// Returns all crafting recipes.
+    pub fn recipes(&self) -> impl Iterator<Item = &CraftingRecipe> {
+        self.recipes.values()
+    }
+
+    /// Returns the crafting recipes that produce an item.
+    pub fn producing(&self, network_id: i32) -> impl Iterator<Item = &CraftingRecipe> {
+        self.lookup(&self.by_output, network_id)
+    }
+
+    /// Returns the crafting recipes that have an item as one of their ingredients.
+    pub fn using(&self, network_id: i32) -> impl Iterator<Item = &CraftingRecipe> {
+        self.lookup(&self.by_input, network_id)
+    }
+
+    /// Returns the recipe matching the items in a square crafting grid, ordered row by row. If
+    /// several recipes match, the one with the lowest priority is used.
+    pub fn find(&self, grid: &[ItemStack], size: usize, block: &str) -> Option<&CraftingRecipe> {
+        let first = grid.iter().find(|stack| !is_air(stack))?;
+        self.using(first.network_id)
+            .filter(|recipe| recipe.block == block && recipe.matches_grid(grid, size))
+            .min_by_key(|recipe| (recipe.priority, recipe.network_id))
+    }
+
+    /// Returns the recipes of a block that can be crafted with the given items, such as the
+    /// contents of the inventory of the player.
+    pub fn craftable<'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a ItemStack>,
+        block: &str,
+    ) -> Vec<&CraftingRecipe> {
+        let available = pool(items);
+        let mut recipes: Vec<_> = self
+            .recipes
+            .values()
+            .filter(|recipe| recipe.block == block)
+            .filter(|recipe| take_ingredients(recipe, &mut available.clone()))
+            .collect();
+        recipes.sort_by_key(|recipe| recipe.network_id);
+        recipes
+    }
+
+    /// Returns how many times a recipe can be crafted with the given items, up to a stack of 64.
+    pub fn times_craftable<'a>(
+        &self,
+        recipe: &CraftingRecipe,
+        items: impl IntoIterator<Item = &'a ItemStack>,
+    ) -> u8 {
+        let mut available = pool(items);
+        let mut times = 0;
+        while times < 64 && take_ingredients(recipe, &mut available) {
+            times += 1;
+        }
+        times
+    }
+
+    /// Returns what smelting an item in a block produces, if anything. Recipes for the exact
+    /// variant of the item take precedence over those for any variant.
+    pub fn smelt(&self, stack: &ItemStack, block: &str) -> Option<&ItemStack> {
+        self.smelting
+            .iter()
+            .filter(|recipe| recipe.block == block && recipe.input.matches(stack))
+            .max_by_key(|recipe| recipe.input.metadata.is_some())
+            .map(|recipe| &recipe.output)
+    }
+
+    /// Returns the network ID and metadata value of the potion that brewing a potion with a reagent
+    /// produces, if anything.
+    pub fn brew(&self, potion: &ItemStack, reagent: &ItemStack) -> Option<(i32, u32)> {
+        let mix = self.potions.iter().find(|recipe| {
+            recipe.input_potion_id.0 == potion.network_id
+                && recipe.input_potion_metadata.0 as u32 == potion.metadata_value
+                && recipe.reagent_item_id.0 == reagent.network_id
+                && recipe.reagent_item_metadata.0 as u32 == reagent.metadata_value
+        });
+        if let Some(mix) = mix {
+            return Some((mix.output_potion_id.0, mix.output_potion_metadata.0 as u32));
+        }
+        // Container changes, such as turning a potion into a splash potion, keep the effect.
+        self.potion_containers
+            .iter()
+            .find(|recipe| {
+                recipe.input_item_id.0 == potion.network_id
+                    && recipe.reagent_item_id.0 == reagent.network_id
+            })
+            .map(|recipe| (recipe.output_item_id.0, potion.metadata_value))
+    }
+
+    /// Returns the material reducer recipes, which are used in Education Edition.
+    pub fn material_reducers(&self) -> &[MaterialReducer] {
+        &self.material_reducers
+    }
+
+    /// Returns the recipes that are implemented by the client itself.
+    pub fn multi_recipes(&self) -> &[MultiRecipe] {
+        &self.multi
+    }
+
+    fn add(&mut self, recipe: &Recipe) {
+        let recipe = match recipe {
+            Recipe::ShapelessRecipe(recipe)
+            | Recipe::ShulkerBoxRecipe(recipe)
+            | Recipe::ShapelessChemistryRecipe(recipe) => CraftingRecipe {
+                network_id: recipe.recipe_network_id.0,
+                id: recipe.recipe_id.clone(),
+                shape: RecipeShape::Shapeless,
+                ingredients: self.resolve_all(&recipe.input),
+                output: recipe.output.clone(),
+                block: recipe.block.clone(),
+                priority: recipe.priority.0,
+            },
+            Recipe::ShapedRecipe(recipe) | Recipe::ShapedChemistryRecipe(recipe) => {
+                CraftingRecipe {
+                    network_id: recipe.recipe_network_id,
+                    id: recipe.recipe_id.clone(),
+                    shape: RecipeShape::Shaped {
+                        width: recipe.width.max(0) as usize,
+                        height: recipe.height.max(0) as usize,
+                    },
+                    ingredients: self.resolve_all(&recipe.input),
+                    output: recipe.output.clone(),
+                    block: recipe.block.clone(),
+                    priority: recipe.priority,
+                }
+            }
+            Recipe::SmithingTransform(recipe) => CraftingRecipe {
+                network_id: recipe.recipe_network_id.0,
+                id: recipe.recipe_id.clone(),
+                shape: RecipeShape::Smithing,
+                ingredients: self.resolve_all(&[
+                    recipe.template.clone(),
+                    recipe.base.clone(),
+                    recipe.addition.clone(),
+                ]),
+                output: vec![recipe.result.clone()],
+                block: recipe.block.clone(),
+                priority: 0,
+            },
+            Recipe::SmithingTrim(recipe) => CraftingRecipe {
+                network_id: recipe.recipe_network_id.0,
+                id: recipe.recipe_id.clone(),
+                shape: RecipeShape::Smithing,
+                ingredients: self.resolve_all(&[
+                    recipe.template.clone(),
+                    recipe.base.clone(),
+                    recipe.addition.clone(),
+                ]),
+                output: Vec::new(),
+                block: recipe.block.clone(),
+                priority: 0,
+            },
+            Recipe::FurnaceRecipe(recipe) => {
+                self.smelting.push(SmeltingRecipe {
+                    input: ItemKind {
+                        network_id: recipe.network_id.0,
+                        metadata: None,
+                    },
+                    output: recipe.output.clone(),
+                    block: recipe.block.clone(),
+                });
+                return;
+            }
+            Recipe::FurnaceDataRecipe(recipe) => {
+                self.smelting.push(SmeltingRecipe {
+                    input: ItemKind {
+                        network_id: recipe.network_id.0,
+                        metadata: Some(recipe.metadata_value.0 as u32),
+                    },
+                    output: recipe.output.clone(),
+                    block: recipe.block.clone(),
+                });
+                return;
+            }
+            Recipe::MultiRecipe(recipe) => {
+                self.multi.push(recipe.clone());
+                return;
+            }
+        };
+
+        let id = recipe.network_id;
+        for stack in &recipe.output {
+            self.by_output.entry(stack.network_id).or_default().push(id);
+        }
+        let mut inputs: Vec<_> = recipe
+            .ingredients
+            .iter()
+            .flat_map(|ingredient| &ingredient.items)
+            .map(|kind| kind.network_id)
+            .collect();
+        inputs.sort_unstable();
+        inputs.dedup();
+        for input in inputs {
+            self.by_input.entry(input).or_default().push(id);
+        }
+        self.recipes.insert(id, recipe);
+    }
+
+    fn resolve_all(&self, descriptors: &[ItemDescriptorCount]) -> Vec<Ingredient> {
+        descriptors
+            .iter()
+            .map(|descriptor| self.resolve(descriptor))
+            .collect()
+    }
+
+    /// Finds the items that a descriptor refers to.
+    fn resolve(&self, descriptor: &ItemDescriptorCount) -> Ingredient {
+        let any = |network_id| ItemKind {
+            network_id,
+            metadata: None,
+        };
+        let by_name = |name: &str, metadata: i16| {
+            self.items.get(name).map(|network_id| ItemKind {
+                network_id: *network_id,
+                metadata: variant(metadata),
+            })
+        };
+        let items = match &descriptor.item_descriptor {
+            ItemDescriptor::InvalidDescriptor(_) => {
+                return Ingredient {
+                    items: Vec::new(),
+                    count: 0,
+                    descriptor: descriptor.clone(),
+                }
+            }
+            ItemDescriptor::DefaultDescriptor(default) => vec![ItemKind {
+                network_id: default.network_id as i32,
+                metadata: variant(default.metadata),
+            }],
+            ItemDescriptor::DeferredDescriptor(deferred) => {
+                by_name(&deferred.name, deferred.metadata)
+                    .into_iter()
+                    .collect()
+            }
+            ItemDescriptor::ComplexAliasDescriptor(alias) => {
+                by_name(&alias.name, ANY_METADATA).into_iter().collect()
+            }
+            ItemDescriptor::ItemTagDescriptor(tag) => self
+                .tags
+                .get(&tag.tag)
+                .map(|ids| ids.iter().copied().map(any).collect())
+                .unwrap_or_default(),
+            // Molang expressions cannot be evaluated by the client.
+            ItemDescriptor::MoLangDescriptor(_) => Vec::new(),
+        };
+        Ingredient {
+            items,
+            count: descriptor.count.0.max(0) as u16,
+            descriptor: descriptor.clone(),
+        }
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        index: &'a HashMap<i32, Vec<u32>>,
+        network_id: i32,
+    ) -> impl Iterator<Item = &'a CraftingRecipe> {
+        index
+            .get(&network_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.recipes.get(id))
+    }
+}
+
+/// Returns the metadata value a descriptor requires, or None if it accepts any variant.
+fn variant(metadata: i16) -> Option<u32> {
+    (metadata != ANY_METADATA).then_some(metadata as u32)
+}
+
+fn is_air(stack: &ItemStack) -> bool {
+    stack.network_id == 0 || stack.count == 0
+}
+
+/// Counts the items in a list of stacks by their network ID and metadata value.
+fn pool<'a>(items: impl IntoIterator<Item = &'a ItemStack>) -> Vec<(ItemStack, u16)> {
+    let mut pool: Vec<(ItemStack, u16)> = Vec::new();
+    for stack in items.into_iter().filter(|stack| !is_air(stack)) {
+        let existing = pool.iter_mut().find(|(other, _)| {
+            other.network_id == stack.network_id && other.metadata_value == stack.metadata_value
+        });
+        match existing {
+            Some((_, count)) => *count += stack.count,
+            None => pool.push((stack.clone(), stack.count)),
+        }
+    }
+    pool
+}
+
+/// Takes the ingredients of a recipe out of a pool of items, returning false if there are not
+/// enough of them. Ingredients take the first items they accept, which is enough for vanilla
+/// recipes.
+fn take_ingredients(recipe: &CraftingRecipe, pool: &mut [(ItemStack, u16)]) -> bool {
+    for ingredient in recipe.ingredients.iter().filter(|i| !i.is_empty()) {
+        let mut needed = ingredient.count;
+        for (stack, count) in pool.iter_mut() {
+            if needed == 0 {
+                break;
+            }
+            if ingredient.accepts(stack) {
+                let taken = needed.min(*count);
+                *count -= taken;
+                needed -= taken;
+            }
+        }
+        if needed > 0 {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::proto::ints::{VarI32, VarU32};
+    use crate::proto::types::item_descriptor::{
+        DefaultDescriptor, InvalidDescriptor, ItemTagDescriptor,
+    };
+    use crate::proto::types::recipe::{ShapedRecipe, ShapelessRecipe};
+
+    use super::*;
+
+    const PLANKS: i32 = 5;
+    const BIRCH_PLANKS: i32 = 6;
+    const STICK: i32 = 280;
+    const CHEST: i32 = 54;
+
+    fn stack(network_id: i32, count: u16) -> ItemStack {
+        ItemStack {
+            network_id,
+            count,
+            ..Default::default()
+        }
+    }
+
+    fn book() -> RecipeBook {
+        let entry = |name: &str, runtime_id| ItemEntry {
+            name: name.to_string(),
+            runtime_id,
+            component_based: false,
+        };
+        let mut book = RecipeBook::new(&[
+            entry("minecraft:oak_planks", PLANKS as i16),
+            entry("minecraft:birch_planks", BIRCH_PLANKS as i16),
+            entry("minecraft:stick", STICK as i16),
+            entry("minecraft:chest", CHEST as i16),
+        ]);
+        let planks = ItemDescriptorCount {
+            item_descriptor: ItemDescriptor::ItemTagDescriptor(ItemTagDescriptor {
+                tag: "minecraft:planks".to_string(),
+            }),
+            count: VarI32(1),
+        };
+        let empty = ItemDescriptorCount {
+            item_descriptor: ItemDescriptor::InvalidDescriptor(InvalidDescriptor),
+            count: VarI32(0),
+        };
+        let oak = ItemDescriptorCount {
+            item_descriptor: ItemDescriptor::DefaultDescriptor(DefaultDescriptor {
+                network_id: PLANKS as i16,
+                metadata: ANY_METADATA,
+            }),
+            count: VarI32(1),
+        };
+        book.handle_crafting_data(&CraftingData {
+            recipes: vec![
+                // Two planks on top of each other make four sticks.
+                Recipe::ShapedRecipe(ShapedRecipe {
+                    recipe_id: "stick".to_string(),
+                    width: 1,
+                    height: 2,
+                    input: vec![planks.clone(), planks.clone()],
+                    output: vec![stack(STICK, 4)],
+                    block: "crafting_table".to_string(),
+                    recipe_network_id: 1,
+                    ..Default::default()
+                }),
+                Recipe::ShapedRecipe(ShapedRecipe {
+                    recipe_id: "chest".to_string(),
+                    width: 3,
+                    height: 3,
+                    input: vec![
+                        planks.clone(),
+                        planks.clone(),
+                        planks.clone(),
+                        planks.clone(),
+                        empty,
+                        planks.clone(),
+                        planks.clone(),
+                        planks.clone(),
+                        planks,
+                    ],
+                    output: vec![stack(CHEST, 1)],
+                    block: "crafting_table".to_string(),
+                    recipe_network_id: 2,
+                    ..Default::default()
+                }),
+                Recipe::ShapelessRecipe(ShapelessRecipe {
+                    recipe_id: "oak_sticks".to_string(),
+                    input: vec![oak.clone(), oak],
+                    output: vec![stack(STICK, 1)],
+                    uuid: Uuid::nil(),
+                    block: "stonecutter".to_string(),
+                    priority: VarI32(0),
+                    recipe_network_id: VarU32(3),
+                }),
+            ],
+            potion_recipes: Vec::new(),
+            potion_container_change_recipes: Vec::new(),
+            material_reducers: Vec::new(),
+            clear_recipes: false,
+        });
+        book
+    }
+
+    #[test]
+    fn lookups() {
+        let book = book();
+        let mut producing: Vec<_> = book.producing(STICK).map(|r| r.network_id).collect();
+        producing.sort();
+        assert_eq!(producing, [1, 3]);
+        // The tag resolves to both kinds of planks.
+        assert_eq!(book.using(BIRCH_PLANKS).count(), 2);
+
+        let inventory = [stack(BIRCH_PLANKS, 3), stack(PLANKS, 4)];
+        let craftable: Vec<_> = book
+            .craftable(&inventory, "crafting_table")
+            .iter()
+            .map(|r| r.network_id)
+            .collect();
+        assert_eq!(craftable, [1]);
+        assert_eq!(book.times_craftable(book.recipe(1).unwrap(), &inventory), 3);
+
+        let action = book.recipe(2).unwrap().auto_craft(2);
+        assert_eq!(action.recipe_network_id, 2);
+        assert_eq!(action.times_crafted, 2);
+        assert_eq!(action.ingredients.len(), 8);
+    }
+
+    #[test]
+    fn grids() {
+        let book = book();
+        let air = ItemStack::default();
+        let mut grid = vec![air.clone(); 4];
+        grid[1] = stack(PLANKS, 1);
+        assert!(book.find(&grid, 2, "crafting_table").is_none());
+        grid[3] = stack(BIRCH_PLANKS, 1);
+        assert_eq!(book.find(&grid, 2, "crafting_table").unwrap().network_id, 1);
+
+        let mut grid = vec![stack(PLANKS, 1); 9];
+        assert!(book.find(&grid, 3, "crafting_table").is_none());
+        grid[4] = air;
+        assert_eq!(book.find(&grid, 3, "crafting_table").unwrap().network_id, 2);
+    }
+}
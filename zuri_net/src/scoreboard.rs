@@ -0,0 +1,263 @@
+//! Keeps track of the scoreboard objectives sent by the server, the slots they are displayed in and
+//! the scores of their entries.
+//!
+//! Objectives are created by the SetDisplayObjective packet, which also puts them in a display
+//! slot, and are filled in with the SetScore packet. Entries of an objective are identified by an
+//! entry ID, which stays the same for a player or fake player across all objectives.
+
+use std::collections::HashMap;
+
+use num_traits::FromPrimitive;
+
+use crate::proto::packet::remove_objective::RemoveObjective;
+use crate::proto::packet::set_display_objective::SetDisplayObjective;
+use crate::proto::packet::set_score::SetScore;
+use crate::proto::packet::set_scoreboard_identity::SetScoreboardIdentity;
+use crate::proto::types::scoreboard::{
+    ScoreboardAction, ScoreboardEntry, ScoreboardIdentity, ScoreboardIdentityAction,
+    ScoreboardSlot, ScoreboardSortOrder,
+};
+
+/// What an entry of an objective refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identity {
+    /// A player, by its unique entity ID.
+    Player(i64),
+    /// Any other entity, by its unique entity ID.
+    Entity(i64),
+    /// A name that does not refer to any entity.
+    FakePlayer(String),
+}
+
+/// A single line of an objective.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub entry_id: i64,
+    pub identity: Identity,
+    pub score: i32,
+}
+
+/// An objective, such as the kills of each player in a minigame.
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub name: String,
+    /// The title shown above the entries of the objective.
+    pub display_name: String,
+    /// The criteria of the objective. The client does not use it.
+    pub criteria: String,
+    pub sort_order: ScoreboardSortOrder,
+    entries: HashMap<i64, Entry>,
+}
+
+impl Objective {
+    /// Returns the entries of the objective in the order they are displayed in. Entries with the
+    /// same score are ordered by their entry ID.
+    pub fn entries(&self) -> Vec<&Entry> {
+        let mut entries: Vec<&Entry> = self.entries.values().collect();
+        entries.sort_by(|a, b| {
+            let order = match self.sort_order {
+                ScoreboardSortOrder::Ascending => a.score.cmp(&b.score),
+                ScoreboardSortOrder::Descending => b.score.cmp(&a.score),
+            };
+            order.then(a.entry_id.cmp(&b.entry_id))
+        });
+        entries
+    }
+
+    /// Returns the entry with the provided entry ID.
+    pub fn entry(&self, entry_id: i64) -> Option<&Entry> {
+        self.entries.get(&entry_id)
+    }
+
+    /// Returns the score of a player or entity by its unique entity ID.
+    pub fn score_of(&self, entity_unique_id: i64) -> Option<i32> {
+        self.entries
+            .values()
+            .find(|entry| match entry.identity {
+                Identity::Player(id) | Identity::Entity(id) => id == entity_unique_id,
+                Identity::FakePlayer(_) => false,
+            })
+            .map(|entry| entry.score)
+    }
+
+    /// Returns the score of a fake player by its name.
+    pub fn score_of_name(&self, name: &str) -> Option<i32> {
+        self.entries
+            .values()
+            .find(|entry| matches!(&entry.identity, Identity::FakePlayer(n) if n == name))
+            .map(|entry| entry.score)
+    }
+}
+
+/// The scoreboard of the player, holding all known objectives and the slots they are shown in.
+#[derive(Debug, Default, Clone)]
+pub struct Scoreboard {
+    objectives: HashMap<String, Objective>,
+    slots: HashMap<ScoreboardSlot, String>,
+}
+
+impl Scoreboard {
+    /// Creates an objective, or updates it if it already exists, and displays it in a slot. The
+    /// objective previously shown in the slot is hidden, but its scores are kept.
+    pub fn handle_set_display_objective(&mut self, pk: &SetDisplayObjective) {
+        let sort_order = ScoreboardSortOrder::from_i32(pk.sort_order.0)
+            .unwrap_or(ScoreboardSortOrder::Descending);
+        let objective = self
+            .objectives
+            .entry(pk.objective_name.clone())
+            .or_insert_with(|| Objective {
+                name: pk.objective_name.clone(),
+                display_name: String::new(),
+                criteria: String::new(),
+                sort_order,
+                entries: HashMap::new(),
+            });
+        objective.display_name = pk.display_name.clone();
+        objective.criteria = pk.criteria_name.clone();
+        objective.sort_order = sort_order;
+
+        if let Some(slot) = ScoreboardSlot::from_string(&pk.display_slot) {
+            self.slots.insert(slot, pk.objective_name.clone());
+        }
+    }
+
+    /// Forgets an objective along with its scores, removing it from any slot it was shown in.
+    pub fn handle_remove_objective(&mut self, pk: &RemoveObjective) {
+        self.objectives.remove(&pk.objective_name);
+        self.slots.retain(|_, name| *name != pk.objective_name);
+    }
+
+    /// Adds, changes or removes entries of objectives. Entries of unknown objectives are ignored.
+    pub fn handle_set_score(&mut self, pk: &SetScore) {
+        for entry in &pk.entries {
+            let Some(objective) = self.objectives.get_mut(&entry.objective_name) else {
+                continue;
+            };
+            match pk.action_type {
+                ScoreboardAction::Modify => {
+                    objective.entries.insert(
+                        entry.entry_id,
+                        Entry {
+                            entry_id: entry.entry_id,
+                            identity: identity(entry),
+                            score: entry.score,
+                        },
+                    );
+                }
+                ScoreboardAction::Remove => {
+                    objective.entries.remove(&entry.entry_id);
+                }
+            }
+        }
+    }
+
+    /// Points entries at a player. The name of the player is not sent when an identity is cleared,
+    /// so cleared entries keep referring to the player they referred to last.
+    pub fn handle_set_scoreboard_identity(&mut self, pk: &SetScoreboardIdentity) {
+        if pk.action_type != ScoreboardIdentityAction::Register {
+            return;
+        }
+        for identity in &pk.entries {
+            for objective in self.objectives.values_mut() {
+                if let Some(entry) = objective.entries.get_mut(&identity.entry_id) {
+                    entry.identity = Identity::Player(identity.entity_unique_id);
+                }
+            }
+        }
+    }
+
+    /// Returns an objective by its name.
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.get(name)
+    }
+
+    /// Returns all known objectives, whether they are displayed or not.
+    pub fn objectives(&self) -> impl Iterator<Item = &Objective> {
+        self.objectives.values()
+    }
+
+    /// Returns the objective shown in a display slot.
+    pub fn displayed(&self, slot: ScoreboardSlot) -> Option<&Objective> {
+        self.objectives.get(self.slots.get(&slot)?)
+    }
+
+    /// Forgets all objectives, such as when the player changes servers.
+    pub fn clear(&mut self) {
+        self.objectives.clear();
+        self.slots.clear();
+    }
+}
+
+fn identity(entry: &ScoreboardEntry) -> Identity {
+    match entry.identity_type {
+        ScoreboardIdentity::Player => Identity::Player(entry.entity_unique_id),
+        ScoreboardIdentity::Entity => Identity::Entity(entry.entity_unique_id),
+        ScoreboardIdentity::FakePlayer => Identity::FakePlayer(entry.display_name.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::proto::ints::VarI32;
+    use crate::proto::types::scoreboard::ScoreboardIdentityEntry;
+
+    use super::*;
+
+    fn score(entry_id: i64, objective: &str, score: i32, name: &str) -> ScoreboardEntry {
+        ScoreboardEntry {
+            entry_id,
+            objective_name: objective.to_string(),
+            score,
+            identity_type: ScoreboardIdentity::FakePlayer,
+            entity_unique_id: 0,
+            display_name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn objectives() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.handle_set_display_objective(&SetDisplayObjective {
+            display_slot: "sidebar".to_string(),
+            objective_name: "kills".to_string(),
+            display_name: "Kills".to_string(),
+            criteria_name: "dummy".to_string(),
+            sort_order: VarI32(1),
+        });
+        scoreboard.handle_set_score(&SetScore {
+            action_type: ScoreboardAction::Modify,
+            entries: vec![
+                score(1, "kills", 3, "Steve"),
+                score(2, "kills", 7, "Alex"),
+                score(3, "unknown", 1, "Nobody"),
+            ],
+        });
+
+        let sidebar = scoreboard.displayed(ScoreboardSlot::Sidebar).unwrap();
+        assert_eq!(sidebar.display_name, "Kills");
+        let scores: Vec<i32> = sidebar.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [7, 3]);
+        assert_eq!(sidebar.score_of_name("Steve"), Some(3));
+        assert!(scoreboard.displayed(ScoreboardSlot::BelowName).is_none());
+
+        scoreboard.handle_set_scoreboard_identity(&SetScoreboardIdentity {
+            action_type: ScoreboardIdentityAction::Register,
+            entries: vec![ScoreboardIdentityEntry {
+                entry_id: 2,
+                entity_unique_id: 42,
+            }],
+        });
+        scoreboard.handle_set_score(&SetScore {
+            action_type: ScoreboardAction::Remove,
+            entries: vec![score(1, "kills", 0, "")],
+        });
+        let sidebar = scoreboard.objective("kills").unwrap();
+        assert_eq!(sidebar.score_of(42), Some(7));
+        assert_eq!(sidebar.entries().len(), 1);
+
+        scoreboard.handle_remove_objective(&RemoveObjective {
+            objective_name: "kills".to_string(),
+        });
+        assert!(scoreboard.displayed(ScoreboardSlot::Sidebar).is_none());
+    }
+}
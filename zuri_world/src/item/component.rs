@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::block::Component;
+
+/// The maximum amount of items that fit in a single stack of the item.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MaxStackSize(pub u8);
+
+impl Component for MaxStackSize {}
+
+/// The amount of times an item can be used before it breaks. The `Damage` tag of a stack counts
+/// how many of these uses are spent.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Durability(pub u32);
+
+impl Component for Durability {}
+
+/// The short name of the texture used as icon of a custom item, as found in the
+/// `item_texture.json` of a resource pack.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Icon(pub String);
+
+impl Component for Icon {}
+
+/// Makes an item place a block when used on the face of another block.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlockPlacer {
+    /// The identifier of the block that gets placed.
+    pub block: String,
+}
+
+impl Component for BlockPlacer {}
+
+/// Stores the components of a single type for all items that have one, keyed by network id.
+///
+/// Unlike block components, only few items have a component of any one type, so items always use
+/// a hashed storage.
+#[derive(Debug)]
+pub struct ItemComponentStorage<T: Component> {
+    storage: HashMap<i32, T, ahash::RandomState>,
+}
+
+impl<T: Component> Default for ItemComponentStorage<T> {
+    fn default() -> Self {
+        Self {
+            storage: Default::default(),
+        }
+    }
+}
+
+impl<T: Component> ItemComponentStorage<T> {
+    /// Gets the component for the item with the provided network id.
+    pub fn get(&self, network_id: i32) -> Option<&T> {
+        self.storage.get(&network_id)
+    }
+
+    /// Gets a mutable reference to the component for the item with the provided network id.
+    pub fn get_mut(&mut self, network_id: i32) -> Option<&mut T> {
+        self.storage.get_mut(&network_id)
+    }
+
+    /// Sets the component of an item, returning the previous value if there was one.
+    pub fn set(&mut self, network_id: i32, value: T) -> Option<T> {
+        self.storage.insert(network_id, value)
+    }
+
+    /// Removes the component from an item.
+    pub fn remove(&mut self, network_id: i32) -> Option<T> {
+        self.storage.remove(&network_id)
+    }
+
+    /// Iterates over all items that have the component, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &T)> {
+        self.storage.iter().map(|(id, value)| (*id, value))
+    }
+}
+
+pub(super) trait AnyItemComponentStorage: downcast_rs::DowncastSync + Debug {}
+downcast_rs::impl_downcast!(sync AnyItemComponentStorage);
+
+impl<T: Component> AnyItemComponentStorage for ItemComponentStorage<T> {}
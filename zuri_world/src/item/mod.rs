@@ -0,0 +1,340 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use zuri_nbt::NBTTag;
+use zuri_net::proto::packet::item_component::ItemComponent;
+use zuri_net::proto::types::item::ItemStack;
+use zuri_net::proto::types::item_stack::{ItemComponentEntry, ItemEntry};
+
+use crate::block::{BlockMap, Component, RuntimeId, ToRuntimeId};
+use crate::item::component::*;
+
+pub mod component;
+mod vanilla;
+
+/// Holds all item types known to the client, along with their components.
+///
+/// Items are identified by a network id on the wire and by a string identifier everywhere else. Both
+/// are sent in the StartGame packet, and custom items get their components from the ItemComponent
+/// packet later on.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Resource))]
+pub struct ItemMap {
+    item_types: HashMap<i32, ItemType>,
+    network_ids: HashMap<Box<str>, i32>,
+    components: HashMap<TypeId, Box<dyn AnyItemComponentStorage>>,
+}
+
+/// A single item type, as sent in the StartGame packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ItemType {
+    identifier: Box<str>,
+    network_id: i32,
+    component_based: bool,
+}
+
+impl ItemType {
+    /// The unique string identifier of the item, such as `minecraft:stick`.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The id used to refer to the item in packets.
+    pub fn network_id(&self) -> i32 {
+        self.network_id
+    }
+
+    /// Whether the item is a custom item that gets its components from the server.
+    pub fn component_based(&self) -> bool {
+        self.component_based
+    }
+}
+
+impl ItemMap {
+    /// Creates an item map from the item entries of the StartGame packet. Vanilla items get their
+    /// stack size and durability assigned straight away.
+    pub fn new(items: &[ItemEntry]) -> Self {
+        let mut map = Self {
+            item_types: HashMap::with_capacity(items.len()),
+            network_ids: HashMap::with_capacity(items.len()),
+            components: HashMap::new(),
+        };
+        for entry in items {
+            let network_id = entry.runtime_id as i32;
+            map.network_ids
+                .insert(entry.name.as_str().into(), network_id);
+            map.item_types.insert(
+                network_id,
+                ItemType {
+                    identifier: entry.name.as_str().into(),
+                    network_id,
+                    component_based: entry.component_based,
+                },
+            );
+            if entry.component_based {
+                continue;
+            }
+            map.set_component(
+                network_id,
+                MaxStackSize(vanilla::max_stack_size(&entry.name)),
+            );
+            if let Some(durability) = vanilla::durability(&entry.name) {
+                map.set_component(network_id, Durability(durability));
+            }
+        }
+        map
+    }
+
+    /// Reads the components of custom items from an ItemComponent packet. Items that are not known
+    /// from the StartGame packet are ignored.
+    pub fn handle_item_component(&mut self, pk: &ItemComponent) {
+        for entry in &pk.items {
+            self.read_components(entry);
+        }
+    }
+
+    fn read_components(&mut self, entry: &ItemComponentEntry) {
+        let Some(network_id) = self.network_id(&entry.name) else {
+            return;
+        };
+        let Some(components) = get(&entry.data, "components") else {
+            return;
+        };
+
+        let properties = get(components, "item_properties");
+        let max_stack_size = properties
+            .and_then(|properties| int(properties, "max_stack_size"))
+            .or_else(|| int(get(components, "minecraft:max_stack_size")?, "value"));
+        if let Some(size) = max_stack_size {
+            self.set_component(network_id, MaxStackSize(size.clamp(1, 255) as u8));
+        }
+
+        if let Some(durability) = get(components, "minecraft:durability")
+            .and_then(|durability| int(durability, "max_durability"))
+        {
+            self.set_component(network_id, Durability(durability.max(0) as u32));
+        }
+
+        // Older versions send the icon as a single texture, newer ones as a set of textures of
+        // which only the default one is used.
+        let icon = properties.and_then(|properties| {
+            let icon = get(properties, "minecraft:icon")?;
+            string(icon, "texture").or_else(|| string(get(icon, "textures")?, "default"))
+        });
+        if let Some(icon) = icon {
+            self.set_component(network_id, Icon(icon.to_string()));
+        }
+
+        if let Some(block) =
+            get(components, "minecraft:block_placer").and_then(|placer| string(placer, "block"))
+        {
+            self.set_component(
+                network_id,
+                BlockPlacer {
+                    block: block.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Returns the item type with the provided network id.
+    pub fn item_type(&self, network_id: i32) -> Option<&ItemType> {
+        self.item_types.get(&network_id)
+    }
+
+    /// Iterates over all known item types, in no particular order.
+    pub fn item_types(&self) -> impl Iterator<Item = &ItemType> {
+        self.item_types.values()
+    }
+
+    /// Returns the network id of the item with the provided string identifier.
+    pub fn network_id(&self, identifier: &str) -> Option<i32> {
+        self.network_ids.get(identifier).copied()
+    }
+
+    /// Returns the string identifier of the item with the provided network id.
+    pub fn identifier(&self, network_id: i32) -> Option<&str> {
+        self.item_type(network_id).map(ItemType::identifier)
+    }
+
+    /// Returns the runtime id of the block that the item places. This is the block from the
+    /// item's [BlockPlacer] component, or otherwise the block with the same identifier as the
+    /// item. The first variant of the block is returned, as the exact block state of a stack is
+    /// sent along with the stack itself.
+    pub fn block_runtime_id(&self, network_id: i32, block_map: &BlockMap) -> Option<RuntimeId> {
+        let identifier = match self.component::<BlockPlacer>(network_id) {
+            Some(placer) => placer.block.as_str(),
+            None => self.identifier(network_id)?,
+        };
+        let block_type = block_map.block_type(identifier)?;
+        block_type.variants().next().map(|block| block.runtime_id())
+    }
+
+    /// Returns the item type used to place the block with the provided runtime id, which is the
+    /// item that shares the block's identifier.
+    pub fn block_item(
+        &self,
+        runtime_id: impl ToRuntimeId,
+        block_map: &BlockMap,
+    ) -> Option<&ItemType> {
+        let block = block_map.block(runtime_id).ok()?;
+        self.item_type(self.network_id(block.identifier())?)
+    }
+
+    /// Returns the maximum amount of items that fit in a stack of the item. Defaults to 64 if the
+    /// item has no [MaxStackSize] component.
+    pub fn max_stack_size(&self, network_id: i32) -> u16 {
+        self.component::<MaxStackSize>(network_id)
+            .map_or(64, |size| size.0 as u16)
+    }
+
+    /// Returns the amount of uses left on a stack, or None if the item does not break with use.
+    pub fn remaining_durability(&self, stack: &ItemStack) -> Option<u32> {
+        let durability = self.component::<Durability>(stack.network_id)?;
+        Some(durability.0.saturating_sub(stack.damage().max(0) as u32))
+    }
+
+    /// Gets the component [T] of the item with the provided network id.
+    pub fn component<T: Component>(&self, network_id: i32) -> Option<&T> {
+        self.components::<T>()?.get(network_id)
+    }
+
+    /// Gets the component storage of a certain type, or None if no item has had a component of
+    /// this type yet.
+    pub fn components<T: Component>(&self) -> Option<&ItemComponentStorage<T>> {
+        self.components
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.downcast_ref().unwrap())
+    }
+
+    /// Gets a mutable reference to the component storage of a certain type, creating it if it does
+    /// not exist yet.
+    pub fn components_mut<T: Component>(&mut self) -> &mut ItemComponentStorage<T> {
+        self.components
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<ItemComponentStorage<T>>::default())
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Sets the component of an item.
+    pub fn set_component<T: Component>(&mut self, network_id: i32, value: T) {
+        self.components_mut::<T>().set(network_id, value);
+    }
+}
+
+fn get<'a>(tag: &'a NBTTag, key: &str) -> Option<&'a NBTTag> {
+    match tag {
+        NBTTag::Compound(compound) => compound.0.get(key),
+        _ => None,
+    }
+}
+
+fn string<'a>(tag: &'a NBTTag, key: &str) -> Option<&'a str> {
+    match get(tag, key)? {
+        NBTTag::String(string) => Some(&string.0),
+        _ => None,
+    }
+}
+
+fn int(tag: &NBTTag, key: &str) -> Option<i32> {
+    match get(tag, key)? {
+        NBTTag::Byte(v) => Some(v.0 as i32),
+        NBTTag::Short(v) => Some(v.0 as i32),
+        NBTTag::Int(v) => Some(v.0),
+        NBTTag::Long(v) => Some(v.0 as i32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zuri_nbt::{tag, NBTTag};
+    use zuri_net::proto::packet::item_component::ItemComponent;
+    use zuri_net::proto::types::item::ItemStack;
+    use zuri_net::proto::types::item_stack::{ItemComponentEntry, ItemEntry};
+
+    use crate::item::component::{BlockPlacer, Durability, Icon};
+    use crate::item::ItemMap;
+
+    fn compound(entries: Vec<(&str, NBTTag)>) -> NBTTag {
+        NBTTag::Compound(tag::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        ))
+    }
+
+    fn entry(name: &str, runtime_id: i16, component_based: bool) -> ItemEntry {
+        ItemEntry {
+            name: name.to_string(),
+            runtime_id,
+            component_based,
+        }
+    }
+
+    #[test]
+    fn test_item_components() {
+        let mut map = ItemMap::new(&[
+            entry("minecraft:stick", 1, false),
+            entry("minecraft:ender_pearl", 2, false),
+            entry("minecraft:diamond_pickaxe", 3, false),
+            entry("custom:wand", 4, true),
+        ]);
+        assert_eq!(map.network_id("minecraft:stick"), Some(1));
+        assert_eq!(map.identifier(3), Some("minecraft:diamond_pickaxe"));
+        assert_eq!(map.max_stack_size(1), 64);
+        assert_eq!(map.max_stack_size(2), 16);
+        assert_eq!(map.max_stack_size(3), 1);
+        assert_eq!(map.component::<Durability>(3), Some(&Durability(1561)));
+
+        map.handle_item_component(&ItemComponent {
+            items: vec![ItemComponentEntry {
+                name: "custom:wand".to_string(),
+                data: compound(vec![(
+                    "components",
+                    compound(vec![
+                        (
+                            "item_properties",
+                            compound(vec![
+                                ("max_stack_size", NBTTag::Int(tag::Int(1))),
+                                (
+                                    "minecraft:icon",
+                                    compound(vec![(
+                                        "texture",
+                                        NBTTag::String(tag::String("wand".to_string())),
+                                    )]),
+                                ),
+                            ]),
+                        ),
+                        (
+                            "minecraft:durability",
+                            compound(vec![("max_durability", NBTTag::Int(tag::Int(100)))]),
+                        ),
+                        (
+                            "minecraft:block_placer",
+                            compound(vec![(
+                                "block",
+                                NBTTag::String(tag::String("custom:glow".to_string())),
+                            )]),
+                        ),
+                    ]),
+                )]),
+            }],
+        });
+        assert_eq!(map.max_stack_size(4), 1);
+        assert_eq!(map.component::<Icon>(4), Some(&Icon("wand".to_string())));
+        assert_eq!(
+            map.component::<BlockPlacer>(4).unwrap().block,
+            "custom:glow"
+        );
+
+        let stack = ItemStack {
+            network_id: 4,
+            nbt_data: compound(vec![("Damage", NBTTag::Int(tag::Int(30)))]),
+            ..Default::default()
+        };
+        assert_eq!(map.remaining_durability(&stack), Some(70));
+    }
+}
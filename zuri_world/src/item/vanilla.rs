@@ -0,0 +1,79 @@
+//! Vanilla items are not sent with any components, so their stack sizes and durability are derived
+//! from their identifiers instead.
+
+/// Returns the durability of a vanilla item, or None if the item does not break with use.
+pub(super) fn durability(identifier: &str) -> Option<u32> {
+    let name = identifier.strip_prefix("minecraft:")?;
+    if let Some((material, tool)) = name.rsplit_once('_') {
+        if matches!(tool, "sword" | "pickaxe" | "axe" | "shovel" | "hoe") {
+            return match material {
+                "wooden" => Some(59),
+                "stone" => Some(131),
+                "iron" => Some(250),
+                "golden" => Some(32),
+                "diamond" => Some(1561),
+                "netherite" => Some(2031),
+                _ => None,
+            };
+        }
+
+        let piece = match tool {
+            "helmet" => 0,
+            "chestplate" => 1,
+            "leggings" => 2,
+            "boots" => 3,
+            _ => return other_durability(name),
+        };
+        let pieces = match material {
+            "leather" => [55, 80, 75, 65],
+            "chainmail" | "iron" => [165, 240, 225, 195],
+            "golden" => [77, 112, 105, 91],
+            "diamond" => [363, 528, 495, 429],
+            "netherite" => [407, 592, 555, 481],
+            "turtle" => [275, 0, 0, 0],
+            _ => return None,
+        };
+        return Some(pieces[piece]).filter(|durability| *durability != 0);
+    }
+    other_durability(name)
+}
+
+fn other_durability(name: &str) -> Option<u32> {
+    match name {
+        "bow" | "fishing_rod" => Some(384),
+        "crossbow" => Some(464),
+        "trident" => Some(250),
+        "shears" => Some(238),
+        "flint_and_steel" | "brush" => Some(64),
+        "shield" => Some(336),
+        "elytra" => Some(432),
+        "carrot_on_a_stick" => Some(25),
+        "warped_fungus_on_a_stick" => Some(100),
+        _ => None,
+    }
+}
+
+/// Returns the maximum stack size of a vanilla item.
+pub(super) fn max_stack_size(identifier: &str) -> u8 {
+    if durability(identifier).is_some() {
+        return 1;
+    }
+    let name = identifier.strip_prefix("minecraft:").unwrap_or(identifier);
+    match name {
+        "ender_pearl" | "snowball" | "egg" | "bucket" | "honey_bottle" | "armor_stand" => 16,
+        "potion" | "splash_potion" | "lingering_potion" | "cake" | "saddle" | "enchanted_book"
+        | "written_book" | "writable_book" | "totem_of_undying" | "mushroom_stew"
+        | "beetroot_soup" | "rabbit_stew" | "suspicious_stew" => 1,
+        _ if name.ends_with("_sign") || name.ends_with("_banner") => 16,
+        _ if name.ends_with("_bucket")
+            || name.ends_with("_boat")
+            || name.ends_with("minecart")
+            || name.ends_with("_horse_armor")
+            || name.starts_with("music_disc_")
+            || name == "bed" =>
+        {
+            1
+        }
+        _ => 64,
+    }
+}
@@ -1,5 +1,6 @@
 pub mod block;
 pub mod chunk;
+pub mod item;
 mod paletted_storage;
 pub mod pos;
 pub mod range;